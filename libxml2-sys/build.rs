@@ -1,4 +1,6 @@
 use std::env;
+#[cfg(feature = "xslt")]
+use std::path::Path;
 use std::path::PathBuf;
 
 fn main() {
@@ -25,9 +27,13 @@ fn main() {
         .define("LIBXML2_WITH_TREE", "ON")
         .define("LIBXML2_WITH_HTML", "ON")
         .define("LIBXML2_WITH_XPATH", "ON")
-        .define("LIBXML2_WITH_THREADS", "ON");
+        .define("LIBXML2_WITH_THREADS", "ON")
+        .define("LIBXML2_WITH_VALID", "ON")
+        .define("LIBXML2_WITH_READER", "ON")
+        .define("LIBXML2_WITH_PUSH", "ON");
 
     // 关键：关闭移动端极易报错的额外依赖
+    // 非 UTF-8 编码由 rlibxml 在 Rust 侧通过 encoding_rs 转码，无需 iconv/ICU
     cmake_config
         .define("LIBXML2_WITH_ICONV", "OFF")
         .define("LIBXML2_WITH_ICU", "OFF")
@@ -41,14 +47,18 @@ fn main() {
         .define("LIBXML2_WITH_PYTHON", "OFF")
         .define("LIBXML2_WITH_PROGRAMS", "OFF")
         .define("LIBXML2_WITH_TESTS", "OFF")
-        .define("LIBXML2_WITH_VALID", "OFF")
-        .define("LIBXML2_WITH_SCHEMAS", "OFF")
         .define("LIBXML2_WITH_CATALOG", "OFF")
         .define("LIBXML2_WITH_MEM_DEBUG", "OFF")
         .define("LIBXML2_WITH_DEBUG", "OFF")
         .define("LIBXML2_WITH_ISO8859X", "OFF")
         .define("LIBXML2_WITH_SAX1", "OFF");
 
+    // 可选模块，由 cargo feature 控制
+    let with_schemas = env::var_os("CARGO_FEATURE_SCHEMAS").is_some();
+    cmake_config
+        .define("LIBXML2_WITH_SCHEMAS", on_off(with_schemas))
+        .define("LIBXML2_WITH_SCHEMATRON", on_off(with_schemas));
+
     // 必须静态链接
     cmake_config.define("BUILD_SHARED_LIBS", "OFF");
 
@@ -59,6 +69,12 @@ fn main() {
 
     let dst = cmake_config.build();
 
+    // 可选的 libxslt，链接顺序必须在 libxml2 之前；
+    // cc 是只在 xslt feature 下启用的构建依赖，编译它的代码必须按 cfg 排除
+    let with_xslt = cfg!(feature = "xslt");
+    #[cfg(feature = "xslt")]
+    build_libxslt(&manifest_dir, &dst, &target);
+
     // 设置库搜索路径和链接
     // cmake 输出固定为 lib/ 目录
     let lib_dir = dst.join("lib");
@@ -92,14 +108,29 @@ fn main() {
     // 生成 FFI 绑定 - cmake 输出目录包含所有头文件（含生成的 xmlversion.h）
     let include_dir = dst.join("include").join("libxml2");
 
-    let bindings = bindgen::Builder::default()
+    let mut builder = bindgen::Builder::default()
         .header("wrapper.h")
-        .clang_arg(format!("-I{}", include_dir.display()))
+        .clang_arg(format!("-I{}", include_dir.display()));
+    if with_xslt {
+        // libxslt 的头文件安装在 include/ 下，与 libxml2 的 include/libxml2 分开
+        builder = builder
+            .clang_arg("-DRLIBXML_WITH_XSLT")
+            .clang_arg(format!("-I{}", dst.join("include").display()));
+    }
+
+    let bindings = builder
 
         // ========================================
         // HTML 解析函数
         // ========================================
         .allowlist_function("htmlReadMemory")
+        .allowlist_function("htmlNewParserCtxt")
+        .allowlist_function("htmlFreeParserCtxt")
+        .allowlist_function("htmlCtxtReadMemory")
+        .allowlist_function("htmlCtxtReadFile")
+        .allowlist_function("htmlCreatePushParserCtxt")
+        .allowlist_function("htmlParseChunk")
+        .allowlist_function("htmlCtxtUseOptions")
 
         // ========================================
         // XML 文档函数
@@ -107,12 +138,72 @@ fn main() {
         .allowlist_function("xmlFreeDoc")
         .allowlist_function("xmlNodeGetContent")
         .allowlist_function("xmlGetNodePath")
+        .allowlist_function("xmlNodeGetBase")
         .allowlist_function("xmlGetProp")
         .allowlist_function("xmlDocGetRootElement")
         .allowlist_function("xmlReadMemory")
         .allowlist_function("xmlCleanupParser")
         .allowlist_function("xmlInitParser")
 
+        // ========================================
+        // 树构建函数
+        // ========================================
+        .allowlist_function("xmlNewDoc")
+        .allowlist_function("xmlNewDocRawNode")
+        .allowlist_function("xmlAddChild")
+        .allowlist_function("xmlFreeNode")
+        .allowlist_function("xmlNewDocNode")
+        .allowlist_function("xmlNewDocText")
+        .allowlist_function("xmlUnlinkNode")
+        .allowlist_function("xmlHasProp")
+        .allowlist_function("xmlSetProp")
+        .allowlist_function("xmlNodeSetContent")
+        .allowlist_function("xmlNewNs")
+        .allowlist_function("xmlSetNs")
+        .allowlist_function("xmlDocSetRootElement")
+        .allowlist_function("xmlCopyDtd")
+        .allowlist_function("xmlSetTreeDoc")
+        .allowlist_function("xmlAddChildList")
+        .allowlist_function("xmlFreeNodeList")
+
+        // ========================================
+        // 解析器上下文与错误处理
+        // ========================================
+        .allowlist_function("xmlNewParserCtxt")
+        .allowlist_function("xmlFreeParserCtxt")
+        .allowlist_function("xmlCtxtReadMemory")
+        .allowlist_function("xmlCtxtReadFile")
+        .allowlist_function("xmlCreatePushParserCtxt")
+        .allowlist_function("xmlParseChunk")
+        .allowlist_function("xmlParseInNodeContext")
+        .allowlist_function("xmlCtxtUseOptions")
+        .allowlist_function("xmlCtxtSetErrorHandler")
+        .allowlist_function("xmlSetStructuredErrorFunc")
+        .allowlist_function("__xmlStructuredError")
+        .allowlist_function("__xmlStructuredErrorContext")
+
+        // ========================================
+        // SAX 解析
+        // ========================================
+        .allowlist_function("xmlSAXVersion")
+        .allowlist_function("xmlSAX2InitHtmlDefaultSAXHandler")
+        .allowlist_function("xmlNewSAXParserCtxt")
+        .allowlist_function("htmlNewSAXParserCtxt")
+        .allowlist_function("xmlStopParser")
+
+        // ========================================
+        // 序列化函数
+        // ========================================
+        .allowlist_function("htmlNodeDumpFormatOutput")
+        .allowlist_function("htmlDocContentDumpFormatOutput")
+        .allowlist_function("xmlNodeDumpOutput")
+        .allowlist_function("xmlDocDumpFormatMemoryEnc")
+        .allowlist_function("xmlAllocOutputBuffer")
+        .allowlist_function("xmlOutputBufferFlush")
+        .allowlist_function("xmlOutputBufferClose")
+        .allowlist_function("xmlOutputBufferGetContent")
+        .allowlist_function("xmlOutputBufferGetSize")
+
         // ========================================
         // XPath 核心函数
         // ========================================
@@ -121,6 +212,104 @@ fn main() {
         .allowlist_function("xmlXPathEvalExpression")
         .allowlist_function("xmlXPathFreeObject")
         .allowlist_function("xmlXPathSetContextNode")
+        .allowlist_function("xmlXPathRegisterNs")
+        .allowlist_function("xmlXPathCtxtCompile")
+        .allowlist_function("xmlXPathCompiledEval")
+        .allowlist_function("xmlXPathFreeCompExpr")
+        .allowlist_function("xmlXPathRegisterVariable")
+        .allowlist_function("xmlXPathNewString")
+        .allowlist_function("xmlXPathNewFloat")
+        .allowlist_function("xmlXPathNewBoolean")
+        .allowlist_function("xmlXPathNewNodeSet")
+        .allowlist_function("xmlXPathNodeSetAdd")
+        .allowlist_function("xmlXPathNodeSetSort")
+        .allowlist_function("xmlXPathRegisterFuncNS")
+        .allowlist_function("xmlXPathErr")
+        .allowlist_function("valuePop")
+        .allowlist_function("valuePush")
+
+        // ========================================
+        // DTD 解析与验证
+        // ========================================
+        .allowlist_function("xmlGetIntSubset")
+        .allowlist_function("xmlIOParseDTD")
+        .allowlist_function("xmlParseDTD")
+        .allowlist_function("xmlParserInputBufferCreateMem")
+        .allowlist_function("xmlFreeDtd")
+        .allowlist_function("xmlNewValidCtxt")
+        .allowlist_function("xmlFreeValidCtxt")
+        .allowlist_function("xmlValidateDtd")
+
+        // ========================================
+        // 流式读取器
+        // ========================================
+        .allowlist_function("xmlReaderForIO")
+        .allowlist_function("xmlFreeTextReader")
+        .allowlist_function("xmlTextReaderSetStructuredErrorHandler")
+        .allowlist_function("xmlTextReaderRead")
+        .allowlist_function("xmlTextReaderNext")
+        .allowlist_function("xmlTextReaderExpand")
+        .allowlist_function("xmlTextReaderNodeType")
+        .allowlist_function("xmlTextReaderDepth")
+        .allowlist_function("xmlTextReaderIsEmptyElement")
+        .allowlist_function("xmlTextReaderIsNamespaceDecl")
+        .allowlist_function("xmlTextReaderConstName")
+        .allowlist_function("xmlTextReaderConstValue")
+        .allowlist_function("xmlTextReaderMoveToNextAttribute")
+        .allowlist_function("xmlTextReaderMoveToElement")
+
+        // ========================================
+        // XML Schema / RELAX NG / Schematron 验证
+        // （schemas feature，未启用时头文件中没有这些声明）
+        // ========================================
+        .allowlist_function("xmlSchemaNewParserCtxt")
+        .allowlist_function("xmlSchemaNewMemParserCtxt")
+        .allowlist_function("xmlSchemaFreeParserCtxt")
+        .allowlist_function("xmlSchemaSetParserStructuredErrors")
+        .allowlist_function("xmlSchemaParse")
+        .allowlist_function("xmlSchemaFree")
+        .allowlist_function("xmlSchemaNewValidCtxt")
+        .allowlist_function("xmlSchemaFreeValidCtxt")
+        .allowlist_function("xmlSchemaSetValidStructuredErrors")
+        .allowlist_function("xmlSchemaValidateDoc")
+        .allowlist_function("xmlRelaxNGNewParserCtxt")
+        .allowlist_function("xmlRelaxNGNewMemParserCtxt")
+        .allowlist_function("xmlRelaxNGFreeParserCtxt")
+        .allowlist_function("xmlRelaxNGSetParserStructuredErrors")
+        .allowlist_function("xmlRelaxNGParse")
+        .allowlist_function("xmlRelaxNGFree")
+        .allowlist_function("xmlRelaxNGNewValidCtxt")
+        .allowlist_function("xmlRelaxNGFreeValidCtxt")
+        .allowlist_function("xmlRelaxNGSetValidStructuredErrors")
+        .allowlist_function("xmlRelaxNGValidateDoc")
+        .allowlist_function("xmlSchematronNewParserCtxt")
+        .allowlist_function("xmlSchematronNewMemParserCtxt")
+        .allowlist_function("xmlSchematronFreeParserCtxt")
+        .allowlist_function("xmlSchematronParse")
+        .allowlist_function("xmlSchematronFree")
+        .allowlist_function("xmlSchematronNewValidCtxt")
+        .allowlist_function("xmlSchematronFreeValidCtxt")
+        .allowlist_function("xmlSchematronSetValidStructuredErrors")
+        .allowlist_function("xmlSchematronValidateDoc")
+
+        // ========================================
+        // XSLT 转换（xslt feature，未启用时 wrapper.h 不包含 libxslt 头文件）
+        // ========================================
+        .allowlist_function("xmlCopyDoc")
+        .allowlist_function("xsltParseStylesheetDoc")
+        .allowlist_function("xsltFreeStylesheet")
+        .allowlist_function("xsltNewTransformContext")
+        .allowlist_function("xsltFreeTransformContext")
+        .allowlist_function("xsltQuoteUserParams")
+        .allowlist_function("xsltApplyStylesheetUser")
+        .allowlist_function("xsltSaveResultToString")
+        .allowlist_function("xsltNewSecurityPrefs")
+        .allowlist_function("xsltFreeSecurityPrefs")
+        .allowlist_function("xsltSetSecurityPrefs")
+        .allowlist_function("xsltSetCtxtSecurityPrefs")
+        .allowlist_function("xsltSecurityForbid")
+        .allowlist_function("rlibxml_xslt_set_error_sink")
+        .allowlist_var("xsltSecurityOption_.*")
 
         // ========================================
         // 核心类型
@@ -132,8 +321,13 @@ fn main() {
         .allowlist_type("xmlDtd")
         .allowlist_type("xmlDict")
         .allowlist_type("xmlError")
+        .allowlist_type("xmlErrorLevel")
+        .allowlist_type("xmlErrorDomain")
+        .allowlist_type("xmlParserCtxt")
+        .allowlist_type("xmlOutputBuffer")
         .allowlist_type("xmlXPathContext")
         .allowlist_type("xmlXPathObject")
+        .allowlist_type("xmlXPathCompExpr")
         .allowlist_type("xmlXPathObjectPtr")
         .allowlist_type("xmlNodeSet")
         .allowlist_type("xmlNodeSetPtr")
@@ -149,6 +343,11 @@ fn main() {
         .allowlist_var("htmlParserOption_.*")
         .allowlist_var("xmlParserOption_.*")
         .allowlist_var("xmlElementType_.*")
+        .allowlist_var("xmlErrorLevel_.*")
+        .allowlist_var("xmlErrorDomain_.*")
+        .allowlist_var("xmlCharEncoding_.*")
+        .allowlist_var("xmlReaderTypes_.*")
+        .allowlist_var("xmlSchematronValidOptions_.*")
         .allowlist_var("XPATH_.*")
         .allowlist_var("XPTR_.*")
         .allowlist_var("XML_XPATH_.*")
@@ -168,3 +367,65 @@ fn main() {
         .write_to_file(out_dir.join("bindings.rs"))
         .expect("Couldn't write bindings!");
 }
+
+/// 编译 libxslt 静态库和错误回调桥接代码，并输出链接指令
+///
+/// libxslt 通过 CMake 的 `find_package(LibXml2)` 找到刚编译的 libxml2，
+/// EXSLT 由 rlibxml 在 Rust 侧实现，不链接 libexslt。
+#[cfg(feature = "xslt")]
+fn build_libxslt(manifest_dir: &Path, libxml2_dst: &Path, target: &str) {
+    let libxslt_src = manifest_dir.join("libxslt_src");
+    println!("cargo:rerun-if-changed=libxslt_src/");
+    println!("cargo:rerun-if-changed=xslt_shim.c");
+
+    if !libxslt_src.exists() {
+        panic!(
+            "libxslt source not found! Please run: git submodule update --init --recursive\n\
+             Or manually clone: git clone https://gitlab.gnome.org/GNOME/libxslt.git libxml2-sys/libxslt_src"
+        );
+    }
+
+    let dst = cmake::Config::new(&libxslt_src)
+        .define("CMAKE_PREFIX_PATH", libxml2_dst)
+        .define("LIBXSLT_WITH_THREADS", "ON")
+        .define("LIBXSLT_WITH_CRYPTO", "OFF")
+        .define("LIBXSLT_WITH_DEBUGGER", "OFF")
+        .define("LIBXSLT_WITH_MODULES", "OFF")
+        .define("LIBXSLT_WITH_PROFILER", "OFF")
+        .define("LIBXSLT_WITH_PROGRAMS", "OFF")
+        .define("LIBXSLT_WITH_PYTHON", "OFF")
+        .define("LIBXSLT_WITH_TESTS", "OFF")
+        .define("BUILD_SHARED_LIBS", "OFF")
+        .build();
+
+    // libxslt 的错误回调是变参函数，由 C 代码格式化后转交 Rust；
+    // cc 会先输出桥接库的链接指令，保证它排在 libxslt 之前
+    cc::Build::new()
+        .file("xslt_shim.c")
+        .include(dst.join("include"))
+        .include(libxml2_dst.join("include").join("libxml2"))
+        .define("LIBXSLT_STATIC", None)
+        .define("LIBXML_STATIC", None)
+        .compile("rlibxml_xslt_shim");
+
+    println!(
+        "cargo:rustc-link-search=native={}",
+        dst.join("lib").display()
+    );
+    // 库名规则与 libxml2 相同
+    let lib_name = if target.contains("windows") {
+        if cfg!(debug_assertions) {
+            "libxsltsd"
+        } else {
+            "libxslts"
+        }
+    } else {
+        "xslt"
+    };
+    println!("cargo:rustc-link-lib=static={}", lib_name);
+}
+
+/// 把开关转换为 CMake 选项值
+fn on_off(enabled: bool) -> &'static str {
+    if enabled { "ON" } else { "OFF" }
+}
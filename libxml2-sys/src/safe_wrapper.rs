@@ -3,10 +3,19 @@
 //! 这个模块提供了对 libxml2 常用操作的安全封装，
 //! 将 unsafe 操作集中在内部处理，对外暴露安全的 API。
 
-use crate::{free_xml_char, xmlDocPtr, xmlNodePtr, xmlXPathContext, xmlXPathObject};
-use std::ffi::CStr;
-use std::os::raw::c_char;
+use crate::{
+    free_xml_char, xmlDocPtr, xmlError, xmlNodePtr, xmlParserCtxt, xmlXPathCompExpr,
+    xmlXPathContext, xmlXPathObject,
+};
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_void};
+use std::io::{self, Read};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicPtr, Ordering};
 
 // ========================================
 // 字符串转换辅助函数
@@ -82,6 +91,22 @@ pub unsafe fn node_get_name(node: xmlNodePtr) -> String {
     }
 }
 
+/// 获取元素命名空间的前缀，没有命名空间或使用默认命名空间时返回 `None`
+///
+/// # Safety
+///
+/// `node` 必须是有效的元素节点
+pub unsafe fn node_get_ns_prefix(node: xmlNodePtr) -> Option<String> {
+    // SAFETY: 调用者保证 node 是有效的元素，ns 非空时指向有效的命名空间
+    unsafe {
+        let ns = (*node).ns;
+        if ns.is_null() {
+            return None;
+        }
+        ptr_to_option_string((*ns).prefix.cast())
+    }
+}
+
 /// 获取节点的 XPath 路径
 ///
 /// # Safety
@@ -99,6 +124,32 @@ pub unsafe fn node_get_path(node: xmlNodePtr) -> String {
     result
 }
 
+/// 获取节点的基准 URL（由文档 URL 和祖先元素的 `xml:base` 计算），未知时返回 `None`
+///
+/// HTML 文档使用 `<base href>`，没有该元素时返回文档 URL。
+///
+/// # Safety
+///
+/// `node` 必须是有效的 xmlNodePtr
+pub unsafe fn node_get_base(node: xmlNodePtr) -> Option<String> {
+    // SAFETY: 调用者保证 node 是有效的
+    unsafe {
+        let doc = (*node).doc;
+        let base = crate::xmlNodeGetBase(doc, node);
+        if base.is_null() {
+            // HTML 文档没有 <base> 时 xmlNodeGetBase 不会回退到文档 URL
+            return if doc.is_null() {
+                None
+            } else {
+                doc_get_url(doc)
+            };
+        }
+        let result = ptr_to_string(base.cast());
+        free_xml_char(base.cast());
+        Some(result)
+    }
+}
+
 /// 获取节点的类型
 ///
 /// # Safety
@@ -140,8 +191,40 @@ pub unsafe fn doc_get_root_element(doc: xmlDocPtr) -> xmlNodePtr {
     unsafe { crate::xmlDocGetRootElement(doc) }
 }
 
+/// 获取文档声明或探测到的原始编码名（如 `ISO-8859-1`），未知时返回 `None`
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_get_encoding(doc: xmlDocPtr) -> Option<String> {
+    // SAFETY: 调用者保证 doc 是有效的
+    unsafe { ptr_to_option_string((*doc).encoding.cast()) }
+}
+
+/// 获取文档的 URL（解析文件时为文件路径），未知时返回 `None`
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_get_url(doc: xmlDocPtr) -> Option<String> {
+    // SAFETY: 调用者保证 doc 是有效的
+    unsafe { ptr_to_option_string((*doc).URL.cast()) }
+}
+
+/// 获取文档解析时使用的选项（`xmlParserOption` / `htmlParserOption` 的组合）
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_get_parse_flags(doc: xmlDocPtr) -> i32 {
+    // SAFETY: 调用者保证 doc 是有效的
+    unsafe { (*doc).parseFlags }
+}
+
 /// 释放文档
 ///
+/// 同时释放文档附带的片段存储（见 [`doc_new_fragment_elements`]）。
+///
 /// # Safety
 ///
 /// `doc` 必须是有效的 xmlDocPtr，且只能释放一次
@@ -149,7 +232,10 @@ pub unsafe fn doc_get_root_element(doc: xmlDocPtr) -> xmlNodePtr {
 pub unsafe fn doc_free(doc: xmlDocPtr) {
     // SAFETY: 调用者保证 doc 是有效的且只释放一次
     if !doc.is_null() {
-        unsafe { crate::xmlFreeDoc(doc) };
+        unsafe {
+            free_fragment_store(doc);
+            crate::xmlFreeDoc(doc);
+        }
     }
 }
 
@@ -245,6 +331,37 @@ pub unsafe fn node_has_parent(node: xmlNodePtr) -> bool {
     unsafe { !(*node).parent.is_null() }
 }
 
+/// 获取元素节点作用域内的命名空间声明
+///
+/// 从节点自身向祖先方向收集 `xmlns` 声明，同一前缀以最近的声明为准。
+/// 返回 `(前缀, URI)` 列表，默认命名空间的前缀为 `None`。
+///
+/// # Safety
+///
+/// `node` 必须是有效的 xmlNodePtr
+pub unsafe fn node_get_in_scope_namespaces(node: xmlNodePtr) -> Vec<(Option<String>, String)> {
+    let mut result: Vec<(Option<String>, String)> = Vec::new();
+    let mut current = node;
+
+    // SAFETY: 调用者保证 node 有效；只有元素节点的 nsDef 字段有意义，
+    // 遇到文档节点等其他类型时停止（其结构体布局与 xmlNode 不同）
+    unsafe {
+        while !current.is_null() && (*current).type_ == crate::xmlElementType_XML_ELEMENT_NODE {
+            let mut ns = (*current).nsDef;
+            while !ns.is_null() {
+                let prefix = ptr_to_option_string((*ns).prefix.cast());
+                if !result.iter().any(|(p, _)| *p == prefix) {
+                    result.push((prefix, ptr_to_string((*ns).href.cast())));
+                }
+                ns = (*ns).next;
+            }
+            current = (*current).parent;
+        }
+    }
+
+    result
+}
+
 // ========================================
 // 属性遍历封装
 // ========================================
@@ -315,262 +432,2892 @@ pub unsafe fn parse_xml_memory(xml: *const c_char, size: i32, options: i32) -> x
 }
 
 // ========================================
-// XPath 操作封装
+// 序列化封装
 // ========================================
 
-/// XPath 查询结果类型常量
-pub const XPATH_NODESET: i32 = 1;
-pub const XPATH_BOOLEAN: i32 = 2;
-pub const XPATH_NUMBER: i32 = 3;
-pub const XPATH_STRING: i32 = 4;
+/// 使用临时输出缓冲区执行序列化，并把结果复制为 String
+///
+/// # Safety
+///
+/// `dump` 只能向传入的缓冲区写入
+unsafe fn dump_to_string(dump: impl FnOnce(*mut crate::xmlOutputBuffer)) -> String {
+    // SAFETY: 传入 null 表示不做编码转换，输出为 UTF-8
+    let buf = unsafe { crate::xmlAllocOutputBuffer(ptr::null_mut()) };
+    if buf.is_null() {
+        return String::new();
+    }
 
-/// XPath 查询的原始结果
+    dump(buf);
+
+    // SAFETY: buf 有效，内容指针在 close 之前一直有效
+    unsafe {
+        crate::xmlOutputBufferFlush(buf);
+        let content = crate::xmlOutputBufferGetContent(buf);
+        let size = crate::xmlOutputBufferGetSize(buf);
+        let result = if content.is_null() || size == 0 {
+            String::new()
+        } else {
+            let bytes = std::slice::from_raw_parts(content, size);
+            String::from_utf8_lossy(bytes).into_owned()
+        };
+        crate::xmlOutputBufferClose(buf);
+        result
+    }
+}
+
+/// 以 HTML 语法序列化节点（包含节点自身）
 ///
-/// 用于内部传递 XPath 查询的原始结果，由调用者负责类型安全。
-pub struct RawXPathResult {
-    /// XPath 对象指针
-    pub object: *mut xmlXPathObject,
-    /// 结果类型
-    pub result_type: i32,
+/// 空元素（如 `<br>`）不输出结束标签，文本与属性值会被正确转义。
+///
+/// # Safety
+///
+/// - `doc` 必须是 `node` 所属的有效文档指针
+/// - `node` 必须是有效的 xmlNodePtr
+pub unsafe fn node_dump_html(doc: xmlDocPtr, node: xmlNodePtr) -> String {
+    // SAFETY: 调用者保证参数有效
+    unsafe {
+        dump_to_string(|buf| {
+            crate::htmlNodeDumpFormatOutput(buf, doc, node, c"UTF-8".as_ptr(), 0);
+        })
+    }
 }
 
-impl RawXPathResult {
-    /// 创建新的原始结果
-    ///
-    /// # Safety
-    ///
-    /// `object` 必须是有效的 xmlXPathObject 指针
-    #[inline]
-    pub unsafe fn new(object: *mut xmlXPathObject) -> Self {
-        // SAFETY: 调用者保证 object 有效
-        let result_type = unsafe { (*object).type_ as i32 };
-        Self {
-            object,
-            result_type,
-        }
+/// 以 XML 语法序列化节点（包含节点自身）
+///
+/// # Safety
+///
+/// - `doc` 必须是 `node` 所属的有效文档指针
+/// - `node` 必须是有效的 xmlNodePtr
+pub unsafe fn node_dump_xml(doc: xmlDocPtr, node: xmlNodePtr, format: bool) -> String {
+    // SAFETY: 调用者保证参数有效
+    unsafe {
+        dump_to_string(|buf| {
+            crate::xmlNodeDumpOutput(buf, doc, node, 0, format as i32, c"UTF-8".as_ptr());
+        })
     }
+}
 
-    /// 提取布尔值
-    ///
-    /// # Safety
-    ///
-    /// 必须是布尔类型的结果
-    #[inline]
-    pub unsafe fn as_boolean(&self) -> bool {
-        // SAFETY: 调用者保证 object 有效且类型正确
-        unsafe { (*self.object).boolval != 0 }
+/// 以 HTML 语法序列化整个文档（包含 DOCTYPE）
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_dump_html(doc: xmlDocPtr, format: bool) -> String {
+    // SAFETY: 调用者保证 doc 有效
+    unsafe {
+        dump_to_string(|buf| {
+            crate::htmlDocContentDumpFormatOutput(buf, doc, c"UTF-8".as_ptr(), format as i32);
+        })
     }
+}
 
-    /// 提取数字值
-    ///
-    /// # Safety
-    ///
-    /// 必须是数字类型的结果
-    #[inline]
-    pub unsafe fn as_number(&self) -> f64 {
-        // SAFETY: 调用者保证 object 有效且类型正确
-        unsafe { (*self.object).floatval }
+/// 以 XML 语法序列化整个文档（包含 XML 声明，编码为 UTF-8）
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_dump_xml(doc: xmlDocPtr, format: bool) -> String {
+    let mut mem: *mut crate::xmlChar = ptr::null_mut();
+    let mut size: i32 = 0;
+    // SAFETY: 调用者保证 doc 有效，mem/size 是有效的输出参数
+    unsafe {
+        crate::xmlDocDumpFormatMemoryEnc(
+            doc,
+            &mut mem,
+            &mut size,
+            c"UTF-8".as_ptr(),
+            format as i32,
+        );
+        if mem.is_null() {
+            return String::new();
+        }
+        let bytes = std::slice::from_raw_parts(mem, size.max(0) as usize);
+        let result = String::from_utf8_lossy(bytes).into_owned();
+        free_xml_char(mem);
+        result
     }
+}
 
-    /// 提取字符串值
+// ========================================
+// 解析诊断封装
+// ========================================
+
+/// libxml2 结构化错误的拷贝
+///
+/// 在错误回调中从 `xmlError` 复制出来，不再引用任何 libxml2 内存。
+#[derive(Debug, Clone)]
+pub struct RawParseError {
+    /// 错误来源模块（`xmlErrorDomain`）
+    pub domain: i32,
+    /// 错误码（`xmlParserErrors`）
+    pub code: i32,
+    /// 错误级别（`xmlErrorLevel`）
+    pub level: i32,
+    /// 错误消息（已去除末尾换行）
+    pub message: String,
+    /// 出错的文件名或 URL
+    pub file: Option<String>,
+    /// 行号（未知时为 0）
+    pub line: i32,
+    /// 列号（未知时为 0）
+    pub column: i32,
+    /// 出错节点的路径（验证错误等与节点相关的错误才有）
+    pub path: Option<String>,
+    /// 附加字符串参数（`xmlError` 的 `str1`、`str2`、`str3`），含义随错误码而定，
+    /// 如 Schematron 错误的 `str3` 是断言文本
+    pub args: [Option<String>; 3],
+}
+
+impl RawParseError {
+    /// 从 libxml2 错误结构复制
     ///
     /// # Safety
     ///
-    /// 必须是字符串类型的结果
-    #[inline]
-    pub unsafe fn as_string(&self) -> String {
-        // SAFETY: 调用者保证 object 有效且类型正确
+    /// `error` 必须是有效的 xmlError 指针，其 `node` 字段为空或指向有效节点
+    pub unsafe fn from_raw(error: *const xmlError) -> Self {
+        // SAFETY: 调用者保证 error 有效
         unsafe {
-            let stringval = (*self.object).stringval;
-            if stringval.is_null() {
-                String::new()
-            } else {
-                ptr_to_string(stringval.cast())
+            let message = ptr_to_string((*error).message.cast());
+            Self {
+                domain: (*error).domain,
+                code: (*error).code,
+                level: (*error).level as i32,
+                message: message.trim_end().to_string(),
+                file: ptr_to_option_string((*error).file.cast()),
+                line: (*error).line,
+                // 对于解析器错误，int2 保存列号
+                column: (*error).int2,
+                path: if (*error).node.is_null() {
+                    None
+                } else {
+                    Some(node_get_path((*error).node.cast()))
+                },
+                args: [
+                    ptr_to_option_string((*error).str1.cast()),
+                    ptr_to_option_string((*error).str2.cast()),
+                    ptr_to_option_string((*error).str3.cast()),
+                ],
             }
         }
     }
+}
+
+/// 结构化错误回调：把错误追加到 `data` 指向的 `Vec<RawParseError>`
+unsafe extern "C" fn collect_parse_error(data: *mut c_void, error: *const xmlError) {
+    if data.is_null() || error.is_null() {
+        return;
+    }
+    // SAFETY: data 由 ParserContextGuard 设置，指向其持有的 Vec
+    unsafe {
+        // XML_ERR_NONE 不是错误，不记录
+        if (*error).level == crate::xmlErrorLevel_XML_ERR_NONE {
+            return;
+        }
+        let errors = &mut *(data as *mut Vec<RawParseError>);
+        errors.push(RawParseError::from_raw(error));
+    }
+}
 
-    /// 提取节点集合
+/// 临时替换当前线程的全局结构化错误回调的守卫
+///
+/// 释放时（包括 panic 展开时）恢复替换前的回调及其上下文，
+/// 不会覆盖使用方应用自己安装的回调。
+struct StructuredErrorScope {
+    handler: crate::xmlStructuredErrorFunc,
+    context: *mut c_void,
+}
+
+impl StructuredErrorScope {
+    /// 保存当前回调并安装 `handler`
     ///
     /// # Safety
     ///
-    /// 必须是节点集合类型的结果
-    #[inline]
-    pub unsafe fn as_nodeset(&self) -> Vec<xmlNodePtr> {
-        // SAFETY: 调用者保证 object 有效且类型正确
+    /// `context` 在守卫存活期间必须对 `handler` 有效
+    unsafe fn install(context: *mut c_void, handler: crate::xmlStructuredErrorFunc) -> Self {
+        // SAFETY: 两个函数返回当前线程全局变量的地址，始终有效
         unsafe {
-            let mut nodes = Vec::new();
-            let nodeset = (*self.object).nodesetval;
-            if !nodeset.is_null() && (*nodeset).nodeNr > 0 {
-                let node_count = (*nodeset).nodeNr as isize;
-                let node_tab = (*nodeset).nodeTab;
-
-                for i in 0..node_count {
-                    let node = *node_tab.offset(i);
-                    if !node.is_null() {
-                        nodes.push(node);
-                    }
-                }
-            }
-            nodes
+            let saved = Self {
+                handler: *crate::__xmlStructuredError(),
+                context: *crate::__xmlStructuredErrorContext(),
+            };
+            crate::xmlSetStructuredErrorFunc(context, handler);
+            saved
         }
     }
 }
 
-impl Drop for RawXPathResult {
+impl Drop for StructuredErrorScope {
     fn drop(&mut self) {
-        // SAFETY: object 在 drop 时仍然有效
-        unsafe {
-            crate::xmlXPathFreeObject(self.object);
-        }
+        // SAFETY: 恢复的是安装前的回调与上下文，由原先的安装者保证有效
+        unsafe { crate::xmlSetStructuredErrorFunc(self.context, self.handler) };
     }
 }
 
-/// XPath 上下文守卫，确保正确释放资源
-pub struct XPathContextGuard {
-    ctx: *mut xmlXPathContext,
+/// 在当前线程临时注册全局结构化错误回调，收集 `f` 执行期间的错误
+///
+/// 用于没有上下文级错误回调的接口（如 DTD 验证、Schematron 规则的编译）。
+/// libxml2 的全局错误回调是线程局部的，不影响其他线程；结束后恢复原来的回调。
+///
+/// # Safety
+///
+/// `f` 内部不能再注册全局错误回调
+unsafe fn collect_global_errors<T>(f: impl FnOnce() -> T) -> (T, Vec<RawParseError>) {
+    let mut errors = Vec::new();
+    // SAFETY: errors 比守卫存活得更久，守卫释放时注销回调
+    let result = unsafe {
+        let _scope =
+            StructuredErrorScope::install((&raw mut errors).cast(), Some(collect_parse_error));
+        f()
+    };
+    (result, errors)
 }
 
-impl XPathContextGuard {
-    /// 创建新的 XPath 上下文
+/// 解析器上下文守卫，确保正确释放资源并收集解析诊断
+///
+/// 创建时即注册结构化错误回调，解析过程中的所有错误和警告都会被记录，
+/// 而不会输出到 stderr。
+pub struct ParserContextGuard {
+    ctxt: *mut xmlParserCtxt,
+    // 装箱保证地址稳定，回调通过裸指针写入
+    #[allow(clippy::box_collection)]
+    errors: Box<Vec<RawParseError>>,
+}
+
+impl ParserContextGuard {
+    /// 创建 HTML 解析器上下文
+    pub fn new_html() -> Option<Self> {
+        // SAFETY: htmlNewParserCtxt 无前置条件
+        unsafe { Self::from_ctxt(crate::htmlNewParserCtxt()) }
+    }
+
+    /// 创建 XML 解析器上下文
+    pub fn new_xml() -> Option<Self> {
+        // SAFETY: xmlNewParserCtxt 无前置条件
+        unsafe { Self::from_ctxt(crate::xmlNewParserCtxt()) }
+    }
+
+    /// 接管解析器上下文并注册错误回调
     ///
     /// # Safety
     ///
-    /// `doc` 必须是有效的文档指针
-    #[inline]
-    pub unsafe fn new(doc: xmlDocPtr) -> Option<Self> {
-        // SAFETY: 调用者保证 doc 有效
-        let ctx = unsafe { crate::xmlXPathNewContext(doc) };
-        if ctx.is_null() {
-            None
-        } else {
-            Some(Self { ctx })
+    /// `ctxt` 必须是新创建的解析器上下文指针或 null，所有权转移给返回值
+    pub unsafe fn from_ctxt(ctxt: *mut xmlParserCtxt) -> Option<Self> {
+        if ctxt.is_null() {
+            return None;
         }
+        let mut guard = Self {
+            ctxt,
+            errors: Box::default(),
+        };
+        let data = &mut *guard.errors as *mut Vec<RawParseError> as *mut c_void;
+        // SAFETY: ctxt 有效；data 指向的 Vec 与上下文同生共死
+        unsafe { crate::xmlCtxtSetErrorHandler(ctxt, Some(collect_parse_error), data) };
+        Some(guard)
     }
 
-    /// 设置上下文节点
+    /// 获取原始上下文指针
+    #[inline]
+    pub fn as_ptr(&self) -> *mut xmlParserCtxt {
+        self.ctxt
+    }
+
+    /// 从内存解析 HTML 文档
+    ///
+    /// `url` 记录为文档的 URL，用于解析相对引用，可以为 null。
     ///
     /// # Safety
     ///
-    /// `node` 必须是有效的节点指针
+    /// - `html` 必须指向至少 `size` 字节的有效内存
+    /// - `url` 必须是有效的 C 字符串或 null
+    /// - `options` 必须是有效的 HTML 解析选项组合
     #[inline]
-    pub unsafe fn set_context_node(&mut self, node: xmlNodePtr) -> bool {
-        // SAFETY: 调用者保证 node 有效
-        unsafe { crate::xmlXPathSetContextNode(node, self.ctx) == 0 }
+    pub unsafe fn read_html_memory(
+        &mut self,
+        html: *const c_char,
+        size: i32,
+        url: *const c_char,
+        options: i32,
+    ) -> xmlDocPtr {
+        // SAFETY: 调用者保证参数有效
+        unsafe { crate::htmlCtxtReadMemory(self.ctxt, html, size, url, c"UTF-8".as_ptr(), options) }
     }
 
-    /// 执行 XPath 表达式
+    /// 从内存解析 XML 文档
+    ///
+    /// `url` 记录为文档的 URL，用于解析相对引用和外部 DTD，可以为 null。
     ///
     /// # Safety
     ///
-    /// `xpath` 必须是有效的以 null 结尾的 C 字符串
+    /// - `xml` 必须指向至少 `size` 字节的有效内存
+    /// - `url` 必须是有效的 C 字符串或 null
+    /// - `options` 必须是有效的 XML 解析选项组合
     #[inline]
-    pub unsafe fn evaluate(&mut self, xpath: *const crate::xmlChar) -> Option<RawXPathResult> {
+    pub unsafe fn read_xml_memory(
+        &mut self,
+        xml: *const c_char,
+        size: i32,
+        url: *const c_char,
+        options: i32,
+    ) -> xmlDocPtr {
         // SAFETY: 调用者保证参数有效
-        let obj = unsafe { crate::xmlXPathEvalExpression(xpath, self.ctx) };
-        if obj.is_null() {
-            None
-        } else {
-            // SAFETY: obj 刚刚创建且非空
-            Some(unsafe { RawXPathResult::new(obj) })
-        }
+        unsafe { crate::xmlCtxtReadMemory(self.ctxt, xml, size, url, ptr::null(), options) }
     }
-}
 
-impl Drop for XPathContextGuard {
-    fn drop(&mut self) {
-        // SAFETY: ctx 在 drop 时仍然有效
-        unsafe {
-            crate::xmlXPathFreeContext(self.ctx);
-        }
+    /// 按 UTF-8 从文件流式解析 HTML 文档，文件路径记录为文档的 URL
+    ///
+    /// # Safety
+    ///
+    /// - `filename` 必须是有效的 C 字符串
+    /// - `options` 必须是有效的 HTML 解析选项组合
+    #[inline]
+    pub unsafe fn read_html_file(&mut self, filename: *const c_char, options: i32) -> xmlDocPtr {
+        // SAFETY: 调用者保证参数有效
+        unsafe { crate::htmlCtxtReadFile(self.ctxt, filename, c"UTF-8".as_ptr(), options) }
     }
-}
 
-/// 在文档上执行 XPath 查询
-///
-/// # Safety
-///
-/// - `doc` 必须是有效的文档指针
-/// - `xpath` 必须是有效的以 null 结尾的 C 字符串
-pub unsafe fn xpath_evaluate(
-    doc: xmlDocPtr,
-    xpath: *const crate::xmlChar,
-) -> Option<RawXPathResult> {
+    /// 从文件流式解析 XML 文档，文件路径记录为文档的 URL
+    ///
+    /// 编码由 BOM 和 XML 声明决定。
+    ///
+    /// # Safety
+    ///
+    /// - `filename` 必须是有效的 C 字符串
+    /// - `options` 必须是有效的 XML 解析选项组合
+    #[inline]
+    pub unsafe fn read_xml_file(&mut self, filename: *const c_char, options: i32) -> xmlDocPtr {
+        // SAFETY: 调用者保证参数有效
+        unsafe { crate::xmlCtxtReadFile(self.ctxt, filename, ptr::null(), options) }
+    }
+
+    /// 取出目前收集到的所有诊断
+    pub fn take_errors(&mut self) -> Vec<RawParseError> {
+        std::mem::take(&mut *self.errors)
+    }
+
+    /// 创建 HTML 推送解析器上下文并应用解析选项
+    ///
+    /// 与 [`ParserContextGuard::read_html_memory`] 一样按 UTF-8 解析，忽略文档内的编码声明
+    /// （不指定编码时推送解析器会回退到 ISO-8859-1）。
+    pub fn new_html_push(options: i32) -> Option<Self> {
+        // SAFETY: 不提供初始数据和 SAX 处理器，参数均为合法的空值
+        let guard = unsafe {
+            Self::from_ctxt(crate::htmlCreatePushParserCtxt(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null(),
+                0,
+                ptr::null(),
+                crate::xmlCharEncoding_XML_CHAR_ENCODING_UTF8,
+            ))
+        }?;
+        // SAFETY: ctxt 有效
+        unsafe { crate::htmlCtxtUseOptions(guard.ctxt, options) };
+        Some(guard)
+    }
+
+    /// 创建 XML 推送解析器上下文并应用解析选项
+    ///
+    /// 编码由 BOM 和 XML 声明决定。
+    pub fn new_xml_push(options: i32) -> Option<Self> {
+        // SAFETY: 不提供初始数据和 SAX 处理器，参数均为合法的空值
+        let guard = unsafe {
+            Self::from_ctxt(crate::xmlCreatePushParserCtxt(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null(),
+                0,
+                ptr::null(),
+            ))
+        }?;
+        // SAFETY: ctxt 有效
+        unsafe { crate::xmlCtxtUseOptions(guard.ctxt, options) };
+        Some(guard)
+    }
+
+    /// 向 HTML 推送解析器提供一块数据，`terminate` 为 `true` 表示输入结束
+    ///
+    /// HTML 解析总会尝试恢复，返回值只反映最近一条错误，不代表解析失败。
+    ///
+    /// # Safety
+    ///
+    /// - 上下文必须由 [`ParserContextGuard::new_html_push`] 创建
+    /// - `chunk` 必须指向至少 `size` 字节的有效内存（`size` 为 0 时可以为 null）
+    pub unsafe fn parse_html_chunk(
+        &mut self,
+        chunk: *const c_char,
+        size: i32,
+        terminate: bool,
+    ) -> i32 {
+        // SAFETY: 调用者保证参数有效
+        unsafe { crate::htmlParseChunk(self.ctxt, chunk, size, terminate as c_int) }
+    }
+
+    /// 向 XML 推送解析器提供一块数据，`terminate` 为 `true` 表示输入结束
+    ///
+    /// 遇到致命错误后返回非零错误码，之后的数据都会被忽略。
+    ///
+    /// # Safety
+    ///
+    /// - 上下文必须由 [`ParserContextGuard::new_xml_push`] 创建
+    /// - `chunk` 必须指向至少 `size` 字节的有效内存（`size` 为 0 时可以为 null）
+    pub unsafe fn parse_xml_chunk(
+        &mut self,
+        chunk: *const c_char,
+        size: i32,
+        terminate: bool,
+    ) -> i32 {
+        // SAFETY: 调用者保证参数有效
+        unsafe { crate::xmlParseChunk(self.ctxt, chunk, size, terminate as c_int) }
+    }
+
+    /// 正在构建的文档（推送解析尚未结束时为部分文档，还没有数据时为 null）
+    ///
+    /// 文档归上下文所有，下一次提供数据时可能被修改。
+    #[inline]
+    pub fn document(&self) -> xmlDocPtr {
+        // SAFETY: ctxt 有效
+        unsafe { (*self.ctxt).myDoc }
+    }
+
+    /// 取走解析得到的文档，并返回目前为止文档是否格式良好
+    ///
+    /// 文档所有权转移给调用者（为 null 时表示没有产生文档）。
+    pub fn take_document(&mut self) -> (xmlDocPtr, bool) {
+        // SAFETY: ctxt 有效；置空 myDoc 后上下文不再引用该文档
+        unsafe {
+            let doc = (*self.ctxt).myDoc;
+            (*self.ctxt).myDoc = ptr::null_mut();
+            (doc, (*self.ctxt).wellFormed != 0)
+        }
+    }
+}
+
+impl Drop for ParserContextGuard {
+    fn drop(&mut self) {
+        // SAFETY: ctxt 在 drop 时仍然有效；一次性解析结束时 myDoc 已被置空，
+        // 只有未取走结果的推送解析器还持有文档
+        unsafe {
+            doc_free((*self.ctxt).myDoc);
+            crate::xmlFreeParserCtxt(self.ctxt);
+        }
+    }
+}
+
+/// 从内存解析 HTML 文档，并收集解析诊断
+///
+/// 返回文档指针（失败时为 null）和解析过程中产生的全部错误与警告。
+///
+/// # Safety
+///
+/// - `html` 必须指向至少 `size` 字节的有效内存
+/// - `url` 必须是有效的 C 字符串或 null
+/// - `options` 必须是有效的解析选项组合
+pub unsafe fn parse_html_memory_with_errors(
+    html: *const c_char,
+    size: i32,
+    url: *const c_char,
+    options: i32,
+) -> (xmlDocPtr, Vec<RawParseError>) {
+    let Some(mut ctxt) = ParserContextGuard::new_html() else {
+        return (ptr::null_mut(), Vec::new());
+    };
     // SAFETY: 调用者保证参数有效
-    let mut ctx = unsafe { XPathContextGuard::new(doc)? };
-    unsafe { ctx.evaluate(xpath) }
+    let doc = unsafe { ctxt.read_html_memory(html, size, url, options) };
+    (doc, ctxt.take_errors())
 }
 
-/// 在节点上下文中执行 XPath 查询
+/// 从内存解析 XML 文档，并收集解析诊断
+///
+/// 返回文档指针（失败时为 null）和解析过程中产生的全部错误与警告。
 ///
 /// # Safety
 ///
-/// - `node` 必须是有效的节点指针
-/// - `xpath` 必须是有效的以 null 结尾的 C 字符串
-pub unsafe fn xpath_evaluate_on_node(
-    node: xmlNodePtr,
-    xpath: *const crate::xmlChar,
-) -> Option<RawXPathResult> {
+/// - `xml` 必须指向至少 `size` 字节的有效内存
+/// - `url` 必须是有效的 C 字符串或 null
+/// - `options` 必须是有效的解析选项组合
+pub unsafe fn parse_xml_memory_with_errors(
+    xml: *const c_char,
+    size: i32,
+    url: *const c_char,
+    options: i32,
+) -> (xmlDocPtr, Vec<RawParseError>) {
+    let Some(mut ctxt) = ParserContextGuard::new_xml() else {
+        return (ptr::null_mut(), Vec::new());
+    };
     // SAFETY: 调用者保证参数有效
-    let doc = unsafe { node_get_document(node) };
-    if doc.is_null() {
-        return None;
-    }
+    let doc = unsafe { ctxt.read_xml_memory(xml, size, url, options) };
+    (doc, ctxt.take_errors())
+}
 
-    let mut ctx = unsafe { XPathContextGuard::new(doc)? };
-    if !unsafe { ctx.set_context_node(node) } {
-        return None;
+/// 按 UTF-8 从文件流式解析 HTML 文档，并收集解析诊断
+///
+/// 文件由 libxml2 分块读取，不会整体读入内存。
+///
+/// # Safety
+///
+/// - `filename` 必须是有效的 C 字符串
+/// - `options` 必须是有效的解析选项组合
+pub unsafe fn parse_html_file_with_errors(
+    filename: *const c_char,
+    options: i32,
+) -> (xmlDocPtr, Vec<RawParseError>) {
+    let Some(mut ctxt) = ParserContextGuard::new_html() else {
+        return (ptr::null_mut(), Vec::new());
+    };
+    // SAFETY: 调用者保证参数有效
+    let doc = unsafe { ctxt.read_html_file(filename, options) };
+    (doc, ctxt.take_errors())
+}
+
+/// 从文件流式解析 XML 文档，并收集解析诊断
+///
+/// 文件由 libxml2 分块读取，不会整体读入内存。
+///
+/// # Safety
+///
+/// - `filename` 必须是有效的 C 字符串
+/// - `options` 必须是有效的解析选项组合
+pub unsafe fn parse_xml_file_with_errors(
+    filename: *const c_char,
+    options: i32,
+) -> (xmlDocPtr, Vec<RawParseError>) {
+    let Some(mut ctxt) = ParserContextGuard::new_xml() else {
+        return (ptr::null_mut(), Vec::new());
+    };
+    // SAFETY: 调用者保证参数有效
+    let doc = unsafe { ctxt.read_xml_file(filename, options) };
+    (doc, ctxt.take_errors())
+}
+
+/// 以 `context` 为上下文解析 XML 片段（`xmlParseInNodeContext`）
+///
+/// 解析在新建的文档中进行：新文档复制原文档的 DTD，根元素与 `context` 同名，并声明
+/// `context` 作用域内的全部命名空间，片段节点成为根元素的子节点。这样片段可以引用 DTD
+/// 中声明的实体、使用上下文中的命名空间前缀，而原文档不会被修改。
+/// 解析选项沿用原文档的 `parseFlags`。
+///
+/// 返回新文档、`xmlParseInNodeContext` 的结果码（0 表示片段格式良好）和诊断；
+/// 结果码不为 0 时根元素没有子节点。无法创建文档时返回的文档为 null。
+///
+/// # Safety
+///
+/// - `context` 必须是 XML 文档中有效的元素节点，解析期间原文档不被修改
+/// - `data` 必须指向至少 `size` 字节的有效内存
+pub unsafe fn parse_xml_in_node_context(
+    context: xmlNodePtr,
+    data: *const c_char,
+    size: i32,
+) -> (xmlDocPtr, i32, Vec<RawParseError>) {
+    // SAFETY: 调用者保证 context 和 data 有效；新文档及其节点只在这里构建，
+    // 对原文档只有读取（复制 DTD、读取名称与命名空间）
+    unsafe {
+        let source = (*context).doc;
+        let doc = crate::xmlNewDoc(c"1.0".as_ptr().cast());
+        if doc.is_null() {
+            return (ptr::null_mut(), -1, Vec::new());
+        }
+        // xmlParseInNodeContext 会修改上下文节点并使用其文档的字典，不能直接在可能被
+        // 多个线程共享的原文档中解析，因此先构建一个只包含 DTD 和上下文元素的副本
+        let source_dtd = (*source).intSubset;
+        if !source_dtd.is_null() {
+            let dtd = crate::xmlCopyDtd(source_dtd);
+            if !dtd.is_null() {
+                (*doc).intSubset = dtd;
+                crate::xmlSetTreeDoc(dtd.cast(), doc);
+                crate::xmlAddChild(doc.cast(), dtd.cast());
+            }
+        }
+
+        let root = crate::xmlNewDocNode(doc, ptr::null_mut(), (*context).name, ptr::null());
+        if root.is_null() {
+            crate::xmlFreeDoc(doc);
+            return (ptr::null_mut(), -1, Vec::new());
+        }
+        crate::xmlDocSetRootElement(doc, root);
+        let context_prefix = node_get_ns_prefix(context);
+        for (prefix, href) in node_get_in_scope_namespaces(context) {
+            // 空 URI 表示取消默认命名空间（xmlns=""），片段中不带前缀的元素没有命名空间
+            if href.is_empty() {
+                continue;
+            }
+            let Ok(href) = CString::new(href) else {
+                continue;
+            };
+            let prefix_c = prefix.as_deref().and_then(|p| CString::new(p).ok());
+            let ns = crate::xmlNewNs(
+                root,
+                href.as_ptr().cast(),
+                prefix_c.as_ref().map_or(ptr::null(), |p| p.as_ptr().cast()),
+            );
+            if !ns.is_null() && prefix == context_prefix && !(*context).ns.is_null() {
+                crate::xmlSetNs(root, ns);
+            }
+        }
+
+        let options = (*source).parseFlags;
+        let mut list: xmlNodePtr = ptr::null_mut();
+        let (ret, errors) = collect_global_errors(|| {
+            crate::xmlParseInNodeContext(root, data, size, options, &mut list)
+        });
+        let ret = ret as i32;
+        if ret == 0 {
+            crate::xmlAddChildList(root, list);
+        } else {
+            // 出错时 libxml2 通常已经释放节点列表并置空
+            crate::xmlFreeNodeList(list);
+        }
+        (doc, ret, errors)
     }
-    unsafe { ctx.evaluate(xpath) }
 }
 
 // ========================================
-// 属性遍历高级封装
+// SAX 封装
 // ========================================
 
-/// 获取节点的所有属性
+/// SAX 事件中的元素名或属性名，直接引用解析器的缓冲区
+#[derive(Debug, Clone, Copy)]
+pub struct RawSaxName<'a> {
+    /// 本地名（HTML 中为完整的名称）
+    pub local_name: &'a [u8],
+    /// 命名空间前缀
+    pub prefix: Option<&'a [u8]>,
+    /// 命名空间 URI
+    pub uri: Option<&'a [u8]>,
+}
+
+/// SAX 开始标签事件中的属性列表，直接引用解析器的缓冲区
+#[derive(Debug, Clone, Copy)]
+pub struct RawSaxAttributes<'a> {
+    // XML（SAX2）：每个属性 5 个指针，依次为本地名、前缀、URI、值的起点和终点；
+    // HTML：名称/值对，布尔属性的值为 null
+    ptrs: &'a [*const crate::xmlChar],
+    html: bool,
+}
+
+impl<'a> RawSaxAttributes<'a> {
+    fn stride(&self) -> usize {
+        if self.html { 2 } else { 5 }
+    }
+
+    /// 属性个数
+    pub fn len(&self) -> usize {
+        self.ptrs.len() / self.stride()
+    }
+
+    /// 是否没有属性
+    pub fn is_empty(&self) -> bool {
+        self.ptrs.is_empty()
+    }
+
+    /// 第 `index` 个属性的名称和值
+    pub fn get(&self, index: usize) -> Option<(RawSaxName<'a>, &'a [u8])> {
+        let stride = self.stride();
+        let entry = self.ptrs.get(index * stride..(index + 1) * stride)?;
+        // SAFETY: 指针由解析器在回调期间提供，名称为以 null 结尾的字符串，
+        // SAX2 的值由起点和终点指针界定
+        unsafe {
+            if self.html {
+                let name = RawSaxName {
+                    local_name: xml_bytes(entry[0]),
+                    prefix: None,
+                    uri: None,
+                };
+                let value = option_xml_bytes(entry[1]).unwrap_or_default();
+                Some((name, value))
+            } else {
+                let name = RawSaxName {
+                    local_name: xml_bytes(entry[0]),
+                    prefix: option_xml_bytes(entry[1]),
+                    uri: option_xml_bytes(entry[2]),
+                };
+                let len = entry[4].offset_from(entry[3]).max(0) as usize;
+                Some((name, std::slice::from_raw_parts(entry[3], len)))
+            }
+        }
+    }
+}
+
+/// SAX 解析产生的事件，其中的字符串只在回调期间有效
+#[derive(Debug, Clone, Copy)]
+pub enum RawSaxEvent<'a> {
+    /// 开始标签
+    StartElement {
+        /// 元素名
+        name: RawSaxName<'a>,
+        /// 属性（XML 中包括 DTD 声明的默认属性，不含命名空间声明）
+        attributes: RawSaxAttributes<'a>,
+    },
+    /// 结束标签
+    EndElement {
+        /// 元素名
+        name: RawSaxName<'a>,
+    },
+    /// 文本（包括 CDATA 段），同一段文本可能分多次报告
+    Characters(&'a [u8]),
+    /// 注释
+    Comment(&'a [u8]),
+    /// 处理指令
+    ProcessingInstruction {
+        /// 目标
+        target: &'a [u8],
+        /// 数据
+        data: &'a [u8],
+    },
+    /// 解析错误或警告
+    Error(&'a RawParseError),
+}
+
+/// SAX 解析的结果
+#[derive(Debug)]
+pub struct RawSaxOutcome {
+    /// 是否成功解析（XML 是格式良好的，或 HTML 解析完成）
+    pub parsed: bool,
+    /// 是否被回调中止
+    pub stopped: bool,
+    /// 解析过程中的全部诊断
+    pub errors: Vec<RawParseError>,
+}
+
+/// 解析期间的回调状态，挂在解析器上下文的 `_private` 上
+struct SaxState<'s> {
+    sink: &'s mut dyn FnMut(RawSaxEvent<'_>) -> bool,
+    ctxt: *mut xmlParserCtxt,
+    errors: Vec<RawParseError>,
+    stopped: bool,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl SaxState<'_> {
+    /// 把事件交给 sink；sink 返回 false 或 panic 时停止解析
+    fn emit(&mut self, event: RawSaxEvent<'_>) {
+        if self.stopped {
+            return;
+        }
+        let sink = &mut *self.sink;
+        match panic::catch_unwind(AssertUnwindSafe(|| sink(event))) {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(payload) => self.panic = Some(payload),
+        }
+        self.stopped = true;
+        // SAFETY: ctxt 在解析期间有效，xmlStopParser 可以在回调中调用
+        unsafe { crate::xmlStopParser(self.ctxt) };
+    }
+}
+
+/// 以 null 结尾的字符串的字节（不含结尾的 null）
 ///
-/// 返回属性名和属性值的键值对向量。
+/// # Safety
+///
+/// `ptr` 必须指向以 null 结尾的有效字符串，且在 `'a` 期间有效
+unsafe fn xml_bytes<'a>(ptr: *const crate::xmlChar) -> &'a [u8] {
+    // SAFETY: 调用者保证 ptr 有效
+    unsafe { CStr::from_ptr(ptr.cast()).to_bytes() }
+}
+
+/// 同 [`xml_bytes`]，`ptr` 为 null 时返回 `None`
 ///
 /// # Safety
 ///
-/// `node` 必须是有效的 xmlNodePtr
-pub unsafe fn node_get_all_attributes(node: xmlNodePtr) -> Vec<(String, String)> {
-    let mut result = Vec::new();
+/// `ptr` 为 null 或满足 [`xml_bytes`] 的要求
+unsafe fn option_xml_bytes<'a>(ptr: *const crate::xmlChar) -> Option<&'a [u8]> {
+    // SAFETY: 调用者保证 ptr 为 null 或有效
+    (!ptr.is_null()).then(|| unsafe { xml_bytes(ptr) })
+}
 
-    // SAFETY: 调用者保证 node 有效
-    let mut attr = unsafe { node_get_first_attribute(node) };
-    while !attr.is_null() {
-        let name_ptr = unsafe { attr_get_name(attr) };
-        if !name_ptr.is_null() {
-            let name = unsafe { ptr_to_string(name_ptr.cast()) };
+/// 从 SAX 回调的 `ctx`（解析器上下文）取出回调状态
+///
+/// # Safety
+///
+/// `ctx` 必须是 [`sax_parse`] 创建的解析器上下文
+unsafe fn sax_state<'a>(ctx: *mut c_void) -> Option<&'a mut SaxState<'a>> {
+    let ctxt = ctx as *mut xmlParserCtxt;
+    if ctxt.is_null() {
+        return None;
+    }
+    // SAFETY: _private 由 sax_parse 设置，指向其栈上的 SaxState，解析期间有效
+    unsafe { ((*ctxt)._private as *mut SaxState<'a>).as_mut() }
+}
 
-            // 获取属性值
-            let value_ptr = unsafe { crate::xmlGetProp(node, name_ptr) };
-            if !value_ptr.is_null() {
-                let value = unsafe { ptr_to_string(value_ptr.cast()) };
-                result.push((name, value));
-                unsafe { free_xml_char(value_ptr) };
+unsafe extern "C" fn sax_start_element_ns(
+    ctx: *mut c_void,
+    localname: *const crate::xmlChar,
+    prefix: *const crate::xmlChar,
+    uri: *const crate::xmlChar,
+    _nb_namespaces: c_int,
+    _namespaces: *mut *const crate::xmlChar,
+    nb_attributes: c_int,
+    _nb_defaulted: c_int,
+    attributes: *mut *const crate::xmlChar,
+) {
+    // SAFETY: 回调参数由解析器提供，在回调期间有效；attributes 含 nb_attributes * 5 个指针
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        let ptrs: &[*const crate::xmlChar] = if attributes.is_null() || nb_attributes <= 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(attributes, nb_attributes as usize * 5)
+        };
+        state.emit(RawSaxEvent::StartElement {
+            name: RawSaxName {
+                local_name: xml_bytes(localname),
+                prefix: option_xml_bytes(prefix),
+                uri: option_xml_bytes(uri),
+            },
+            attributes: RawSaxAttributes { ptrs, html: false },
+        });
+    }
+}
+
+unsafe extern "C" fn sax_end_element_ns(
+    ctx: *mut c_void,
+    localname: *const crate::xmlChar,
+    prefix: *const crate::xmlChar,
+    uri: *const crate::xmlChar,
+) {
+    // SAFETY: 回调参数由解析器提供，在回调期间有效
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        state.emit(RawSaxEvent::EndElement {
+            name: RawSaxName {
+                local_name: xml_bytes(localname),
+                prefix: option_xml_bytes(prefix),
+                uri: option_xml_bytes(uri),
+            },
+        });
+    }
+}
+
+unsafe extern "C" fn sax_start_element_html(
+    ctx: *mut c_void,
+    name: *const crate::xmlChar,
+    atts: *mut *const crate::xmlChar,
+) {
+    // SAFETY: 回调参数由解析器提供，在回调期间有效；atts 为以 null 名称结尾的名称/值对
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        let mut len = 0;
+        if !atts.is_null() {
+            while !(*atts.add(len)).is_null() {
+                len += 2;
             }
         }
-        attr = unsafe { attr_get_next(attr) };
+        let ptrs: &[*const crate::xmlChar] = if len == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(atts, len)
+        };
+        state.emit(RawSaxEvent::StartElement {
+            name: RawSaxName {
+                local_name: xml_bytes(name),
+                prefix: None,
+                uri: None,
+            },
+            attributes: RawSaxAttributes { ptrs, html: true },
+        });
     }
+}
 
-    result
+unsafe extern "C" fn sax_end_element_html(ctx: *mut c_void, name: *const crate::xmlChar) {
+    // SAFETY: 回调参数由解析器提供，在回调期间有效
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        state.emit(RawSaxEvent::EndElement {
+            name: RawSaxName {
+                local_name: xml_bytes(name),
+                prefix: None,
+                uri: None,
+            },
+        });
+    }
 }
 
-/// 检查节点是否具有指定属性
+unsafe extern "C" fn sax_characters(ctx: *mut c_void, ch: *const crate::xmlChar, len: c_int) {
+    if ch.is_null() || len <= 0 {
+        return;
+    }
+    // SAFETY: ch 指向解析器缓冲区中的 len 字节，在回调期间有效
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        state.emit(RawSaxEvent::Characters(std::slice::from_raw_parts(
+            ch,
+            len as usize,
+        )));
+    }
+}
+
+unsafe extern "C" fn sax_comment(ctx: *mut c_void, value: *const crate::xmlChar) {
+    // SAFETY: value 为以 null 结尾的字符串，在回调期间有效
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        state.emit(RawSaxEvent::Comment(
+            option_xml_bytes(value).unwrap_or_default(),
+        ));
+    }
+}
+
+unsafe extern "C" fn sax_processing_instruction(
+    ctx: *mut c_void,
+    target: *const crate::xmlChar,
+    data: *const crate::xmlChar,
+) {
+    // SAFETY: target 和 data 为以 null 结尾的字符串或 null，在回调期间有效
+    unsafe {
+        let Some(state) = sax_state(ctx) else {
+            return;
+        };
+        state.emit(RawSaxEvent::ProcessingInstruction {
+            target: option_xml_bytes(target).unwrap_or_default(),
+            data: option_xml_bytes(data).unwrap_or_default(),
+        });
+    }
+}
+
+/// 结构化错误回调：记录错误并作为事件报告，`data` 指向 `SaxState`
+unsafe extern "C" fn sax_error(data: *mut c_void, error: *const xmlError) {
+    if data.is_null() || error.is_null() {
+        return;
+    }
+    // SAFETY: data 由 sax_parse 设置，指向其栈上的 SaxState
+    unsafe {
+        if (*error).level == crate::xmlErrorLevel_XML_ERR_NONE {
+            return;
+        }
+        let state = &mut *(data as *mut SaxState);
+        let error = RawParseError::from_raw(error);
+        state.emit(RawSaxEvent::Error(&error));
+        state.errors.push(error);
+    }
+}
+
+/// 以 SAX 方式解析内存中的 XML 或 HTML，事件逐个交给 `sink`，不构建文档树
+///
+/// `sink` 返回 false 时停止解析。`sink` 中的 panic 会在解析结束后继续传播。
+/// 创建解析器上下文失败时返回 `None`。
 ///
 /// # Safety
 ///
-/// - `node` 必须是有效的 xmlNodePtr
-/// - `name` 必须是有效的以 null 结尾的 C 字符串
-pub unsafe fn node_has_attribute(node: xmlNodePtr, name: *const crate::xmlChar) -> bool {
+/// - `input` 必须指向至少 `size` 字节的有效内存
+/// - `options` 必须是有效的解析选项组合
+unsafe fn sax_parse(
+    html: bool,
+    input: *const c_char,
+    size: i32,
+    options: i32,
+    sink: &mut dyn FnMut(RawSaxEvent<'_>) -> bool,
+) -> Option<RawSaxOutcome> {
+    // SAFETY: 处理器先由 libxml2 填入默认回调（实体、DTD 等仍需默认实现），再替换内容相关的回调；
+    // 回调通过上下文的 _private 找到 state，state 在解析器上下文释放后才离开作用域
+    unsafe {
+        let mut handler: crate::xmlSAXHandler = std::mem::zeroed();
+        if html {
+            crate::xmlSAX2InitHtmlDefaultSAXHandler(&mut handler);
+            handler.startElement = Some(sax_start_element_html);
+            handler.endElement = Some(sax_end_element_html);
+        } else {
+            crate::xmlSAXVersion(&mut handler, 2);
+            handler.startElementNs = Some(sax_start_element_ns);
+            handler.endElementNs = Some(sax_end_element_ns);
+            // 与构建文档树时一致，保留空白；NOBLANKS 选项会重新把它设为忽略
+            handler.ignorableWhitespace = Some(sax_characters);
+        }
+        handler.characters = Some(sax_characters);
+        handler.cdataBlock = Some(sax_characters);
+        handler.comment = Some(sax_comment);
+        handler.processingInstruction = Some(sax_processing_instruction);
+
+        let ctxt = if html {
+            crate::htmlNewSAXParserCtxt(&handler, ptr::null_mut())
+        } else {
+            crate::xmlNewSAXParserCtxt(&handler, ptr::null_mut())
+        };
+        if ctxt.is_null() {
+            return None;
+        }
+
+        let mut state = SaxState {
+            sink,
+            ctxt,
+            errors: Vec::new(),
+            stopped: false,
+            panic: None,
+        };
+        let state_ptr = (&raw mut state).cast::<c_void>();
+        (*ctxt)._private = state_ptr;
+        crate::xmlCtxtSetErrorHandler(ctxt, Some(sax_error), state_ptr);
+
+        // 内容回调不构建节点，返回的文档只含 DTD 等声明
+        let doc = if html {
+            crate::htmlCtxtReadMemory(ctxt, input, size, ptr::null(), c"UTF-8".as_ptr(), options)
+        } else {
+            crate::xmlCtxtReadMemory(ctxt, input, size, ptr::null(), ptr::null(), options)
+        };
+        let parsed = !doc.is_null();
+        doc_free(doc);
+        if html {
+            crate::htmlFreeParserCtxt(ctxt);
+        } else {
+            crate::xmlFreeParserCtxt(ctxt);
+        }
+
+        if let Some(payload) = state.panic.take() {
+            panic::resume_unwind(payload);
+        }
+        Some(RawSaxOutcome {
+            parsed,
+            stopped: state.stopped,
+            errors: state.errors,
+        })
+    }
+}
+
+/// 以 SAX 方式解析内存中的 XML
+///
+/// 见 [`sax_parse_html_memory`]。
+///
+/// # Safety
+///
+/// - `xml` 必须指向至少 `size` 字节的有效内存
+/// - `options` 必须是有效的 XML 解析选项组合
+pub unsafe fn sax_parse_xml_memory(
+    xml: *const c_char,
+    size: i32,
+    options: i32,
+    sink: &mut dyn FnMut(RawSaxEvent<'_>) -> bool,
+) -> Option<RawSaxOutcome> {
     // SAFETY: 调用者保证参数有效
-    let prop = unsafe { crate::xmlGetProp(node, name) };
-    if prop.is_null() {
-        return false;
+    unsafe { sax_parse(false, xml, size, options, sink) }
+}
+
+/// 以 SAX 方式解析内存中的 HTML，事件逐个交给 `sink`，不构建文档树
+///
+/// `sink` 返回 false 时停止解析。`sink` 中的 panic 会在解析结束后继续传播。
+/// 创建解析器上下文失败时返回 `None`。
+///
+/// # Safety
+///
+/// - `html` 必须指向至少 `size` 字节的有效内存
+/// - `options` 必须是有效的 HTML 解析选项组合
+pub unsafe fn sax_parse_html_memory(
+    html: *const c_char,
+    size: i32,
+    options: i32,
+    sink: &mut dyn FnMut(RawSaxEvent<'_>) -> bool,
+) -> Option<RawSaxOutcome> {
+    // SAFETY: 调用者保证参数有效
+    unsafe { sax_parse(true, html, size, options, sink) }
+}
+
+// ========================================
+// 结果树片段
+// ========================================
+
+/// 挂在文档 `_private` 上的片段存储
+///
+/// 扩展函数（如 EXSLT 的 `str:tokenize`）需要返回新建的节点。这些节点创建在附属的暂存文档中，
+/// 与原文档一同释放，因此可以像原文档的节点一样按文档生命周期借用。
+struct FragmentStore {
+    // 暂存文档，片段节点的 doc 指向它
+    doc: xmlDocPtr,
+    // 每个片段的临时父节点。父节点之间互不相连，新建片段不会修改已返回的节点
+    roots: Mutex<Vec<xmlNodePtr>>,
+}
+
+// SAFETY: 暂存文档只在持有 roots 锁时修改，节点创建后不再变动
+unsafe impl Send for FragmentStore {}
+unsafe impl Sync for FragmentStore {}
+
+impl Drop for FragmentStore {
+    fn drop(&mut self) {
+        let roots = self
+            .roots
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // SAFETY: 临时父节点未挂到任何树上，由这里独占释放
+        unsafe {
+            for &root in roots.iter() {
+                crate::xmlFreeNode(root);
+            }
+            crate::xmlFreeDoc(self.doc);
+        }
+    }
+}
+
+/// 释放文档附带的片段存储（如果有）
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr，且 `_private` 只由本模块使用
+unsafe fn free_fragment_store(doc: xmlDocPtr) {
+    // SAFETY: _private 非空时指向 fragment_store 创建的 FragmentStore
+    unsafe {
+        let store = (*doc)._private as *mut FragmentStore;
+        if !store.is_null() {
+            (*doc)._private = ptr::null_mut();
+            drop(Box::from_raw(store));
+        }
+    }
+}
+
+/// 获取文档的片段存储，首次使用时创建
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr，且 `_private` 只由本模块使用
+unsafe fn fragment_store<'a>(doc: xmlDocPtr) -> Option<&'a FragmentStore> {
+    // SAFETY: 调用者保证 doc 有效；多个线程可能同时查询同一文档，用原子操作初始化
+    unsafe {
+        let slot = AtomicPtr::<c_void>::from_ptr(&raw mut (*doc)._private);
+        let current = slot.load(Ordering::Acquire);
+        if !current.is_null() {
+            return Some(&*(current as *const FragmentStore));
+        }
+
+        let scratch = crate::xmlNewDoc(c"1.0".as_ptr().cast());
+        if scratch.is_null() {
+            return None;
+        }
+        let store = Box::into_raw(Box::new(FragmentStore {
+            doc: scratch,
+            roots: Mutex::new(Vec::new()),
+        }));
+        match slot.compare_exchange(
+            ptr::null_mut(),
+            store.cast(),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => Some(&*store),
+            Err(existing) => {
+                // 其他线程抢先完成了初始化
+                drop(Box::from_raw(store));
+                Some(&*(existing as *const FragmentStore))
+            }
+        }
+    }
+}
+
+/// 在文档的片段存储中创建一组元素，每个元素只包含一个文本子节点
+///
+/// 元素按顺序挂在同一个临时父节点下，保持给定的顺序；它们在 `doc` 被
+/// [`doc_free`] 释放时一同释放。创建失败时返回 `None`。
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_new_fragment_elements<S: AsRef<CStr>>(
+    doc: xmlDocPtr,
+    name: &CStr,
+    contents: &[S],
+) -> Option<Vec<xmlNodePtr>> {
+    // SAFETY: 调用者保证 doc 有效
+    let store = unsafe { fragment_store(doc) }?;
+    let mut roots = store
+        .roots
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    // SAFETY: store.doc 在存储存活期间有效；新建的父节点立即交给 roots 管理
+    unsafe {
+        let root = crate::xmlNewDocRawNode(
+            store.doc,
+            ptr::null_mut(),
+            c"fragment".as_ptr().cast(),
+            ptr::null(),
+        );
+        if root.is_null() {
+            return None;
+        }
+        roots.push(root);
+
+        let mut nodes = Vec::with_capacity(contents.len());
+        for content in contents {
+            let node = crate::xmlNewDocRawNode(
+                store.doc,
+                ptr::null_mut(),
+                name.as_ptr().cast(),
+                content.as_ref().as_ptr().cast(),
+            );
+            if node.is_null() {
+                return None;
+            }
+            crate::xmlAddChild(root, node);
+            nodes.push(node);
+        }
+        Some(nodes)
+    }
+}
+
+/// 检查节点是否属于文档或其片段存储
+///
+/// # Safety
+///
+/// `doc` 和 `node` 必须是有效的指针
+pub unsafe fn doc_owns_node(doc: xmlDocPtr, node: xmlNodePtr) -> bool {
+    // SAFETY: 调用者保证指针有效；_private 非空时指向 FragmentStore
+    unsafe {
+        let node_doc = (*node).doc;
+        if node_doc == doc {
+            return true;
+        }
+        let store = AtomicPtr::<c_void>::from_ptr(&raw mut (*doc)._private).load(Ordering::Acquire)
+            as *const FragmentStore;
+        !store.is_null() && (*store).doc == node_doc
+    }
+}
+
+// ========================================
+// XPath 操作封装
+// ========================================
+
+/// XPath 查询结果类型常量
+pub const XPATH_NODESET: i32 = 1;
+pub const XPATH_BOOLEAN: i32 = 2;
+pub const XPATH_NUMBER: i32 = 3;
+pub const XPATH_STRING: i32 = 4;
+
+/// XPath 查询的原始结果
+///
+/// 用于内部传递 XPath 查询的原始结果，由调用者负责类型安全。
+pub struct RawXPathResult {
+    /// XPath 对象指针
+    pub object: *mut xmlXPathObject,
+    /// 结果类型
+    pub result_type: i32,
+}
+
+impl RawXPathResult {
+    /// 创建新的原始结果
+    ///
+    /// # Safety
+    ///
+    /// `object` 必须是有效的 xmlXPathObject 指针
+    #[inline]
+    pub unsafe fn new(object: *mut xmlXPathObject) -> Self {
+        // SAFETY: 调用者保证 object 有效
+        let result_type = unsafe { (*object).type_ as i32 };
+        Self {
+            object,
+            result_type,
+        }
+    }
+
+    /// 提取布尔值
+    ///
+    /// # Safety
+    ///
+    /// 必须是布尔类型的结果
+    #[inline]
+    pub unsafe fn as_boolean(&self) -> bool {
+        // SAFETY: 调用者保证 object 有效且类型正确
+        unsafe { (*self.object).boolval != 0 }
+    }
+
+    /// 提取数字值
+    ///
+    /// # Safety
+    ///
+    /// 必须是数字类型的结果
+    #[inline]
+    pub unsafe fn as_number(&self) -> f64 {
+        // SAFETY: 调用者保证 object 有效且类型正确
+        unsafe { (*self.object).floatval }
+    }
+
+    /// 提取字符串值
+    ///
+    /// # Safety
+    ///
+    /// 必须是字符串类型的结果
+    #[inline]
+    pub unsafe fn as_string(&self) -> String {
+        // SAFETY: 调用者保证 object 有效且类型正确
+        unsafe {
+            let stringval = (*self.object).stringval;
+            if stringval.is_null() {
+                String::new()
+            } else {
+                ptr_to_string(stringval.cast())
+            }
+        }
+    }
+
+    /// 提取节点集合
+    ///
+    /// # Safety
+    ///
+    /// 必须是节点集合类型的结果
+    #[inline]
+    pub unsafe fn as_nodeset(&self) -> Vec<xmlNodePtr> {
+        // SAFETY: 调用者保证 object 有效且类型正确
+        unsafe {
+            let mut nodes = Vec::new();
+            let nodeset = (*self.object).nodesetval;
+            if !nodeset.is_null() && (*nodeset).nodeNr > 0 {
+                let node_count = (*nodeset).nodeNr as isize;
+                let node_tab = (*nodeset).nodeTab;
+
+                for i in 0..node_count {
+                    let node = *node_tab.offset(i);
+                    if !node.is_null() {
+                        nodes.push(node);
+                    }
+                }
+            }
+            nodes
+        }
+    }
+}
+
+impl Drop for RawXPathResult {
+    fn drop(&mut self) {
+        // SAFETY: object 在 drop 时仍然有效
+        unsafe {
+            crate::xmlXPathFreeObject(self.object);
+        }
+    }
+}
+
+/// 创建 XPath 字符串对象
+///
+/// 返回的对象由调用者负责释放（或转交给 libxml2）。
+///
+/// # Safety
+///
+/// `value` 必须是有效的以 null 结尾的 C 字符串
+#[inline]
+pub unsafe fn xpath_new_string(value: *const crate::xmlChar) -> *mut xmlXPathObject {
+    // SAFETY: 调用者保证 value 有效；libxml2 会复制字符串
+    unsafe { crate::xmlXPathNewString(value) }
+}
+
+/// 创建 XPath 数字对象
+///
+/// 返回的对象由调用者负责释放（或转交给 libxml2）。
+#[inline]
+pub fn xpath_new_number(value: f64) -> *mut xmlXPathObject {
+    // SAFETY: 无前置条件
+    unsafe { crate::xmlXPathNewFloat(value) }
+}
+
+/// 创建 XPath 布尔对象
+///
+/// 返回的对象由调用者负责释放（或转交给 libxml2）。
+#[inline]
+pub fn xpath_new_boolean(value: bool) -> *mut xmlXPathObject {
+    // SAFETY: 无前置条件
+    unsafe { crate::xmlXPathNewBoolean(value as i32) }
+}
+
+/// 创建 XPath 节点集合对象
+///
+/// 节点按文档顺序排序并去重。集合只引用节点，不复制节点。
+/// 返回的对象由调用者负责释放（或转交给 libxml2）。
+///
+/// # Safety
+///
+/// `nodes` 中的指针必须是有效的节点指针，且在对象存活期间保持有效
+pub unsafe fn xpath_new_nodeset(nodes: &[xmlNodePtr]) -> *mut xmlXPathObject {
+    // SAFETY: 调用者保证节点有效
+    unsafe {
+        let obj = crate::xmlXPathNewNodeSet(ptr::null_mut());
+        if obj.is_null() {
+            return obj;
+        }
+        let set = (*obj).nodesetval;
+        for &node in nodes {
+            if set.is_null() || crate::xmlXPathNodeSetAdd(set, node) != 0 {
+                crate::xmlXPathFreeObject(obj);
+                return ptr::null_mut();
+            }
+        }
+        crate::xmlXPathNodeSetSort(set);
+        obj
+    }
+}
+
+/// XPath 编译或求值错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawXPathError {
+    /// 错误码（`xmlXPathError` 偏移到 `xmlParserErrors` 后的值）
+    pub code: i32,
+    /// 错误消息（已去除末尾换行）
+    pub message: String,
+    /// 出错的表达式
+    pub expression: Option<String>,
+    /// 出错位置在表达式中的字节偏移
+    pub offset: usize,
+}
+
+impl RawXPathError {
+    /// 从 libxml2 的 `xmlError` 复制错误信息
+    ///
+    /// XPath 错误把表达式放在 `str1`、把出错偏移放在 `int1`。
+    ///
+    /// # Safety
+    ///
+    /// `error` 必须指向有效的 xmlError
+    pub unsafe fn from_raw(error: *const xmlError) -> Self {
+        // SAFETY: 调用者保证 error 有效
+        unsafe {
+            let error = &*error;
+            Self {
+                code: error.code,
+                message: ptr_to_string(error.message.cast()).trim_end().to_string(),
+                expression: ptr_to_option_string(error.str1.cast()),
+                offset: error.int1.max(0) as usize,
+            }
+        }
+    }
+}
+
+/// 自定义 XPath 函数回调
+///
+/// 参数为上下文关联的文档和按调用顺序排列的实参；
+/// 返回新建的 XPath 对象（所有权转交给 libxml2），或错误消息。
+pub type XPathFunctionCallback =
+    Box<dyn Fn(xmlDocPtr, &[RawXPathResult]) -> Result<*mut xmlXPathObject, String>>;
+
+/// XPath 上下文的附加数据，通过 `userData` 传给回调
+struct XPathContextData {
+    // 第一条编译或求值错误
+    error: Option<RawXPathError>,
+    // 自定义函数失败时的消息，用于替换 libxml2 的通用错误消息
+    callback_error: Option<String>,
+    // 以 (命名空间 URI, 函数名) 为键的自定义函数
+    functions: HashMap<(String, String), XPathFunctionCallback>,
+}
+
+/// 结构化错误回调：把第一条错误记录到 `data` 指向的 `XPathContextData`
+///
+/// 上下文的 `lastError` 不保存错误消息，因此需要在回调中复制。
+unsafe extern "C" fn record_xpath_error(data: *mut c_void, error: *const xmlError) {
+    if data.is_null() || error.is_null() {
+        return;
+    }
+    // SAFETY: data 由 XPathContextGuard 设置，指向其持有的 XPathContextData
+    unsafe {
+        let data = &mut *(data as *mut XPathContextData);
+        let mut error = RawXPathError::from_raw(error);
+        if let Some(message) = data.callback_error.take() {
+            error.message = message;
+        }
+        if data.error.is_none() {
+            data.error = Some(error);
+        }
+    }
+}
+
+/// 所有自定义函数共用的跳板
+///
+/// libxml2 在调用扩展函数前会把函数名和命名空间写入上下文，据此找到对应的 Rust 回调。
+/// 回调中的 panic 会被捕获并转换为 XPath 错误，不会跨越 C 栈展开。
+unsafe extern "C" fn call_xpath_function(ctxt: *mut crate::xmlXPathParserContext, nargs: i32) {
+    // SAFETY: libxml2 保证 ctxt 及其上下文在调用期间有效；
+    // userData 由 XPathContextGuard 设置，指向其持有的 XPathContextData
+    unsafe {
+        let context = (*ctxt).context;
+        let data = (*context).userData as *mut XPathContextData;
+        if data.is_null() {
+            crate::xmlXPathErr(ctxt, crate::xmlXPathError_XPATH_UNKNOWN_FUNC_ERROR as i32);
+            return;
+        }
+
+        // 实参按逆序出栈
+        let mut args = Vec::with_capacity(nargs.max(0) as usize);
+        for _ in 0..nargs {
+            let obj = crate::valuePop(ctxt);
+            if obj.is_null() {
+                crate::xmlXPathErr(ctxt, crate::xmlXPathError_XPATH_STACK_ERROR as i32);
+                return;
+            }
+            args.push(RawXPathResult::new(obj));
+        }
+        args.reverse();
+
+        // functionURI 由 libxml2 从编译结果中缓存的命名空间查找结果设置，指向首次求值时
+        // 上下文的命名空间表；evaluate_compiled 的调用约定保证该上下文就是当前上下文
+        let key = (
+            ptr_to_string((*context).functionURI.cast()),
+            ptr_to_string((*context).function.cast()),
+        );
+        let outcome = match (*data).functions.get(&key) {
+            Some(callback) => {
+                let doc = (*context).doc;
+                panic::catch_unwind(AssertUnwindSafe(|| callback(doc, &args))).unwrap_or_else(
+                    |payload| {
+                        let reason = payload
+                            .downcast_ref::<&str>()
+                            .map(|s| s.to_string())
+                            .or_else(|| payload.downcast_ref::<String>().cloned())
+                            .unwrap_or_else(|| "unknown panic".to_string());
+                        Err(format!("XPath function '{}' panicked: {}", key.1, reason))
+                    },
+                )
+            }
+            None => Err(format!("XPath function '{}' is not registered", key.1)),
+        };
+
+        match outcome {
+            Ok(obj) if !obj.is_null() => {
+                crate::valuePush(ctxt, obj);
+            }
+            Ok(_) => {
+                (*data).callback_error = Some(format!("XPath function '{}' returned null", key.1));
+                crate::xmlXPathErr(ctxt, crate::xmlXPathError_XPATH_MEMORY_ERROR as i32);
+            }
+            Err(message) => {
+                (*data).callback_error = Some(message);
+                crate::xmlXPathErr(ctxt, crate::xmlXPathError_XPATH_EXPR_ERROR as i32);
+            }
+        }
+    }
+}
+
+/// 编译后的 XPath 表达式守卫，确保正确释放资源
+///
+/// 编译结果不引用任何文档或上下文，可以在多个上下文中反复求值，但同一时刻只能有一个求值。
+pub struct CompiledXPathGuard {
+    comp: *mut xmlXPathCompExpr,
+}
+
+// SAFETY: 编译结果不绑定线程，可以在线程间转移所有权。
+// 注意它不是 Sync：libxml2 求值时会把函数查找结果缓存进编译结果，不能并发求值。
+unsafe impl Send for CompiledXPathGuard {}
+
+impl CompiledXPathGuard {
+    /// 获取原始编译表达式指针
+    #[inline]
+    pub fn as_ptr(&self) -> *mut xmlXPathCompExpr {
+        self.comp
+    }
+}
+
+impl Drop for CompiledXPathGuard {
+    fn drop(&mut self) {
+        // SAFETY: comp 在 drop 时仍然有效
+        unsafe {
+            crate::xmlXPathFreeCompExpr(self.comp);
+        }
+    }
+}
+
+/// XPath 上下文守卫，确保正确释放资源
+///
+/// 创建时即注册结构化错误回调，编译和求值错误不会输出到 stderr，
+/// 可以通过 [`XPathContextGuard::last_error`] 读取。
+pub struct XPathContextGuard {
+    ctx: *mut xmlXPathContext,
+    // 装箱保证地址稳定，回调通过裸指针访问
+    data: Box<XPathContextData>,
+}
+
+impl XPathContextGuard {
+    /// 创建新的 XPath 上下文
+    ///
+    /// # Safety
+    ///
+    /// `doc` 必须是有效的文档指针；仅用于编译表达式时可以为 null
+    #[inline]
+    pub unsafe fn new(doc: xmlDocPtr) -> Option<Self> {
+        // SAFETY: 调用者保证 doc 有效
+        let ctx = unsafe { crate::xmlXPathNewContext(doc) };
+        if ctx.is_null() {
+            return None;
+        }
+
+        let mut guard = Self {
+            ctx,
+            data: Box::new(XPathContextData {
+                error: None,
+                callback_error: None,
+                functions: HashMap::new(),
+            }),
+        };
+        let data = &mut *guard.data as *mut XPathContextData as *mut c_void;
+        // SAFETY: ctx 刚刚创建且非空；data 与上下文同生共死
+        unsafe {
+            (*ctx).error = Some(record_xpath_error);
+            (*ctx).userData = data;
+        }
+        Some(guard)
+    }
+
+    /// 获取最近一次编译或求值的错误
+    #[inline]
+    pub fn last_error(&self) -> Option<RawXPathError> {
+        self.data.error.clone()
+    }
+
+    /// 清除上一次记录的错误
+    #[inline]
+    fn reset_error(&mut self) {
+        self.data.error = None;
+        self.data.callback_error = None;
+    }
+
+    /// 注册自定义函数
+    ///
+    /// `ns_uri` 为空字符串时注册为无命名空间的函数。同名函数会被替换。
+    pub fn register_function(
+        &mut self,
+        ns_uri: &CStr,
+        name: &CStr,
+        callback: XPathFunctionCallback,
+    ) -> bool {
+        let uri_ptr = if ns_uri.is_empty() {
+            ptr::null()
+        } else {
+            ns_uri.as_ptr() as *const crate::xmlChar
+        };
+        // SAFETY: ctx 有效；name 和 ns_uri 是有效的 C 字符串，libxml2 会复制它们
+        let registered = unsafe {
+            crate::xmlXPathRegisterFuncNS(
+                self.ctx,
+                name.as_ptr() as *const crate::xmlChar,
+                uri_ptr,
+                Some(call_xpath_function),
+            ) == 0
+        };
+        if registered {
+            let key = (
+                ns_uri.to_string_lossy().into_owned(),
+                name.to_string_lossy().into_owned(),
+            );
+            self.data.functions.insert(key, callback);
+        }
+        registered
+    }
+
+    /// 编译 XPath 表达式
+    ///
+    /// 失败时返回 `None`，错误详情可通过 [`XPathContextGuard::last_error`] 获取。
+    ///
+    /// # Safety
+    ///
+    /// `xpath` 必须是有效的以 null 结尾的 C 字符串
+    #[inline]
+    pub unsafe fn compile(&mut self, xpath: *const crate::xmlChar) -> Option<CompiledXPathGuard> {
+        self.reset_error();
+        // SAFETY: 调用者保证参数有效
+        let comp = unsafe { crate::xmlXPathCtxtCompile(self.ctx, xpath) };
+        if comp.is_null() {
+            None
+        } else {
+            Some(CompiledXPathGuard { comp })
+        }
+    }
+
+    /// 求值编译后的 XPath 表达式
+    ///
+    /// # Safety
+    ///
+    /// 上下文关联的文档和上下文节点必须仍然有效。libxml2 会把函数查找结果（包括指向上下文
+    /// 命名空间表的 URI）缓存在编译结果中，因此调用扩展函数的 `comp` 只能在同一个上下文中求值
+    #[inline]
+    pub unsafe fn evaluate_compiled(
+        &mut self,
+        comp: &CompiledXPathGuard,
+    ) -> Option<RawXPathResult> {
+        self.reset_error();
+        // SAFETY: comp 有效，调用者保证上下文有效
+        let obj = unsafe { crate::xmlXPathCompiledEval(comp.comp, self.ctx) };
+        if obj.is_null() {
+            None
+        } else {
+            // SAFETY: obj 刚刚创建且非空
+            Some(unsafe { RawXPathResult::new(obj) })
+        }
+    }
+
+    /// 设置上下文节点
+    ///
+    /// # Safety
+    ///
+    /// `node` 必须是有效的节点指针
+    #[inline]
+    pub unsafe fn set_context_node(&mut self, node: xmlNodePtr) -> bool {
+        // SAFETY: 调用者保证 node 有效
+        unsafe { crate::xmlXPathSetContextNode(node, self.ctx) == 0 }
+    }
+
+    /// 清除上下文节点，恢复为新建上下文时的文档级求值
+    #[inline]
+    pub fn clear_context_node(&mut self) {
+        // SAFETY: ctx 在守卫存活期间始终有效
+        unsafe { (*self.ctx).node = ptr::null_mut() }
+    }
+
+    /// 注册命名空间前缀
+    ///
+    /// libxml2 会复制前缀和 URI，调用返回后参数即可释放。
+    ///
+    /// # Safety
+    ///
+    /// `prefix` 和 `uri` 必须是有效的以 null 结尾的 C 字符串
+    #[inline]
+    pub unsafe fn register_namespace(
+        &mut self,
+        prefix: *const crate::xmlChar,
+        uri: *const crate::xmlChar,
+    ) -> bool {
+        // SAFETY: 调用者保证参数有效
+        unsafe { crate::xmlXPathRegisterNs(self.ctx, prefix, uri) == 0 }
+    }
+
+    /// 注册变量
+    ///
+    /// 上下文接管 `value` 的所有权，同名变量会被替换。注册失败时 `value` 会被释放。
+    ///
+    /// # Safety
+    ///
+    /// - `name` 必须是有效的以 null 结尾的 C 字符串
+    /// - `value` 必须是有效的、未被其他地方持有的 xmlXPathObject
+    #[inline]
+    pub unsafe fn register_variable(
+        &mut self,
+        name: *const crate::xmlChar,
+        value: *mut xmlXPathObject,
+    ) -> bool {
+        // SAFETY: 调用者保证参数有效
+        unsafe {
+            if crate::xmlXPathRegisterVariable(self.ctx, name, value) == 0 {
+                true
+            } else {
+                crate::xmlXPathFreeObject(value);
+                false
+            }
+        }
+    }
+
+    /// 获取原始上下文指针
+    #[inline]
+    pub fn as_ptr(&self) -> *mut xmlXPathContext {
+        self.ctx
+    }
+
+    /// 执行 XPath 表达式
+    ///
+    /// # Safety
+    ///
+    /// `xpath` 必须是有效的以 null 结尾的 C 字符串
+    #[inline]
+    pub unsafe fn evaluate(&mut self, xpath: *const crate::xmlChar) -> Option<RawXPathResult> {
+        self.reset_error();
+        // SAFETY: 调用者保证参数有效
+        let obj = unsafe { crate::xmlXPathEvalExpression(xpath, self.ctx) };
+        if obj.is_null() {
+            None
+        } else {
+            // SAFETY: obj 刚刚创建且非空
+            Some(unsafe { RawXPathResult::new(obj) })
+        }
+    }
+}
+
+impl Drop for XPathContextGuard {
+    fn drop(&mut self) {
+        // SAFETY: ctx 在 drop 时仍然有效
+        unsafe {
+            crate::xmlXPathFreeContext(self.ctx);
+        }
+    }
+}
+
+/// 在文档上执行 XPath 查询
+///
+/// # Safety
+///
+/// - `doc` 必须是有效的文档指针
+/// - `xpath` 必须是有效的以 null 结尾的 C 字符串
+pub unsafe fn xpath_evaluate(
+    doc: xmlDocPtr,
+    xpath: *const crate::xmlChar,
+) -> Option<RawXPathResult> {
+    // SAFETY: 调用者保证参数有效
+    let mut ctx = unsafe { XPathContextGuard::new(doc)? };
+    unsafe { ctx.evaluate(xpath) }
+}
+
+/// 在节点上下文中执行 XPath 查询
+///
+/// # Safety
+///
+/// - `node` 必须是有效的节点指针
+/// - `xpath` 必须是有效的以 null 结尾的 C 字符串
+pub unsafe fn xpath_evaluate_on_node(
+    node: xmlNodePtr,
+    xpath: *const crate::xmlChar,
+) -> Option<RawXPathResult> {
+    // SAFETY: 调用者保证参数有效
+    let doc = unsafe { node_get_document(node) };
+    if doc.is_null() {
+        return None;
+    }
+
+    let mut ctx = unsafe { XPathContextGuard::new(doc)? };
+    if !unsafe { ctx.set_context_node(node) } {
+        return None;
+    }
+    unsafe { ctx.evaluate(xpath) }
+}
+
+// ========================================
+// 属性遍历高级封装
+// ========================================
+
+/// 获取节点的所有属性
+///
+/// 返回属性名和属性值的键值对向量。
+///
+/// # Safety
+///
+/// `node` 必须是有效的 xmlNodePtr
+pub unsafe fn node_get_all_attributes(node: xmlNodePtr) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+
+    // SAFETY: 调用者保证 node 有效
+    let mut attr = unsafe { node_get_first_attribute(node) };
+    while !attr.is_null() {
+        let name_ptr = unsafe { attr_get_name(attr) };
+        if !name_ptr.is_null() {
+            let name = unsafe { ptr_to_string(name_ptr.cast()) };
+
+            // 获取属性值
+            let value_ptr = unsafe { crate::xmlGetProp(node, name_ptr) };
+            if !value_ptr.is_null() {
+                let value = unsafe { ptr_to_string(value_ptr.cast()) };
+                result.push((name, value));
+                unsafe { free_xml_char(value_ptr) };
+            }
+        }
+        attr = unsafe { attr_get_next(attr) };
+    }
+
+    result
+}
+
+/// 检查节点是否具有指定属性
+///
+/// # Safety
+///
+/// - `node` 必须是有效的 xmlNodePtr
+/// - `name` 必须是有效的以 null 结尾的 C 字符串
+pub unsafe fn node_has_attribute(node: xmlNodePtr, name: *const crate::xmlChar) -> bool {
+    // SAFETY: 调用者保证参数有效
+    let prop = unsafe { crate::xmlGetProp(node, name) };
+    if prop.is_null() {
+        return false;
+    }
+    unsafe { free_xml_char(prop) };
+    true
+}
+
+// ========================================
+// 树修改封装
+// ========================================
+
+/// 创建属于 `doc` 的元素节点（尚未插入树中）
+///
+/// # Safety
+///
+/// - `doc` 必须是有效的 xmlDocPtr
+/// - `name` 必须是有效的以 null 结尾的 C 字符串
+#[inline]
+pub unsafe fn node_new_element(doc: xmlDocPtr, name: *const crate::xmlChar) -> xmlNodePtr {
+    // SAFETY: 调用者保证参数有效；名称会复制到文档的字典中
+    unsafe { crate::xmlNewDocNode(doc, ptr::null_mut(), name, ptr::null()) }
+}
+
+/// 创建属于 `doc` 的文本节点（尚未插入树中）
+///
+/// 内容按原样保存，序列化时才转义特殊字符。
+///
+/// # Safety
+///
+/// - `doc` 必须是有效的 xmlDocPtr
+/// - `content` 必须是有效的以 null 结尾的 C 字符串
+#[inline]
+pub unsafe fn node_new_text(doc: xmlDocPtr, content: *const crate::xmlChar) -> xmlNodePtr {
+    // SAFETY: 调用者保证参数有效
+    unsafe { crate::xmlNewDocText(doc, content) }
+}
+
+/// 把节点从树上摘下，但不释放内存
+///
+/// 属性节点会从所属元素的属性链表中移除。
+///
+/// # Safety
+///
+/// `node` 必须是有效的 xmlNodePtr
+#[inline]
+pub unsafe fn node_unlink(node: xmlNodePtr) {
+    // SAFETY: 调用者保证 node 有效
+    unsafe { crate::xmlUnlinkNode(node) }
+}
+
+/// 释放节点及其整棵子树
+///
+/// # Safety
+///
+/// - `node` 必须是有效的 xmlNodePtr，且已经从树上摘下
+/// - 释放后不能再访问该节点及其任何后代
+#[inline]
+pub unsafe fn node_free(node: xmlNodePtr) {
+    // SAFETY: 调用者保证 node 已摘下且只释放一次
+    unsafe { crate::xmlFreeNode(node) }
+}
+
+/// 把已摘下的节点追加为 `parent` 的最后一个子节点
+///
+/// 与 `xmlAddChild` 不同，这里不会合并相邻的文本节点：
+/// 合并会释放传入的节点，使仍指向它的句柄悬垂。
+///
+/// # Safety
+///
+/// - `parent` 必须是有效的元素、属性或文档节点
+/// - `child` 必须是有效的、已摘下的节点，且与 `parent` 属于同一文档
+/// - `child` 不能是 `parent` 本身或其祖先
+pub unsafe fn node_append_child(parent: xmlNodePtr, child: xmlNodePtr) {
+    // SAFETY: 调用者保证两个节点有效；元素、属性与文档结构体的
+    // children/last 字段位于相同偏移，可以统一按 xmlNode 访问
+    unsafe {
+        let last = (*parent).last;
+        (*child).parent = parent;
+        (*child).prev = last;
+        (*child).next = ptr::null_mut();
+        if last.is_null() {
+            (*parent).children = child;
+        } else {
+            (*last).next = child;
+        }
+        (*parent).last = child;
+    }
+}
+
+/// 把已摘下的节点插入到 `reference` 之前
+///
+/// 同 [`node_append_child`]，不会合并相邻的文本节点。
+///
+/// # Safety
+///
+/// - `reference` 必须是有效的、有父节点的节点
+/// - `node` 必须是有效的、已摘下的节点，且与 `reference` 属于同一文档
+/// - `node` 不能是 `reference` 本身或其祖先
+pub unsafe fn node_insert_before(reference: xmlNodePtr, node: xmlNodePtr) {
+    // SAFETY: 调用者保证两个节点有效且 reference 有父节点
+    unsafe {
+        let parent = (*reference).parent;
+        let prev = (*reference).prev;
+        (*node).parent = parent;
+        (*node).prev = prev;
+        (*node).next = reference;
+        if prev.is_null() {
+            (*parent).children = node;
+        } else {
+            (*prev).next = node;
+        }
+        (*reference).prev = node;
+    }
+}
+
+/// 检查 `ancestor` 是否为 `node` 本身或其祖先
+///
+/// # Safety
+///
+/// `ancestor` 和 `node` 必须是有效的 xmlNodePtr
+pub unsafe fn node_is_ancestor_or_self(ancestor: xmlNodePtr, node: xmlNodePtr) -> bool {
+    let mut current = node;
+    while !current.is_null() {
+        if current == ancestor {
+            return true;
+        }
+        // SAFETY: current 是 node 或其祖先，调用者保证有效
+        current = unsafe { (*current).parent };
+    }
+    false
+}
+
+/// 查找元素上的属性节点
+///
+/// 只返回元素上实际存在的属性，不包括 DTD 中声明的默认属性。
+///
+/// # Safety
+///
+/// - `node` 必须是有效的元素节点
+/// - `name` 必须是有效的以 null 结尾的 C 字符串
+pub unsafe fn node_find_attribute(node: xmlNodePtr, name: *const crate::xmlChar) -> *mut xmlAttr {
+    // SAFETY: 调用者保证参数有效
+    let attr = unsafe { crate::xmlHasProp(node, name) };
+    // xmlHasProp 找不到属性时可能返回 DTD 中的属性声明
+    if attr.is_null() || unsafe { (*attr).type_ } != crate::xmlElementType_XML_ATTRIBUTE_NODE {
+        return ptr::null_mut();
+    }
+    attr
+}
+
+/// 设置元素的属性值，属性不存在时新建
+///
+/// 值按原样保存，序列化时才转义特殊字符。
+/// 已有属性的旧值子节点会被释放，调用者需要先摘下仍被引用的子节点。
+///
+/// # Safety
+///
+/// - `node` 必须是有效的元素节点
+/// - `name` 和 `value` 必须是有效的以 null 结尾的 C 字符串
+#[inline]
+pub unsafe fn node_set_attribute(
+    node: xmlNodePtr,
+    name: *const crate::xmlChar,
+    value: *const crate::xmlChar,
+) -> bool {
+    // SAFETY: 调用者保证参数有效
+    unsafe { !crate::xmlSetProp(node, name, value).is_null() }
+}
+
+/// 设置文本、CDATA、注释或处理指令节点的内容
+///
+/// # Safety
+///
+/// - `node` 必须是有效的、没有子节点的文本类节点
+/// - `content` 必须是有效的以 null 结尾的 C 字符串
+#[inline]
+pub unsafe fn node_set_content(node: xmlNodePtr, content: *const crate::xmlChar) {
+    // SAFETY: 调用者保证参数有效；文本类节点的内容按原样复制
+    unsafe {
+        crate::xmlNodeSetContent(node, content);
+    }
+}
+
+// ========================================
+// DTD 封装
+// ========================================
+
+/// 文档类型声明（`<!DOCTYPE ...>`）的拷贝
+#[derive(Debug, Clone)]
+pub struct RawDoctype {
+    /// 根元素名
+    pub name: Option<String>,
+    /// 公共标识符（`PUBLIC` 后的第一个字符串）
+    pub public_id: Option<String>,
+    /// 系统标识符（DTD 的 URI）
+    pub system_id: Option<String>,
+}
+
+/// 获取文档的 DOCTYPE 声明，没有时返回 `None`
+///
+/// # Safety
+///
+/// `doc` 必须是有效的 xmlDocPtr
+pub unsafe fn doc_get_doctype(doc: xmlDocPtr) -> Option<RawDoctype> {
+    // SAFETY: doc 有效；内部子集与文档同生命周期
+    unsafe {
+        let dtd = crate::xmlGetIntSubset(doc);
+        if dtd.is_null() {
+            return None;
+        }
+        Some(RawDoctype {
+            name: ptr_to_option_string((*dtd).name.cast()),
+            public_id: ptr_to_option_string((*dtd).ExternalID.cast()),
+            system_id: ptr_to_option_string((*dtd).SystemID.cast()),
+        })
+    }
+}
+
+/// 独立解析的 DTD 守卫，确保正确释放资源
+///
+/// 验证时 libxml2 会按需编译元素内容模型并写回 DTD，因此它不是 Sync：
+/// 同一时刻只能有一个验证在进行。
+pub struct DtdGuard {
+    dtd: *mut crate::xmlDtd,
+}
+
+// SAFETY: DTD 不属于任何文档，可以在线程间转移所有权
+unsafe impl Send for DtdGuard {}
+
+impl DtdGuard {
+    /// 从内存中的 DTD 文本解析
+    ///
+    /// # Safety
+    ///
+    /// `buffer` 必须指向至少 `size` 字节的有效内存
+    pub unsafe fn parse_memory(
+        buffer: *const c_char,
+        size: i32,
+    ) -> (Option<Self>, Vec<RawParseError>) {
+        // DTD 解析没有上下文级错误回调，只能临时接管全局回调
+        // SAFETY: 调用者保证 buffer 有效；xmlIOParseDTD 在任何情况下都会释放输入缓冲区
+        let (dtd, errors) = unsafe {
+            collect_global_errors(|| {
+                let encoding = crate::xmlCharEncoding_XML_CHAR_ENCODING_NONE;
+                let input = crate::xmlParserInputBufferCreateMem(buffer, size, encoding);
+                if input.is_null() {
+                    return ptr::null_mut();
+                }
+                crate::xmlIOParseDTD(ptr::null_mut(), input, encoding)
+            })
+        };
+        ((!dtd.is_null()).then_some(Self { dtd }), errors)
+    }
+
+    /// 从文件或 URL 解析
+    ///
+    /// # Safety
+    ///
+    /// `url` 必须是有效的以 null 结尾的 C 字符串
+    pub unsafe fn parse_file(url: *const c_char) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 url 有效
+        let (dtd, errors) =
+            unsafe { collect_global_errors(|| crate::xmlParseDTD(ptr::null(), url.cast())) };
+        ((!dtd.is_null()).then_some(Self { dtd }), errors)
+    }
+
+    /// 按此 DTD 验证文档
+    ///
+    /// 返回 libxml2 的结果码（1 表示有效，0 表示无效）和全部验证错误。
+    ///
+    /// # Safety
+    ///
+    /// - `doc` 必须是有效的 xmlDocPtr
+    /// - 验证期间 libxml2 会临时替换文档的 DTD 并登记 ID 属性，调用者需保证没有并发访问该文档
+    pub unsafe fn validate_doc(&mut self, doc: xmlDocPtr) -> (i32, Vec<RawParseError>) {
+        // SAFETY: dtd 有效且被独占借用；验证上下文只在此函数内使用
+        unsafe {
+            let ctxt = crate::xmlNewValidCtxt();
+            if ctxt.is_null() {
+                return (-1, Vec::new());
+            }
+            let (ret, errors) =
+                collect_global_errors(|| crate::xmlValidateDtd(ctxt, doc, self.dtd));
+            crate::xmlFreeValidCtxt(ctxt);
+            (ret, errors)
+        }
+    }
+}
+
+impl Drop for DtdGuard {
+    fn drop(&mut self) {
+        // SAFETY: dtd 在 drop 时仍然有效，且不属于任何文档
+        unsafe {
+            crate::xmlFreeDtd(self.dtd);
+        }
+    }
+}
+
+// ========================================
+// 流式读取器封装
+// ========================================
+
+/// 读取回调的上下文：数据源，以及读取过程中发生的 I/O 错误或 panic
+struct ReaderSource<R> {
+    inner: R,
+    error: Option<io::Error>,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+/// `xmlInputReadCallback`：从 `context` 指向的 `ReaderSource<R>` 读取数据
+///
+/// 出错或 panic 时记录下来并返回 -1，libxml2 随即停止解析。
+unsafe extern "C" fn read_source<R: Read>(
+    context: *mut c_void,
+    buffer: *mut c_char,
+    len: c_int,
+) -> c_int {
+    if context.is_null() || buffer.is_null() || len <= 0 {
+        return 0;
+    }
+    // SAFETY: context 由 TextReaderGuard 设置，指向其持有的 ReaderSource<R>；
+    // libxml2 保证 buffer 可写入 len 字节
+    let (source, buffer) = unsafe {
+        (
+            &mut *(context as *mut ReaderSource<R>),
+            std::slice::from_raw_parts_mut(buffer.cast::<u8>(), len as usize),
+        )
+    };
+    if source.error.is_some() || source.panic.is_some() {
+        return -1;
+    }
+    loop {
+        match panic::catch_unwind(AssertUnwindSafe(|| source.inner.read(buffer))) {
+            // read 返回的字节数不超过 buffer 长度，不会溢出 c_int
+            Ok(Ok(n)) => return n as c_int,
+            Ok(Err(e)) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Ok(Err(e)) => source.error = Some(e),
+            Err(payload) => source.panic = Some(payload),
+        }
+        return -1;
+    }
+}
+
+/// 流式读取器（`xmlTextReader`）守卫，确保正确释放资源并收集解析诊断
+///
+/// 数据通过回调从 `R` 按需读取，读取器只保留当前节点附近的少量树结构，
+/// 已经读过的节点会被逐步释放。
+pub struct TextReaderGuard<R> {
+    reader: *mut crate::xmlTextReader,
+    // 装箱保证地址稳定，回调通过裸指针访问
+    source: Box<ReaderSource<R>>,
+    #[allow(clippy::box_collection)]
+    errors: Box<Vec<RawParseError>>,
+    // 读取器内部文档，展开过节点后才会记录；释放前要清理其片段存储
+    doc: xmlDocPtr,
+}
+
+// SAFETY: 读取器及其内部文档只被守卫独占访问，数据源随守卫一起转移
+unsafe impl<R: Send> Send for TextReaderGuard<R> {}
+
+impl<R: Read> TextReaderGuard<R> {
+    /// 创建从 `source` 读取 XML 的读取器
+    ///
+    /// 不指定编码，由 libxml2 根据 BOM 和 XML 声明探测。
+    /// `options` 为 `xmlParserOption` 位掩码。
+    pub fn new(source: R, options: i32) -> Option<Self> {
+        let mut source = Box::new(ReaderSource {
+            inner: source,
+            error: None,
+            panic: None,
+        });
+        let context = &mut *source as *mut ReaderSource<R> as *mut c_void;
+        // SAFETY: context 指向的 ReaderSource 与读取器同生共死；不注册关闭回调，数据源由守卫释放
+        let reader = unsafe {
+            crate::xmlReaderForIO(
+                Some(read_source::<R>),
+                None,
+                context,
+                ptr::null(),
+                ptr::null(),
+                options,
+            )
+        };
+        if reader.is_null() {
+            return None;
+        }
+        let mut guard = Self {
+            reader,
+            source,
+            errors: Box::default(),
+            doc: ptr::null_mut(),
+        };
+        let data = &mut *guard.errors as *mut Vec<RawParseError> as *mut c_void;
+        // SAFETY: reader 有效；data 指向的 Vec 与读取器同生共死
+        unsafe {
+            crate::xmlTextReaderSetStructuredErrorHandler(reader, Some(collect_parse_error), data)
+        };
+        Some(guard)
+    }
+
+    /// 前进到文档顺序中的下一个节点
+    ///
+    /// 返回 1 表示成功，0 表示已到文档末尾，-1 表示出错。
+    /// 数据源的 `read` 发生 panic 时，panic 会在这里继续传播。
+    pub fn read(&mut self) -> i32 {
+        // SAFETY: reader 有效
+        let ret = unsafe { crate::xmlTextReaderRead(self.reader) };
+        self.resume_panic();
+        ret
+    }
+
+    /// 跳过当前节点的子树，前进到其后的节点，返回值同 [`TextReaderGuard::read`]
+    pub fn skip_subtree(&mut self) -> i32 {
+        // SAFETY: reader 有效
+        let ret = unsafe { crate::xmlTextReaderNext(self.reader) };
+        self.resume_panic();
+        ret
+    }
+
+    /// 读入当前节点的完整子树并返回节点，出错时返回 null
+    ///
+    /// 返回的节点在下一次 [`read`](TextReaderGuard::read) 或
+    /// [`skip_subtree`](TextReaderGuard::skip_subtree) 之前有效。
+    pub fn expand(&mut self) -> xmlNodePtr {
+        // SAFETY: reader 有效
+        let node = unsafe { crate::xmlTextReaderExpand(self.reader) };
+        self.resume_panic();
+        if !node.is_null() {
+            // SAFETY: node 刚由读取器返回，有效
+            self.doc = unsafe { (*node).doc };
+        }
+        node
+    }
+
+    /// 当前节点类型（`xmlReaderTypes`）
+    pub fn node_type(&self) -> i32 {
+        // SAFETY: reader 有效
+        unsafe { crate::xmlTextReaderNodeType(self.reader) }
+    }
+
+    /// 当前节点的深度，根元素为 0
+    pub fn depth(&self) -> i32 {
+        // SAFETY: reader 有效
+        unsafe { crate::xmlTextReaderDepth(self.reader) }
+    }
+
+    /// 当前节点是否为空元素（`<a/>`），空元素没有对应的结束节点
+    pub fn is_empty_element(&self) -> bool {
+        // SAFETY: reader 有效
+        unsafe { crate::xmlTextReaderIsEmptyElement(self.reader) == 1 }
+    }
+
+    /// 当前节点的限定名（如 `g:price`）
+    pub fn name(&self) -> String {
+        // SAFETY: reader 有效；返回的字符串属于读取器的字典，立即复制
+        unsafe { ptr_to_string(crate::xmlTextReaderConstName(self.reader).cast()) }
+    }
+
+    /// 当前节点的文本值（文本、注释、处理指令的内容），元素没有值
+    pub fn value(&self) -> Option<String> {
+        // SAFETY: reader 有效；返回的字符串在读取器前进前有效，立即复制
+        unsafe { ptr_to_option_string(crate::xmlTextReaderConstValue(self.reader).cast()) }
+    }
+
+    /// 当前元素的属性（按文档顺序），不含命名空间声明
+    pub fn attributes(&mut self) -> Vec<(String, String)> {
+        let mut attributes = Vec::new();
+        // SAFETY: reader 有效；遍历结束后回到元素节点
+        unsafe {
+            while crate::xmlTextReaderMoveToNextAttribute(self.reader) == 1 {
+                if crate::xmlTextReaderIsNamespaceDecl(self.reader) != 1 {
+                    attributes.push((self.name(), self.value().unwrap_or_default()));
+                }
+            }
+            crate::xmlTextReaderMoveToElement(self.reader);
+        }
+        attributes
+    }
+
+    /// 取出目前收集到的所有诊断
+    pub fn take_errors(&mut self) -> Vec<RawParseError> {
+        std::mem::take(&mut *self.errors)
+    }
+
+    /// 取出数据源返回的 I/O 错误
+    pub fn take_io_error(&mut self) -> Option<io::Error> {
+        self.source.error.take()
+    }
+
+    fn resume_panic(&mut self) {
+        if let Some(payload) = self.source.panic.take() {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl<R> Drop for TextReaderGuard<R> {
+    fn drop(&mut self) {
+        // SAFETY: reader 在 drop 时仍然有效。片段节点属于存储自己的暂存文档，不依赖读取器文档，
+        // 但存储挂在读取器文档的 `_private` 上：xmlFreeTextReader 会直接释放该文档而不清理
+        // `_private`，所以必须先在文档仍然有效时取出并释放存储，否则存储会泄漏
+        unsafe {
+            if !self.doc.is_null() {
+                free_fragment_store(self.doc);
+            }
+            crate::xmlFreeTextReader(self.reader);
+        }
+    }
+}
+
+// ========================================
+// XML Schema / RELAX NG / Schematron 验证封装
+// ========================================
+
+/// 编译后的 XML Schema 守卫，确保正确释放资源
+///
+/// 编译结果在验证时只读，每次验证使用独立的验证上下文，因此可以跨线程共享。
+#[cfg(feature = "schemas")]
+pub struct SchemaGuard {
+    schema: *mut crate::xmlSchema,
+}
+
+// SAFETY: 验证只读取编译后的 schema，验证状态保存在每次新建的验证上下文中
+#[cfg(feature = "schemas")]
+unsafe impl Send for SchemaGuard {}
+#[cfg(feature = "schemas")]
+unsafe impl Sync for SchemaGuard {}
+
+#[cfg(feature = "schemas")]
+impl SchemaGuard {
+    /// 从内存中的 XSD 文本编译 schema
+    ///
+    /// 返回编译结果（失败时为 `None`）和编译过程中的全部错误与警告。
+    ///
+    /// # Safety
+    ///
+    /// `buffer` 必须指向至少 `size` 字节的有效内存
+    pub unsafe fn parse_memory(
+        buffer: *const c_char,
+        size: i32,
+    ) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 buffer 有效
+        unsafe { Self::parse(crate::xmlSchemaNewMemParserCtxt(buffer, size)) }
+    }
+
+    /// 从文件或 URL 编译 schema，相对路径的 `xs:include`/`xs:import` 以它为基准解析
+    ///
+    /// # Safety
+    ///
+    /// `url` 必须是有效的以 null 结尾的 C 字符串
+    pub unsafe fn parse_file(url: *const c_char) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 url 有效
+        unsafe { Self::parse(crate::xmlSchemaNewParserCtxt(url)) }
+    }
+
+    /// 使用解析上下文编译 schema，并释放上下文
+    unsafe fn parse(ctxt: *mut crate::xmlSchemaParserCtxt) -> (Option<Self>, Vec<RawParseError>) {
+        let mut errors = Vec::new();
+        if ctxt.is_null() {
+            return (None, errors);
+        }
+        // SAFETY: ctxt 由调用方新建且有效；errors 在整个编译期间存活
+        let schema = unsafe {
+            let data = (&raw mut errors).cast::<c_void>();
+            crate::xmlSchemaSetParserStructuredErrors(ctxt, Some(collect_parse_error), data);
+            let schema = crate::xmlSchemaParse(ctxt);
+            crate::xmlSchemaFreeParserCtxt(ctxt);
+            schema
+        };
+        ((!schema.is_null()).then_some(Self { schema }), errors)
+    }
+
+    /// 验证文档
+    ///
+    /// 返回 libxml2 的结果码（0 表示有效，正数表示无效，负数表示内部错误）
+    /// 和验证过程中的全部错误与警告。
+    ///
+    /// # Safety
+    ///
+    /// - `doc` 必须是有效的 xmlDocPtr
+    /// - 验证会把 `xs:ID` 类型的属性登记到文档中，调用者需保证没有并发访问该文档
+    pub unsafe fn validate_doc(&self, doc: xmlDocPtr) -> (i32, Vec<RawParseError>) {
+        let mut errors = Vec::new();
+        // SAFETY: schema 有效；验证上下文只在此函数内使用
+        unsafe {
+            let ctxt = crate::xmlSchemaNewValidCtxt(self.schema);
+            if ctxt.is_null() {
+                return (-1, errors);
+            }
+            let data = (&raw mut errors).cast::<c_void>();
+            crate::xmlSchemaSetValidStructuredErrors(ctxt, Some(collect_parse_error), data);
+            let ret = crate::xmlSchemaValidateDoc(ctxt, doc);
+            crate::xmlSchemaFreeValidCtxt(ctxt);
+            (ret, errors)
+        }
+    }
+}
+
+#[cfg(feature = "schemas")]
+impl Drop for SchemaGuard {
+    fn drop(&mut self) {
+        // SAFETY: schema 在 drop 时仍然有效
+        unsafe {
+            crate::xmlSchemaFree(self.schema);
+        }
+    }
+}
+
+/// 编译后的 RELAX NG 语法守卫，确保正确释放资源
+///
+/// 与 [`SchemaGuard`] 相同，验证状态保存在每次新建的验证上下文中，可以跨线程共享。
+#[cfg(feature = "schemas")]
+pub struct RelaxNgGuard {
+    grammar: *mut crate::xmlRelaxNG,
+}
+
+// SAFETY: 验证只读取编译后的语法，验证状态保存在每次新建的验证上下文中
+#[cfg(feature = "schemas")]
+unsafe impl Send for RelaxNgGuard {}
+#[cfg(feature = "schemas")]
+unsafe impl Sync for RelaxNgGuard {}
+
+#[cfg(feature = "schemas")]
+impl RelaxNgGuard {
+    /// 从内存中的 RELAX NG（XML 语法）文本编译
+    ///
+    /// # Safety
+    ///
+    /// `buffer` 必须指向至少 `size` 字节的有效内存
+    pub unsafe fn parse_memory(
+        buffer: *const c_char,
+        size: i32,
+    ) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 buffer 有效
+        unsafe { Self::parse(crate::xmlRelaxNGNewMemParserCtxt(buffer, size)) }
+    }
+
+    /// 从文件或 URL 编译，`externalRef`/`include` 以它为基准解析
+    ///
+    /// # Safety
+    ///
+    /// `url` 必须是有效的以 null 结尾的 C 字符串
+    pub unsafe fn parse_file(url: *const c_char) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 url 有效
+        unsafe { Self::parse(crate::xmlRelaxNGNewParserCtxt(url)) }
+    }
+
+    /// 使用解析上下文编译语法，并释放上下文
+    unsafe fn parse(ctxt: *mut crate::xmlRelaxNGParserCtxt) -> (Option<Self>, Vec<RawParseError>) {
+        let mut errors = Vec::new();
+        if ctxt.is_null() {
+            return (None, errors);
+        }
+        // SAFETY: ctxt 由调用方新建且有效；errors 在整个编译期间存活
+        let grammar = unsafe {
+            let data = (&raw mut errors).cast::<c_void>();
+            crate::xmlRelaxNGSetParserStructuredErrors(ctxt, Some(collect_parse_error), data);
+            let grammar = crate::xmlRelaxNGParse(ctxt);
+            crate::xmlRelaxNGFreeParserCtxt(ctxt);
+            grammar
+        };
+        ((!grammar.is_null()).then_some(Self { grammar }), errors)
+    }
+
+    /// 验证文档
+    ///
+    /// 返回 libxml2 的结果码（0 表示有效，正数表示无效，负数表示内部错误）
+    /// 和验证过程中的全部错误与警告。
+    ///
+    /// # Safety
+    ///
+    /// - `doc` 必须是有效的 xmlDocPtr
+    /// - 验证可能把 ID 类型的属性登记到文档中，调用者需保证没有并发访问该文档
+    pub unsafe fn validate_doc(&self, doc: xmlDocPtr) -> (i32, Vec<RawParseError>) {
+        let mut errors = Vec::new();
+        // SAFETY: grammar 有效；验证上下文只在此函数内使用
+        unsafe {
+            let ctxt = crate::xmlRelaxNGNewValidCtxt(self.grammar);
+            if ctxt.is_null() {
+                return (-1, errors);
+            }
+            let data = (&raw mut errors).cast::<c_void>();
+            crate::xmlRelaxNGSetValidStructuredErrors(ctxt, Some(collect_parse_error), data);
+            let ret = crate::xmlRelaxNGValidateDoc(ctxt, doc);
+            crate::xmlRelaxNGFreeValidCtxt(ctxt);
+            (ret, errors)
+        }
+    }
+}
+
+#[cfg(feature = "schemas")]
+impl Drop for RelaxNgGuard {
+    fn drop(&mut self) {
+        // SAFETY: grammar 在 drop 时仍然有效
+        unsafe {
+            crate::xmlRelaxNGFree(self.grammar);
+        }
+    }
+}
+
+/// 编译后的 Schematron 规则守卫，确保正确释放资源
+///
+/// 规则中的 XPath 编译结果在求值时会被写入缓存，因此它不是 Sync：
+/// 同一时刻只能有一个验证在进行。
+#[cfg(feature = "schemas")]
+pub struct SchematronGuard {
+    schema: *mut crate::xmlSchematron,
+}
+
+// SAFETY: 编译结果不绑定线程，可以在线程间转移所有权
+#[cfg(feature = "schemas")]
+unsafe impl Send for SchematronGuard {}
+
+#[cfg(feature = "schemas")]
+impl SchematronGuard {
+    /// 从内存中的 Schematron 文本编译
+    ///
+    /// # Safety
+    ///
+    /// `buffer` 必须指向至少 `size` 字节的有效内存
+    pub unsafe fn parse_memory(
+        buffer: *const c_char,
+        size: i32,
+    ) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 buffer 有效
+        unsafe { Self::parse(crate::xmlSchematronNewMemParserCtxt(buffer, size)) }
+    }
+
+    /// 从文件或 URL 编译
+    ///
+    /// # Safety
+    ///
+    /// `url` 必须是有效的以 null 结尾的 C 字符串
+    pub unsafe fn parse_file(url: *const c_char) -> (Option<Self>, Vec<RawParseError>) {
+        // SAFETY: 调用者保证 url 有效
+        unsafe { Self::parse(crate::xmlSchematronNewParserCtxt(url)) }
+    }
+
+    /// 使用解析上下文编译规则，并释放上下文
+    unsafe fn parse(
+        ctxt: *mut crate::xmlSchematronParserCtxt,
+    ) -> (Option<Self>, Vec<RawParseError>) {
+        if ctxt.is_null() {
+            return (None, Vec::new());
+        }
+        // Schematron 解析上下文没有错误回调接口，只能临时接管全局回调
+        // SAFETY: ctxt 由调用方新建且有效
+        let (schema, errors) = unsafe {
+            collect_global_errors(|| {
+                let schema = crate::xmlSchematronParse(ctxt);
+                crate::xmlSchematronFreeParserCtxt(ctxt);
+                schema
+            })
+        };
+        ((!schema.is_null()).then_some(Self { schema }), errors)
+    }
+
+    /// 验证文档
+    ///
+    /// 未通过的 `assert` 和触发的 `report` 都会作为错误报告。
+    /// 返回 libxml2 的结果码（0 表示有效，正数表示违规数量，负数表示内部错误）
+    /// 和全部违规。
+    ///
+    /// # Safety
+    ///
+    /// `doc` 必须是有效的 xmlDocPtr
+    pub unsafe fn validate_doc(&mut self, doc: xmlDocPtr) -> (i32, Vec<RawParseError>) {
+        let mut errors = Vec::new();
+        // SAFETY: schema 有效且被独占借用；验证上下文只在此函数内使用
+        unsafe {
+            let ctxt = crate::xmlSchematronNewValidCtxt(
+                self.schema,
+                crate::xmlSchematronValidOptions_XML_SCHEMATRON_OUT_ERROR as i32,
+            );
+            if ctxt.is_null() {
+                return (-1, errors);
+            }
+            let data = (&raw mut errors).cast::<c_void>();
+            crate::xmlSchematronSetValidStructuredErrors(ctxt, Some(collect_parse_error), data);
+            let ret = crate::xmlSchematronValidateDoc(ctxt, doc);
+            crate::xmlSchematronFreeValidCtxt(ctxt);
+            (ret, errors)
+        }
+    }
+}
+
+#[cfg(feature = "schemas")]
+impl Drop for SchematronGuard {
+    fn drop(&mut self) {
+        // SAFETY: schema 在 drop 时仍然有效
+        unsafe {
+            crate::xmlSchematronFree(self.schema);
+        }
+    }
+}
+
+// ========================================
+// XSLT 封装
+// ========================================
+
+#[cfg(feature = "xslt")]
+thread_local! {
+    // 当前线程正在收集的错误消息，未在收集时为 None
+    static XSLT_MESSAGES: std::cell::RefCell<Option<String>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// 把一段错误消息追加到当前线程的收集缓冲区
+#[cfg(feature = "xslt")]
+fn push_xslt_message(message: &str) {
+    let _ = XSLT_MESSAGES.try_with(|messages| {
+        if let Ok(mut messages) = messages.try_borrow_mut()
+            && let Some(buf) = messages.as_mut()
+        {
+            buf.push_str(message);
+        }
+    });
+}
+
+/// 接收 xslt_shim.c 格式化后的 libxslt 错误消息（可能是一行中的片段）
+#[cfg(feature = "xslt")]
+unsafe extern "C" fn xslt_message_sink(message: *const c_char) {
+    // SAFETY: shim 保证 message 是有效的 C 字符串
+    push_xslt_message(&unsafe { CStr::from_ptr(message) }.to_string_lossy());
+}
+
+/// 接收转换期间 libxml2 报告的结构化错误（如 XPath 求值错误）
+#[cfg(feature = "xslt")]
+unsafe extern "C" fn xslt_structured_error(_data: *mut c_void, error: *const xmlError) {
+    if error.is_null() {
+        return;
+    }
+    // SAFETY: error 在回调期间有效
+    let message = unsafe { ptr_to_string((*error).message) };
+    push_xslt_message(message.trim_end());
+    push_xslt_message("\n");
+}
+
+/// 按时间顺序收集 `f` 执行期间 libxslt 与 libxml2 报告的错误消息，每行一条
+///
+/// libxslt 的错误回调是进程级的，首次调用时安装，之后按线程分别收集；
+/// 不在收集期间的消息被丢弃，不会输出到 stderr。
+///
+/// # Safety
+///
+/// `f` 内部不能再注册全局错误回调
+#[cfg(feature = "xslt")]
+unsafe fn collect_xslt_errors<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    // SAFETY: sink 是静态函数，在进程生命周期内有效
+    INSTALL.call_once(|| unsafe { crate::rlibxml_xslt_set_error_sink(Some(xslt_message_sink)) });
+
+    XSLT_MESSAGES.with(|messages| *messages.borrow_mut() = Some(String::new()));
+    // SAFETY: 回调不使用上下文，守卫释放时恢复原来的回调
+    let result = unsafe {
+        let _scope = StructuredErrorScope::install(ptr::null_mut(), Some(xslt_structured_error));
+        f()
+    };
+    let collected = XSLT_MESSAGES
+        .with(|messages| messages.borrow_mut().take())
+        .unwrap_or_default();
+    let messages = collected
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    (result, messages)
+}
+
+/// 编译后的 XSLT 样式表守卫，确保正确释放资源
+///
+/// 样式表中的 XPath 编译结果在求值时会被写入缓存，因此它不是 Sync：
+/// 同一时刻只能有一个转换在进行。
+#[cfg(feature = "xslt")]
+pub struct StylesheetGuard {
+    style: *mut crate::xsltStylesheet,
+}
+
+// SAFETY: 样式表拥有自己的文档副本，不绑定线程，可以在线程间转移所有权
+#[cfg(feature = "xslt")]
+unsafe impl Send for StylesheetGuard {}
+
+#[cfg(feature = "xslt")]
+impl StylesheetGuard {
+    /// 从已解析的样式表文档编译
+    ///
+    /// libxslt 会接管传入的文档，因此这里编译的是文档副本，原文档不受影响。
+    /// 返回编译结果和期间报告的错误消息。
+    ///
+    /// # Safety
+    ///
+    /// `doc` 必须是有效的 xmlDocPtr，且编译期间没有并发修改
+    pub unsafe fn parse(doc: xmlDocPtr) -> (Option<Self>, Vec<String>) {
+        // SAFETY: doc 有效；副本由样式表接管，编译失败时由这里释放
+        let (style, messages) = unsafe {
+            collect_xslt_errors(|| {
+                let copy = crate::xmlCopyDoc(doc, 1);
+                if copy.is_null() {
+                    return ptr::null_mut();
+                }
+                let style = crate::xsltParseStylesheetDoc(copy);
+                if style.is_null() {
+                    crate::xmlFreeDoc(copy);
+                }
+                style
+            })
+        };
+        ((!style.is_null()).then_some(Self { style }), messages)
+    }
+
+    /// 对文档执行转换，返回结果文档（失败时为 null）和期间报告的错误消息
+    ///
+    /// libxslt 会在源文档节点上记录临时标记，并按 `xsl:strip-space` 删除空白节点，
+    /// 因此转换的是文档副本。转换禁止写文件、创建目录和访问网络。
+    ///
+    /// # Safety
+    ///
+    /// - `doc` 必须是有效的 xmlDocPtr，且转换期间没有并发修改
+    /// - `params` 必须是参数名与字符串值交替排列、以 null 结尾的 C 字符串数组
+    /// - 返回的非 null 文档由调用者负责释放
+    pub unsafe fn transform(
+        &mut self,
+        doc: xmlDocPtr,
+        params: *const *const c_char,
+    ) -> (xmlDocPtr, Vec<String>) {
+        // SAFETY: style 有效且被独占借用；副本、转换上下文和安全策略只在此函数内使用
+        unsafe {
+            collect_xslt_errors(|| {
+                let source = crate::xmlCopyDoc(doc, 1);
+                if source.is_null() {
+                    return ptr::null_mut();
+                }
+                let ctxt = crate::xsltNewTransformContext(self.style, source);
+                let prefs = crate::xsltNewSecurityPrefs();
+                let mut result = ptr::null_mut();
+                if !ctxt.is_null() && !prefs.is_null() {
+                    for option in [
+                        crate::xsltSecurityOption_XSLT_SECPREF_WRITE_FILE,
+                        crate::xsltSecurityOption_XSLT_SECPREF_CREATE_DIRECTORY,
+                        crate::xsltSecurityOption_XSLT_SECPREF_READ_NETWORK,
+                        crate::xsltSecurityOption_XSLT_SECPREF_WRITE_NETWORK,
+                    ] {
+                        crate::xsltSetSecurityPrefs(prefs, option, Some(crate::xsltSecurityForbid));
+                    }
+                    if crate::xsltSetCtxtSecurityPrefs(prefs, ctxt) == 0
+                        && crate::xsltQuoteUserParams(ctxt, params.cast_mut()) == 0
+                    {
+                        result = crate::xsltApplyStylesheetUser(
+                            self.style,
+                            source,
+                            ptr::null_mut(),
+                            ptr::null(),
+                            ptr::null_mut(),
+                            ctxt,
+                        );
+                    }
+                }
+                if !ctxt.is_null() {
+                    crate::xsltFreeTransformContext(ctxt);
+                }
+                if !prefs.is_null() {
+                    crate::xsltFreeSecurityPrefs(prefs);
+                }
+                crate::xmlFreeDoc(source);
+                result
+            })
+        }
+    }
+
+    /// 按样式表的 `xsl:output` 设置序列化结果文档，失败时返回 `None`
+    ///
+    /// 输出使用 `xsl:output` 声明的编码，不一定是 UTF-8。
+    ///
+    /// # Safety
+    ///
+    /// `result` 必须是有效的 xmlDocPtr
+    pub unsafe fn save_result(&self, result: xmlDocPtr) -> Option<Vec<u8>> {
+        let mut buf: *mut crate::xmlChar = ptr::null_mut();
+        let mut len = 0;
+        // SAFETY: style 和 result 有效；buf 由 libxml2 分配，复制后释放
+        unsafe {
+            let ret = crate::xsltSaveResultToString(&mut buf, &mut len, result, self.style);
+            let bytes = if buf.is_null() {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(buf, len.max(0) as usize).to_vec()
+            };
+            free_xml_char(buf);
+            (ret == 0).then_some(bytes)
+        }
+    }
+}
+
+#[cfg(feature = "xslt")]
+impl Drop for StylesheetGuard {
+    fn drop(&mut self) {
+        // SAFETY: style 在 drop 时仍然有效，同时释放它接管的文档
+        unsafe {
+            crate::xsltFreeStylesheet(self.style);
+        }
     }
-    unsafe { free_xml_char(prop) };
-    true
 }
@@ -2,5 +2,27 @@
 // 仅包含爬虫所需的 HTML 解析和 XPath 功能
 
 #include <libxml/HTMLparser.h>
+#include <libxml/HTMLtree.h>
+#include <libxml/SAX2.h>
 #include <libxml/xpath.h>
 #include <libxml/xpathInternals.h>
+#include <libxml/valid.h>
+#include <libxml/xmlreader.h>
+
+// 可选模块：未在 CMake 中启用时，头文件中的声明会被条件编译去掉
+#include <libxml/xmlschemas.h>
+#include <libxml/relaxng.h>
+#include <libxml/schematron.h>
+
+// XSLT：由 build.rs 在启用 xslt feature 时定义 RLIBXML_WITH_XSLT
+#ifdef RLIBXML_WITH_XSLT
+#include <libxslt/xslt.h>
+#include <libxslt/xsltInternals.h>
+#include <libxslt/transform.h>
+#include <libxslt/variables.h>
+#include <libxslt/xsltutils.h>
+#include <libxslt/security.h>
+
+// xslt_shim.c：把 libxslt 的变参错误回调格式化为完整字符串后转交给 sink
+void rlibxml_xslt_set_error_sink(void (*sink)(const char *message));
+#endif
@@ -0,0 +1,53 @@
+// xslt_shim.c - libxslt 错误回调桥接（仅在启用 xslt feature 时编译）
+//
+// libxslt 的错误回调是 C 变参函数，Rust 无法直接实现。
+// 这里把每次回调格式化为完整字符串，再转交给 Rust 注册的 sink。
+
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include <libxslt/xsltutils.h>
+
+typedef void (*rlibxml_message_sink)(const char *message);
+
+static rlibxml_message_sink message_sink = NULL;
+
+static void rlibxml_xslt_error(void *ctx, const char *msg, ...) {
+    char stack_buf[512];
+    va_list args;
+    int len;
+
+    (void)ctx;
+    if (message_sink == NULL || msg == NULL) {
+        return;
+    }
+
+    va_start(args, msg);
+    len = vsnprintf(stack_buf, sizeof(stack_buf), msg, args);
+    va_end(args);
+    if (len < 0) {
+        return;
+    }
+    if ((size_t)len < sizeof(stack_buf)) {
+        message_sink(stack_buf);
+        return;
+    }
+
+    // 超长消息重新格式化到堆上
+    char *heap_buf = malloc((size_t)len + 1);
+    if (heap_buf == NULL) {
+        return;
+    }
+    va_start(args, msg);
+    vsnprintf(heap_buf, (size_t)len + 1, msg, args);
+    va_end(args);
+    message_sink(heap_buf);
+    free(heap_buf);
+}
+
+// 注册 sink 并接管 libxslt 的全局错误回调（进程级，只需调用一次）
+void rlibxml_xslt_set_error_sink(rlibxml_message_sink sink) {
+    message_sink = sink;
+    xsltSetGenericErrorFunc(NULL, rlibxml_xslt_error);
+}
@@ -0,0 +1,236 @@
+//! rlibxml-derive - rlibxml 的派生宏
+//!
+//! 提供 `#[derive(FromNode)]`，根据字段上的 `#[xpath]` / `#[attr]` 声明生成
+//! `rlibxml::FromNode` 的实现。请通过 `rlibxml` 的 `derive` feature 使用，不要直接依赖这个 crate。
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{
+    Data, DeriveInput, Fields, GenericArgument, Ident, LitStr, PathArguments, Token, Type,
+    parse_macro_input,
+};
+
+/// 为具名字段的结构体实现 `rlibxml::FromNode`
+///
+/// 每个字段必须带有以下属性之一（或同时带有两者）：
+///
+/// - `#[xpath("./h2/text()")]` - 以当前节点为上下文查询，取匹配节点的文本
+/// - `#[attr("href")]` - 取属性值；与 `#[xpath]` 同用时取匹配节点的属性，否则取当前节点的属性
+/// - `#[xpath("./div[@class='offer']", nested)]` - 把匹配的节点交给字段类型的 `FromNode` 实现
+///
+/// 字段类型决定取值方式：
+///
+/// - `T` - 第一个匹配，没有匹配时报错
+/// - `Option<T>` - 第一个匹配，没有匹配时为 `None`
+/// - `Vec<T>` - 所有匹配
+///
+/// 非 `nested` 字段的 `T` 需要实现 `FromStr`（错误类型实现 `Display`），值去除首尾空白后解析。
+/// 任何字段失败时返回 `rlibxml::Error::Extract`，其中包含字段路径、选择器和原因。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, FromNode};
+///
+/// #[derive(FromNode)]
+/// struct Offer {
+///     #[xpath("./span")]
+///     price: f64,
+///     #[attr("data-currency")]
+///     currency: Option<String>,
+/// }
+///
+/// #[derive(FromNode)]
+/// struct Product {
+///     #[xpath("./h2/text()")]
+///     name: String,
+///     #[xpath("./a")]
+///     #[attr("href")]
+///     link: String,
+///     #[xpath("./ul/li")]
+///     tags: Vec<String>,
+///     #[xpath("./div[@class='offer']", nested)]
+///     offers: Vec<Offer>,
+/// }
+///
+/// let html = r#"
+///     <div class="product">
+///         <h2>Pen</h2>
+///         <a href="/pen">details</a>
+///         <ul><li>office</li><li>blue</li></ul>
+///         <div class="offer" data-currency="EUR"><span> 1.50 </span></div>
+///         <div class="offer"><span>2</span></div>
+///     </div>
+/// "#;
+/// let doc = Document::parse(html)?;
+/// let products: Vec<Product> = doc.extract_all("//div[@class='product']")?;
+/// let pen = &products[0];
+/// assert_eq!(pen.name, "Pen");
+/// assert_eq!(pen.link, "/pen");
+/// assert_eq!(pen.tags, ["office", "blue"]);
+/// assert_eq!(pen.offers[0].price, 1.5);
+/// assert_eq!(pen.offers[0].currency.as_deref(), Some("EUR"));
+/// assert_eq!(pen.offers[1].currency, None);
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+#[proc_macro_derive(FromNode, attributes(xpath, attr))]
+pub fn derive_from_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "FromNode can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FromNode can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut inits = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let spec = FieldSpec::parse(field)?;
+        let name = ident.to_string();
+        let xpath = option_tokens(spec.xpath.as_ref());
+        let attr = option_tokens(spec.attr.as_ref());
+        let (method, ty) = extraction(&field.ty, spec.nested);
+        inits.push(quote! {
+            #ident: ::rlibxml::__private::Field {
+                name: #name,
+                xpath: #xpath,
+                attr: #attr,
+            }
+            .#method::<#ty>(node)?
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rlibxml::FromNode for #ident #ty_generics #where_clause {
+            fn from_node(node: &::rlibxml::SelectedNode<'_>) -> ::rlibxml::Result<Self> {
+                ::std::result::Result::Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+/// 字段上的 `#[xpath]` / `#[attr]` 声明
+struct FieldSpec {
+    xpath: Option<LitStr>,
+    attr: Option<LitStr>,
+    nested: bool,
+}
+
+impl FieldSpec {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut spec = FieldSpec {
+            xpath: None,
+            attr: None,
+            nested: false,
+        };
+        for attr in &field.attrs {
+            if attr.path().is_ident("xpath") {
+                if spec.xpath.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "duplicate #[xpath] attribute",
+                    ));
+                }
+                attr.parse_args_with(|input: ParseStream| {
+                    spec.xpath = Some(input.parse()?);
+                    if input.parse::<Option<Token![,]>>()?.is_some() {
+                        let flag: Ident = input.parse()?;
+                        if flag != "nested" {
+                            return Err(syn::Error::new_spanned(flag, "expected `nested`"));
+                        }
+                        spec.nested = true;
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("attr") {
+                if spec.attr.is_some() {
+                    return Err(syn::Error::new_spanned(attr, "duplicate #[attr] attribute"));
+                }
+                spec.attr = Some(attr.parse_args()?);
+            }
+        }
+
+        if spec.xpath.is_none() && spec.attr.is_none() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "field needs an #[xpath(\"...\")] or #[attr(\"...\")] attribute",
+            ));
+        }
+        if spec.nested && spec.attr.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "nested fields cannot use #[attr]",
+            ));
+        }
+        Ok(spec)
+    }
+}
+
+fn option_tokens(lit: Option<&LitStr>) -> TokenStream2 {
+    match lit {
+        Some(lit) => quote!(::std::option::Option::Some(#lit)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+/// 按字段类型选择 `Field` 的方法，返回方法名和传给它的元素类型
+fn extraction(ty: &Type, nested: bool) -> (Ident, &Type) {
+    let (kind, inner) = if let Some(inner) = wrapped(ty, "Option") {
+        ("optional", inner)
+    } else if let Some(inner) = wrapped(ty, "Vec") {
+        ("all", inner)
+    } else {
+        ("one", ty)
+    };
+    let method = if nested {
+        format!("nested_{kind}")
+    } else {
+        kind.to_string()
+    };
+    (Ident::new(&method, proc_macro2::Span::call_site()), inner)
+}
+
+/// `ty` 是 `wrapper<T>` 时返回 `T`
+fn wrapped<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
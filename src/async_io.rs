@@ -0,0 +1,222 @@
+//! 基于 tokio 的异步解析
+//!
+//! libxml2 的解析是同步的 CPU 计算。这里从 [`AsyncRead`] 异步读取数据，按 [`AsyncOptions`]
+//! 把解析工作放到 tokio 的阻塞线程池中执行，避免阻塞运行时的工作线程。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::options::XmlParseOptions;
+use crate::push::PushParser;
+use crate::reader::{ReaderEvent, XmlReader};
+use std::io::{self, BufReader, Read};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// [`AsyncXmlReader`] 的事件通道容量
+const EVENT_BUFFER: usize = 256;
+
+/// 异步解析选项
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::AsyncOptions;
+///
+/// // 每次读取 1 MiB，在当前任务中直接解析（适合数据很小或已经在专用线程上运行的场景）
+/// let options = AsyncOptions {
+///     chunk_size: 1 << 20,
+///     spawn_blocking: false,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsyncOptions {
+    /// 每次从数据源读取并交给解析器的最大字节数（默认 64 KiB）
+    ///
+    /// 开启 `spawn_blocking` 时每一块都会提交一次阻塞任务，块越大调度开销越小；
+    /// 关闭时每一块的解析都在当前任务中完成，块越小占用工作线程的时间越短。
+    pub chunk_size: usize,
+    /// 是否通过 [`tokio::task::spawn_blocking`] 在阻塞线程池中解析（默认 `true`）
+    ///
+    /// [`AsyncXmlReader`] 总是在阻塞线程池中运行，不受此选项影响。
+    pub spawn_blocking: bool,
+}
+
+impl Default for AsyncOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64 * 1024,
+            spawn_blocking: true,
+        }
+    }
+}
+
+/// 从异步数据源读取全部数据并用推送解析器解析
+pub(crate) async fn parse_push<R: AsyncRead + Unpin>(
+    mut source: R,
+    mut parser: PushParser,
+    options: AsyncOptions,
+) -> Result<Document> {
+    let mut buf = vec![0; options.chunk_size.max(1)];
+    loop {
+        let n = source.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        if options.spawn_blocking {
+            let result;
+            (parser, buf, result) = offload(move || {
+                let result = parser.feed(&buf[..n]);
+                (parser, buf, result)
+            })
+            .await?;
+            result?;
+        } else {
+            parser.feed(&buf[..n])?;
+        }
+    }
+    if options.spawn_blocking {
+        offload(move || parser.finish()).await?
+    } else {
+        parser.finish()
+    }
+}
+
+/// 在阻塞线程池中执行 `f`，任务中的 panic 会在调用者处重新抛出
+async fn offload<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T> {
+    join(tokio::task::spawn_blocking(f)).await
+}
+
+async fn join<T>(task: JoinHandle<T>) -> Result<T> {
+    match task.await {
+        Ok(value) => Ok(value),
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(Error::Custom(format!("blocking parse task failed: {e}"))),
+    }
+}
+
+/// 在阻塞线程中以同步方式读取异步数据源
+struct BlockingSource<R> {
+    inner: R,
+    handle: Handle,
+}
+
+impl<R: AsyncRead + Unpin> Read for BlockingSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.handle.block_on(self.inner.read(buf))
+    }
+}
+
+/// 异步流式 XML 读取器
+///
+/// 在 tokio 的阻塞线程池中运行 [`XmlReader`]，通过通道把 [`ReaderEvent`] 交给异步任务，
+/// 内存占用与文档大小无关。事件与 [`XmlReader::next_event`] 完全相同；由于事件跨线程传递，
+/// 不支持 [`XmlReader::expand`]，需要整条记录时请根据事件自行组装。
+///
+/// 丢弃读取器后，后台线程会在下一次产生事件时停止。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{AsyncXmlReader, ReaderEvent};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> rlibxml::Result<()> {
+/// let body: &[u8] = b"<feed><item>1</item><item>2</item></feed>";
+/// let mut reader = AsyncXmlReader::new(body);
+/// let mut items = 0;
+/// while let Some(event) = reader.next_event().await? {
+///     if matches!(&event, ReaderEvent::StartElement { name, .. } if name == "item") {
+///         items += 1;
+///     }
+/// }
+/// assert_eq!(items, 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncXmlReader {
+    events: mpsc::Receiver<Result<ReaderEvent>>,
+    // 事件读完后等待后台任务结束，以便传播其中的 panic
+    task: Option<JoinHandle<()>>,
+}
+
+impl AsyncXmlReader {
+    /// 使用默认选项创建读取器
+    ///
+    /// # Panics
+    ///
+    /// 不在 tokio 运行时中调用时 panic。
+    pub fn new<R: AsyncRead + Unpin + Send + 'static>(source: R) -> Self {
+        Self::with_options(source, XmlParseOptions::default(), AsyncOptions::default())
+    }
+
+    /// 使用自定义选项创建读取器
+    ///
+    /// `async_options.chunk_size` 决定每次从数据源读取的字节数。
+    ///
+    /// # Panics
+    ///
+    /// 不在 tokio 运行时中调用时 panic。
+    pub fn with_options<R: AsyncRead + Unpin + Send + 'static>(
+        source: R,
+        options: XmlParseOptions,
+        async_options: AsyncOptions,
+    ) -> Self {
+        let (sender, events) = mpsc::channel(EVENT_BUFFER);
+        let source = BlockingSource {
+            inner: source,
+            handle: Handle::current(),
+        };
+        let source = BufReader::with_capacity(async_options.chunk_size.max(1), source);
+        let task = tokio::task::spawn_blocking(move || {
+            let mut reader = match XmlReader::with_options(source, options) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    let _ = sender.blocking_send(Err(e));
+                    return;
+                }
+            };
+            loop {
+                match reader.next_event() {
+                    Ok(Some(event)) => {
+                        // 接收端已被丢弃时停止读取
+                        if sender.blocking_send(Ok(event)).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = sender.blocking_send(Err(e));
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            events,
+            task: Some(task),
+        }
+    }
+
+    /// 读取下一个事件，到达文档末尾时返回 `None`
+    ///
+    /// # Errors
+    ///
+    /// 与 [`XmlReader::next_event`] 相同；出错后再次调用返回 `None`。
+    pub async fn next_event(&mut self) -> Result<Option<ReaderEvent>> {
+        if let Some(event) = self.events.recv().await {
+            return event.map(Some);
+        }
+        if let Some(task) = self.task.take() {
+            join(task).await?;
+        }
+        Ok(None)
+    }
+}
+
+impl std::fmt::Debug for AsyncXmlReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncXmlReader").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,211 @@
+//! XPath 结果的类型转换
+//!
+//! [`FromXPathResult`] 把 [`XPathResult`] 严格地转换为 Rust 类型，供
+//! [`Document::eval`](crate::Document::eval) 使用。与 [`XPathResult::as_number`] 等方法不同，
+//! 类型不匹配、文本无法解析或数字超出范围时返回 [`Error::XPathConversion`]，不会退回默认值。
+
+use crate::date::{IsoDate, IsoDateTime};
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use crate::xpath::XPathResult;
+use std::str::FromStr;
+
+/// 可以从 XPath 结果严格转换得到的类型
+///
+/// 内置实现的转换规则：
+///
+/// - 数字类型接受数字结果，以及字符串结果或单个节点的文本（去除首尾空白后解析）；
+///   整数类型要求值是范围内的整数，所有数字类型都不接受 `NaN` 和无穷大
+/// - `bool` 接受布尔结果，以及内容为 `true` / `false` 的字符串或节点文本
+/// - `String`、[`IsoDate`]、[`IsoDateTime`] 接受字符串结果或单个节点的文本
+/// - [`SelectedNode`] 接受只含一个节点的节点集合
+/// - `Option<T>` 在节点集合为空时为 `None`，否则按 `T` 转换
+/// - `Vec<T>` 接受节点集合，把每个节点按 `T` 转换
+///
+/// # Example
+///
+/// 为自定义类型实现转换时，可以先转换为内置类型：
+///
+/// ```
+/// use rlibxml::{Document, Error, FromXPathResult, Result, XPathResult};
+///
+/// struct Sku(String);
+///
+/// impl<'a> FromXPathResult<'a> for Sku {
+///     fn from_xpath_result(result: XPathResult<'a>) -> Result<Self> {
+///         let text = String::from_xpath_result(result)?;
+///         match text.strip_prefix("SKU-") {
+///             Some(code) => Ok(Sku(code.to_string())),
+///             None => Err(Error::xpath_conversion("SKU", format!("{text:?}"))),
+///         }
+///     }
+/// }
+///
+/// let doc = Document::parse("<td>SKU-42</td><td>n/a</td>")?;
+/// assert_eq!(doc.eval::<Sku>("//td[1]")?.0, "42");
+/// assert!(doc.eval::<Vec<Sku>>("//td").is_err());
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub trait FromXPathResult<'a>: Sized {
+    /// 转换 XPath 结果
+    fn from_xpath_result(result: XPathResult<'a>) -> Result<Self>;
+}
+
+/// 描述结果的类型和值，用于错误消息
+fn describe(result: &XPathResult<'_>) -> String {
+    match result {
+        XPathResult::NodeSet(nodes) if nodes.is_empty() => "empty node-set".to_string(),
+        XPathResult::NodeSet(nodes) if nodes.len() == 1 => "node-set of 1 node".to_string(),
+        XPathResult::NodeSet(nodes) => format!("node-set of {} nodes", nodes.len()),
+        XPathResult::Boolean(b) => format!("boolean {b}"),
+        XPathResult::Number(n) => format!("number {n}"),
+        XPathResult::String(s) => format!("string {s:?}"),
+        XPathResult::Empty => "empty result".to_string(),
+    }
+}
+
+fn mismatch(expected: &str, result: &XPathResult<'_>) -> Error {
+    Error::xpath_conversion(expected, describe(result))
+}
+
+/// 字符串结果，或单个节点的文本
+fn text(result: XPathResult<'_>, expected: &str) -> Result<String> {
+    match result {
+        XPathResult::String(s) => Ok(s),
+        XPathResult::NodeSet(nodes) if nodes.len() == 1 => Ok(nodes[0].text()),
+        other => Err(mismatch(expected, &other)),
+    }
+}
+
+/// 字符串结果或单个节点的文本，去除首尾空白后解析
+fn parse_text<T: FromStr>(result: XPathResult<'_>, expected: &str) -> Result<T> {
+    let text = text(result, expected)?;
+    let trimmed = text.trim();
+    trimmed
+        .parse()
+        .map_err(|_| Error::xpath_conversion(expected, format!("string {trimmed:?}")))
+}
+
+impl<'a> FromXPathResult<'a> for XPathResult<'a> {
+    fn from_xpath_result(result: XPathResult<'a>) -> Result<Self> {
+        Ok(result)
+    }
+}
+
+impl<'a> FromXPathResult<'a> for SelectedNode<'a> {
+    fn from_xpath_result(result: XPathResult<'a>) -> Result<Self> {
+        match result {
+            XPathResult::NodeSet(mut nodes) if nodes.len() == 1 => Ok(nodes.remove(0)),
+            other => Err(mismatch("node", &other)),
+        }
+    }
+}
+
+impl FromXPathResult<'_> for String {
+    fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+        text(result, "string")
+    }
+}
+
+impl FromXPathResult<'_> for bool {
+    fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+        match result {
+            XPathResult::Boolean(b) => Ok(b),
+            other => parse_text(other, "bool"),
+        }
+    }
+}
+
+impl FromXPathResult<'_> for f64 {
+    fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+        let n = match result {
+            XPathResult::Number(n) => n,
+            other => parse_text(other, "f64")?,
+        };
+        if !n.is_finite() {
+            return Err(Error::xpath_conversion("f64", format!("number {n}")));
+        }
+        Ok(n)
+    }
+}
+
+impl FromXPathResult<'_> for f32 {
+    fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+        let n = f64::from_xpath_result(result).map_err(|e| retarget(e, "f32"))?;
+        let narrowed = n as f32;
+        if !narrowed.is_finite() {
+            return Err(Error::xpath_conversion("f32", format!("number {n}")));
+        }
+        Ok(narrowed)
+    }
+}
+
+/// 把内部转换的错误改为报告外层期望的类型
+fn retarget(error: Error, expected: &str) -> Error {
+    match error {
+        Error::XPathConversion { xpath, found, .. } => Error::XPathConversion {
+            xpath,
+            expected: expected.to_string(),
+            found,
+        },
+        other => other,
+    }
+}
+
+macro_rules! impl_integer {
+    ($($ty:ty),*) => {$(
+        impl FromXPathResult<'_> for $ty {
+            fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+                let expected = stringify!($ty);
+                match result {
+                    // XPath 的数字都是双精度浮点数，只接受可以精确表示为该整数的值
+                    XPathResult::Number(n) => {
+                        if n.fract() == 0.0 && n >= <$ty>::MIN as f64 && n < <$ty>::MAX as f64 + 1.0 {
+                            Ok(n as $ty)
+                        } else {
+                            Err(Error::xpath_conversion(expected, format!("number {n}")))
+                        }
+                    }
+                    other => parse_text(other, expected),
+                }
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromXPathResult<'_> for IsoDate {
+    fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+        parse_text(result, "date")
+    }
+}
+
+impl FromXPathResult<'_> for IsoDateTime {
+    fn from_xpath_result(result: XPathResult<'_>) -> Result<Self> {
+        parse_text(result, "date-time")
+    }
+}
+
+impl<'a, T: FromXPathResult<'a>> FromXPathResult<'a> for Option<T> {
+    fn from_xpath_result(result: XPathResult<'a>) -> Result<Self> {
+        match result {
+            XPathResult::NodeSet(nodes) if nodes.is_empty() => Ok(None),
+            XPathResult::Empty => Ok(None),
+            other => T::from_xpath_result(other).map(Some),
+        }
+    }
+}
+
+impl<'a, T: FromXPathResult<'a>> FromXPathResult<'a> for Vec<T> {
+    fn from_xpath_result(result: XPathResult<'a>) -> Result<Self> {
+        match result {
+            XPathResult::NodeSet(nodes) => nodes
+                .into_iter()
+                .map(|node| T::from_xpath_result(XPathResult::NodeSet(vec![node])))
+                .collect(),
+            XPathResult::Empty => Ok(Vec::new()),
+            other => Err(mismatch("node-set", &other)),
+        }
+    }
+}
@@ -0,0 +1,679 @@
+//! CSS 选择器到 XPath 1.0 的转换
+//!
+//! 支持 CSS Selectors Level 3，以及常用的扩展伪类 `:has()`、`:contains()`、`:is()`。
+//! 转换结果是相对于上下文节点的 XPath，只匹配上下文节点的后代元素。
+//!
+//! 元素名和属性名按原样区分大小写（HTML 解析器已把它们转为小写）。
+//! 命名空间前缀（`ns|tag`）、伪元素和动态伪类（`:hover` 等）不受支持，会报告语法错误。
+
+use crate::error::{Error, Result};
+
+/// 把 CSS 选择器转换为 XPath 1.0 表达式
+///
+/// 生成的表达式以上下文节点为起点，只匹配其后代元素；逗号分隔的选择器转换为并集。
+///
+/// # Errors
+///
+/// - [`Error::CssSyntax`] - 选择器有语法错误或使用了不支持的特性，附带出错位置
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::css_to_xpath;
+///
+/// assert_eq!(css_to_xpath("ul > li").unwrap(), "descendant::ul/li");
+/// assert_eq!(css_to_xpath("a#top").unwrap(), "descendant::a[@id = 'top']");
+/// ```
+pub fn css_to_xpath(selector: &str) -> Result<String> {
+    let mut parser = Parser {
+        input: selector,
+        pos: 0,
+    };
+    let list = parser.parse_selector_list(false)?;
+    parser.skip_whitespace();
+    if parser.peek().is_some() {
+        return Err(parser.unexpected("',' or end of selector"));
+    }
+
+    let paths: Vec<String> = list.iter().map(|steps| path(steps)).collect();
+    Ok(paths.join(" | "))
+}
+
+/// 组合符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// 空白：后代
+    Descendant,
+    /// `>`：子元素
+    Child,
+    /// `+`：紧邻的后继兄弟
+    Adjacent,
+    /// `~`：后继兄弟
+    Sibling,
+}
+
+/// 复合选择器，对应一个 XPath 步骤
+#[derive(Debug, Default)]
+struct Compound {
+    // 元素名，`None` 表示任意元素
+    element: Option<String>,
+    // XPath 谓词（不含方括号）
+    predicates: Vec<String>,
+}
+
+impl Compound {
+    /// 转换为 XPath 步骤（不含轴）
+    fn step(&self) -> String {
+        let mut step = element_test(self.element.as_deref());
+        for predicate in &self.predicates {
+            step.push('[');
+            step.push_str(predicate);
+            step.push(']');
+        }
+        step
+    }
+}
+
+/// 由组合符连接的复合选择器序列，第一个组合符决定相对于上下文节点的轴
+type Complex = Vec<(Combinator, Compound)>;
+
+/// 把复杂选择器转换为相对路径
+fn path(steps: &[(Combinator, Compound)]) -> String {
+    let mut path = String::new();
+    for (index, (combinator, compound)) in steps.iter().enumerate() {
+        if index > 0 {
+            path.push('/');
+        }
+        let step = compound.step();
+        match combinator {
+            Combinator::Descendant => path.push_str("descendant::"),
+            Combinator::Child => {}
+            Combinator::Adjacent => path.push_str("following-sibling::*[1]/self::"),
+            Combinator::Sibling => path.push_str("following-sibling::"),
+        }
+        path.push_str(&step);
+    }
+    path
+}
+
+/// 元素名测试：合法的 XPath 名称直接使用，否则按 `name()` 比较
+fn element_test(name: Option<&str>) -> String {
+    match name {
+        None => "*".to_string(),
+        Some(name) if is_xpath_name(name) => name.to_string(),
+        Some(name) => format!("*[name() = {}]", literal(name)),
+    }
+}
+
+/// 属性引用：合法的 XPath 名称直接使用，否则按 `name()` 比较
+fn attribute_ref(name: &str) -> String {
+    if is_xpath_name(name) {
+        format!("@{}", name)
+    } else {
+        format!("@*[name() = {}]", literal(name))
+    }
+}
+
+/// 是否可以直接写入 XPath 名称测试（不含前缀的 NCName）
+fn is_xpath_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_name_start) && chars.all(|c| is_name_char(c) || c == '.')
+}
+
+/// 转换为 XPath 字符串字面量
+///
+/// 同时包含单引号和双引号时用 `concat()` 拼接。
+fn literal(value: &str) -> String {
+    if !value.contains('\'') {
+        format!("'{}'", value)
+    } else if !value.contains('"') {
+        format!("\"{}\"", value)
+    } else {
+        let parts: Vec<String> = value
+            .split('\'')
+            .map(|part| format!("'{}'", part))
+            .collect();
+        format!("concat({})", parts.join(", \"'\", "))
+    }
+}
+
+/// 以空白分隔的单词列表中包含 `word`
+fn contains_word(subject: &str, word: &str) -> String {
+    if word.is_empty() || word.chars().any(char::is_whitespace) {
+        return "false()".to_string();
+    }
+    format!(
+        "contains(concat(' ', normalize-space({}), ' '), {})",
+        subject,
+        literal(&format!(" {} ", word))
+    )
+}
+
+/// `an+b` 对应的谓词
+///
+/// `count` 是同级中位于前面（或后面）的元素个数，元素位置为 `count + 1`。
+/// 系数或偏移量的运算溢出时返回 `None`。
+fn nth_predicate(count: &str, a: i64, b: i64) -> Option<String> {
+    // 位置 a*n + b = count + 1，即 count = a*n + offset（n >= 0）
+    let offset = b.checked_sub(1)?;
+    let diff = match offset {
+        0 => count.to_string(),
+        o if o > 0 => format!("({} - {})", count, o),
+        o => format!("({} + {})", count, o.checked_neg()?),
+    };
+    Some(match a {
+        0 => format!("{} = {}", count, offset),
+        a if a > 0 && offset <= 0 => format!("{} mod {} = 0", diff, a),
+        a if a > 0 => format!("{} >= {} and {} mod {} = 0", count, offset, diff, a),
+        a => format!(
+            "{} <= {} and {} mod {} = 0",
+            count,
+            offset,
+            diff,
+            a.checked_neg()?
+        ),
+    })
+}
+
+/// 解析 `an+b`、`odd`、`even`
+fn parse_nth(argument: &str) -> Option<(i64, i64)> {
+    let argument: String = argument
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match argument.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+    let Some(index) = argument.find('n') else {
+        return Some((0, argument.parse().ok()?));
+    };
+    let a = match &argument[..index] {
+        "" | "+" => 1,
+        "-" => -1,
+        coefficient => coefficient.parse().ok()?,
+    };
+    let rest = &argument[index + 1..];
+    let b = if rest.is_empty() {
+        0
+    } else if rest.starts_with(['+', '-']) {
+        rest.parse().ok()?
+    } else {
+        return None;
+    };
+    Some((a, b))
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+/// 递归下降解析器，边解析边生成 XPath 片段
+struct Parser<'s> {
+    input: &'s str,
+    // 当前字节偏移
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.input[self.pos..].chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 跳过空白，返回是否跳过了任何字符
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+        self.pos > start
+    }
+
+    fn error(&self, position: usize, message: impl Into<String>) -> Error {
+        Error::CssSyntax {
+            selector: self.input.to_string(),
+            message: message.into(),
+            position,
+        }
+    }
+
+    /// 在当前位置报告意外字符
+    fn unexpected(&self, expected: &str) -> Error {
+        match self.peek() {
+            Some(c) => self.error(self.pos, format!("expected {}, found '{}'", expected, c)),
+            None => self.error(
+                self.pos,
+                format!("expected {}, found end of selector", expected),
+            ),
+        }
+    }
+
+    /// 逗号分隔的选择器列表
+    ///
+    /// `relative` 为真时（`:has()` 的参数）每个选择器可以以组合符开头。
+    fn parse_selector_list(&mut self, relative: bool) -> Result<Vec<Complex>> {
+        let mut list = Vec::new();
+        loop {
+            self.skip_whitespace();
+            list.push(self.parse_complex(relative)?);
+            self.skip_whitespace();
+            if !self.eat(',') {
+                return Ok(list);
+            }
+        }
+    }
+
+    fn parse_complex(&mut self, relative: bool) -> Result<Complex> {
+        let mut steps = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        if relative && let Some(leading) = self.parse_combinator() {
+            combinator = leading;
+            self.skip_whitespace();
+        }
+
+        loop {
+            let compound = self.parse_compound()?;
+            steps.push((combinator, compound));
+
+            let had_whitespace = self.skip_whitespace();
+            combinator = match self.parse_combinator() {
+                Some(explicit) => explicit,
+                None => match self.peek() {
+                    Some(',') | Some(')') | None => return Ok(steps),
+                    Some(_) if had_whitespace => Combinator::Descendant,
+                    Some(_) => return Err(self.unexpected("combinator")),
+                },
+            };
+            self.skip_whitespace();
+        }
+    }
+
+    fn parse_combinator(&mut self) -> Option<Combinator> {
+        let combinator = match self.peek()? {
+            '>' => Combinator::Child,
+            '+' => Combinator::Adjacent,
+            '~' => Combinator::Sibling,
+            _ => return None,
+        };
+        self.bump();
+        Some(combinator)
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound> {
+        let start = self.pos;
+        let mut compound = Compound::default();
+
+        if self.eat('*') {
+            // 任意元素
+        } else if self.at_ident_start() {
+            compound.element = Some(self.parse_name("element name")?);
+        }
+        if self.peek() == Some('|') {
+            return Err(self.error(self.pos, "namespace prefixes are not supported"));
+        }
+
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.bump();
+                    let id = self.parse_name("id")?;
+                    compound.predicates.push(format!("@id = {}", literal(&id)));
+                }
+                Some('.') => {
+                    self.bump();
+                    let class = self.parse_ident("class name")?;
+                    compound.predicates.push(contains_word("@class", &class));
+                }
+                Some('[') => {
+                    let predicate = self.parse_attribute()?;
+                    compound.predicates.push(predicate);
+                }
+                Some(':') => {
+                    let predicate = self.parse_pseudo(&compound)?;
+                    compound.predicates.push(predicate);
+                }
+                _ => break,
+            }
+        }
+
+        if self.pos == start {
+            return Err(self.unexpected("selector"));
+        }
+        Ok(compound)
+    }
+
+    /// 属性选择器 `[name]`、`[name op value]`，支持 Level 4 的 `i` 标志
+    fn parse_attribute(&mut self) -> Result<String> {
+        self.bump(); // '['
+        self.skip_whitespace();
+        let name = self.parse_ident("attribute name")?;
+        if self.peek() == Some('|') && self.peek_nth(1) != Some('=') {
+            return Err(self.error(self.pos, "namespace prefixes are not supported"));
+        }
+        self.skip_whitespace();
+
+        let attr = attribute_ref(&name);
+        let operator = match self.peek() {
+            Some(']') => {
+                self.bump();
+                return Ok(attr);
+            }
+            Some('=') => {
+                self.bump();
+                '='
+            }
+            Some(c @ ('~' | '|' | '^' | '$' | '*')) if self.peek_nth(1) == Some('=') => {
+                self.bump();
+                self.bump();
+                c
+            }
+            _ => return Err(self.unexpected("attribute operator or ']'")),
+        };
+        self.skip_whitespace();
+
+        let mut value = match self.peek() {
+            Some('"' | '\'') => self.parse_string()?,
+            _ => self.parse_name("attribute value")?,
+        };
+        self.skip_whitespace();
+
+        let mut subject = attr.clone();
+        if matches!(self.peek(), Some('i' | 'I' | 's' | 'S'))
+            && matches!(self.peek_nth(1), Some(']') | Some(' ' | '\t' | '\n'))
+        {
+            if self
+                .bump()
+                .is_some_and(|flag| flag.eq_ignore_ascii_case(&'i'))
+            {
+                subject = format!(
+                    "translate({}, 'ABCDEFGHIJKLMNOPQRSTUVWXYZ', 'abcdefghijklmnopqrstuvwxyz')",
+                    attr
+                );
+                value = value.to_ascii_lowercase();
+            }
+            self.skip_whitespace();
+        }
+        if !self.eat(']') {
+            return Err(self.unexpected("']'"));
+        }
+
+        let condition = match operator {
+            '=' => format!("{} = {}", subject, literal(&value)),
+            '~' => contains_word(&subject, &value),
+            '|' => format!(
+                "{} = {} or starts-with({}, {})",
+                subject,
+                literal(&value),
+                subject,
+                literal(&format!("{}-", value))
+            ),
+            _ if value.is_empty() => return Ok("false()".to_string()),
+            '^' => format!("starts-with({}, {})", subject, literal(&value)),
+            '$' => format!(
+                "substring({}, string-length({}) - {}) = {}",
+                subject,
+                subject,
+                value.chars().count() - 1,
+                literal(&value)
+            ),
+            _ => format!("contains({}, {})", subject, literal(&value)),
+        };
+        // 缺失的属性经过 translate() 会变成空字符串，需要先确认属性存在
+        if subject != attr {
+            Ok(format!("{} and ({})", attr, condition))
+        } else {
+            Ok(condition)
+        }
+    }
+
+    /// 伪类，返回对应的谓词
+    fn parse_pseudo(&mut self, compound: &Compound) -> Result<String> {
+        let start = self.pos;
+        self.bump(); // ':'
+        if self.peek() == Some(':') {
+            return Err(self.error(start, "pseudo-elements are not supported"));
+        }
+        let name = self.parse_ident("pseudo-class name")?.to_ascii_lowercase();
+
+        if !self.eat('(') {
+            let predicate = match name.as_str() {
+                "first-child" => "not(preceding-sibling::*)".to_string(),
+                "last-child" => "not(following-sibling::*)".to_string(),
+                "only-child" => {
+                    "not(preceding-sibling::*) and not(following-sibling::*)".to_string()
+                }
+                "first-of-type" => format!(
+                    "not(preceding-sibling::{})",
+                    self.of_type(compound, &name, start)?
+                ),
+                "last-of-type" => format!(
+                    "not(following-sibling::{})",
+                    self.of_type(compound, &name, start)?
+                ),
+                "only-of-type" => {
+                    let element = self.of_type(compound, &name, start)?;
+                    format!(
+                        "not(preceding-sibling::{}) and not(following-sibling::{})",
+                        element, element
+                    )
+                }
+                "empty" => "not(*) and not(text())".to_string(),
+                "root" => "not(parent::*)".to_string(),
+                "checked" => {
+                    "(self::input and @checked) or (self::option and @selected)".to_string()
+                }
+                "disabled" => "@disabled".to_string(),
+                "enabled" => "(self::button or self::input or self::select or self::textarea \
+                              or self::option or self::optgroup) and not(@disabled)"
+                    .to_string(),
+                "link" | "any-link" => {
+                    "(self::a or self::area or self::link) and @href".to_string()
+                }
+                _ => {
+                    return Err(self.error(start, format!("unsupported pseudo-class ':{}'", name)));
+                }
+            };
+            return Ok(predicate);
+        }
+
+        let predicate = match name.as_str() {
+            "nth-child" | "nth-last-child" | "nth-of-type" | "nth-last-of-type" => {
+                let axis = if name.starts_with("nth-last") {
+                    "following-sibling"
+                } else {
+                    "preceding-sibling"
+                };
+                let element = if name.ends_with("of-type") {
+                    self.of_type(compound, &name, start)?
+                } else {
+                    "*".to_string()
+                };
+                self.parse_nth_argument(&format!("count({}::{})", axis, element))?
+            }
+            "not" | "is" | "where" | "matches" | "any" => {
+                let mut tests = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    tests.push(format!("self::{}", self.parse_compound()?.step()));
+                    self.skip_whitespace();
+                    if !self.eat(',') {
+                        break;
+                    }
+                }
+                let tests = tests.join(" or ");
+                if name == "not" {
+                    format!("not({})", tests)
+                } else {
+                    tests
+                }
+            }
+            "has" => {
+                let list = self.parse_selector_list(true)?;
+                let paths: Vec<String> = list.iter().map(|steps| path(steps)).collect();
+                paths.join(" or ")
+            }
+            "contains" => {
+                self.skip_whitespace();
+                let text = match self.peek() {
+                    Some('"' | '\'') => self.parse_string()?,
+                    _ => {
+                        let text_start = self.pos;
+                        while self.peek().is_some_and(|c| c != ')') {
+                            self.bump();
+                        }
+                        self.input[text_start..self.pos].trim().to_string()
+                    }
+                };
+                format!("contains(string(.), {})", literal(&text))
+            }
+            _ => {
+                return Err(self.error(start, format!("unsupported pseudo-class ':{}()'", name)));
+            }
+        };
+
+        self.skip_whitespace();
+        if !self.eat(')') {
+            return Err(self.unexpected("')'"));
+        }
+        Ok(predicate)
+    }
+
+    /// `*-of-type` 伪类需要的元素名测试
+    fn of_type(&self, compound: &Compound, name: &str, start: usize) -> Result<String> {
+        match compound.element.as_deref() {
+            Some(element) => Ok(element_test(Some(element))),
+            None => Err(self.error(
+                start,
+                format!(":{} requires an element name, e.g. 'li:{}'", name, name),
+            )),
+        }
+    }
+
+    /// `:nth-*()` 的参数，转换为以 `count` 计算位置的谓词，不消耗右括号
+    fn parse_nth_argument(&mut self, count: &str) -> Result<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != ')') {
+            self.bump();
+        }
+        let (a, b) = parse_nth(&self.input[start..self.pos])
+            .ok_or_else(|| self.error(start, "invalid an+b expression"))?;
+        nth_predicate(count, a, b).ok_or_else(|| self.error(start, "an+b expression out of range"))
+    }
+
+    fn at_ident_start(&self) -> bool {
+        let mut chars = self.input[self.pos..].chars();
+        match chars.next() {
+            Some('-') => chars
+                .next()
+                .is_some_and(|c| c == '-' || c == '\\' || is_name_start(c)),
+            Some(c) => c == '\\' || is_name_start(c),
+            None => false,
+        }
+    }
+
+    /// 标识符（不能以数字开头）
+    fn parse_ident(&mut self, what: &str) -> Result<String> {
+        if !self.at_ident_start() {
+            return Err(self.unexpected(what));
+        }
+        self.parse_name(what)
+    }
+
+    /// 由名称字符组成的序列，支持转义
+    fn parse_name(&mut self, what: &str) -> Result<String> {
+        let mut name = String::new();
+        loop {
+            match self.peek() {
+                Some('\\') => name.push(self.parse_escape()?),
+                Some(c) if is_name_char(c) => {
+                    self.bump();
+                    name.push(c);
+                }
+                _ => break,
+            }
+        }
+        if name.is_empty() {
+            return Err(self.unexpected(what));
+        }
+        Ok(name)
+    }
+
+    /// 转义序列：`\` 加 1 到 6 位十六进制数（可跟一个空白），或任意字符
+    fn parse_escape(&mut self) -> Result<char> {
+        let start = self.pos;
+        self.bump(); // '\\'
+
+        let hex_start = self.pos;
+        while self.pos - hex_start < 6 && self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            self.bump();
+        }
+        if self.pos > hex_start {
+            let code = u32::from_str_radix(&self.input[hex_start..self.pos], 16).unwrap_or(0);
+            if self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            }
+            return Ok(char::from_u32(code)
+                .filter(|&c| c != '\0')
+                .unwrap_or('\u{FFFD}'));
+        }
+
+        match self.bump() {
+            None | Some('\n') => Err(self.error(start, "invalid escape")),
+            Some(c) => Ok(c),
+        }
+    }
+
+    /// 带引号的字符串
+    fn parse_string(&mut self) -> Result<String> {
+        let start = self.pos;
+        let Some(quote) = self.bump() else {
+            return Err(self.unexpected("string"));
+        };
+
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error(start, "unterminated string")),
+                Some('\\') if self.peek_nth(1) == Some('\n') => {
+                    // 续行
+                    self.bump();
+                    self.bump();
+                }
+                Some('\\') => value.push(self.parse_escape()?),
+                Some(c) => {
+                    self.bump();
+                    if c == quote {
+                        return Ok(value);
+                    }
+                    value.push(c);
+                }
+            }
+        }
+    }
+}
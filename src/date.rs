@@ -0,0 +1,205 @@
+//! ISO 8601 日期与时间
+//!
+//! 网页和数据源中的日期通常以 `2024-03-15`、`2024-03-15T08:30:00+08:00` 的形式出现在
+//! `<time datetime>`、`<pubDate>` 等位置。这里只做严格的解析和校验，不涉及时区换算和日历运算，
+//! 需要这些功能时可以把各个字段交给专门的日期库。
+
+use crate::error::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// 日期，格式为 `YYYY-MM-DD`
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::IsoDate;
+///
+/// let date: IsoDate = "2024-02-29".parse()?;
+/// assert_eq!((date.year, date.month, date.day), (2024, 2, 29));
+/// assert!("2023-02-29".parse::<IsoDate>().is_err());
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IsoDate {
+    /// 年（四位数字）
+    pub year: i32,
+    /// 月（1-12）
+    pub month: u8,
+    /// 日（1-31，按月份和闰年校验）
+    pub day: u8,
+}
+
+/// 日期和时间，格式为 `YYYY-MM-DDTHH:MM[:SS[.fraction]][Z|±HH:MM]`
+///
+/// 日期和时间之间也可以用空格分隔；没有时区后缀时 [`offset_minutes`](Self::offset_minutes)
+/// 为 `None`，表示本地时间。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::IsoDateTime;
+///
+/// let time: IsoDateTime = "2024-03-15T08:30:05.25+08:00".parse()?;
+/// assert_eq!(time.date.day, 15);
+/// assert_eq!((time.hour, time.minute, time.second), (8, 30, 5));
+/// assert_eq!(time.nanosecond, 250_000_000);
+/// assert_eq!(time.offset_minutes, Some(480));
+/// assert_eq!(time.to_string(), "2024-03-15T08:30:05.25+08:00");
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IsoDateTime {
+    /// 日期部分
+    pub date: IsoDate,
+    /// 时（0-23）
+    pub hour: u8,
+    /// 分（0-59）
+    pub minute: u8,
+    /// 秒（0-59），省略时为 0
+    pub second: u8,
+    /// 秒的小数部分（纳秒）
+    pub nanosecond: u32,
+    /// 相对 UTC 的偏移（分钟），`Z` 为 `Some(0)`
+    pub offset_minutes: Option<i16>,
+}
+
+impl FromStr for IsoDate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_date(s).ok_or_else(|| Error::Custom(format!("invalid date '{s}'")))
+    }
+}
+
+impl FromStr for IsoDateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_date_time(s).ok_or_else(|| Error::Custom(format!("invalid date-time '{s}'")))
+    }
+}
+
+impl fmt::Display for IsoDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for IsoDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}",
+            self.date, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        match self.offset_minutes {
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_date(s: &str) -> Option<IsoDate> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let year = digits(&s[0..4])? as i32;
+    let month = digits(&s[5..7])? as u8;
+    let day = digits(&s[8..10])? as u8;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some(IsoDate { year, month, day })
+}
+
+fn parse_date_time(s: &str) -> Option<IsoDateTime> {
+    let date = parse_date(s.get(..10)?)?;
+    let rest = s[10..].strip_prefix(['T', ' '])?;
+
+    // 时区后缀：Z 或 ±HH:MM
+    let (time, offset_minutes) = if let Some(time) = rest.strip_suffix('Z') {
+        (time, Some(0))
+    } else if let Some(pos) = rest.rfind(['+', '-']) {
+        let offset = &rest[pos + 1..];
+        if offset.len() != 5 || offset.as_bytes()[2] != b':' {
+            return None;
+        }
+        let hours = digits(&offset[..2])?;
+        let minutes = digits(&offset[3..])?;
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        let offset = (hours * 60 + minutes) as i16;
+        let sign = if rest.as_bytes()[pos] == b'-' { -1 } else { 1 };
+        (&rest[..pos], Some(sign * offset))
+    } else {
+        (rest, None)
+    };
+
+    let (clock, fraction) = match time.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (time, None),
+    };
+    let mut parts = clock.split(':');
+    let hour = two_digits(parts.next()?)?;
+    let minute = two_digits(parts.next()?)?;
+    let second = match parts.next() {
+        Some(second) => two_digits(second)?,
+        None if fraction.is_none() => 0,
+        None => return None,
+    };
+    if parts.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let nanosecond = match fraction {
+        Some(fraction) if (1..=9).contains(&fraction.len()) => {
+            digits(fraction)? * 10u32.pow(9 - fraction.len() as u32)
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+
+    Some(IsoDateTime {
+        date,
+        hour,
+        minute,
+        second,
+        nanosecond,
+        offset_minutes,
+    })
+}
+
+/// 只由 ASCII 数字组成的字符串的值
+fn digits(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn two_digits(s: &str) -> Option<u8> {
+    if s.len() != 2 {
+        return None;
+    }
+    digits(s).map(|n| n as u8)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
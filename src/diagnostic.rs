@@ -0,0 +1,120 @@
+//! 解析诊断信息
+//!
+//! 解析过程中 libxml2 报告的错误与警告会被逐条记录为 [`Diagnostic`]，
+//! 即使容错模式最终成功恢复，也可以通过 [`Document::diagnostics`](crate::Document::diagnostics) 查看。
+
+use libxml2_sys::{RawParseError, xmlErrorDomain_XML_FROM_VALID};
+
+/// 诊断级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DiagnosticLevel {
+    /// 警告：不影响文档结构
+    Warning,
+    /// 错误：文档不合规，但解析器可以恢复
+    Error,
+    /// 致命错误：文档不是格式良好的，严格模式下解析会失败
+    Fatal,
+}
+
+impl DiagnosticLevel {
+    /// 从 libxml2 的 `xmlErrorLevel` 转换
+    ///
+    /// `XML_ERR_NONE` 在收集时已被过滤；其他未知的级别按警告处理，不会被当成致命错误。
+    #[inline]
+    pub(crate) fn from_raw(value: i32) -> Self {
+        match value {
+            2 => DiagnosticLevel::Error,
+            3 => DiagnosticLevel::Fatal,
+            _ => DiagnosticLevel::Warning,
+        }
+    }
+
+    /// 获取级别名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiagnosticLevel::Warning => "warning",
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Fatal => "fatal",
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// 一条解析诊断
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{DiagnosticLevel, Document};
+///
+/// let doc = Document::parse("<div><p>text</span></div>").unwrap();
+/// let diag = &doc.diagnostics()[0];
+/// assert_eq!(diag.level, DiagnosticLevel::Error);
+/// assert_eq!(diag.line, 1);
+/// assert!(diag.message.contains("span"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 诊断级别
+    pub level: DiagnosticLevel,
+    /// libxml2 错误码（`xmlParserErrors`）
+    pub code: i32,
+    /// 错误来源模块（`xmlErrorDomain`），如解析器、HTML 解析器、命名空间
+    pub domain: i32,
+    /// 错误消息
+    pub message: String,
+    /// 行号（从 1 开始，未知时为 0）
+    pub line: usize,
+    /// 列号（从 1 开始，未知时为 0）
+    pub column: usize,
+    /// 出错的文件名或 URL（从内存解析时为 `None`）
+    pub file: Option<String>,
+}
+
+impl Diagnostic {
+    /// 从 libxml2-sys 的原始错误转换
+    pub(crate) fn from_raw(raw: RawParseError) -> Self {
+        Self {
+            level: DiagnosticLevel::from_raw(raw.level),
+            code: raw.code,
+            domain: raw.domain,
+            message: raw.message,
+            line: raw.line.max(0) as usize,
+            column: raw.column.max(0) as usize,
+            file: raw.file,
+        }
+    }
+
+    /// 检查是否为错误或致命错误
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.level >= DiagnosticLevel::Error
+    }
+
+    /// 检查是否为 DTD 有效性错误
+    ///
+    /// 只有启用 [`XmlParseOptions::dtd_valid`](crate::XmlParseOptions::dtd_valid) 时才会出现，
+    /// 文档本身仍是格式良好的。
+    #[inline]
+    pub fn is_validity_error(&self) -> bool {
+        self.domain == xmlErrorDomain_XML_FROM_VALID as i32
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.level, self.message
+        )
+    }
+}
@@ -2,14 +2,43 @@
 //!
 //! 提供对 XML/HTML 文档的解析和生命周期管理。
 
+#[cfg(feature = "tokio")]
+use crate::async_io::{AsyncOptions, parse_push};
+use crate::convert::FromXPathResult;
+use crate::css::css_to_xpath;
+use crate::diagnostic::Diagnostic;
+use crate::document_mut::DocumentMut;
+use crate::dtd::Doctype;
+use crate::encoding::{
+    PRESCAN_LIMIT, decode_html, decode_xml, html_file_is_utf8, is_utf8_stream,
+    xml_file_native_encoding,
+};
 use crate::error::{Error, Result};
+use crate::extract::FromNode;
+use crate::fragment::Fragment;
 use crate::node::SelectedNode;
 use crate::options::{ParseOptions, XmlParseOptions};
-use crate::xpath::{XPathResult, evaluate_xpath};
+use crate::owned::OwnedNode;
+#[cfg(feature = "tokio")]
+use crate::push::PushParser;
+#[cfg(feature = "schemas")]
+use crate::schema::Schema;
+#[cfg(feature = "schemas")]
+use crate::validation::ValidationError;
+use crate::xpath::{
+    XPathResult, XPathValue, evaluate_xpath, evaluate_xpath_on_node, evaluate_xpath_with,
+    first_match_xpath, with_original_xpath,
+};
+use crate::xpath_context::XPathContext;
+use encoding_rs::{Encoding, UTF_8};
 use libxml2_sys::*;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{self, Read};
 use std::marker::PhantomData;
+use std::path::Path;
 use std::ptr;
+use std::sync::Arc;
 
 /// 输入数据的最大大小（略小于 2GB 以留出安全边界）
 pub(crate) const MAX_INPUT_SIZE: usize = i32::MAX as usize - 1024;
@@ -53,12 +82,16 @@ pub(crate) const MAX_INPUT_SIZE: usize = i32::MAX as usize - 1024;
 /// ```
 pub struct Document {
     doc_ptr: xmlDocPtr,
+    // 解析时最终采用的字符编码
+    encoding: &'static Encoding,
+    // 解析过程中收集到的诊断信息
+    diagnostics: Vec<Diagnostic>,
     // 防止跨线程发送（裸指针使得类型 !Send + !Sync）
     _marker: PhantomData<*const ()>,
 }
 
 // SAFETY: libxml2 中独立解析的文档树是线程安全的（只要不并发修剪树节点或者在多线程调解析方法）。
-// 本库 API 仅对外暴露了不可变借用进行提取，因此它是 Send 和 Sync 的。
+// 本库 API 仅通过不可变借用进行提取，修改必须经由独占借用的 DocumentMut，因此它是 Send 和 Sync 的。
 unsafe impl Send for Document {}
 unsafe impl Sync for Document {}
 
@@ -110,13 +143,177 @@ impl Document {
     /// let html = "<div>Hello</div>";
     /// let options = ParseOptions {
     ///     recover: true,
-    ///     no_error: true,
-    ///     no_warning: true,
     ///     no_blanks: true,
+    ///     ..Default::default()
     /// };
     /// let doc = Document::parse_html_with_options(html, options).unwrap();
     /// ```
     pub fn parse_html_with_options(html: &str, options: ParseOptions) -> Result<Self> {
+        Self::read_html(html, options.to_raw(), UTF_8, None)
+    }
+
+    /// 在 `context_tag` 元素的上下文中解析 HTML 片段
+    ///
+    /// 适用于 `<td>` 单元格、`<li>` 列表、CMS 正文字段等不完整的 HTML：
+    /// 结果只包含片段自身的节点，不会补上 `<html><body>`，顶层文本也不会被包进 `<p>`。
+    /// 自动闭合规则按上下文生效，例如在 `ul` 中未闭合的 `<li>` 会被下一个 `<li>` 闭合。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NullByte`] - 片段包含空字节
+    /// - [`Error::Custom`] - `context_tag` 不是合法的元素名
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let fragment = Document::parse_fragment("<li>Pen<li>Ink", "ul")?;
+    /// assert_eq!(fragment.to_html(), "<li>Pen</li><li>Ink</li>");
+    ///
+    /// // 整文档解析会补上外层结构并把文本包进 <p>
+    /// let fragment = Document::parse_fragment("Hello <b>world</b>", "div")?;
+    /// assert_eq!(fragment.to_html(), "Hello <b>world</b>");
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn parse_fragment(html: &str, context_tag: &str) -> Result<Fragment> {
+        Self::parse_fragment_with_options(html, context_tag, ParseOptions::default())
+    }
+
+    /// 使用自定义选项在 `context_tag` 元素的上下文中解析 HTML 片段
+    pub fn parse_fragment_with_options(
+        html: &str,
+        context_tag: &str,
+        options: ParseOptions,
+    ) -> Result<Fragment> {
+        Fragment::parse_html_in_tag(html, context_tag, options)
+    }
+
+    /// 从原始字节解析 HTML 文档，自动探测字符编码
+    ///
+    /// 编码按以下优先级确定：BOM、文档内的 `<meta charset>` 或
+    /// `<meta http-equiv="Content-Type">` 声明、开头的 `<?xml encoding="..."?>` 声明；
+    /// 都没有时按 UTF-8 处理，若内容不是合法的 UTF-8 则回退到 windows-1252。
+    /// 最终采用的编码可通过 [`Document::encoding`] 查询。
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - 未解码的 HTML 字节（例如 HTTP 响应体）
+    /// * `options` - 解析选项
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NullByte`] - 解码后的内容包含空字节
+    /// - [`Error::InputTooLarge`] - 解码后的内容超过大小限制
+    /// - [`Error::ParseFailed`] - 解析失败
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, ParseOptions};
+    ///
+    /// // "中文" 的 GBK 编码
+    /// let mut body = b"<html><head><meta charset=\"gbk\"></head><body><p>".to_vec();
+    /// body.extend_from_slice(&[0xD6, 0xD0, 0xCE, 0xC4]);
+    /// body.extend_from_slice(b"</p></body></html>");
+    ///
+    /// let doc = Document::from_bytes(&body, ParseOptions::default()).unwrap();
+    /// assert_eq!(doc.encoding(), "GBK");
+    /// assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+    /// ```
+    pub fn from_bytes(bytes: &[u8], options: ParseOptions) -> Result<Self> {
+        Self::from_bytes_with_hint(bytes, None, options)
+    }
+
+    /// 从原始字节解析 HTML 文档，并提供外部编码提示
+    ///
+    /// `hint` 通常来自 HTTP `Content-Type` 头，既可以是完整的头部值
+    /// （`text/html; charset=gbk`），也可以是裸编码标签（`gbk`）。
+    /// 提示的优先级低于 BOM、高于文档内声明；无法识别的提示会被忽略。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, ParseOptions};
+    ///
+    /// // "日本" 的 Shift_JIS 编码
+    /// let body = [b"<p>".as_slice(), &[0x93, 0xFA, 0x96, 0x7B], b"</p>"].concat();
+    ///
+    /// let doc = Document::from_bytes_with_hint(
+    ///     &body,
+    ///     Some("text/html; charset=Shift_JIS"),
+    ///     ParseOptions::default(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.encoding(), "Shift_JIS");
+    /// assert_eq!(doc.extract_string("string(//p)").unwrap(), "日本");
+    /// ```
+    pub fn from_bytes_with_hint(
+        bytes: &[u8],
+        hint: Option<&str>,
+        options: ParseOptions,
+    ) -> Result<Self> {
+        let decoded = decode_html(bytes, hint);
+        // 内容已转换为 UTF-8，忽略文档内的编码声明
+        let raw_options = options.to_raw() | htmlParserOption_HTML_PARSE_IGNORE_ENC as i32;
+        Self::read_html(&decoded.text, raw_options, decoded.encoding, None)
+    }
+
+    /// 从文件解析 HTML 文档
+    ///
+    /// 文件路径会记录为文档的 URL（见 [`Document::url`]），相对链接和 [`SelectedNode::base_url`]
+    /// 据此解析。编码探测规则与 [`Document::from_bytes`] 相同。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] - 无法读取文件，或路径不是合法的 UTF-8
+    /// - [`Error::NullByte`] - 路径或解码后的内容包含空字节
+    /// - [`Error::ParseFailed`] - 解析失败
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse_html_file("site/index.html")?;
+    /// assert_eq!(doc.url().as_deref(), Some("site/index.html"));
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn parse_html_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse_html_file_with_options(path, ParseOptions::default())
+    }
+
+    /// 使用自定义选项从文件解析 HTML 文档
+    ///
+    /// 内容全部是合法 UTF-8 的文件由 libxml2 直接分块读取，不会整体读入内存；
+    /// 其他文件先整体读入并按 [`Document::from_bytes`] 的规则转换为 UTF-8 再解析。
+    pub fn parse_html_file_with_options(
+        path: impl AsRef<Path>,
+        options: ParseOptions,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let (c_path, prefix) = open_for_parse(path)?;
+        if html_file_is_utf8(&prefix) && is_utf8_stream(File::open(path)?)? {
+            // SAFETY: c_path 是有效的 CString
+            let (doc_ptr, raw_errors) =
+                unsafe { parse_html_file_with_errors(c_path.as_ptr(), options.to_raw()) };
+            let diagnostics = raw_errors.into_iter().map(Diagnostic::from_raw).collect();
+            return Self::from_parsed(doc_ptr, UTF_8, diagnostics);
+        }
+
+        let bytes = std::fs::read(path)?;
+        let decoded = decode_html(&bytes, None);
+        let raw_options = options.to_raw() | htmlParserOption_HTML_PARSE_IGNORE_ENC as i32;
+        Self::read_html(&decoded.text, raw_options, decoded.encoding, Some(&c_path))
+    }
+
+    /// 解析 UTF-8 HTML 文本的内部实现，`url` 记录为文档的 URL
+    fn read_html(
+        html: &str,
+        raw_options: i32,
+        encoding: &'static Encoding,
+        url: Option<&CStr>,
+    ) -> Result<Self> {
         // 检查输入大小
         let size = html.len();
         if size > MAX_INPUT_SIZE {
@@ -128,34 +325,14 @@ impl Document {
 
         let c_html = CString::new(html).map_err(|_| Error::NullByte)?;
 
-        // 构建 HTML 解析选项
-        let mut raw_options: htmlParserOption = 0;
-        if options.recover {
-            raw_options |= htmlParserOption_HTML_PARSE_RECOVER;
-        }
-        if options.no_error {
-            raw_options |= htmlParserOption_HTML_PARSE_NOERROR;
-        }
-        if options.no_warning {
-            raw_options |= htmlParserOption_HTML_PARSE_NOWARNING;
-        }
-        if options.no_blanks {
-            raw_options |= htmlParserOption_HTML_PARSE_NOBLANKS;
-        }
-
         // SAFETY: c_html 是有效的 CString，size 已验证
         // 调用 libxml2-sys 的安全封装函数
-        let doc_ptr =
-            unsafe { parse_html_memory(c_html.as_ptr(), size as i32, raw_options as i32) };
+        let (doc_ptr, raw_errors) = unsafe {
+            parse_html_memory_with_errors(c_html.as_ptr(), size as i32, url_ptr(url), raw_options)
+        };
+        let diagnostics = raw_errors.into_iter().map(Diagnostic::from_raw).collect();
 
-        if doc_ptr.is_null() {
-            return Err(Error::ParseFailed { detail: None });
-        }
-
-        Ok(Self {
-            doc_ptr,
-            _marker: PhantomData,
-        })
+        Self::from_parsed(doc_ptr, encoding, diagnostics)
     }
 
     /// 从 XML 字符串解析文档
@@ -183,6 +360,175 @@ impl Document {
     /// * `xml` - XML 字符串
     /// * `options` - XML 解析选项
     pub fn parse_xml_with_options(xml: &str, options: XmlParseOptions) -> Result<Self> {
+        // 字符串总是 UTF-8，忽略 XML 声明中的编码
+        let raw_options = options.to_raw() | xmlParserOption_XML_PARSE_IGNORE_ENC as i32;
+        Self::read_xml(xml, raw_options, UTF_8, None)
+    }
+
+    /// 从原始字节解析 XML 文档，自动探测字符编码
+    ///
+    /// 编码按以下优先级确定：BOM（或无 BOM 的 UTF-16 特征）、
+    /// XML 声明中的 `encoding` 伪属性；都没有时按 UTF-8 处理。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// // "café" 的 Latin-1 编码
+    /// let xml = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><root>caf\xE9</root>";
+    ///
+    /// let doc = Document::from_xml_bytes(xml, Default::default()).unwrap();
+    /// assert_eq!(doc.encoding(), "windows-1252");
+    /// assert_eq!(doc.root().unwrap().text(), "café");
+    /// ```
+    pub fn from_xml_bytes(bytes: &[u8], options: XmlParseOptions) -> Result<Self> {
+        Self::from_xml_bytes_with_hint(bytes, None, options)
+    }
+
+    /// 从原始字节解析 XML 文档，并提供外部编码提示
+    ///
+    /// 提示的格式与优先级同 [`Document::from_bytes_with_hint`]。
+    pub fn from_xml_bytes_with_hint(
+        bytes: &[u8],
+        hint: Option<&str>,
+        options: XmlParseOptions,
+    ) -> Result<Self> {
+        let decoded = decode_xml(bytes, hint);
+        // 内容已转换为 UTF-8，忽略 XML 声明中的编码
+        let raw_options = options.to_raw() | xmlParserOption_XML_PARSE_IGNORE_ENC as i32;
+        Self::read_xml(&decoded.text, raw_options, decoded.encoding, None)
+    }
+
+    /// 从异步数据源读取并解析 HTML 文档
+    ///
+    /// 数据按块读取并交给推送解析器（见 [`PushParser`]），解析在 tokio 的阻塞线程池中进行，
+    /// 不会阻塞运行时的工作线程。数据必须是 UTF-8；其他编码请读取完整内容后使用
+    /// [`Document::from_bytes`]。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] - 读取数据源失败
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> rlibxml::Result<()> {
+    /// let body: &[u8] = b"<title>Shop</title><p>Hello</p>";
+    /// let doc = Document::parse_async(body).await?;
+    /// assert_eq!(doc.extract_string("string(//title)")?, "Shop");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn parse_async<R: tokio::io::AsyncRead + Unpin>(source: R) -> Result<Self> {
+        Self::parse_html_async_with_options(
+            source,
+            ParseOptions::default(),
+            AsyncOptions::default(),
+        )
+        .await
+    }
+
+    /// 使用自定义选项从异步数据源解析 HTML 文档
+    ///
+    /// `async_options` 控制每次读取的块大小以及是否在阻塞线程池中解析。
+    #[cfg(feature = "tokio")]
+    pub async fn parse_html_async_with_options<R: tokio::io::AsyncRead + Unpin>(
+        source: R,
+        options: ParseOptions,
+        async_options: AsyncOptions,
+    ) -> Result<Self> {
+        parse_push(source, PushParser::html(options)?, async_options).await
+    }
+
+    /// 从异步数据源读取并解析 XML 文档
+    ///
+    /// 编码由 BOM 和 XML 声明决定，支持 UTF-8、UTF-16 和 ISO-8859-1。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] - 读取数据源失败
+    /// - [`Error::ParseFailed`] - 文档不是格式良好的（读到第一个致命错误时即返回）
+    #[cfg(feature = "tokio")]
+    pub async fn parse_xml_async<R: tokio::io::AsyncRead + Unpin>(source: R) -> Result<Self> {
+        Self::parse_xml_async_with_options(
+            source,
+            XmlParseOptions::default(),
+            AsyncOptions::default(),
+        )
+        .await
+    }
+
+    /// 使用自定义选项从异步数据源解析 XML 文档
+    #[cfg(feature = "tokio")]
+    pub async fn parse_xml_async_with_options<R: tokio::io::AsyncRead + Unpin>(
+        source: R,
+        options: XmlParseOptions,
+        async_options: AsyncOptions,
+    ) -> Result<Self> {
+        parse_push(source, PushParser::xml(options)?, async_options).await
+    }
+
+    /// 从文件解析 XML 文档
+    ///
+    /// 文件路径会记录为文档的 URL（见 [`Document::url`]），外部 DTD、相对链接和 `xml:base`
+    /// 据此解析。编码探测规则与 [`Document::from_xml_bytes`] 相同。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] - 无法读取文件，或路径不是合法的 UTF-8
+    /// - [`Error::NullByte`] - 路径或解码后的内容包含空字节
+    /// - [`Error::ParseFailed`] - 文档不是格式良好的
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse_xml_file("data/feed.xml")?;
+    /// let item = doc.select("//item")?.into_iter().next().unwrap();
+    /// println!("{:?}", item.base_url());
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn parse_xml_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse_xml_file_with_options(path, XmlParseOptions::default())
+    }
+
+    /// 使用自定义选项从文件解析 XML 文档
+    ///
+    /// UTF-8 和 UTF-16 文件由 libxml2 直接分块读取，不会整体读入内存；
+    /// 其他编码的文件先整体读入并转换为 UTF-8 再解析。
+    pub fn parse_xml_file_with_options(
+        path: impl AsRef<Path>,
+        options: XmlParseOptions,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let (c_path, prefix) = open_for_parse(path)?;
+        if let Some(encoding) = xml_file_native_encoding(&prefix) {
+            // SAFETY: c_path 是有效的 CString
+            let (doc_ptr, raw_errors) =
+                unsafe { parse_xml_file_with_errors(c_path.as_ptr(), options.to_raw()) };
+            let diagnostics = raw_errors.into_iter().map(Diagnostic::from_raw).collect();
+            return Self::from_parsed(doc_ptr, encoding, diagnostics);
+        }
+
+        let bytes = std::fs::read(path)?;
+        let decoded = decode_xml(&bytes, None);
+        let raw_options = options.to_raw() | xmlParserOption_XML_PARSE_IGNORE_ENC as i32;
+        Self::read_xml(&decoded.text, raw_options, decoded.encoding, Some(&c_path))
+    }
+
+    /// 解析 UTF-8 XML 文本的内部实现，`url` 记录为文档的 URL
+    fn read_xml(
+        xml: &str,
+        raw_options: i32,
+        encoding: &'static Encoding,
+        url: Option<&CStr>,
+    ) -> Result<Self> {
         // 检查输入大小
         let size = xml.len();
         if size > MAX_INPUT_SIZE {
@@ -194,25 +540,30 @@ impl Document {
 
         let c_xml = CString::new(xml).map_err(|_| Error::NullByte)?;
 
-        // 构建 XML 解析选项
-        let mut raw_options: xmlParserOption = 0;
-        if options.no_blanks {
-            raw_options |= xmlParserOption_XML_PARSE_NOBLANKS;
-        }
-        if options.no_dtd {
-            raw_options |= xmlParserOption_XML_PARSE_DTDLOAD;
-        }
-
         // SAFETY: c_xml 是有效的 CString，size 已验证
         // 调用 libxml2-sys 的安全封装函数
-        let doc_ptr = unsafe { parse_xml_memory(c_xml.as_ptr(), size as i32, raw_options as i32) };
+        let (doc_ptr, raw_errors) = unsafe {
+            parse_xml_memory_with_errors(c_xml.as_ptr(), size as i32, url_ptr(url), raw_options)
+        };
+        let diagnostics = raw_errors.into_iter().map(Diagnostic::from_raw).collect();
 
+        Self::from_parsed(doc_ptr, encoding, diagnostics)
+    }
+
+    /// 由解析结果构造文档，解析失败时携带诊断信息返回错误
+    pub(crate) fn from_parsed(
+        doc_ptr: xmlDocPtr,
+        encoding: &'static Encoding,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Result<Self> {
         if doc_ptr.is_null() {
-            return Err(Error::ParseFailed { detail: None });
+            return Err(Error::parse_failed_with_diagnostics(diagnostics));
         }
 
         Ok(Self {
             doc_ptr,
+            encoding,
+            diagnostics,
             _marker: PhantomData,
         })
     }
@@ -274,6 +625,50 @@ impl Document {
         evaluate_xpath(self.doc_ptr, xpath)
     }
 
+    /// 执行 XPath 查询并把结果严格转换为 `T`
+    ///
+    /// 可以转换为整数、浮点数、`bool`、`String`、[`IsoDate`](crate::IsoDate)、
+    /// [`IsoDateTime`](crate::IsoDateTime)、节点以及它们的 `Option` / `Vec`，
+    /// 转换规则见 [`FromXPathResult`]。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidXPath`] - XPath 表达式无效
+    /// - [`Error::XPathConversion`] - 结果的类型或值与 `T` 不符
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, Error, IsoDate};
+    ///
+    /// let doc = Document::parse(
+    ///     r#"<ul><li>3</li><li>5</li></ul><time datetime="2024-03-15">Fri</time><b>n/a</b>"#,
+    /// )?;
+    /// assert_eq!(doc.eval::<u32>("count(//li)")?, 2);
+    /// assert_eq!(doc.eval::<Vec<i64>>("//li")?, [3, 5]);
+    /// assert_eq!(doc.eval::<IsoDate>("//time/@datetime")?.month, 3);
+    /// assert_eq!(doc.eval::<Option<String>>("//h1")?, None);
+    ///
+    /// // 不会像 extract_number 那样退回 0
+    /// assert!(matches!(doc.eval::<f64>("//b"), Err(Error::XPathConversion { .. })));
+    /// assert!(doc.eval::<i64>("sum(//li) div 3").is_err());
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn eval<'a, T: FromXPathResult<'a>>(&'a self, xpath: &str) -> Result<T> {
+        T::from_xpath_result(self.evaluate(xpath)?).map_err(|e| match e {
+            Error::XPathConversion {
+                xpath: None,
+                expected,
+                found,
+            } => Error::XPathConversion {
+                xpath: Some(xpath.to_string()),
+                expected,
+                found,
+            },
+            other => other,
+        })
+    }
+
     /// 执行 XPath 查询并返回匹配的节点（便捷方法）
     ///
     /// 这是 `evaluate()` 的便捷包装，只返回节点集合。
@@ -299,6 +694,192 @@ impl Document {
         }
     }
 
+    /// 执行 XPath 查询，只返回按文档顺序的第一个匹配
+    ///
+    /// 找到第一个匹配后即停止遍历，不为其余的匹配分配内存，适合在大文档中查找单个节点。
+    /// 表达式不返回节点集合时返回 `None`。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<p>A</p><p>B</p>").unwrap();
+    /// let first = doc.select_first("//p").unwrap().unwrap();
+    /// assert_eq!(first.text(), "A");
+    /// assert!(doc.select_first("//h1").unwrap().is_none());
+    /// ```
+    pub fn select_first<'a>(&'a self, xpath: &str) -> Result<Option<SelectedNode<'a>>> {
+        let Some(first) = first_match_xpath(xpath) else {
+            // 括号不配对，按原表达式求值并报告错误
+            return Ok(self.select(xpath)?.into_iter().next());
+        };
+        match self
+            .evaluate(&first)
+            .map_err(|e| with_original_xpath(e, xpath))?
+        {
+            XPathResult::NodeSet(nodes) => Ok(nodes.into_iter().next()),
+            _ => Ok(None),
+        }
+    }
+
+    /// 绑定变量后执行 XPath 查询并返回结果
+    ///
+    /// 表达式中以 `$name` 引用变量，值无需转义，可以安全地包含引号。
+    ///
+    /// # Arguments
+    ///
+    /// * `xpath` - XPath 表达式
+    /// * `variables` - `(变量名, 值)` 列表
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidXPath`] - XPath 表达式无效或引用了未绑定的变量
+    /// - [`Error::InvalidVariable`] - 节点集合变量包含其他文档的节点
+    /// - [`Error::NullByte`] - XPath、变量名或字符串值包含空字节
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<p>a</p><p>b</p><p>c</p>").unwrap();
+    /// let first = doc.select("//p").unwrap()[0].clone();
+    ///
+    /// let count = doc
+    ///     .evaluate_with(
+    ///         "count(//p[. != $skip]) - count($first)",
+    ///         &[("skip", "b".into()), ("first", first.into())],
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(count.as_number(), 1.0);
+    /// ```
+    pub fn evaluate_with<'a>(
+        &'a self,
+        xpath: &str,
+        variables: &[(&str, XPathValue<'a>)],
+    ) -> Result<XPathResult<'a>> {
+        evaluate_xpath_with(self.doc_ptr, xpath, variables)
+    }
+
+    /// 绑定变量后执行 XPath 查询并返回匹配的节点
+    ///
+    /// 如果 XPath 返回非节点类型，返回空向量。参见 [`Document::evaluate_with`]。
+    pub fn select_with<'a>(
+        &'a self,
+        xpath: &str,
+        variables: &[(&str, XPathValue<'a>)],
+    ) -> Result<Vec<SelectedNode<'a>>> {
+        match self.evaluate_with(xpath, variables)? {
+            XPathResult::NodeSet(nodes) => Ok(nodes),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 使用命名空间前缀执行 XPath 查询并返回匹配的节点
+    ///
+    /// 需要多次查询时，使用 [`Document::xpath_context`] 复用已注册的前缀。
+    ///
+    /// # Arguments
+    ///
+    /// * `xpath` - XPath 表达式
+    /// * `namespaces` - `(前缀, URI)` 列表
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry/><entry/></feed>"#;
+    /// let doc = Document::parse_xml(xml).unwrap();
+    /// let entries = doc
+    ///     .select_ns("//atom:entry", &[("atom", "http://www.w3.org/2005/Atom")])
+    ///     .unwrap();
+    /// assert_eq!(entries.len(), 2);
+    /// ```
+    pub fn select_ns<'a>(
+        &'a self,
+        xpath: &str,
+        namespaces: &[(&str, &str)],
+    ) -> Result<Vec<SelectedNode<'a>>> {
+        let mut ctx = self.xpath_context()?;
+        ctx.register_namespaces(namespaces)?;
+        ctx.select(xpath)
+    }
+
+    /// 使用 CSS 选择器查询节点
+    ///
+    /// 选择器先转换为 XPath（参见 [`css_to_xpath`](crate::css_to_xpath)），结果按文档顺序排列。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::CssSyntax`] - 选择器有语法错误，附带出错位置
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse(r#"
+    ///     <ul>
+    ///         <li class="item"><a href="http://a">A</a></li>
+    ///         <li class="item"><a href="/b">B</a></li>
+    ///         <li class="item"><a href="https://c">C</a></li>
+    ///     </ul>"#).unwrap();
+    /// let links = doc.css("ul > li.item:nth-child(2n+1) a[href^=http]").unwrap();
+    /// assert_eq!(links.len(), 2);
+    /// ```
+    pub fn css<'a>(&'a self, selector: &str) -> Result<Vec<SelectedNode<'a>>> {
+        let xpath = css_to_xpath(selector)?;
+        // 文档节点与元素节点共享相同的头部布局，可以作为上下文节点
+        evaluate_xpath_on_node(self.doc_ptr.cast(), &xpath)
+    }
+
+    /// 执行 XPath 查询，返回不借用文档的 [`OwnedNode`]
+    ///
+    /// 与 [`Document::select`] 相同，但每个节点都持有 [`OwnedDocument`](crate::OwnedDocument) 的引用计数，
+    /// 可以从函数返回、与文档一起存入结构体或发送到其他线程。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, OwnedNode};
+    /// use std::sync::Arc;
+    ///
+    /// struct Page {
+    ///     items: Vec<OwnedNode>,
+    /// }
+    ///
+    /// let doc = Arc::new(Document::parse("<ul><li>a</li><li>b</li></ul>")?);
+    /// let page = Page { items: doc.select_owned("//li")? };
+    /// drop(doc);
+    /// assert_eq!(page.items[1].text(), "b");
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn select_owned(self: &Arc<Self>, xpath: &str) -> Result<Vec<OwnedNode>> {
+        Ok(self.to_owned_nodes(self.select(xpath)?))
+    }
+
+    /// 使用 CSS 选择器查询，返回不借用文档的 [`OwnedNode`]
+    pub fn css_owned(self: &Arc<Self>, selector: &str) -> Result<Vec<OwnedNode>> {
+        Ok(self.to_owned_nodes(self.css(selector)?))
+    }
+
+    fn to_owned_nodes(self: &Arc<Self>, nodes: Vec<SelectedNode<'_>>) -> Vec<OwnedNode> {
+        nodes
+            .into_iter()
+            // SAFETY: 节点由本文档的查询得到
+            .map(|node| unsafe { OwnedNode::from_raw(self, node.node_ptr) })
+            .collect()
+    }
+
+    /// 创建可复用的 XPath 上下文
+    ///
+    /// 参见 [`XPathContext`]。
+    pub fn xpath_context(&self) -> Result<XPathContext<'_>> {
+        XPathContext::new(self)
+    }
+
     /// 执行 XPath 查询并返回所有匹配节点的文本内容
     ///
     /// # Arguments
@@ -405,6 +986,45 @@ impl Document {
         }
     }
 
+    /// 以根元素为上下文提取 `T`
+    ///
+    /// 字段中的绝对路径（如 `//title`）在整个文档中查询，相对路径从根元素开始。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NodeNotFound`] - 文档没有根元素
+    /// - [`Error::Extract`] - 某个字段提取失败
+    pub fn extract<T: FromNode>(&self) -> Result<T> {
+        let root = self.root().ok_or_else(|| Error::NodeNotFound {
+            node: "root element".to_string(),
+        })?;
+        T::from_node(&root)
+    }
+
+    /// 对 `xpath` 匹配的每个节点提取 `T`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, FromNode, Result, SelectedNode};
+    ///
+    /// struct Price(f64);
+    ///
+    /// impl FromNode for Price {
+    ///     fn from_node(node: &SelectedNode<'_>) -> Result<Self> {
+    ///         Ok(Price(node.text().parse().unwrap_or_default()))
+    ///     }
+    /// }
+    ///
+    /// let doc = Document::parse("<b>1.5</b><b>2</b>")?;
+    /// let prices: Vec<Price> = doc.extract_all("//b")?;
+    /// assert_eq!(prices.iter().map(|p| p.0).sum::<f64>(), 3.5);
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn extract_all<T: FromNode>(&self, xpath: &str) -> Result<Vec<T>> {
+        self.select(xpath)?.iter().map(T::from_node).collect()
+    }
+
     /// 获取文档根节点
     ///
     /// # Example
@@ -428,11 +1048,188 @@ impl Document {
         }
     }
 
+    /// 获取文档根节点，返回不借用文档的 [`OwnedNode`]
+    pub fn root_owned(self: &Arc<Self>) -> Option<OwnedNode> {
+        // SAFETY: 根节点属于本文档
+        self.root()
+            .map(|root| unsafe { OwnedNode::from_raw(self, root.node_ptr) })
+    }
+
     /// 检查文档是否为空
     pub fn is_empty(&self) -> bool {
         self.root().is_none()
     }
 
+    /// 获取解析时最终采用的字符编码名称
+    ///
+    /// 从 `&str` 解析的文档始终为 `"UTF-8"`；从字节解析的文档返回探测到的编码，
+    /// 名称遵循 WHATWG Encoding 标准（如 `"GBK"`、`"Shift_JIS"`、`"windows-1252"`）。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<div>Hello</div>").unwrap();
+    /// assert_eq!(doc.encoding(), "UTF-8");
+    /// ```
+    pub fn encoding(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// 获取文档的 URL，从文件解析时为文件路径；从字符串或字节解析的文档返回 `None`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<div>Hello</div>").unwrap();
+    /// assert_eq!(doc.url(), None);
+    /// ```
+    pub fn url(&self) -> Option<String> {
+        // SAFETY: doc_ptr 在 Document 生命周期内有效
+        unsafe { doc_get_url(self.doc_ptr) }
+    }
+
+    /// 获取解析过程中产生的诊断信息（错误与警告）
+    ///
+    /// 容错模式下即使文档成功解析，也可能记录了若干错误，
+    /// 可据此判断输入是否只能在恢复模式下解析。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<div><p>ok</p></div>").unwrap();
+    /// assert!(doc.diagnostics().is_empty());
+    ///
+    /// let doc = Document::parse("<div><p>broken</span></div>").unwrap();
+    /// for diag in doc.diagnostics() {
+    ///     println!("{}:{} {}", diag.line, diag.column, diag.message);
+    /// }
+    /// assert!(!doc.diagnostics().is_empty());
+    /// ```
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// 获取文档的 DOCTYPE 声明，没有时返回 `None`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse_xml(r#"<!DOCTYPE feed SYSTEM "feed.dtd"><feed/>"#).unwrap();
+    /// let doctype = doc.doctype().unwrap();
+    /// assert_eq!(doctype.name, "feed");
+    /// assert_eq!(doctype.system_id.as_deref(), Some("feed.dtd"));
+    /// ```
+    pub fn doctype(&self) -> Option<Doctype> {
+        // SAFETY: doc_ptr 在 Document 生命周期内有效
+        unsafe { doc_get_doctype(self.doc_ptr) }.map(Doctype::from_raw)
+    }
+
+    /// 以 HTML 语法序列化整个文档
+    ///
+    /// 输出包含 DOCTYPE、注释等全部节点，可用于归档清洗后的页面。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<!DOCTYPE html><html><body><p>Hi<br></p></body></html>").unwrap();
+    /// let html = doc.to_html();
+    /// assert!(html.starts_with("<!DOCTYPE html>"));
+    /// assert!(html.contains("<p>Hi<br></p>"));
+    /// ```
+    pub fn to_html(&self) -> String {
+        // SAFETY: doc_ptr 在 Document 存活期间始终有效
+        // 使用 libxml2-sys 提供的安全封装函数
+        unsafe { doc_dump_html(self.doc_ptr, false) }
+    }
+
+    /// 以 XML 语法序列化整个文档
+    ///
+    /// 输出包含 XML 声明（编码固定为 UTF-8）。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse_xml("<root><item>data</item></root>").unwrap();
+    /// let xml = doc.to_xml();
+    /// assert!(xml.starts_with("<?xml"));
+    /// assert!(xml.contains("<root><item>data</item></root>"));
+    /// ```
+    pub fn to_xml(&self) -> String {
+        // SAFETY: doc_ptr 在 Document 存活期间始终有效
+        // 使用 libxml2-sys 提供的安全封装函数
+        unsafe { doc_dump_xml(self.doc_ptr, false) }
+    }
+
+    /// 使用 XML Schema 验证文档（需要启用 `schemas` feature）
+    ///
+    /// 返回全部验证错误，文档有效时为空。验证会把 `xs:ID` 类型的属性登记到文档中，
+    /// 因此需要独占借用。
+    ///
+    /// # Errors
+    ///
+    /// 验证器内部出错（如内存不足）时返回错误；文档无效不属于错误。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, Schema};
+    ///
+    /// let schema = Schema::from_str(r#"
+    ///     <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    ///         <xs:element name="feed">
+    ///             <xs:complexType><xs:sequence>
+    ///                 <xs:element name="qty" type="xs:int" maxOccurs="unbounded"/>
+    ///             </xs:sequence></xs:complexType>
+    ///         </xs:element>
+    ///     </xs:schema>"#)?;
+    ///
+    /// let mut doc = Document::parse_xml("<feed>\n<qty>1</qty>\n<qty>two</qty>\n</feed>")?;
+    /// let errors = doc.validate(&schema)?;
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].line, 3);
+    /// assert_eq!(errors[0].path.as_deref(), Some("/feed/qty[2]"));
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    #[cfg(feature = "schemas")]
+    pub fn validate(&mut self, schema: &Schema) -> Result<Vec<ValidationError>> {
+        schema.validate(self)
+    }
+
+    /// 开启编辑会话以修改文档
+    ///
+    /// 会话独占借用文档，期间不能再通过 `doc` 查询；会话本身提供全部只读方法。
+    /// 详见 [`DocumentMut`]。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let mut doc = Document::parse("<div><style>p{}</style><p>Hi</p></div>")?;
+    /// let mut edit = doc.edit();
+    /// for style in edit.select_ids("//style")? {
+    ///     edit.remove(style)?;
+    /// }
+    /// drop(edit);
+    /// assert!(doc.select("//style")?.is_empty());
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn edit(&mut self) -> DocumentMut<'_> {
+        DocumentMut::new(self)
+    }
+
     /// 获取原始文档指针（用于高级用途）
     ///
     /// # Safety
@@ -465,3 +1262,21 @@ impl std::fmt::Debug for Document {
         write!(f, "Document(root: {})", root_info)
     }
 }
+
+/// 打开文件并读取开头用于编码探测，返回交给 libxml2 的路径
+fn open_for_parse(path: &Path) -> Result<(CString, Vec<u8>)> {
+    let mut prefix = Vec::with_capacity(PRESCAN_LIMIT);
+    File::open(path)?
+        .take(PRESCAN_LIMIT as u64)
+        .read_to_end(&mut prefix)?;
+    let name = path.to_str().ok_or_else(|| Error::Io {
+        kind: io::ErrorKind::InvalidInput,
+        message: format!("path is not valid UTF-8: {}", path.display()),
+    })?;
+    let c_path = CString::new(name).map_err(|_| Error::NullByte)?;
+    Ok((c_path, prefix))
+}
+
+fn url_ptr(url: Option<&CStr>) -> *const std::ffi::c_char {
+    url.map_or(ptr::null(), CStr::as_ptr)
+}
@@ -0,0 +1,542 @@
+//! 文档修改
+//!
+//! [`Document`] 本身只提供只读访问。需要修改 DOM 时，通过 [`Document::edit`]
+//! 开启一个编辑会话 [`DocumentMut`]：会话独占借用文档，因此修改期间不可能存在
+//! 其他线程或其他位置持有的 [`SelectedNode`]。
+//!
+//! 会话中的节点以 [`NodeId`] 句柄表示。句柄不借用文档，可以在修改前批量收集；
+//! 被移除的节点只是从树上摘下，直到会话结束才统一释放，所以句柄在整个会话内
+//! 始终指向有效内存。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use libxml2_sys::*;
+use std::ffi::CString;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 编辑会话编号，用于识别句柄来自哪个会话
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(1);
+
+/// 编辑会话中的节点句柄
+///
+/// 由 [`DocumentMut::id`]、[`DocumentMut::select_ids`] 或
+/// [`DocumentMut::create_element`] 等方法返回。句柄只在创建它的会话内有效，
+/// 交给其他会话使用时返回 [`Error::InvalidEdit`]。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    node_ptr: xmlNodePtr,
+    session: u64,
+}
+
+/// 文档的编辑会话
+///
+/// 通过 [`Document::edit`] 获取。会话通过 `Deref` 提供 [`Document`] 的全部只读方法，
+/// 修改方法则需要 `&mut self`，因此查询得到的 [`SelectedNode`] 必须在修改前释放，
+/// 需要跨越修改保留的节点请先转换为 [`NodeId`]。
+///
+/// 被移除的节点以及新建后未插入树中的节点会在会话结束时释放。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::Document;
+///
+/// let mut doc = Document::parse(
+///     r#"<html><head></head><body><script>track()</script><a href="/r?u=x">x</a></body></html>"#,
+/// )?;
+///
+/// let mut edit = doc.edit();
+/// for script in edit.select_ids("//script")? {
+///     edit.remove(script)?;
+/// }
+/// for link in edit.css_ids("a[href^='/r']")? {
+///     edit.unwrap(link)?;
+/// }
+/// let head = edit.select_ids("//head")?[0];
+/// let base = edit.create_element("base")?;
+/// edit.set_attr(base, "href", "https://example.com/")?;
+/// edit.append_child(head, base)?;
+/// drop(edit);
+///
+/// let html = doc.to_html();
+/// assert!(html.contains(r#"<base href="https://example.com/">"#));
+/// assert!(html.contains("<body>x</body>"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct DocumentMut<'d> {
+    doc: &'d mut Document,
+    session: u64,
+    // 已摘下或新建后尚未插入的节点，会话结束时释放其中仍然游离的部分
+    detached: Vec<xmlNodePtr>,
+}
+
+impl<'d> DocumentMut<'d> {
+    pub(crate) fn new(doc: &'d mut Document) -> Self {
+        Self {
+            doc,
+            session: NEXT_SESSION.fetch_add(1, Ordering::Relaxed),
+            detached: Vec::new(),
+        }
+    }
+
+    // ========================================
+    // 句柄
+    // ========================================
+
+    /// 把查询得到的节点转换为句柄
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 节点不属于本文档（如其他文档的节点、
+    ///   EXSLT 函数新建的节点或命名空间节点）
+    pub fn id(&self, node: &SelectedNode<'_>) -> Result<NodeId> {
+        let node_ptr = node.node_ptr;
+        // SAFETY: node 借用期间节点有效；命名空间节点的结构体没有 doc 字段，先按类型排除
+        let owned = unsafe {
+            node_get_type(node_ptr) as xmlElementType != xmlElementType_XML_NAMESPACE_DECL
+                && node_get_document(node_ptr) == self.doc.as_ptr()
+        };
+        if !owned {
+            return Err(Error::invalid_edit("node does not belong to this document"));
+        }
+        Ok(self.stamp(node_ptr))
+    }
+
+    /// 执行 XPath 查询并返回节点句柄
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidXPath`] - XPath 表达式无效
+    /// - [`Error::InvalidEdit`] - 结果中包含不属于本文档的节点
+    pub fn select_ids(&self, xpath: &str) -> Result<Vec<NodeId>> {
+        self.doc.select(xpath)?.iter().map(|n| self.id(n)).collect()
+    }
+
+    /// 执行 CSS 选择器查询并返回节点句柄
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::CssSyntax`] - 选择器语法错误
+    pub fn css_ids(&self, selector: &str) -> Result<Vec<NodeId>> {
+        self.doc.css(selector)?.iter().map(|n| self.id(n)).collect()
+    }
+
+    /// 通过句柄访问节点的只读方法
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let mut doc = Document::parse("<p>Hello</p>")?;
+    /// let mut edit = doc.edit();
+    /// let p = edit.select_ids("//p")?[0];
+    /// edit.set_text(p, "Bye")?;
+    /// assert_eq!(edit.node(p)?.text(), "Bye");
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn node(&self, id: NodeId) -> Result<SelectedNode<'_>> {
+        let node_ptr = self.resolve(id)?;
+        // SAFETY: 会话内的节点在会话结束前不会被释放，生命周期绑定到 self
+        Ok(unsafe { SelectedNode::from_raw(node_ptr) })
+    }
+
+    // ========================================
+    // 新建节点
+    // ========================================
+
+    /// 新建元素
+    ///
+    /// 新元素不在树中，需要通过 [`append_child`](Self::append_child) 等方法插入；
+    /// 会话结束时仍未插入的元素会被释放。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 名称为空或包含空白、`<`、`>` 等字符
+    pub fn create_element(&mut self, name: &str) -> Result<NodeId> {
+        let name = to_name(name)?;
+        // SAFETY: doc 在会话期间有效，name 是有效的 C 字符串
+        let node_ptr = unsafe { node_new_element(self.doc.as_ptr(), name.as_ptr().cast()) };
+        self.adopt(node_ptr)
+    }
+
+    /// 新建文本节点
+    ///
+    /// 文本按原样保存，序列化时自动转义 `<`、`&` 等字符。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NullByte`] - 文本包含空字节
+    pub fn create_text(&mut self, text: &str) -> Result<NodeId> {
+        let text = CString::new(text).map_err(|_| Error::NullByte)?;
+        // SAFETY: doc 在会话期间有效，text 是有效的 C 字符串
+        let node_ptr = unsafe { node_new_text(self.doc.as_ptr(), text.as_ptr().cast()) };
+        self.adopt(node_ptr)
+    }
+
+    // ========================================
+    // 属性与文本
+    // ========================================
+
+    /// 设置元素的属性值，属性不存在时新建
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 节点不是元素，或属性名无效
+    /// - [`Error::NullByte`] - 值包含空字节
+    pub fn set_attr(&mut self, id: NodeId, name: &str, value: &str) -> Result<()> {
+        let node_ptr = self.element(id)?;
+        let name = to_name(name)?;
+        let value = CString::new(value).map_err(|_| Error::NullByte)?;
+        // SAFETY: node_ptr 是本文档的元素节点
+        unsafe {
+            // xmlSetProp 会释放旧值的文本节点，先把它们摘下，避免句柄悬垂
+            let attr = node_find_attribute(node_ptr, name.as_ptr().cast());
+            if !attr.is_null() {
+                self.detach_children(attr.cast());
+            }
+            if !node_set_attribute(node_ptr, name.as_ptr().cast(), value.as_ptr().cast()) {
+                return Err(Error::invalid_edit(format!(
+                    "failed to set attribute '{}'",
+                    name.to_string_lossy()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 移除元素的属性
+    ///
+    /// 返回属性是否存在。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 节点不是元素
+    pub fn remove_attr(&mut self, id: NodeId, name: &str) -> Result<bool> {
+        let node_ptr = self.element(id)?;
+        let Ok(name) = CString::new(name) else {
+            return Ok(false);
+        };
+        // SAFETY: node_ptr 是本文档的元素节点
+        let attr = unsafe { node_find_attribute(node_ptr, name.as_ptr().cast()) };
+        if attr.is_null() {
+            return Ok(false);
+        }
+        self.detach(attr.cast());
+        Ok(true)
+    }
+
+    /// 设置节点的文本内容
+    ///
+    /// 对元素和属性节点，移除全部子节点后写入一个文本子节点；
+    /// 对文本、CDATA、注释和处理指令节点，直接替换其内容。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 节点类型不支持设置文本
+    /// - [`Error::NullByte`] - 文本包含空字节
+    pub fn set_text(&mut self, id: NodeId, text: &str) -> Result<()> {
+        let node_ptr = self.resolve(id)?;
+        let content = CString::new(text).map_err(|_| Error::NullByte)?;
+        // SAFETY: node_ptr 是本文档的节点
+        if is_element(node_ptr) || node_kind(node_ptr) == xmlElementType_XML_ATTRIBUTE_NODE {
+            // SAFETY: node_ptr 是本文档的元素或属性节点
+            unsafe {
+                self.detach_children(node_ptr);
+                if !text.is_empty() {
+                    let text_ptr = node_new_text(self.doc.as_ptr(), content.as_ptr().cast());
+                    if text_ptr.is_null() {
+                        return Err(Error::invalid_edit("failed to create text node"));
+                    }
+                    node_append_child(node_ptr, text_ptr);
+                }
+            }
+        } else if TEXT_KINDS.contains(&node_kind(node_ptr)) {
+            // SAFETY: 文本类节点没有子节点，内容按原样替换
+            unsafe { node_set_content(node_ptr, content.as_ptr().cast()) };
+        } else {
+            return Err(Error::invalid_edit("node cannot hold text"));
+        }
+        Ok(())
+    }
+
+    // ========================================
+    // 树结构
+    // ========================================
+
+    /// 把节点（连同子树）从树上移除
+    ///
+    /// 也可用于属性节点。已经游离的节点保持不变。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 尝试移除文档节点
+    pub fn remove(&mut self, id: NodeId) -> Result<()> {
+        let node_ptr = self.resolve(id)?;
+        if is_document(node_ptr) {
+            return Err(Error::invalid_edit("cannot remove the document node"));
+        }
+        self.detach(node_ptr);
+        Ok(())
+    }
+
+    /// 把节点追加为 `parent` 的最后一个子节点
+    ///
+    /// `child` 已在树中时会被移动到新位置。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - `parent` 不能包含子节点，`child` 不能被插入
+    ///   （如属性或文档节点），或 `child` 是 `parent` 本身或其祖先
+    pub fn append_child(&mut self, parent: NodeId, child: NodeId) -> Result<()> {
+        let parent_ptr = self.resolve(parent)?;
+        let child_ptr = self.content(child)?;
+        if !is_container(parent_ptr) {
+            return Err(Error::invalid_edit("parent node cannot have children"));
+        }
+        self.check_not_ancestor(child_ptr, parent_ptr)?;
+        self.detach(child_ptr);
+        // SAFETY: child 已摘下，两者属于同一文档且不构成环
+        unsafe { node_append_child(parent_ptr, child_ptr) };
+        Ok(())
+    }
+
+    /// 把节点插入到 `reference` 之前
+    ///
+    /// `node` 已在树中时会被移动到新位置。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - `reference` 没有父节点，节点不能被插入，
+    ///   或 `node` 是 `reference` 本身或其祖先
+    pub fn insert_before(&mut self, reference: NodeId, node: NodeId) -> Result<()> {
+        let reference_ptr = self.attached(reference)?;
+        let node_ptr = self.content(node)?;
+        self.check_not_ancestor(node_ptr, reference_ptr)?;
+        self.detach(node_ptr);
+        // SAFETY: node 已摘下，reference 有父节点，两者属于同一文档且不构成环
+        unsafe { node_insert_before(reference_ptr, node_ptr) };
+        Ok(())
+    }
+
+    /// 用 `replacement` 替换 `node`，被替换的节点从树上移除
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - `node` 没有父节点，节点不能被插入，
+    ///   或 `replacement` 是 `node` 的祖先
+    pub fn replace_with(&mut self, node: NodeId, replacement: NodeId) -> Result<()> {
+        if node == replacement {
+            return Ok(());
+        }
+        self.insert_before(node, replacement)?;
+        self.remove(node)
+    }
+
+    /// 移除元素但保留其子节点
+    ///
+    /// 子节点按原顺序移动到元素原来的位置，常用于去掉跟踪链接等包装元素。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidEdit`] - 节点不是元素或没有父节点
+    pub fn unwrap(&mut self, id: NodeId) -> Result<()> {
+        let node_ptr = self.attached(id)?;
+        if !is_element(node_ptr) {
+            return Err(Error::invalid_edit("only elements can be unwrapped"));
+        }
+        // SAFETY: node_ptr 是本文档中有父节点的元素，子节点逐个摘下后插入到它之前
+        unsafe {
+            let mut child = node_get_first_child(node_ptr);
+            while !child.is_null() {
+                let next = node_get_next_sibling(child);
+                node_unlink(child);
+                node_insert_before(node_ptr, child);
+                child = next;
+            }
+        }
+        self.detach(node_ptr);
+        Ok(())
+    }
+
+    // ========================================
+    // 私有辅助方法
+    // ========================================
+
+    fn stamp(&self, node_ptr: xmlNodePtr) -> NodeId {
+        NodeId {
+            node_ptr,
+            session: self.session,
+        }
+    }
+
+    /// 检查句柄来自本会话，并取出节点指针
+    fn resolve(&self, id: NodeId) -> Result<xmlNodePtr> {
+        if id.session != self.session {
+            return Err(Error::invalid_edit(
+                "node id belongs to another edit session",
+            ));
+        }
+        Ok(id.node_ptr)
+    }
+
+    /// 取出元素节点
+    fn element(&self, id: NodeId) -> Result<xmlNodePtr> {
+        let node_ptr = self.resolve(id)?;
+        if !is_element(node_ptr) {
+            return Err(Error::invalid_edit("node is not an element"));
+        }
+        Ok(node_ptr)
+    }
+
+    /// 取出可以插入到元素或文档中的节点
+    fn content(&self, id: NodeId) -> Result<xmlNodePtr> {
+        let node_ptr = self.resolve(id)?;
+        if !is_content(node_ptr) {
+            return Err(Error::invalid_edit("node cannot be inserted into a tree"));
+        }
+        Ok(node_ptr)
+    }
+
+    /// 取出挂在元素或文档下的内容节点
+    fn attached(&self, id: NodeId) -> Result<xmlNodePtr> {
+        let node_ptr = self.content(id)?;
+        // SAFETY: node_ptr 是本文档的节点
+        let parent = unsafe { node_get_parent(node_ptr) };
+        if parent.is_null() || !is_container(parent) {
+            return Err(Error::invalid_edit("node has no parent"));
+        }
+        Ok(node_ptr)
+    }
+
+    fn check_not_ancestor(&self, node_ptr: xmlNodePtr, target: xmlNodePtr) -> Result<()> {
+        // SAFETY: 两个指针都是本文档中的节点
+        if unsafe { node_is_ancestor_or_self(node_ptr, target) } {
+            return Err(Error::invalid_edit(
+                "cannot insert a node into itself or its descendants",
+            ));
+        }
+        Ok(())
+    }
+
+    /// 登记新建的节点
+    fn adopt(&mut self, node_ptr: xmlNodePtr) -> Result<NodeId> {
+        if node_ptr.is_null() {
+            return Err(Error::invalid_edit("failed to create node"));
+        }
+        self.detached.push(node_ptr);
+        Ok(self.stamp(node_ptr))
+    }
+
+    /// 把节点从树上摘下并登记，等会话结束时再决定是否释放
+    fn detach(&mut self, node_ptr: xmlNodePtr) {
+        // SAFETY: node_ptr 是本文档的节点
+        unsafe {
+            if !node_get_parent(node_ptr).is_null() {
+                node_unlink(node_ptr);
+                self.detached.push(node_ptr);
+            }
+        }
+    }
+
+    /// 摘下节点的全部子节点
+    fn detach_children(&mut self, node_ptr: xmlNodePtr) {
+        // SAFETY: node_ptr 是本文档的元素或属性节点
+        unsafe {
+            let mut child = node_get_first_child(node_ptr);
+            while !child.is_null() {
+                let next = node_get_next_sibling(child);
+                self.detach(child);
+                child = next;
+            }
+        }
+    }
+}
+
+impl Deref for DocumentMut<'_> {
+    type Target = Document;
+
+    fn deref(&self) -> &Document {
+        self.doc
+    }
+}
+
+impl Drop for DocumentMut<'_> {
+    fn drop(&mut self) {
+        self.detached.sort_unstable();
+        self.detached.dedup();
+        // 先找出仍然没有父节点的节点，再统一释放：释放一个节点会同时释放它的后代，
+        // 后代也可能登记在列表中，释放之后不能再读取它们的父节点
+        let roots: Vec<xmlNodePtr> = self
+            .detached
+            .iter()
+            .copied()
+            .filter(|&node_ptr| {
+                // SAFETY: 登记的节点在会话期间从未释放，此时都仍然有效
+                unsafe { node_get_parent(node_ptr) }.is_null()
+            })
+            .collect();
+        for node_ptr in roots {
+            // SAFETY: 没有父节点的节点不属于任何树，也不是其他根的后代，因此每个节点最多被释放一次
+            unsafe { node_free(node_ptr) };
+        }
+    }
+}
+
+impl std::fmt::Debug for DocumentMut<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DocumentMut")
+            .field("document", &self.doc)
+            .field("detached", &self.detached.len())
+            .finish()
+    }
+}
+
+/// 检查元素或属性名称，返回 C 字符串
+fn to_name(name: &str) -> Result<CString> {
+    let invalid = name.is_empty()
+        || name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '<' | '>' | '/' | '=' | '"' | '\'' | '&'));
+    if invalid {
+        return Err(Error::invalid_edit(format!("invalid name '{name}'")));
+    }
+    CString::new(name).map_err(|_| Error::NullByte)
+}
+
+/// 内容可以直接替换的文本类节点
+const TEXT_KINDS: [xmlElementType; 4] = [
+    xmlElementType_XML_TEXT_NODE,
+    xmlElementType_XML_CDATA_SECTION_NODE,
+    xmlElementType_XML_COMMENT_NODE,
+    xmlElementType_XML_PI_NODE,
+];
+
+fn node_kind(node_ptr: xmlNodePtr) -> xmlElementType {
+    // SAFETY: 调用者只传入本文档中的有效节点
+    unsafe { node_get_type(node_ptr) as xmlElementType }
+}
+
+fn is_element(node_ptr: xmlNodePtr) -> bool {
+    node_kind(node_ptr) == xmlElementType_XML_ELEMENT_NODE
+}
+
+fn is_document(node_ptr: xmlNodePtr) -> bool {
+    let kind = node_kind(node_ptr);
+    kind == xmlElementType_XML_DOCUMENT_NODE || kind == xmlElementType_XML_HTML_DOCUMENT_NODE
+}
+
+/// 可以拥有内容子节点的节点：元素、文档和文档片段
+fn is_container(node_ptr: xmlNodePtr) -> bool {
+    is_element(node_ptr)
+        || is_document(node_ptr)
+        || node_kind(node_ptr) == xmlElementType_XML_DOCUMENT_FRAG_NODE
+}
+
+/// 可以作为元素或文档子节点插入的节点
+fn is_content(node_ptr: xmlNodePtr) -> bool {
+    let kind = node_kind(node_ptr);
+    is_element(node_ptr) || kind == xmlElementType_XML_ENTITY_REF_NODE || TEXT_KINDS.contains(&kind)
+}
@@ -0,0 +1,146 @@
+//! DTD 验证与文档类型声明
+//!
+//! 文档自带的 DTD 可以在解析时通过 [`XmlParseOptions::dtd_valid`](crate::XmlParseOptions::dtd_valid)
+//! 验证；按另行提供的 DTD 验证时使用 [`Dtd`]。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::validation::{ValidationError, invalid_schema, path_to_url, source_len};
+use libxml2_sys::{DtdGuard, RawDoctype, RawParseError};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// 文档类型声明（`<!DOCTYPE name PUBLIC "public-id" "system-id">`）
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::Document;
+///
+/// let doc = Document::parse(r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN"
+///     "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd"><html></html>"#)?;
+/// let doctype = doc.doctype().unwrap();
+/// assert_eq!(doctype.name, "html");
+/// assert_eq!(doctype.public_id.as_deref(), Some("-//W3C//DTD XHTML 1.0 Strict//EN"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doctype {
+    /// 根元素名
+    pub name: String,
+    /// 公共标识符
+    pub public_id: Option<String>,
+    /// 系统标识符（DTD 的 URI）
+    pub system_id: Option<String>,
+}
+
+impl Doctype {
+    /// 从 libxml2-sys 的原始声明转换
+    pub(crate) fn from_raw(raw: RawDoctype) -> Self {
+        Self {
+            name: raw.name.unwrap_or_default(),
+            public_id: raw.public_id,
+            system_id: raw.system_id,
+        }
+    }
+}
+
+/// 独立解析的 DTD
+///
+/// 验证时 libxml2 会按需编译元素内容模型并写回 DTD，
+/// 因此同一个 `Dtd` 上的验证会串行执行。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, Dtd};
+///
+/// let dtd = Dtd::parse(r#"
+///     <!ELEMENT feed (item+)>
+///     <!ELEMENT item (#PCDATA)>
+///     <!ATTLIST item id ID #REQUIRED>"#)?;
+///
+/// let mut doc = Document::parse_xml(r#"<feed><item id="a">x</item><item>y</item></feed>"#)?;
+/// let errors = dtd.validate(&mut doc)?;
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].path.as_deref(), Some("/feed/item[2]"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct Dtd {
+    guard: Mutex<DtdGuard>,
+}
+
+impl Dtd {
+    /// 从 DTD 文本（外部子集的语法，不含 `<!DOCTYPE`）解析
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - DTD 语法错误
+    /// - [`Error::InputTooLarge`] - 输入数据超过大小限制
+    pub fn parse(dtd: &str) -> Result<Self> {
+        let size = source_len(dtd)?;
+        // SAFETY: dtd 指向 size 字节的有效内存
+        let (guard, errors) = unsafe { DtdGuard::parse_memory(dtd.as_ptr().cast(), size) };
+        Self::from_parts(guard, errors)
+    }
+
+    /// 从文件解析 DTD
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - 文件无法读取或 DTD 语法错误
+    /// - [`Error::NullByte`] - 路径包含空字节
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let url = path_to_url(path.as_ref())?;
+        // SAFETY: url 是有效的 C 字符串
+        let (guard, errors) = unsafe { DtdGuard::parse_file(url.as_ptr()) };
+        Self::from_parts(guard, errors)
+    }
+
+    fn from_parts(guard: Option<DtdGuard>, errors: Vec<RawParseError>) -> Result<Self> {
+        guard
+            .map(|guard| Self {
+                guard: Mutex::new(guard),
+            })
+            .ok_or_else(|| invalid_schema(errors))
+    }
+
+    /// 按此 DTD 验证文档，返回全部验证错误，文档有效时为空
+    ///
+    /// 文档自身的 DOCTYPE 声明会被忽略。验证会把 ID 类型的属性登记到文档中，因此需要可变借用。
+    ///
+    /// # Errors
+    ///
+    /// 验证器内部出错（如内存不足）时返回 [`Error::ValidationFailed`]；文档无效不属于错误。
+    pub fn validate(&self, doc: &mut Document) -> Result<Vec<ValidationError>> {
+        let mut guard = self
+            .guard
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // SAFETY: 文档被独占借用，验证期间不会有其他访问
+        let (ret, errors) = unsafe { guard.validate_doc(doc.as_ptr()) };
+        let errors = errors.into_iter().map(ValidationError::from_raw).collect();
+        if ret < 0 {
+            return Err(Error::ValidationFailed {
+                validator: "DTD".to_string(),
+                errors,
+            });
+        }
+        Ok(errors)
+    }
+}
+
+impl FromStr for Dtd {
+    type Err = Error;
+
+    fn from_str(dtd: &str) -> Result<Self> {
+        Dtd::parse(dtd)
+    }
+}
+
+impl std::fmt::Debug for Dtd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dtd").finish_non_exhaustive()
+    }
+}
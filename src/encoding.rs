@@ -0,0 +1,349 @@
+//! 字符编码探测与转码
+//!
+//! libxml2 以精简模式编译（`LIBXML2_WITH_ICONV=OFF`），自身只认识 UTF-8、UTF-16 和 Latin-1。
+//! 这里在 Rust 侧完成编码探测，并借助 `encoding_rs` 把任意 WHATWG 编码统一转换为 UTF-8，
+//! 再交给 libxml2 解析。
+//!
+//! 探测优先级：
+//!
+//! 1. BOM（UTF-8 / UTF-16LE / UTF-16BE）
+//! 2. 调用者提供的提示（例如 HTTP `Content-Type` 头）
+//! 3. 文档内声明：HTML 的 `<meta charset>` / `http-equiv`（其次是开头的 `<?xml encoding?>`），
+//!    XML 的 `<?xml encoding?>`
+//! 4. 默认值：HTML 为 UTF-8（非法时回退到 windows-1252），XML 为 UTF-8
+
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252, X_USER_DEFINED};
+use std::borrow::Cow;
+use std::io::{self, Read};
+
+/// `<meta>` 预扫描的字节上限（与 WHATWG 规范一致）
+pub(crate) const PRESCAN_LIMIT: usize = 1024;
+
+/// 转码后的文档内容
+pub(crate) struct Decoded<'a> {
+    /// UTF-8 文本
+    pub text: Cow<'a, str>,
+    /// 最终采用的编码
+    pub encoding: &'static Encoding,
+}
+
+/// 探测 HTML 字节流的编码并转换为 UTF-8
+pub(crate) fn decode_html<'a>(bytes: &'a [u8], hint: Option<&str>) -> Decoded<'a> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return decode_with(encoding, &bytes[bom_len..]);
+    }
+
+    let declared = hint
+        .and_then(resolve_hint)
+        .or_else(|| html_declaration(bytes));
+
+    let encoding = match declared {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => WINDOWS_1252,
+    };
+    decode_with(encoding, bytes)
+}
+
+/// 探测 XML 字节流的编码并转换为 UTF-8
+pub(crate) fn decode_xml<'a>(bytes: &'a [u8], hint: Option<&str>) -> Decoded<'a> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return decode_with(encoding, &bytes[bom_len..]);
+    }
+
+    // 无 BOM 的 UTF-16：依据 `<?` 的字节排列判断
+    if bytes.starts_with(&[0x3C, 0x00, 0x3F, 0x00]) {
+        return decode_with(UTF_16LE, bytes);
+    }
+    if bytes.starts_with(&[0x00, 0x3C, 0x00, 0x3F]) {
+        return decode_with(UTF_16BE, bytes);
+    }
+
+    let encoding = hint
+        .and_then(resolve_hint)
+        .or_else(|| xml_declaration_encoding(bytes).map(declared_override))
+        .unwrap_or(UTF_8);
+    decode_with(encoding, bytes)
+}
+
+/// 根据文件开头判断 HTML 文件是否可能不经转码直接由 libxml2 按 UTF-8 读取
+///
+/// 没有 BOM、也没有声明 UTF-8 以外的编码时成立，探测规则与 [`decode_html`] 相同。
+/// 未声明编码的文件还需要用 [`is_utf8_stream`] 确认全部内容都是 UTF-8，
+/// 否则 [`decode_html`] 会回退到 windows-1252。
+pub(crate) fn html_file_is_utf8(prefix: &[u8]) -> bool {
+    Encoding::for_bom(prefix).is_none()
+        && html_declaration(prefix).is_none_or(|encoding| encoding == UTF_8)
+}
+
+/// 分块检查数据流是否全部为合法的 UTF-8，不会把内容整体读入内存
+pub(crate) fn is_utf8_stream(mut reader: impl Read) -> io::Result<bool> {
+    let mut buf = [0u8; 8192];
+    // 上一块末尾未读完整的字符，已移到 buf 开头
+    let mut pending = 0;
+    loop {
+        let len = match reader.read(&mut buf[pending..]) {
+            Ok(0) => return Ok(pending == 0),
+            Ok(len) => pending + len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        pending = match std::str::from_utf8(&buf[..len]) {
+            Ok(_) => 0,
+            Err(e) if e.error_len().is_none() => {
+                buf.copy_within(e.valid_up_to()..len, 0);
+                len - e.valid_up_to()
+            }
+            Err(_) => return Ok(false),
+        };
+    }
+}
+
+/// 根据文件开头判断 libxml2 能否自行识别 XML 文件的编码，能识别时返回该编码
+///
+/// 带 BOM 或无 BOM 的 UTF-16 文件，以及未声明编码或声明为 UTF-8 的文件可以直接读取；
+/// 其余编码需要先由 [`decode_xml`] 转码。
+pub(crate) fn xml_file_native_encoding(prefix: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(prefix) {
+        return Some(encoding);
+    }
+    if prefix.starts_with(&[0x3C, 0x00, 0x3F, 0x00]) {
+        return Some(UTF_16LE);
+    }
+    if prefix.starts_with(&[0x00, 0x3C, 0x00, 0x3F]) {
+        return Some(UTF_16BE);
+    }
+    match xml_declaration_encoding(prefix) {
+        None => Some(UTF_8),
+        Some(encoding) if encoding == UTF_8 => Some(UTF_8),
+        Some(_) => None,
+    }
+}
+
+/// 使用指定编码解码（不再处理 BOM）
+fn decode_with<'a>(encoding: &'static Encoding, bytes: &'a [u8]) -> Decoded<'a> {
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    Decoded { text, encoding }
+}
+
+/// 文档内声明的 UTF-16 必然是错误的（能以 ASCII 读到声明就说明不是 UTF-16）
+fn declared_override(encoding: &'static Encoding) -> &'static Encoding {
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        UTF_8
+    } else if encoding == X_USER_DEFINED {
+        WINDOWS_1252
+    } else {
+        encoding
+    }
+}
+
+/// HTML 文档内声明的编码：`<meta>` 优先，其次是 XHTML 常见的 XML 声明
+fn html_declaration(bytes: &[u8]) -> Option<&'static Encoding> {
+    prescan_meta(bytes)
+        .or_else(|| xml_declaration_encoding(bytes))
+        .map(declared_override)
+}
+
+/// 解析调用者提供的编码提示
+///
+/// 既接受裸标签（`gbk`），也接受完整的 `Content-Type` 值（`text/html; charset=gbk`）。
+/// 无法识别的标签会被忽略。
+fn resolve_hint(hint: &str) -> Option<&'static Encoding> {
+    let label = match extract_charset(hint.as_bytes()) {
+        Some(label) => label,
+        None if hint.contains('=') || hint.contains('/') => return None,
+        None => hint.as_bytes(),
+    };
+    Encoding::for_label(label)
+}
+
+// ========================================
+// 文档内声明扫描
+// ========================================
+
+/// 读取 XML 声明中的 `encoding` 伪属性
+fn xml_declaration_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    if !bytes.starts_with(b"<?xml") {
+        return None;
+    }
+
+    let limit = bytes.len().min(PRESCAN_LIMIT);
+    let end = find(&bytes[..limit], b"?>")?;
+    let decl = &bytes[5..end];
+
+    let pos = find(decl, b"encoding")?;
+    let rest = skip_whitespace(&decl[pos + b"encoding".len()..]);
+    let rest = skip_whitespace(rest.strip_prefix(b"=")?);
+    let quote = *rest.first()?;
+    if quote != b'"' && quote != b'\'' {
+        return None;
+    }
+    let value = &rest[1..];
+    let close = value.iter().position(|&b| b == quote)?;
+    Encoding::for_label(&value[..close])
+}
+
+/// 预扫描 HTML 开头，查找 `<meta charset>` 或 `http-equiv="Content-Type"` 声明
+fn prescan_meta(bytes: &[u8]) -> Option<&'static Encoding> {
+    let bytes = &bytes[..bytes.len().min(PRESCAN_LIMIT)];
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if rest.starts_with(b"<!--") {
+            pos += match find(&rest[4..], b"-->") {
+                Some(end) => 4 + end + 3,
+                None => return None,
+            };
+        } else if starts_with_ignore_case(rest, b"<meta")
+            && rest
+                .get(5)
+                .is_some_and(|&b| b.is_ascii_whitespace() || b == b'/')
+        {
+            let (encoding, consumed) = scan_meta_attributes(&rest[5..]);
+            if encoding.is_some() {
+                return encoding;
+            }
+            pos += 5 + consumed;
+        } else if rest.first() == Some(&b'<') {
+            pos += match rest.iter().position(|&b| b == b'>') {
+                Some(end) => end + 1,
+                None => return None,
+            };
+        } else {
+            pos += 1;
+        }
+    }
+
+    None
+}
+
+/// 解析 `<meta` 之后的属性列表
+///
+/// 返回识别出的编码以及消耗的字节数。
+fn scan_meta_attributes(bytes: &[u8]) -> (Option<&'static Encoding>, usize) {
+    let mut charset = None;
+    let mut content = None;
+    let mut is_content_type = false;
+    let mut pos = 0;
+
+    loop {
+        while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'/') {
+            pos += 1;
+        }
+        if pos >= bytes.len() || bytes[pos] == b'>' {
+            break;
+        }
+
+        let name_start = pos;
+        while pos < bytes.len()
+            && !bytes[pos].is_ascii_whitespace()
+            && !matches!(bytes[pos], b'=' | b'>' | b'/')
+        {
+            pos += 1;
+        }
+        let name = &bytes[name_start..pos];
+
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        let mut value: &[u8] = &[];
+        if bytes.get(pos) == Some(&b'=') {
+            pos += 1;
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            match bytes.get(pos) {
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    let start = pos + 1;
+                    let len = bytes[start..].iter().position(|&b| b == quote);
+                    let end = len.map_or(bytes.len(), |len| start + len);
+                    value = &bytes[start..end];
+                    pos = (end + 1).min(bytes.len());
+                }
+                _ => {
+                    let start = pos;
+                    while pos < bytes.len()
+                        && !bytes[pos].is_ascii_whitespace()
+                        && bytes[pos] != b'>'
+                    {
+                        pos += 1;
+                    }
+                    value = &bytes[start..pos];
+                }
+            }
+        }
+
+        if name.eq_ignore_ascii_case(b"charset") {
+            charset.get_or_insert(value);
+        } else if name.eq_ignore_ascii_case(b"content") {
+            content.get_or_insert(value);
+        } else if name.eq_ignore_ascii_case(b"http-equiv") {
+            is_content_type |= value.eq_ignore_ascii_case(b"content-type");
+        }
+    }
+
+    let label = charset.or_else(|| {
+        if is_content_type {
+            content.and_then(extract_charset)
+        } else {
+            None
+        }
+    });
+    (label.and_then(Encoding::for_label), pos)
+}
+
+/// 从 `Content-Type` 风格的字符串中提取 `charset=` 的值
+fn extract_charset(bytes: &[u8]) -> Option<&[u8]> {
+    let mut rest = bytes;
+    loop {
+        let pos = find_ignore_case(rest, b"charset")?;
+        let after = skip_whitespace(&rest[pos + b"charset".len()..]);
+        let Some(value) = after.strip_prefix(b"=") else {
+            rest = after;
+            continue;
+        };
+        let value = skip_whitespace(value);
+        return match value.first() {
+            Some(&quote) if quote == b'"' || quote == b'\'' => {
+                let value = &value[1..];
+                let end = value.iter().position(|&b| b == quote)?;
+                Some(&value[..end])
+            }
+            Some(_) => {
+                let end = value
+                    .iter()
+                    .position(|&b| b == b';' || b.is_ascii_whitespace())
+                    .unwrap_or(value.len());
+                Some(&value[..end])
+            }
+            None => None,
+        };
+    }
+}
+
+// ========================================
+// 字节工具函数
+// ========================================
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn find_ignore_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+fn skip_whitespace(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
@@ -1,5 +1,7 @@
 //! 错误类型定义
 
+use crate::diagnostic::Diagnostic;
+use crate::validation::ValidationError;
 use thiserror::Error;
 
 /// rlibxml 库的错误类型
@@ -25,10 +27,28 @@ pub enum Error {
     /// HTML/XML 解析失败
     ///
     /// 输入数据无法被正确解析。使用容错模式可能有所帮助。
+    ///
+    /// 以后可能增加新的字段：匹配时请使用 `..`，构造请使用 [`Error::parse_failed`]
+    /// 或 [`Error::parse_failed_with_diagnostics`]。
     #[error("Failed to parse HTML/XML{}", .detail.as_ref().map(|d| format!(": {}", d)).unwrap_or_default())]
+    #[non_exhaustive]
     ParseFailed {
         /// 可选的错误详情
         detail: Option<String>,
+        /// 解析过程中收集到的诊断信息
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// 读取输入失败
+    ///
+    /// 读取文件、异步数据源或 [`XmlReader`](crate::XmlReader) 的数据源时发生的 I/O 错误，
+    /// 也包括无法交给 libxml2 的文件路径（如不是合法的 UTF-8）。
+    #[error("I/O error: {message}")]
+    Io {
+        /// 错误类型
+        kind: std::io::ErrorKind,
+        /// 错误消息
+        message: String,
     },
 
     /// XPath 表达式无效
@@ -42,12 +62,122 @@ pub enum Error {
         reason: Option<String>,
     },
 
+    /// XPath 表达式语法错误
+    ///
+    /// 由 [`XPath::compile`](crate::XPath::compile) 返回，附带出错位置。
+    #[error("XPath syntax error at position {position} in '{xpath}': {message}")]
+    XPathSyntax {
+        /// XPath 表达式
+        xpath: String,
+        /// 错误消息
+        message: String,
+        /// 出错位置（表达式中的字节偏移）
+        position: usize,
+    },
+
+    /// CSS 选择器语法错误
+    ///
+    /// 选择器无法解析，或使用了不支持的特性（如伪元素）。
+    #[error("CSS selector syntax error at position {position} in '{selector}': {message}")]
+    CssSyntax {
+        /// CSS 选择器
+        selector: String,
+        /// 错误消息
+        message: String,
+        /// 出错位置（选择器中的字节偏移）
+        position: usize,
+    },
+
+    /// 验证规则无法编译
+    ///
+    /// 例如 XSD 文件不存在、不是格式良好的 XML、引用了未定义的类型，或 DTD 有语法错误。
+    #[error("Invalid schema{}", .errors.first().map(|e| format!(": {}", e)).unwrap_or_default())]
+    InvalidSchema {
+        /// 编译过程中报告的错误，行号和路径指向规则文档
+        errors: Vec<ValidationError>,
+    },
+
+    /// 验证器内部出错
+    ///
+    /// 由 `Dtd`、`Schema`、`RelaxNg`、`Schematron` 的 `validate` 在 libxml2 无法完成验证时返回，
+    /// 例如内存不足或规则在验证时才暴露的问题。文档无效不属于这种错误。
+    #[error("{validator} validation failed{}", .errors.first().map(|e| format!(": {}", e)).unwrap_or_default())]
+    ValidationFailed {
+        /// 验证器名称，如 `XML Schema`
+        validator: String,
+        /// 出错前已经收集到的验证错误
+        errors: Vec<ValidationError>,
+    },
+
+    /// XSLT 样式表无法编译
+    #[error("Invalid stylesheet{}", .messages.first().map(|m| format!(": {}", m)).unwrap_or_default())]
+    InvalidStylesheet {
+        /// libxslt 与 libxml2 报告的错误消息，每行一条
+        messages: Vec<String>,
+    },
+
+    /// XSLT 转换失败
+    ///
+    /// 例如运行时错误、`xsl:message terminate="yes"` 或被禁止的文件写入。
+    #[error("XSLT transformation failed{}", .messages.first().map(|m| format!(": {}", m)).unwrap_or_default())]
+    TransformFailed {
+        /// 转换过程中报告的消息（包括 `xsl:message` 的输出），每行一条
+        messages: Vec<String>,
+    },
+
+    /// XPath 结果无法转换为请求的类型
+    ///
+    /// 由 [`Document::eval`](crate::Document::eval) 与
+    /// [`FromXPathResult`](crate::FromXPathResult) 的实现返回，例如期望数字时得到了无法解析的文本。
+    #[error("Cannot convert XPath result{} to {expected}: found {found}", .xpath.as_ref().map(|x| format!(" of '{}'", x)).unwrap_or_default())]
+    XPathConversion {
+        /// XPath 表达式（由 `Document::eval` 填写）
+        xpath: Option<String>,
+        /// 期望的类型
+        expected: String,
+        /// 实际得到的结果
+        found: String,
+    },
+
     /// 创建 XPath 上下文失败
     ///
     /// 这是一个内部错误，通常表示内存不足。
     #[error("Failed to create XPath context")]
     XPathContextFailed,
 
+    /// 命名空间前缀注册失败
+    ///
+    /// libxml2 拒绝注册空前缀。
+    #[error("Invalid namespace prefix '{prefix}' for '{uri}'")]
+    InvalidNamespace {
+        /// 命名空间前缀
+        prefix: String,
+        /// 命名空间 URI
+        uri: String,
+    },
+
+    /// XPath 变量绑定失败
+    ///
+    /// 例如节点集合中包含其他文档的节点。
+    #[error("Invalid XPath variable '${name}': {reason}")]
+    InvalidVariable {
+        /// 变量名
+        name: String,
+        /// 错误原因
+        reason: String,
+    },
+
+    /// XPath 扩展函数注册失败
+    ///
+    /// 例如在同一个上下文中重复注册同名函数。
+    #[error("Failed to register XPath function '{name}' in namespace '{uri}'")]
+    InvalidFunction {
+        /// 函数名
+        name: String,
+        /// 命名空间 URI（没有命名空间时为空）
+        uri: String,
+    },
+
     /// 节点不存在
     ///
     /// 尝试访问不存在的节点或属性。
@@ -64,6 +194,29 @@ pub enum Error {
         name: String,
     },
 
+    /// DOM 修改操作无效
+    ///
+    /// 例如把节点插入到它自己的后代中，或使用了其他编辑会话的节点句柄。
+    #[error("Invalid DOM edit: {reason}")]
+    InvalidEdit {
+        /// 错误原因
+        reason: String,
+    },
+
+    /// 类型化提取失败
+    ///
+    /// 由 [`FromNode`](crate::FromNode) 的派生实现返回。嵌套结构中的字段以 `.` 连接，
+    /// `Vec` 中的元素带有下标，例如 `offers[1].price`。
+    #[error("Failed to extract field '{field}' with '{selector}': {reason}")]
+    Extract {
+        /// 失败的字段路径
+        field: String,
+        /// 字段使用的选择器（XPath 与属性名）
+        selector: String,
+        /// 错误原因
+        reason: String,
+    },
+
     /// 文档已释放
     ///
     /// 尝试在文档被释放后访问其内容。
@@ -88,6 +241,15 @@ impl From<String> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io {
+            kind: e.kind(),
+            message: e.to_string(),
+        }
+    }
+}
+
 /// 便捷的 Result 类型别名
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -97,6 +259,20 @@ impl Error {
     pub fn parse_failed(detail: impl Into<String>) -> Self {
         Error::ParseFailed {
             detail: Some(detail.into()),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// 创建解析失败错误（附带诊断信息）
+    ///
+    /// 错误详情取自第一条错误级别的诊断。
+    pub fn parse_failed_with_diagnostics(diagnostics: Vec<Diagnostic>) -> Self {
+        Error::ParseFailed {
+            detail: diagnostics
+                .iter()
+                .find(|d| d.is_error())
+                .map(|d| d.to_string()),
+            diagnostics,
         }
     }
 
@@ -115,4 +291,22 @@ impl Error {
             reason: Some(reason.into()),
         }
     }
+
+    /// 创建 XPath 结果转换错误
+    ///
+    /// 供自定义的 [`FromXPathResult`](crate::FromXPathResult) 实现使用。
+    pub fn xpath_conversion(expected: impl Into<String>, found: impl Into<String>) -> Self {
+        Error::XPathConversion {
+            xpath: None,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    /// 创建 DOM 修改错误
+    pub fn invalid_edit(reason: impl Into<String>) -> Self {
+        Error::InvalidEdit {
+            reason: reason.into(),
+        }
+    }
 }
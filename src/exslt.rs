@@ -0,0 +1,808 @@
+//! EXSLT 扩展函数
+//!
+//! 启用 `exslt` feature 后，库内创建的每个 XPath 上下文都会注册这里的函数，
+//! 并预先绑定标准前缀，无需额外配置即可在表达式中使用：
+//!
+//! | 前缀 | 命名空间 | 函数 |
+//! |------|----------|------|
+//! | `regexp` | `http://exslt.org/regular-expressions` | `test` `match` `replace` |
+//! | `str` | `http://exslt.org/strings` | `tokenize` `split` `replace` `concat` `padding` |
+//! | `set` | `http://exslt.org/sets` | `distinct` `difference` `intersection` `has-same-node` `leading` `trailing` |
+//! | `math` | `http://exslt.org/math` | `min` `max` `highest` `lowest` `abs` `sqrt` `power` `log` `exp` 及三角函数 |
+//! | `date` | `http://exslt.org/dates-and-times` | `date-time` `date` `time` `year` `month-in-year` `day-in-month` `day-in-week` `hour-in-day` `minute-in-hour` `second-in-minute` |
+//!
+//! 函数用 Rust 实现，不依赖 libexslt。与规范的差异：
+//!
+//! - 正则表达式使用 [`regex`] crate 的语法，支持 `g`（全局）和 `i`（忽略大小写）标志；
+//!   `regexp:replace` 的替换串可以用 `$1`、`${name}` 引用分组。
+//! - `date:date-time()` 返回 UTC 时间（以 `Z` 结尾）。
+//!
+//! `str:tokenize`、`str:split` 和 `regexp:match` 返回的新节点存放在文档附带的片段存储中，
+//! 随文档一同释放。
+
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use crate::xpath::{XPathResult, XPathValue, register_namespace, register_raw_function};
+use libxml2_sys::{XPathContextGuard, doc_new_fragment_elements, xmlDocPtr};
+use regex::{Regex, RegexBuilder};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::ops::RangeInclusive;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const REGEXP_NS: &str = "http://exslt.org/regular-expressions";
+const STRINGS_NS: &str = "http://exslt.org/strings";
+const SETS_NS: &str = "http://exslt.org/sets";
+const MATH_NS: &str = "http://exslt.org/math";
+const DATES_NS: &str = "http://exslt.org/dates-and-times";
+
+/// 预先绑定的标准前缀
+const NAMESPACES: &[(&str, &str)] = &[
+    ("regexp", REGEXP_NS),
+    ("str", STRINGS_NS),
+    ("set", SETS_NS),
+    ("math", MATH_NS),
+    ("date", DATES_NS),
+];
+
+type Function = for<'n> fn(xmlDocPtr, &[XPathResult<'n>]) -> Result<XPathValue<'n>>;
+
+const FUNCTIONS: &[(&str, &str, Function)] = &[
+    (REGEXP_NS, "test", regexp_test),
+    (REGEXP_NS, "match", regexp_match),
+    (REGEXP_NS, "replace", regexp_replace),
+    (STRINGS_NS, "tokenize", str_tokenize),
+    (STRINGS_NS, "split", str_split),
+    (STRINGS_NS, "replace", str_replace),
+    (STRINGS_NS, "concat", str_concat),
+    (STRINGS_NS, "padding", str_padding),
+    (SETS_NS, "distinct", set_distinct),
+    (SETS_NS, "difference", set_difference),
+    (SETS_NS, "intersection", set_intersection),
+    (SETS_NS, "has-same-node", set_has_same_node),
+    (SETS_NS, "leading", set_leading),
+    (SETS_NS, "trailing", set_trailing),
+    (MATH_NS, "min", math_min),
+    (MATH_NS, "max", math_max),
+    (MATH_NS, "highest", math_highest),
+    (MATH_NS, "lowest", math_lowest),
+    (MATH_NS, "abs", math_abs),
+    (MATH_NS, "sqrt", math_sqrt),
+    (MATH_NS, "log", math_log),
+    (MATH_NS, "exp", math_exp),
+    (MATH_NS, "sin", math_sin),
+    (MATH_NS, "cos", math_cos),
+    (MATH_NS, "tan", math_tan),
+    (MATH_NS, "asin", math_asin),
+    (MATH_NS, "acos", math_acos),
+    (MATH_NS, "atan", math_atan),
+    (MATH_NS, "power", math_power),
+    (MATH_NS, "atan2", math_atan2),
+    (DATES_NS, "date-time", date_date_time),
+    (DATES_NS, "date", date_date),
+    (DATES_NS, "time", date_time),
+    (DATES_NS, "year", date_year),
+    (DATES_NS, "month-in-year", date_month_in_year),
+    (DATES_NS, "day-in-month", date_day_in_month),
+    (DATES_NS, "day-in-week", date_day_in_week),
+    (DATES_NS, "hour-in-day", date_hour_in_day),
+    (DATES_NS, "minute-in-hour", date_minute_in_hour),
+    (DATES_NS, "second-in-minute", date_second_in_minute),
+];
+
+/// 在上下文中注册全部 EXSLT 函数和标准前缀
+pub(crate) fn register(ctx: &mut XPathContextGuard) -> Result<()> {
+    for (prefix, uri) in NAMESPACES {
+        register_namespace(ctx, prefix, uri)?;
+    }
+    for &(uri, name, function) in FUNCTIONS {
+        register_raw_function(ctx, uri, name, function)?;
+    }
+    Ok(())
+}
+
+// ========================================
+// 参数处理
+// ========================================
+
+/// 检查实参个数
+fn check_arity(
+    name: &str,
+    args: &[XPathResult<'_>],
+    expected: RangeInclusive<usize>,
+) -> Result<()> {
+    if expected.contains(&args.len()) {
+        return Ok(());
+    }
+    let (min, max) = (*expected.start(), *expected.end());
+    let count = if min == max {
+        min.to_string()
+    } else {
+        format!("{} to {}", min, max)
+    };
+    Err(Error::Custom(format!(
+        "{}() expects {} arguments, got {}",
+        name,
+        count,
+        args.len()
+    )))
+}
+
+/// 按 XPath `string()` 的规则转换实参
+fn string(arg: &XPathResult<'_>) -> String {
+    match arg {
+        XPathResult::Number(n) if n.is_infinite() => {
+            if *n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+        }
+        other => other.as_string(),
+    }
+}
+
+/// 按 XPath `number()` 的规则转换实参
+fn number(arg: &XPathResult<'_>) -> f64 {
+    match arg {
+        XPathResult::Number(n) => *n,
+        XPathResult::Boolean(b) => f64::from(u8::from(*b)),
+        other => string(other).trim().parse().unwrap_or(f64::NAN),
+    }
+}
+
+/// 取出节点集合实参
+fn nodes<'a, 'n>(name: &str, arg: &'a XPathResult<'n>) -> Result<&'a [SelectedNode<'n>]> {
+    match arg {
+        XPathResult::NodeSet(nodes) => Ok(nodes),
+        _ => Err(Error::Custom(format!(
+            "{}() expects node-set arguments",
+            name
+        ))),
+    }
+}
+
+/// 节点身份比较
+fn same_node(a: &SelectedNode<'_>, b: &SelectedNode<'_>) -> bool {
+    a.node_ptr == b.node_ptr
+}
+
+/// 在文档的片段存储中创建一组只含文本的元素
+fn fragment<'n>(doc: xmlDocPtr, name: &CStr, texts: &[String]) -> Result<XPathValue<'n>> {
+    if texts.is_empty() {
+        return Ok(XPathValue::NodeSet(Vec::new()));
+    }
+    if doc.is_null() {
+        return Err(Error::Custom(
+            "no document to create result nodes in".to_string(),
+        ));
+    }
+    let contents = texts
+        .iter()
+        .map(|text| CString::new(text.as_str()))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| Error::NullByte)?;
+
+    // SAFETY: doc 是上下文关联的文档，片段节点与它一同释放
+    let node_ptrs = unsafe { doc_new_fragment_elements(doc, name, &contents) }
+        .ok_or_else(|| Error::Custom("failed to create result nodes".to_string()))?;
+    // SAFETY: 节点在文档存活期间有效
+    let nodes = node_ptrs
+        .into_iter()
+        .map(|ptr| unsafe { SelectedNode::from_raw(ptr) })
+        .collect();
+    Ok(XPathValue::NodeSet(nodes))
+}
+
+// ========================================
+// regexp:
+// ========================================
+
+/// 每个线程缓存的正则表达式数量上限
+const REGEX_CACHE_SIZE: usize = 64;
+
+thread_local! {
+    // 谓词中的正则会对每个节点求值一次，缓存编译结果
+    static REGEX_CACHE: RefCell<HashMap<(String, bool), Regex>> = RefCell::new(HashMap::new());
+}
+
+/// 解析正则标志，返回 `(全局, 忽略大小写)`
+fn regex_flags(name: &str, flags: &str) -> Result<(bool, bool)> {
+    let mut global = false;
+    let mut case_insensitive = false;
+    for flag in flags.chars() {
+        match flag {
+            'g' => global = true,
+            'i' => case_insensitive = true,
+            other => {
+                return Err(Error::Custom(format!(
+                    "{}(): unsupported flag '{}'",
+                    name, other
+                )));
+            }
+        }
+    }
+    Ok((global, case_insensitive))
+}
+
+/// 编译正则表达式，优先使用缓存
+fn compile_regex(pattern: &str, case_insensitive: bool) -> Result<Regex> {
+    REGEX_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let key = (pattern.to_string(), case_insensitive);
+        if let Some(regex) = cache.get(&key) {
+            return Ok(regex.clone());
+        }
+
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| {
+                Error::Custom(format!("invalid regular expression '{}': {}", pattern, e))
+            })?;
+        if cache.len() >= REGEX_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(key, regex.clone());
+        Ok(regex)
+    })
+}
+
+/// `regexp:test(string, regexp, flags?)`
+fn regexp_test<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("regexp:test", args, 2..=3)?;
+    let flags = args.get(2).map(string).unwrap_or_default();
+    let (_, case_insensitive) = regex_flags("regexp:test", &flags)?;
+    let regex = compile_regex(&string(&args[1]), case_insensitive)?;
+    Ok(XPathValue::Boolean(regex.is_match(&string(&args[0]))))
+}
+
+/// `regexp:match(string, regexp, flags?)`
+///
+/// 非全局匹配返回整体匹配和各分组；全局匹配返回每一处整体匹配。
+/// 结果是 `<match>` 元素的节点集合。
+fn regexp_match<'n>(doc: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("regexp:match", args, 2..=3)?;
+    let flags = args.get(2).map(string).unwrap_or_default();
+    let (global, case_insensitive) = regex_flags("regexp:match", &flags)?;
+    let regex = compile_regex(&string(&args[1]), case_insensitive)?;
+    let input = string(&args[0]);
+
+    let texts: Vec<String> = if global {
+        regex
+            .find_iter(&input)
+            .map(|m| m.as_str().to_string())
+            .collect()
+    } else {
+        regex
+            .captures(&input)
+            .map(|caps| {
+                caps.iter()
+                    .map(|group| group.map(|m| m.as_str().to_string()).unwrap_or_default())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    fragment(doc, c"match", &texts)
+}
+
+/// `regexp:replace(string, regexp, flags, replacement)`
+fn regexp_replace<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("regexp:replace", args, 4..=4)?;
+    let (global, case_insensitive) = regex_flags("regexp:replace", &string(&args[2]))?;
+    let regex = compile_regex(&string(&args[1]), case_insensitive)?;
+    let input = string(&args[0]);
+    let replacement = string(&args[3]);
+
+    let replaced = if global {
+        regex.replace_all(&input, replacement.as_str())
+    } else {
+        regex.replace(&input, replacement.as_str())
+    };
+    Ok(XPathValue::String(replaced.into_owned()))
+}
+
+// ========================================
+// str:
+// ========================================
+
+/// `str:tokenize(string, delimiters?)`
+///
+/// 按分隔字符集合切分，丢弃空记号；分隔符为空字符串时逐字符切分。
+fn str_tokenize<'n>(doc: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("str:tokenize", args, 1..=2)?;
+    let input = string(&args[0]);
+    let delimiters = args
+        .get(1)
+        .map(string)
+        .unwrap_or_else(|| " \t\n\r".to_string());
+
+    let tokens: Vec<String> = if delimiters.is_empty() {
+        input.chars().map(String::from).collect()
+    } else {
+        input
+            .split(|c| delimiters.contains(c))
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    fragment(doc, c"token", &tokens)
+}
+
+/// `str:split(string, pattern?)`
+///
+/// 按整个分隔串切分，丢弃空记号；分隔串为空字符串时逐字符切分。
+fn str_split<'n>(doc: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("str:split", args, 1..=2)?;
+    let input = string(&args[0]);
+    let pattern = args.get(1).map(string).unwrap_or_else(|| " ".to_string());
+
+    let tokens: Vec<String> = if pattern.is_empty() {
+        input.chars().map(String::from).collect()
+    } else {
+        input
+            .split(pattern.as_str())
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    fragment(doc, c"token", &tokens)
+}
+
+/// `str:replace(string, search, replacement)`
+fn str_replace<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("str:replace", args, 3..=3)?;
+    let input = string(&args[0]);
+    let search = string(&args[1]);
+    if search.is_empty() {
+        return Ok(XPathValue::String(input));
+    }
+    Ok(XPathValue::String(
+        input.replace(&search, &string(&args[2])),
+    ))
+}
+
+/// `str:concat(node-set)`
+fn str_concat<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("str:concat", args, 1..=1)?;
+    let nodes = nodes("str:concat", &args[0])?;
+    Ok(XPathValue::String(
+        nodes.iter().map(|node| node.text()).collect(),
+    ))
+}
+
+/// `str:padding` 结果的最大长度（字符数），防止表达式申请无法满足的内存
+const MAX_PADDING_LENGTH: f64 = (1 << 24) as f64;
+
+/// `str:padding(length, string?)`
+fn str_padding<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("str:padding", args, 1..=2)?;
+    let length = number(&args[0]);
+    let pad = args.get(1).map(string).unwrap_or_else(|| " ".to_string());
+    if length.is_nan() || length < 1.0 || pad.is_empty() {
+        return Ok(XPathValue::String(String::new()));
+    }
+    if length > MAX_PADDING_LENGTH {
+        return Err(Error::Custom(format!(
+            "str:padding() length {} exceeds the limit of {}",
+            length, MAX_PADDING_LENGTH
+        )));
+    }
+    Ok(XPathValue::String(
+        pad.chars().cycle().take(length as usize).collect(),
+    ))
+}
+
+// ========================================
+// set:
+// ========================================
+
+/// `set:distinct(node-set)`：字符串值互不相同的节点，保留先出现者
+fn set_distinct<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("set:distinct", args, 1..=1)?;
+    let mut seen = std::collections::HashSet::new();
+    let distinct = nodes("set:distinct", &args[0])?
+        .iter()
+        .filter(|node| seen.insert(node.text()))
+        .cloned()
+        .collect();
+    Ok(XPathValue::NodeSet(distinct))
+}
+
+/// `set:difference(a, b)`：属于 a 但不属于 b 的节点
+fn set_difference<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("set:difference", args, 2..=2)?;
+    let a = nodes("set:difference", &args[0])?;
+    let b = nodes("set:difference", &args[1])?;
+    Ok(XPathValue::NodeSet(
+        a.iter()
+            .filter(|node| !b.iter().any(|other| same_node(node, other)))
+            .cloned()
+            .collect(),
+    ))
+}
+
+/// `set:intersection(a, b)`：同时属于 a 和 b 的节点
+fn set_intersection<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("set:intersection", args, 2..=2)?;
+    let a = nodes("set:intersection", &args[0])?;
+    let b = nodes("set:intersection", &args[1])?;
+    Ok(XPathValue::NodeSet(
+        a.iter()
+            .filter(|node| b.iter().any(|other| same_node(node, other)))
+            .cloned()
+            .collect(),
+    ))
+}
+
+/// `set:has-same-node(a, b)`
+fn set_has_same_node<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("set:has-same-node", args, 2..=2)?;
+    let a = nodes("set:has-same-node", &args[0])?;
+    let b = nodes("set:has-same-node", &args[1])?;
+    Ok(XPathValue::Boolean(
+        a.iter()
+            .any(|node| b.iter().any(|other| same_node(node, other))),
+    ))
+}
+
+/// `set:leading(a, b)`：a 中位于 b 的第一个节点之前的节点
+///
+/// b 为空时返回 a；b 的第一个节点不在 a 中时返回空集合。
+fn set_leading<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("set:leading", args, 2..=2)?;
+    let a = nodes("set:leading", &args[0])?;
+    let b = nodes("set:leading", &args[1])?;
+    let leading = match b.first() {
+        None => a.to_vec(),
+        Some(first) => match a.iter().position(|node| same_node(node, first)) {
+            Some(index) => a[..index].to_vec(),
+            None => Vec::new(),
+        },
+    };
+    Ok(XPathValue::NodeSet(leading))
+}
+
+/// `set:trailing(a, b)`：a 中位于 b 的第一个节点之后的节点
+///
+/// b 为空时返回 a；b 的第一个节点不在 a 中时返回空集合。
+fn set_trailing<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("set:trailing", args, 2..=2)?;
+    let a = nodes("set:trailing", &args[0])?;
+    let b = nodes("set:trailing", &args[1])?;
+    let trailing = match b.first() {
+        None => a.to_vec(),
+        Some(first) => match a.iter().position(|node| same_node(node, first)) {
+            Some(index) => a[index + 1..].to_vec(),
+            None => Vec::new(),
+        },
+    };
+    Ok(XPathValue::NodeSet(trailing))
+}
+
+// ========================================
+// math:
+// ========================================
+
+/// 节点字符串值对应的数字；空集合或包含非数字时返回 `None`
+fn node_numbers(name: &str, arg: &XPathResult<'_>) -> Result<Option<Vec<f64>>> {
+    let values: Vec<f64> = nodes(name, arg)?
+        .iter()
+        .map(|node| node.text().trim().parse().unwrap_or(f64::NAN))
+        .collect();
+    if values.is_empty() || values.iter().any(|v| v.is_nan()) {
+        Ok(None)
+    } else {
+        Ok(Some(values))
+    }
+}
+
+/// `math:min(node-set)`
+fn math_min<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("math:min", args, 1..=1)?;
+    let min = node_numbers("math:min", &args[0])?
+        .map(|values| values.into_iter().fold(f64::INFINITY, f64::min))
+        .unwrap_or(f64::NAN);
+    Ok(XPathValue::Number(min))
+}
+
+/// `math:max(node-set)`
+fn math_max<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("math:max", args, 1..=1)?;
+    let max = node_numbers("math:max", &args[0])?
+        .map(|values| values.into_iter().fold(f64::NEG_INFINITY, f64::max))
+        .unwrap_or(f64::NAN);
+    Ok(XPathValue::Number(max))
+}
+
+/// 选出数值等于 `pick` 结果的节点
+fn extreme_nodes<'n>(
+    name: &str,
+    arg: &XPathResult<'n>,
+    pick: fn(f64, f64) -> f64,
+    init: f64,
+) -> Result<XPathValue<'n>> {
+    let Some(values) = node_numbers(name, arg)? else {
+        return Ok(XPathValue::NodeSet(Vec::new()));
+    };
+    let target = values.iter().copied().fold(init, pick);
+    let nodes = nodes(name, arg)?
+        .iter()
+        .zip(values)
+        .filter(|(_, value)| *value == target)
+        .map(|(node, _)| node.clone())
+        .collect();
+    Ok(XPathValue::NodeSet(nodes))
+}
+
+/// `math:highest(node-set)`
+fn math_highest<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("math:highest", args, 1..=1)?;
+    extreme_nodes("math:highest", &args[0], f64::max, f64::NEG_INFINITY)
+}
+
+/// `math:lowest(node-set)`
+fn math_lowest<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("math:lowest", args, 1..=1)?;
+    extreme_nodes("math:lowest", &args[0], f64::min, f64::INFINITY)
+}
+
+/// 定义单参数的数学函数
+macro_rules! math_unary {
+    ($($function:ident($name:literal) => $op:path;)*) => {
+        $(
+            #[doc = concat!("`", $name, "(number)`")]
+            fn $function<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+                check_arity($name, args, 1..=1)?;
+                Ok(XPathValue::Number($op(number(&args[0]))))
+            }
+        )*
+    };
+}
+
+math_unary! {
+    math_abs("math:abs") => f64::abs;
+    math_sqrt("math:sqrt") => f64::sqrt;
+    math_log("math:log") => f64::ln;
+    math_exp("math:exp") => f64::exp;
+    math_sin("math:sin") => f64::sin;
+    math_cos("math:cos") => f64::cos;
+    math_tan("math:tan") => f64::tan;
+    math_asin("math:asin") => f64::asin;
+    math_acos("math:acos") => f64::acos;
+    math_atan("math:atan") => f64::atan;
+}
+
+/// `math:power(base, exponent)`
+fn math_power<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("math:power", args, 2..=2)?;
+    Ok(XPathValue::Number(number(&args[0]).powf(number(&args[1]))))
+}
+
+/// `math:atan2(y, x)`
+fn math_atan2<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("math:atan2", args, 2..=2)?;
+    Ok(XPathValue::Number(number(&args[0]).atan2(number(&args[1]))))
+}
+
+// ========================================
+// date:
+// ========================================
+
+static DATE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(-?\d{4,})(?:-(\d{2})(?:-(\d{2}))?)?$").unwrap());
+static TIME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{2}):(\d{2}):(\d{2}(?:\.\d+)?)$").unwrap());
+static ZONE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(Z|[+-]\d{2}:\d{2})$").unwrap());
+
+/// 解析后的日期时间，各部分按输入格式可能缺失
+#[derive(Default)]
+struct DateTime<'s> {
+    year: Option<i64>,
+    month: Option<u32>,
+    day: Option<u32>,
+    hour: Option<u32>,
+    minute: Option<u32>,
+    second: Option<f64>,
+    // 完整日期部分 `YYYY-MM-DD` 的原文
+    date: Option<&'s str>,
+    // 时间部分的原文
+    time: Option<&'s str>,
+    zone: &'s str,
+}
+
+/// 解析 `xs:dateTime`、`xs:date`、`xs:time`、`xs:gYearMonth` 或 `xs:gYear` 格式的值
+fn parse_date_time(value: &str) -> Option<DateTime<'_>> {
+    let value = value.trim();
+    let (body, zone) = match ZONE_RE.find(value) {
+        Some(m) if m.start() > 0 => (&value[..m.start()], m.as_str()),
+        _ => (value, ""),
+    };
+    let (date_part, time_part) = match body.split_once('T') {
+        Some((date, time)) => (Some(date), Some(time)),
+        None if body.contains(':') => (None, Some(body)),
+        None => (Some(body), None),
+    };
+
+    let mut parsed = DateTime {
+        zone,
+        ..Default::default()
+    };
+    if let Some(date) = date_part {
+        let caps = DATE_RE.captures(date)?;
+        let year: i64 = caps[1].parse().ok()?;
+        parsed.year = Some(year);
+        if let Some(month) = caps.get(2) {
+            let month: u32 = month.as_str().parse().ok()?;
+            if !(1..=12).contains(&month) {
+                return None;
+            }
+            parsed.month = Some(month);
+            if let Some(day) = caps.get(3) {
+                let day: u32 = day.as_str().parse().ok()?;
+                if day == 0 || day > days_in_month(year, month) {
+                    return None;
+                }
+                parsed.day = Some(day);
+                parsed.date = Some(date);
+            }
+        }
+        // 日期时间必须包含完整日期
+        if time_part.is_some() && parsed.day.is_none() {
+            return None;
+        }
+    }
+    if let Some(time) = time_part {
+        let caps = TIME_RE.captures(time)?;
+        let hour: u32 = caps[1].parse().ok()?;
+        let minute: u32 = caps[2].parse().ok()?;
+        let second: f64 = caps[3].parse().ok()?;
+        if hour > 23 || minute > 59 || second >= 60.0 {
+            return None;
+        }
+        parsed.hour = Some(hour);
+        parsed.minute = Some(minute);
+        parsed.second = Some(second);
+        parsed.time = Some(time);
+    }
+    Some(parsed)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 公历日期距 1970-01-01 的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// 距 1970-01-01 的天数对应的公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// 当前 UTC 时间，格式为 `YYYY-MM-DDThh:mm:ssZ`
+fn now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    let rem = secs.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// 日期函数的可选实参，缺省时为当前时间
+fn date_arg(name: &str, args: &[XPathResult<'_>]) -> Result<String> {
+    check_arity(name, args, 0..=1)?;
+    Ok(args.first().map(string).unwrap_or_else(now))
+}
+
+/// 取出日期时间的某个数字部分，无法解析或缺失时为 NaN
+fn date_component<'n>(
+    name: &str,
+    args: &[XPathResult<'n>],
+    component: fn(&DateTime<'_>) -> Option<f64>,
+) -> Result<XPathValue<'n>> {
+    let value = date_arg(name, args)?;
+    let number = parse_date_time(&value)
+        .as_ref()
+        .and_then(component)
+        .unwrap_or(f64::NAN);
+    Ok(XPathValue::Number(number))
+}
+
+/// `date:date-time()`
+fn date_date_time<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    check_arity("date:date-time", args, 0..=0)?;
+    Ok(XPathValue::String(now()))
+}
+
+/// `date:date(date-time?)`：日期部分，保留时区
+fn date_date<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    let value = date_arg("date:date", args)?;
+    let date = parse_date_time(&value)
+        .and_then(|dt| dt.date.map(|date| format!("{}{}", date, dt.zone)))
+        .unwrap_or_default();
+    Ok(XPathValue::String(date))
+}
+
+/// `date:time(date-time?)`：时间部分，保留时区
+fn date_time<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    let value = date_arg("date:time", args)?;
+    let time = parse_date_time(&value)
+        .and_then(|dt| dt.time.map(|time| format!("{}{}", time, dt.zone)))
+        .unwrap_or_default();
+    Ok(XPathValue::String(time))
+}
+
+/// `date:year(date-time?)`
+fn date_year<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:year", args, |dt| dt.year.map(|y| y as f64))
+}
+
+/// `date:month-in-year(date-time?)`
+fn date_month_in_year<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:month-in-year", args, |dt| dt.month.map(f64::from))
+}
+
+/// `date:day-in-month(date-time?)`
+fn date_day_in_month<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:day-in-month", args, |dt| dt.day.map(f64::from))
+}
+
+/// `date:day-in-week(date-time?)`：星期日为 1，星期六为 7
+fn date_day_in_week<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:day-in-week", args, |dt| {
+        let days = days_from_civil(dt.year?, dt.month?, dt.day?);
+        // 1970-01-01 是星期四
+        Some(((days + 4).rem_euclid(7) + 1) as f64)
+    })
+}
+
+/// `date:hour-in-day(date-time?)`
+fn date_hour_in_day<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:hour-in-day", args, |dt| dt.hour.map(f64::from))
+}
+
+/// `date:minute-in-hour(date-time?)`
+fn date_minute_in_hour<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:minute-in-hour", args, |dt| dt.minute.map(f64::from))
+}
+
+/// `date:second-in-minute(date-time?)`
+fn date_second_in_minute<'n>(_: xmlDocPtr, args: &[XPathResult<'n>]) -> Result<XPathValue<'n>> {
+    date_component("date:second-in-minute", args, |dt| dt.second)
+}
@@ -0,0 +1,188 @@
+//! 类型化提取
+//!
+//! [`FromNode`] 把一个节点转换为结构体。启用 `derive` feature 后可以用 `#[derive(FromNode)]`
+//! 在字段上声明 XPath 和属性名，由宏生成查询、取值和 [`FromStr`] 解析的代码；
+//! 任何字段失败时返回 [`Error::Extract`]，指出字段路径和所用的选择器。
+
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// 从节点提取的类型
+///
+/// 通常由 `#[derive(FromNode)]`（`derive` feature）生成，也可以手动实现。
+/// 提取整个文档时使用 [`Document::extract`](crate::Document::extract)，
+/// 提取每个匹配节点时使用 [`Document::extract_all`](crate::Document::extract_all)。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, FromNode, Result, SelectedNode};
+///
+/// struct Link {
+///     href: String,
+///     text: String,
+/// }
+///
+/// impl FromNode for Link {
+///     fn from_node(node: &SelectedNode<'_>) -> Result<Self> {
+///         Ok(Link {
+///             href: node.attr("href").unwrap_or_default(),
+///             text: node.text(),
+///         })
+///     }
+/// }
+///
+/// let doc = Document::parse(r#"<a href="/a">A</a><a href="/b">B</a>"#)?;
+/// let links: Vec<Link> = doc.extract_all("//a")?;
+/// assert_eq!(links[1].href, "/b");
+/// assert_eq!(links[1].text, "B");
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub trait FromNode: Sized {
+    /// 以 `node` 为上下文提取
+    fn from_node(node: &SelectedNode<'_>) -> Result<Self>;
+}
+
+/// 派生宏生成的代码使用的辅助类型，不属于公共 API
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    /// 一个字段的提取规则
+    ///
+    /// 有 `xpath` 时以其匹配的节点为目标，否则以当前节点为目标；
+    /// 有 `attr` 时取目标的属性值（没有该属性的目标被跳过），否则取目标的文本。
+    /// 值去除首尾空白后再解析。
+    pub struct Field {
+        pub name: &'static str,
+        pub xpath: Option<&'static str>,
+        pub attr: Option<&'static str>,
+    }
+
+    impl Field {
+        /// 必需的值：没有匹配时报错
+        pub fn one<T>(&self, node: &SelectedNode<'_>) -> Result<T>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            self.optional(node)?
+                .ok_or_else(|| self.error(self.name, "no match"))
+        }
+
+        /// 可选的值：取第一个匹配
+        pub fn optional<T>(&self, node: &SelectedNode<'_>) -> Result<Option<T>>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            self.values(node)?
+                .into_iter()
+                .next()
+                .map(|value| self.parse(&value))
+                .transpose()
+        }
+
+        /// 所有匹配的值
+        pub fn all<T>(&self, node: &SelectedNode<'_>) -> Result<Vec<T>>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            self.values(node)?
+                .iter()
+                .map(|value| self.parse(value))
+                .collect()
+        }
+
+        /// 必需的嵌套结构：没有匹配时报错
+        pub fn nested_one<T: FromNode>(&self, node: &SelectedNode<'_>) -> Result<T> {
+            self.nested_optional(node)?
+                .ok_or_else(|| self.error(self.name, "no match"))
+        }
+
+        /// 可选的嵌套结构：取第一个匹配的节点
+        pub fn nested_optional<T: FromNode>(&self, node: &SelectedNode<'_>) -> Result<Option<T>> {
+            self.targets(node)?
+                .first()
+                .map(|target| T::from_node(target).map_err(|e| self.nested_error(self.name, e)))
+                .transpose()
+        }
+
+        /// 所有匹配节点的嵌套结构，出错时字段路径带上下标
+        pub fn nested_all<T: FromNode>(&self, node: &SelectedNode<'_>) -> Result<Vec<T>> {
+            self.targets(node)?
+                .iter()
+                .enumerate()
+                .map(|(i, target)| {
+                    T::from_node(target)
+                        .map_err(|e| self.nested_error(&format!("{}[{i}]", self.name), e))
+                })
+                .collect()
+        }
+
+        fn targets<'a>(&self, node: &SelectedNode<'a>) -> Result<Vec<SelectedNode<'a>>> {
+            match self.xpath {
+                Some(xpath) => node
+                    .select(xpath)
+                    .map_err(|e| self.error(self.name, e.to_string())),
+                None => Ok(vec![node.clone()]),
+            }
+        }
+
+        fn values(&self, node: &SelectedNode<'_>) -> Result<Vec<String>> {
+            let targets = self.targets(node)?;
+            let values = match self.attr {
+                Some(attr) => targets.iter().filter_map(|t| t.attr(attr)).collect(),
+                None => targets.iter().map(|t| t.text()).collect(),
+            };
+            Ok(values)
+        }
+
+        fn parse<T>(&self, value: &str) -> Result<T>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            let value = value.trim();
+            value
+                .parse()
+                .map_err(|e| self.error(self.name, format!("cannot parse {value:?}: {e}")))
+        }
+
+        fn selector(&self) -> String {
+            match (self.xpath, self.attr) {
+                (Some(xpath), Some(attr)) => format!("{xpath}/@{attr}"),
+                (Some(xpath), None) => xpath.to_string(),
+                (None, Some(attr)) => format!("@{attr}"),
+                (None, None) => ".".to_string(),
+            }
+        }
+
+        fn error(&self, field: &str, reason: impl Into<String>) -> Error {
+            Error::Extract {
+                field: field.to_string(),
+                selector: self.selector(),
+                reason: reason.into(),
+            }
+        }
+
+        /// 嵌套结构的错误保留内层的选择器和原因，字段路径加上外层字段名
+        fn nested_error(&self, field: &str, error: Error) -> Error {
+            match error {
+                Error::Extract {
+                    field: inner,
+                    selector,
+                    reason,
+                } => Error::Extract {
+                    field: format!("{field}.{inner}"),
+                    selector,
+                    reason,
+                },
+                other => self.error(field, other.to_string()),
+            }
+        }
+    }
+}
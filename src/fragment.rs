@@ -0,0 +1,315 @@
+//! 片段解析
+//!
+//! 在某个元素的上下文中解析一段 HTML / XML 内容，例如表格单元格、列表项或 CMS 的正文字段。
+//! 结果只包含片段自身的节点，不带 `<html><body>` 之类的外层结构。
+//!
+//! XML 片段由 libxml2 的 `xmlParseInNodeContext` 解析，可以使用上下文中声明的命名空间前缀
+//! 和文档 DTD 中声明的实体，解析选项与文档相同。`xmlParseInNodeContext` 会修改上下文节点，
+//! 而文档可能被多个线程共享，因此解析在只包含 DTD 和上下文元素的副本中进行。
+//!
+//! HTML 片段没有使用 `xmlParseInNodeContext`：libxml2 在 HTML 中解析片段时不会打开上下文元素，
+//! 顶层文本会被补上 `<p>`，自动闭合规则也不考虑上下文元素。
+//! 这里用推送解析器先打开上下文元素及其祖先，再解析片段本身。
+
+use crate::diagnostic::Diagnostic;
+use crate::document::{Document, MAX_INPUT_SIZE};
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use crate::options::ParseOptions;
+use crate::push::PushParser;
+use encoding_rs::UTF_8;
+use libxml2_sys::*;
+use std::fmt::Write;
+
+/// 在元素上下文中解析得到的 HTML / XML 片段
+///
+/// 片段的顶层节点可以是元素、文本或注释，按顺序由 [`Fragment::nodes`] 返回；
+/// [`Fragment::to_html`] / [`Fragment::to_xml`] 依次序列化这些节点，不包含上下文元素。
+/// 片段拥有自己的节点，与用作上下文的文档相互独立。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::Document;
+///
+/// let fragment = Document::parse_fragment("<td>Pen</td><td>1.50", "tr")?;
+/// assert_eq!(fragment.len(), 2);
+/// assert_eq!(fragment.to_html(), "<td>Pen</td><td>1.50</td>");
+/// assert_eq!(fragment.select("td[2]")?[0].text(), "1.50");
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct Fragment {
+    // 持有片段节点所在的文档，随片段一同释放
+    _doc: Document,
+    // 上下文元素，片段的顶层节点是它的子节点
+    context: xmlNodePtr,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// SAFETY: context 属于 doc，与 Document 一样在创建后只读访问
+unsafe impl Send for Fragment {}
+unsafe impl Sync for Fragment {}
+
+impl Fragment {
+    /// 在名为 `context_tag` 的 HTML 元素中解析片段
+    pub(crate) fn parse_html_in_tag(
+        html: &str,
+        context_tag: &str,
+        options: ParseOptions,
+    ) -> Result<Self> {
+        let tag = context_tag.to_ascii_lowercase();
+        let ancestors = match tag.as_str() {
+            "html" => vec![tag],
+            "head" | "body" => vec!["html".to_string(), tag],
+            _ => vec!["html".to_string(), "body".to_string(), tag],
+        };
+        Self::parse_html(html, &ancestors, options)
+    }
+
+    /// 以 `node` 为上下文解析片段，HTML 文档中的节点按 HTML 解析，其他按 XML 解析
+    ///
+    /// 两种情况都沿用文档解析时的选项。
+    pub(crate) fn parse_in_node(markup: &str, node: xmlNodePtr) -> Result<Self> {
+        // SAFETY: 调用者传入所属文档仍然存活的有效节点
+        unsafe {
+            if node_get_type(node) != xmlElementType_XML_ELEMENT_NODE as i32 {
+                return Err(Error::Custom(
+                    "fragment context must be an element".to_string(),
+                ));
+            }
+            let doc = node_get_document(node);
+            if node_get_type(doc.cast()) == xmlElementType_XML_HTML_DOCUMENT_NODE as i32 {
+                let mut ancestors = Vec::new();
+                let mut current = node;
+                while !current.is_null()
+                    && node_get_type(current) == xmlElementType_XML_ELEMENT_NODE as i32
+                {
+                    ancestors.push(node_get_name(current));
+                    current = node_get_parent(current);
+                }
+                ancestors.reverse();
+                let options = ParseOptions::from_raw(doc_get_parse_flags(doc));
+                Self::parse_html(markup, &ancestors, options)
+            } else {
+                Self::parse_xml(markup, node)
+            }
+        }
+    }
+
+    /// 依次打开 `ancestors`（从外到内的元素名）后解析 HTML 片段
+    fn parse_html(html: &str, ancestors: &[String], options: ParseOptions) -> Result<Self> {
+        check_markup(html)?;
+        let mut prefix = String::new();
+        for name in ancestors {
+            check_name(name)?;
+            write!(prefix, "<{name}>").unwrap();
+        }
+
+        let mut parser = PushParser::html(options)?;
+        parser.feed(prefix.as_bytes())?;
+        // 此时树中只有前缀打开的元素（以及解析器补上的 html/body），最深处的就是上下文
+        let context = parser
+            .root()
+            .map(|root| deepest_last_element(root.node_ptr))
+            .ok_or_else(|| Error::Custom("failed to open fragment context".to_string()))?;
+        parser.feed(html.as_bytes())?;
+        let doc = parser.finish()?;
+
+        // SAFETY: context 属于 doc
+        unsafe { collect_escaped(context) };
+        Ok(Self::new(doc, context, prefix.chars().count()))
+    }
+
+    /// 以 XML 元素 `node` 为上下文解析片段
+    ///
+    /// # Safety
+    ///
+    /// `node` 必须是 XML 文档中有效的元素节点
+    unsafe fn parse_xml(xml: &str, node: xmlNodePtr) -> Result<Self> {
+        check_markup(xml)?;
+        if xml.len() > MAX_INPUT_SIZE {
+            return Err(Error::InputTooLarge {
+                size: xml.len(),
+                max: MAX_INPUT_SIZE,
+            });
+        }
+        // SAFETY: 调用者保证 node 有效；xml 指向 xml.len() 字节的有效内存
+        let (doc_ptr, ret, errors) =
+            unsafe { parse_xml_in_node_context(node, xml.as_ptr().cast(), xml.len() as i32) };
+        if doc_ptr.is_null() {
+            return Err(Error::Custom(
+                "failed to create fragment document".to_string(),
+            ));
+        }
+        let diagnostics: Vec<Diagnostic> = errors.into_iter().map(Diagnostic::from_raw).collect();
+        // 片段文档总是 UTF-8；片段格式不良时文档随 Document 一同释放
+        let doc = Document::from_parsed(doc_ptr, UTF_8, diagnostics)?;
+        if ret != 0 {
+            return Err(Error::parse_failed_with_diagnostics(
+                doc.diagnostics().to_vec(),
+            ));
+        }
+        let context = doc
+            .root()
+            .map(|root| root.node_ptr)
+            .ok_or_else(|| Error::parse_failed_with_diagnostics(Vec::new()))?;
+        Ok(Self::new(doc, context, 0))
+    }
+
+    fn new(doc: Document, context: xmlNodePtr, shift: usize) -> Self {
+        let mut diagnostics = doc.diagnostics().to_vec();
+        shift_columns(&mut diagnostics, shift);
+        Self {
+            _doc: doc,
+            context,
+            diagnostics,
+        }
+    }
+
+    fn context(&self) -> SelectedNode<'_> {
+        // SAFETY: context 属于 self._doc，在片段存活期间有效
+        unsafe { SelectedNode::from_raw(self.context) }
+    }
+
+    /// 片段的顶层节点（元素、文本、注释等）
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, NodeType};
+    ///
+    /// let fragment = Document::parse_fragment("Hello <b>world</b>", "div")?;
+    /// let nodes = fragment.nodes();
+    /// assert_eq!(nodes[0].node_type(), NodeType::Text);
+    /// assert_eq!(nodes[1].tag_name(), "b");
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn nodes(&self) -> Vec<SelectedNode<'_>> {
+        self.context().children()
+    }
+
+    /// 顶层节点数量
+    pub fn len(&self) -> usize {
+        self.context().child_count()
+    }
+
+    /// 片段是否没有任何节点
+    pub fn is_empty(&self) -> bool {
+        // SAFETY: context 在片段存活期间有效
+        unsafe { !node_has_children(self.context) }
+    }
+
+    /// 执行 XPath 查询
+    ///
+    /// 表达式以片段为上下文求值：相对路径（如 `li`）从顶层节点开始匹配，
+    /// `.//a` 匹配片段中的所有 `a` 元素。
+    pub fn select(&self, xpath: &str) -> Result<Vec<SelectedNode<'_>>> {
+        self.context().select(xpath)
+    }
+
+    /// 使用 CSS 选择器查询片段中的元素
+    pub fn css(&self, selector: &str) -> Result<Vec<SelectedNode<'_>>> {
+        self.context().css(selector)
+    }
+
+    /// 片段的文本内容
+    pub fn text(&self) -> String {
+        self.context().text()
+    }
+
+    /// 以 HTML 语法依次序列化顶层节点
+    pub fn to_html(&self) -> String {
+        self.context().inner_html()
+    }
+
+    /// 以 XML 语法依次序列化顶层节点
+    pub fn to_xml(&self) -> String {
+        self.context().inner_xml()
+    }
+
+    /// 解析过程中产生的诊断信息，行列位置相对于片段本身
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+}
+
+impl std::fmt::Debug for Fragment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Fragment(nodes: {})", self.len())
+    }
+}
+
+fn check_markup(markup: &str) -> Result<()> {
+    if markup.contains('\0') {
+        return Err(Error::NullByte);
+    }
+    Ok(())
+}
+
+/// 上下文元素名会被拼接到标签中，只接受普通的（限定）名称
+fn check_name(name: &str) -> Result<()> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    if !valid {
+        return Err(Error::Custom(format!(
+            "invalid fragment context element '{name}'"
+        )));
+    }
+    Ok(())
+}
+
+/// 第一行的列号扣除上下文前缀的长度
+fn shift_columns(diagnostics: &mut [Diagnostic], shift: usize) {
+    for diagnostic in diagnostics.iter_mut().filter(|d| d.line == 1) {
+        diagnostic.column = diagnostic.column.saturating_sub(shift);
+    }
+}
+
+/// 从 `node` 开始不断进入最后一个子元素，返回最深处的元素
+fn deepest_last_element(node: xmlNodePtr) -> xmlNodePtr {
+    let mut current = node;
+    // SAFETY: node 属于推送解析器正在构建的文档，遍历期间树不会变化
+    unsafe {
+        loop {
+            let mut child = node_get_last_child(current);
+            while !child.is_null() && node_get_type(child) != xmlElementType_XML_ELEMENT_NODE as i32
+            {
+                child = node_get_prev_sibling(child);
+            }
+            if child.is_null() {
+                return current;
+            }
+            current = child;
+        }
+    }
+}
+
+/// 把片段中闭合了上下文元素后跑到外层的节点按文档顺序移回上下文元素
+///
+/// 例如在 `<ul>` 中解析 `<li>a</ul>b` 时，`b` 会出现在 `<ul>` 之后。
+///
+/// # Safety
+///
+/// `context` 必须是有效的元素节点，其所在文档由调用者独占
+unsafe fn collect_escaped(context: xmlNodePtr) {
+    // SAFETY: 调用者保证 context 有效；移动的节点都属于同一文档
+    unsafe {
+        let mut current = context;
+        while !current.is_null() && node_get_type(current) == xmlElementType_XML_ELEMENT_NODE as i32
+        {
+            let mut next = node_get_next_sibling(current);
+            while !next.is_null() {
+                let following = node_get_next_sibling(next);
+                node_unlink(next);
+                node_append_child(context, next);
+                next = following;
+            }
+            current = node_get_parent(current);
+        }
+    }
+}
@@ -0,0 +1,618 @@
+//! 惰性节点迭代器
+//!
+//! 迭代器直接沿 libxml2 节点的 `children` / `next` / `prev` / `parent` 指针移动，
+//! 每一步都不分配内存，提前结束（`find`、`take`、`break`）时不会访问剩余的节点。
+//! 除 [`Children`] 和 [`Traverse`] 外，各迭代器的范围与同名 XPath 轴一致：不包含属性节点，
+//! 也不包含 DTD 和实体引用节点及其子节点；[`Ancestors`] 不包含文档节点本身。
+
+use crate::node::SelectedNode;
+use libxml2_sys::*;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr;
+
+/// 节点是否是文档节点（向上遍历的终点）
+///
+/// # Safety
+///
+/// `node` 必须是有效的节点
+unsafe fn is_document(node: xmlNodePtr) -> bool {
+    // SAFETY: 调用者保证 node 有效
+    let kind = unsafe { node_get_type(node) };
+    kind == xmlElementType_XML_DOCUMENT_NODE as i32
+        || kind == xmlElementType_XML_HTML_DOCUMENT_NODE as i32
+}
+
+/// # Safety
+///
+/// `node` 必须是有效的节点
+unsafe fn is_attribute(node: xmlNodePtr) -> bool {
+    // SAFETY: 调用者保证 node 有效
+    unsafe { node_get_type(node) == xmlElementType_XML_ATTRIBUTE_NODE as i32 }
+}
+
+/// 节点是否被遍历跳过：与 libxml2 的 XPath 一致，DTD 和实体引用不出现在任何轴中
+///
+/// # Safety
+///
+/// `node` 必须是有效的节点
+unsafe fn is_hidden(node: xmlNodePtr) -> bool {
+    // SAFETY: 调用者保证 node 有效
+    let kind = unsafe { node_get_type(node) };
+    kind == xmlElementType_XML_ENTITY_REF_NODE as i32 || kind == xmlElementType_XML_DTD_NODE as i32
+}
+
+/// 是否进入节点的子节点：实体引用的子节点属于实体声明，DTD 不属于文档树
+///
+/// # Safety
+///
+/// `node` 必须是有效的节点
+unsafe fn descends(node: xmlNodePtr) -> bool {
+    // SAFETY: 调用者保证 node 有效
+    let kind = unsafe { node_get_type(node) };
+    kind != xmlElementType_XML_ENTITY_REF_NODE as i32 && kind != xmlElementType_XML_DTD_NODE as i32
+}
+
+/// 按文档顺序位于 `node` 子树之后的第一个节点，不越出 `root` 的子树（`root` 为空时不限）
+///
+/// # Safety
+///
+/// `node` 必须是有效的节点，`root` 为空或是 `node` 的祖先或自身
+unsafe fn next_after_subtree(mut node: xmlNodePtr, root: xmlNodePtr) -> xmlNodePtr {
+    // SAFETY: 调用者保证 node 有效，沿树移动得到的节点同样有效
+    unsafe {
+        loop {
+            if node == root {
+                return ptr::null_mut();
+            }
+            let next = node_get_next_sibling(node);
+            if !next.is_null() {
+                return next;
+            }
+            node = node_get_parent(node);
+            if node.is_null() || is_document(node) {
+                return ptr::null_mut();
+            }
+        }
+    }
+}
+
+/// 按文档顺序位于 `node` 之后的第一个节点，跳过 DTD 和实体引用，不越出 `root` 的子树
+///
+/// # Safety
+///
+/// 同 [`next_after_subtree`]
+unsafe fn next_in_order(node: xmlNodePtr, root: xmlNodePtr) -> xmlNodePtr {
+    // SAFETY: 调用者保证 node 有效
+    unsafe {
+        let child = if descends(node) {
+            node_get_first_child(node)
+        } else {
+            ptr::null_mut()
+        };
+        let next = if child.is_null() {
+            next_after_subtree(node, root)
+        } else {
+            child
+        };
+        skip_hidden(next, root)
+    }
+}
+
+/// 跳过 DTD 和实体引用节点及其子树
+///
+/// # Safety
+///
+/// `node` 为空或是有效的节点
+unsafe fn skip_hidden(mut node: xmlNodePtr, root: xmlNodePtr) -> xmlNodePtr {
+    // SAFETY: 调用者保证 node 有效
+    unsafe {
+        while !node.is_null() && is_hidden(node) {
+            node = next_after_subtree(node, root);
+        }
+    }
+    node
+}
+
+/// `node` 子树中按文档顺序的最后一个节点
+///
+/// # Safety
+///
+/// `node` 必须是有效的节点
+unsafe fn deepest_last(mut node: xmlNodePtr) -> xmlNodePtr {
+    // SAFETY: 调用者保证 node 有效
+    unsafe {
+        while descends(node) {
+            let last = node_get_last_child(node);
+            if last.is_null() {
+                break;
+            }
+            node = last;
+        }
+    }
+    node
+}
+
+/// `node` 之前最近的不被跳过的兄弟节点，见 [`is_hidden`]
+///
+/// # Safety
+///
+/// `node` 必须是有效的节点
+unsafe fn prev_visible(node: xmlNodePtr) -> xmlNodePtr {
+    // SAFETY: 调用者保证 node 有效
+    unsafe {
+        let mut prev = node_get_prev_sibling(node);
+        while !prev.is_null() && is_hidden(prev) {
+            prev = node_get_prev_sibling(prev);
+        }
+        prev
+    }
+}
+
+/// `node` 本身或之后最近的不被跳过的兄弟节点
+///
+/// # Safety
+///
+/// `node` 为空或是有效的节点
+unsafe fn skip_hidden_siblings(mut node: xmlNodePtr) -> xmlNodePtr {
+    // SAFETY: 调用者保证 node 有效
+    unsafe {
+        while !node.is_null() && is_hidden(node) {
+            node = node_get_next_sibling(node);
+        }
+    }
+    node
+}
+
+/// 子节点迭代器，由 [`SelectedNode::children_iter`] 返回
+#[derive(Clone)]
+pub struct Children<'a> {
+    next: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// 后代节点迭代器（按文档顺序，不包括自身），由 [`SelectedNode::descendants`] 返回
+#[derive(Clone)]
+pub struct Descendants<'a> {
+    root: xmlNodePtr,
+    next: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// 祖先元素迭代器（由近及远），由 [`SelectedNode::ancestors`] 返回
+#[derive(Clone)]
+pub struct Ancestors<'a> {
+    next: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// 后续兄弟节点迭代器，由 [`SelectedNode::following_siblings`] 返回
+#[derive(Clone)]
+pub struct FollowingSiblings<'a> {
+    next: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// 前面的兄弟节点迭代器（由近及远），由 [`SelectedNode::preceding_siblings`] 返回
+#[derive(Clone)]
+pub struct PrecedingSiblings<'a> {
+    next: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// 文档中位于节点之后的节点（不包括后代），由 [`SelectedNode::following`] 返回
+#[derive(Clone)]
+pub struct Following<'a> {
+    next: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// 文档中位于节点之前的节点（不包括祖先，由近及远），由 [`SelectedNode::preceding`] 返回
+#[derive(Clone)]
+pub struct Preceding<'a> {
+    // 当前所在的祖先链节点（起点或其祖先），为空表示遍历结束
+    chain: xmlNodePtr,
+    // 正在倒序遍历的子树的根：chain 的某个前面的兄弟，或 chain 本身
+    top: xmlNodePtr,
+    // 上一次返回的节点，为空表示 top 的子树尚未开始
+    current: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+/// [`Traverse`] 产生的事件：进入或离开一个节点
+#[derive(Debug, Clone)]
+pub enum Edge<'a> {
+    /// 进入节点，随后是它的子节点
+    Open(SelectedNode<'a>),
+    /// 离开节点，它的子节点都已经返回
+    Close(SelectedNode<'a>),
+}
+
+impl<'a> Edge<'a> {
+    /// 事件对应的节点
+    pub fn node(&self) -> &SelectedNode<'a> {
+        match self {
+            Edge::Open(node) | Edge::Close(node) => node,
+        }
+    }
+}
+
+/// 深度优先遍历，按文档顺序产生 [`Edge::Open`] / [`Edge::Close`]，由 [`SelectedNode::traverse`] 返回
+///
+/// 起点本身也会产生一对事件，每个节点（包括文本等叶子节点）的 `Open` 之后都有对应的 `Close`。
+/// 与 [`Children`] 一样包含注释、处理指令等所有子节点，但不进入实体引用和 DTD 的子节点，
+/// 也不进入属性的值。
+#[derive(Clone)]
+pub struct Traverse<'a> {
+    root: xmlNodePtr,
+    // 下一个事件的节点与类型，节点为空表示遍历结束
+    next: xmlNodePtr,
+    open: bool,
+    // 上一个事件是 Open 时为其节点，供 skip_subtree 使用
+    last_open: xmlNodePtr,
+    _marker: PhantomData<&'a ()>,
+}
+
+// SAFETY: 与 SelectedNode 相同，迭代器只持有只读的节点指针，生命周期绑定到所属文档
+unsafe impl Send for Children<'_> {}
+unsafe impl Sync for Children<'_> {}
+unsafe impl Send for Descendants<'_> {}
+unsafe impl Sync for Descendants<'_> {}
+unsafe impl Send for Ancestors<'_> {}
+unsafe impl Sync for Ancestors<'_> {}
+unsafe impl Send for FollowingSiblings<'_> {}
+unsafe impl Sync for FollowingSiblings<'_> {}
+unsafe impl Send for PrecedingSiblings<'_> {}
+unsafe impl Sync for PrecedingSiblings<'_> {}
+unsafe impl Send for Following<'_> {}
+unsafe impl Sync for Following<'_> {}
+unsafe impl Send for Preceding<'_> {}
+unsafe impl Sync for Preceding<'_> {}
+unsafe impl Send for Traverse<'_> {}
+unsafe impl Sync for Traverse<'_> {}
+
+impl<'a> Children<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        Self {
+            // SAFETY: 节点在 'a 内有效
+            next: unsafe { node_get_first_child(node.node_ptr) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Descendants<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        let root = node.node_ptr;
+        // SAFETY: 节点在 'a 内有效；与 XPath 一致，属性的值文本不算作后代
+        let next = unsafe {
+            if is_attribute(root) {
+                ptr::null_mut()
+            } else {
+                next_in_order(root, root)
+            }
+        };
+        Self {
+            root,
+            next,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Ancestors<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        // SAFETY: 节点在 'a 内有效
+        let parent = unsafe { node_get_parent(node.node_ptr) };
+        Self {
+            next: if parent.is_null() || unsafe { is_document(parent) } {
+                ptr::null_mut()
+            } else {
+                parent
+            },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> FollowingSiblings<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        // SAFETY: 节点在 'a 内有效；属性节点的 next 指向下一个属性，不属于兄弟轴
+        let next = unsafe {
+            if is_attribute(node.node_ptr) {
+                ptr::null_mut()
+            } else {
+                skip_hidden_siblings(node_get_next_sibling(node.node_ptr))
+            }
+        };
+        Self {
+            next,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> PrecedingSiblings<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        // SAFETY: 节点在 'a 内有效；属性节点没有兄弟
+        let next = unsafe {
+            if is_attribute(node.node_ptr) {
+                ptr::null_mut()
+            } else {
+                prev_visible(node.node_ptr)
+            }
+        };
+        Self {
+            next,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Following<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        let mut node = node.node_ptr;
+        // SAFETY: 节点在 'a 内有效；与 libxml2 的 following 轴一致，属性从所属元素之后开始
+        let next = unsafe {
+            if is_attribute(node) {
+                node = node_get_parent(node);
+            }
+            skip_hidden(next_after_subtree(node, ptr::null_mut()), ptr::null_mut())
+        };
+        Self {
+            next,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Preceding<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        let mut chain = node.node_ptr;
+        // SAFETY: 节点在 'a 内有效；属性前面的节点就是所属元素前面的节点
+        if unsafe { is_attribute(chain) } {
+            chain = unsafe { node_get_parent(chain) };
+        }
+        Self {
+            chain,
+            top: chain,
+            current: ptr::null_mut(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Traverse<'a> {
+    pub(crate) fn new(node: &SelectedNode<'a>) -> Self {
+        Self {
+            root: node.node_ptr,
+            next: node.node_ptr,
+            open: true,
+            last_open: ptr::null_mut(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 跳过最近一次 [`Edge::Open`] 的节点的子节点，下一个事件是它的 [`Edge::Close`]
+    ///
+    /// 上一个事件是 [`Edge::Close`] 时没有效果。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, Edge};
+    ///
+    /// let doc = Document::parse("<div><p>A</p><pre>B</pre><p>C</p></div>").unwrap();
+    /// let div = &doc.select("//div").unwrap()[0];
+    /// let mut text = String::new();
+    /// let mut traverse = div.traverse();
+    /// while let Some(edge) = traverse.next() {
+    ///     match edge {
+    ///         Edge::Open(node) if node.tag_name() == "pre" => traverse.skip_subtree(),
+    ///         Edge::Open(node) if node.node_type().is_text() => text.push_str(&node.text()),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// assert_eq!(text, "AC");
+    /// ```
+    pub fn skip_subtree(&mut self) {
+        if !self.last_open.is_null() {
+            self.next = self.last_open;
+            self.open = false;
+            self.last_open = ptr::null_mut();
+        }
+    }
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 是有效节点，其兄弟同样属于文档
+        unsafe {
+            self.next = node_get_next_sibling(current);
+            Some(SelectedNode::from_raw(current))
+        }
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 位于 root 的子树中，遍历不越出该子树
+        unsafe {
+            self.next = next_in_order(current, self.root);
+            Some(SelectedNode::from_raw(current))
+        }
+    }
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 是有效节点，遇到文档节点时停止
+        unsafe {
+            let parent = node_get_parent(current);
+            self.next = if parent.is_null() || is_document(parent) {
+                ptr::null_mut()
+            } else {
+                parent
+            };
+            Some(SelectedNode::from_raw(current))
+        }
+    }
+}
+
+impl<'a> Iterator for FollowingSiblings<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 是有效节点
+        unsafe {
+            self.next = skip_hidden_siblings(node_get_next_sibling(current));
+            Some(SelectedNode::from_raw(current))
+        }
+    }
+}
+
+impl<'a> Iterator for PrecedingSiblings<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 是有效节点
+        unsafe {
+            self.next = prev_visible(current);
+            Some(SelectedNode::from_raw(current))
+        }
+    }
+}
+
+impl<'a> Iterator for Following<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 是有效节点，遍历在文档节点处停止
+        unsafe {
+            self.next = next_in_order(current, ptr::null_mut());
+            Some(SelectedNode::from_raw(current))
+        }
+    }
+}
+
+impl<'a> Iterator for Preceding<'a> {
+    type Item = SelectedNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chain.is_null() {
+            return None;
+        }
+        // SAFETY: 所有指针都来自有效节点沿树的移动，遍历在文档节点处停止
+        unsafe {
+            // 在 top 的子树中倒序前进：前一个兄弟子树的最后一个节点，或者父节点
+            if !self.current.is_null() && self.current != self.top {
+                let prev = prev_visible(self.current);
+                self.current = if prev.is_null() {
+                    node_get_parent(self.current)
+                } else {
+                    deepest_last(prev)
+                };
+                return Some(SelectedNode::from_raw(self.current));
+            }
+            // top 的子树已经遍历完，转到前一个兄弟；没有兄弟时沿祖先链上移（祖先本身不返回）
+            loop {
+                let prev = prev_visible(self.top);
+                if !prev.is_null() {
+                    self.top = prev;
+                    self.current = deepest_last(prev);
+                    return Some(SelectedNode::from_raw(self.current));
+                }
+                let parent = node_get_parent(self.chain);
+                if parent.is_null() || is_document(parent) {
+                    self.chain = ptr::null_mut();
+                    return None;
+                }
+                self.chain = parent;
+                self.top = parent;
+                self.current = ptr::null_mut();
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Traverse<'a> {
+    type Item = Edge<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: current 位于 root 的子树中；不进入实体引用的子节点，
+        // 因此沿 parent 向上总能回到 root
+        unsafe {
+            if self.open {
+                let child = if descends(current) && !is_attribute(current) {
+                    node_get_first_child(current)
+                } else {
+                    ptr::null_mut()
+                };
+                if child.is_null() {
+                    self.open = false;
+                } else {
+                    self.next = child;
+                }
+                self.last_open = current;
+                return Some(Edge::Open(SelectedNode::from_raw(current)));
+            }
+
+            self.last_open = ptr::null_mut();
+            if current == self.root {
+                self.next = ptr::null_mut();
+            } else {
+                let next = node_get_next_sibling(current);
+                if next.is_null() {
+                    self.next = node_get_parent(current);
+                } else {
+                    self.next = next;
+                    self.open = true;
+                }
+            }
+            Some(Edge::Close(SelectedNode::from_raw(current)))
+        }
+    }
+}
+
+impl FusedIterator for Children<'_> {}
+impl FusedIterator for Descendants<'_> {}
+impl FusedIterator for Ancestors<'_> {}
+impl FusedIterator for FollowingSiblings<'_> {}
+impl FusedIterator for PrecedingSiblings<'_> {}
+impl FusedIterator for Following<'_> {}
+impl FusedIterator for Preceding<'_> {}
+impl FusedIterator for Traverse<'_> {}
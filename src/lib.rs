@@ -59,14 +59,68 @@
 //! - [`Document::parse`] - 解析 HTML（容错模式）
 //! - [`Document::parse_xml`] - 解析 XML（严格模式）
 //! - [`Document::parse_html_with_options`] - 使用自定义选项解析
+//! - [`Document::from_bytes`] - 从原始字节解析 HTML（自动探测编码）
+//! - [`Document::from_xml_bytes`] - 从原始字节解析 XML（自动探测编码）
+//! - [`Document::parse_html_file`] / [`Document::parse_xml_file`] - 从文件解析，并记录文档 URL（[`Document::url`]）
+//! - [`Document::diagnostics`] - 获取解析诊断（错误与警告的行列位置）
+//! - [`Document::doctype`] - 获取 DOCTYPE 的名称、公共标识符和系统标识符
+//! - [`PushParser`] - 按数据块增量解析 HTML / XML，可以在数据到齐前检查已解析的部分
+//! - [`Document::parse_fragment`] / [`SelectedNode::parse_in_context`] - 在元素上下文中解析片段，
+//!   得到不带外层结构的 [`Fragment`]
+//!
+//! ## 流式读取
+//!
+//! - [`XmlReader::new`] - 从任意 `std::io::Read` 流式读取超大 XML，内存占用与文档大小无关
+//! - [`XmlReader::next_event`] - 逐个读取 [`ReaderEvent`]（开始/结束标签、文本、属性、深度）
+//! - [`XmlReader::expand`] - 把当前元素读入为临时子树，在其上使用 XPath
+//! - [`sax_parse_xml`] / [`sax_parse_html`] - 以 SAX 方式解析，把事件交给 [`SaxHandler`]，
+//!   不构建任何树，处理器可以随时中止解析
+//!
+//! 启用 `tokio` feature 后可用：
+//!
+//! - `Document::parse_async` / `Document::parse_xml_async` - 从 `AsyncRead` 分块读取并在阻塞线程池中解析
+//! - `AsyncXmlReader` - 异步逐个读取 [`ReaderEvent`]，内存占用与文档大小无关
+//!
+//! ## 验证
+//!
+//! - [`XmlParseOptions::dtd_valid`] - 解析时按文档自带的 DTD 验证
+//! - [`Dtd::parse`] / [`Dtd::validate`] - 按另行提供的 DTD 验证
+//!
+//! 启用 `schemas` feature 后可用：
+//!
+//! - `Schema::from_str` / `Schema::from_file` - 编译 XML Schema（可跨线程共享）
+//! - `Document::validate` - 验证文档，返回带行号和元素路径的 [`ValidationError`]
+//! - `RelaxNg` - 编译 RELAX NG（XML 语法），用法与 `Schema` 相同
+//! - `Schematron` - 编译 Schematron 规则，违规附带未通过的断言文本
+//!
+//! ## 序列化
+//!
+//! - [`Document::to_html`] / [`Document::to_xml`] - 序列化整个文档
+//! - [`SelectedNode::outer_html`] / [`SelectedNode::inner_html`] - 以 HTML 语法序列化节点
+//! - [`SelectedNode::outer_xml`] / [`SelectedNode::inner_xml`] - 以 XML 语法序列化节点
 //!
 //! ## XPath 查询
 //!
 //! - [`Document::select`] - 查询节点
+//! - [`Document::select_first`] / [`SelectedNode::select_first`] - 只取第一个匹配，找到后即停止
 //! - [`Document::evaluate`] - 查询并返回任意类型结果
+//! - [`Document::eval`] - 查询并严格转换为 [`FromXPathResult`] 类型（整数、`bool`、`String`、
+//!   [`IsoDate`]、`Option<T>`、`Vec<T>` 等），不匹配时报错而不是退回默认值
 //! - [`Document::extract_texts`] - 提取所有匹配节点的文本
 //! - [`Document::extract_number`] - 提取数字结果
 //! - [`Document::extract_boolean`] - 提取布尔结果
+//! - [`Document::select_ns`] - 使用命名空间前缀查询
+//! - [`Document::evaluate_with`] / [`Document::select_with`] - 绑定 `$变量` 后查询
+//! - [`XPathContext`] - 可复用的 XPath 上下文（注册命名空间后多次查询）
+//! - [`XPath::compile`] - 预编译表达式，在多个文档上复用
+//!
+//! ## CSS 选择器
+//!
+//! - [`Document::css`] / [`SelectedNode::css`] - 使用 CSS 选择器查询
+//! - [`css_to_xpath`] - 把 CSS 选择器转换为 XPath
+//!
+//! 启用 `exslt` feature 后，所有查询都可以直接使用 EXSLT 的 `regexp:`、`str:`、`set:`、
+//! `math:` 和 `date:` 函数。
 //!
 //! ## 节点操作
 //!
@@ -76,22 +130,110 @@
 //! - [`SelectedNode::attrs`] - 获取所有属性
 //! - [`SelectedNode::children`] - 获取子节点
 //! - [`SelectedNode::parent`] - 获取父节点
+//! - [`SelectedNode::children_iter`] / [`SelectedNode::descendants`] / [`SelectedNode::ancestors`] /
+//!   [`SelectedNode::following_siblings`] / [`SelectedNode::preceding_siblings`] /
+//!   [`SelectedNode::following`] / [`SelectedNode::preceding`] - 惰性遍历，每一步都不分配内存
+//! - [`SelectedNode::traverse`] - 深度优先遍历，产生进入 / 离开节点的 [`Edge`] 事件
+//! - [`SelectedNode::walk`] - 用 [`Visitor`] 按节点类型访问元素、文本与注释，可以跳过子树
 //! - [`SelectedNode::select`] - 在节点上下文中查询
+//! - [`FromNode`] / [`Document::extract`] / [`Document::extract_all`] - 把节点提取为结构体；
+//!   启用 `derive` feature 后可以用 `#[derive(FromNode)]` 在字段上声明 XPath 和属性名
+//! - [`OwnedNode`] - 持有 [`OwnedDocument`]（`Arc<Document>`）引用计数的节点句柄，
+//!   由 [`Document::select_owned`] / [`Document::css_owned`] 等方法返回，可以存入结构体或发送到其他线程
+//!
+//! ## 文档修改
+//!
+//! - [`Document::edit`] - 开启编辑会话（独占借用文档）
+//! - [`DocumentMut::select_ids`] / [`DocumentMut::id`] - 获取节点句柄
+//! - [`DocumentMut::set_attr`] / [`DocumentMut::remove_attr`] / [`DocumentMut::set_text`] - 修改属性与文本
+//! - [`DocumentMut::append_child`] / [`DocumentMut::insert_before`] /
+//!   [`DocumentMut::replace_with`] / [`DocumentMut::remove`] / [`DocumentMut::unwrap`] - 调整树结构
+//! - [`DocumentMut::create_element`] / [`DocumentMut::create_text`] - 新建节点
+//!
+//! ## XSLT 转换
+//!
+//! 启用 `xslt` feature 后可用：
+//!
+//! - `Stylesheet::parse` - 从已解析的文档编译样式表
+//! - `Stylesheet::transform` - 转换文档，传入字符串参数，返回新的 [`Document`]
+//! - `Stylesheet::serialize` - 按 `xsl:output` 设置序列化转换结果
 
+#[cfg(feature = "tokio")]
+mod async_io;
+mod convert;
+mod css;
+mod date;
+mod diagnostic;
 mod document;
+mod document_mut;
+mod dtd;
+mod encoding;
 mod error;
+#[cfg(feature = "exslt")]
+mod exslt;
+mod extract;
+mod fragment;
+mod iter;
 mod node;
 mod node_type;
 mod options;
+mod owned;
+mod push;
+mod reader;
+#[cfg(feature = "schemas")]
+mod relaxng;
+mod sax;
+#[cfg(feature = "schemas")]
+mod schema;
+#[cfg(feature = "schemas")]
+mod schematron;
+mod validation;
+mod visit;
 mod xpath;
+mod xpath_context;
+#[cfg(feature = "xslt")]
+mod xslt;
 
 // 重导出公共 API
+#[cfg(feature = "tokio")]
+pub use async_io::{AsyncOptions, AsyncXmlReader};
+pub use convert::FromXPathResult;
+pub use css::css_to_xpath;
+pub use date::{IsoDate, IsoDateTime};
+pub use diagnostic::{Diagnostic, DiagnosticLevel};
 pub use document::Document;
+pub use document_mut::{DocumentMut, NodeId};
+pub use dtd::{Doctype, Dtd};
 pub use error::{Error, Result};
+#[doc(hidden)]
+pub use extract::__private;
+pub use extract::FromNode;
+pub use fragment::Fragment;
+pub use iter::{
+    Ancestors, Children, Descendants, Edge, Following, FollowingSiblings, Preceding,
+    PrecedingSiblings, Traverse,
+};
 pub use node::SelectedNode;
 pub use node_type::NodeType;
 pub use options::{ParseOptions, XmlParseOptions};
-pub use xpath::XPathResult;
+pub use owned::{OwnedDocument, OwnedNode};
+pub use push::PushParser;
+pub use reader::{ReaderEvent, XmlReader};
+#[cfg(feature = "schemas")]
+pub use relaxng::RelaxNg;
+#[cfg(feature = "derive")]
+pub use rlibxml_derive::FromNode;
+pub use sax::{Attribute, Attributes, QName, SaxHandler, sax_parse_html, sax_parse_xml};
+#[cfg(feature = "schemas")]
+pub use schema::Schema;
+#[cfg(feature = "schemas")]
+pub use schematron::Schematron;
+pub use validation::ValidationError;
+pub use visit::{Visit, Visitor};
+pub use xpath::{XPath, XPathResult, XPathValue};
+pub use xpath_context::XPathContext;
+#[cfg(feature = "xslt")]
+pub use xslt::Stylesheet;
 
 // ========================================
 // 全局函数
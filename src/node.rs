@@ -2,9 +2,19 @@
 //!
 //! 提供对 XML/HTML DOM 节点的安全访问。
 
+use crate::css::css_to_xpath;
 use crate::error::Result;
+use crate::fragment::Fragment;
+use crate::iter::{
+    Ancestors, Children, Descendants, Following, FollowingSiblings, Preceding, PrecedingSiblings,
+    Traverse,
+};
 use crate::node_type::NodeType;
-use crate::xpath::evaluate_xpath_on_node;
+use crate::visit::{self, Visitor};
+use crate::xpath::{
+    XPathValue, evaluate_xpath_on_node, evaluate_xpath_on_node_with, first_match_xpath,
+    with_original_xpath,
+};
 use libxml2_sys::*;
 use std::collections::HashMap;
 use std::ffi::CString;
@@ -147,6 +157,26 @@ impl<'a> SelectedNode<'a> {
         unsafe { node_get_path(self.node_ptr) }
     }
 
+    /// 获取节点的基准 URL，用于解析节点内的相对链接
+    ///
+    /// 由文档的 URL（见 [`Document::url`](crate::Document::url)）和祖先元素上的 `xml:base`
+    /// 属性逐级合成；HTML 文档使用 `<base href>`，没有时为文档的 URL。都没有时返回 `None`。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let xml = r#"<feed xml:base="http://example.com/news/"><item xml:base="2024/"><link/></item></feed>"#;
+    /// let doc = Document::parse_xml(xml).unwrap();
+    /// let link = &doc.select("//link").unwrap()[0];
+    /// assert_eq!(link.base_url().as_deref(), Some("http://example.com/news/2024/"));
+    /// ```
+    pub fn base_url(&self) -> Option<String> {
+        // SAFETY: node_ptr 在节点存活期间始终有效
+        unsafe { node_get_base(self.node_ptr) }
+    }
+
     /// 获取节点的类型
     ///
     /// # Example
@@ -289,19 +319,23 @@ impl<'a> SelectedNode<'a> {
     /// assert_eq!(children.len(), 2);
     /// ```
     pub fn children(&self) -> Vec<SelectedNode<'a>> {
-        let mut result = Vec::new();
-
-        // SAFETY: node_ptr 在节点存活期间始终有效
-        // 使用 libxml2-sys 提供的安全封装函数
-        unsafe {
-            let mut child = node_get_first_child(self.node_ptr);
-            while !child.is_null() {
-                result.push(Self::from_raw(child));
-                child = node_get_next_sibling(child);
-            }
-        }
+        self.children_iter().collect()
+    }
 
-        result
+    /// 逐个返回子节点，不分配 `Vec`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<ul><li>A</li><li class='x'>B</li><li>C</li></ul>").unwrap();
+    /// let ul = &doc.select("//ul").unwrap()[0];
+    /// let x = ul.children_iter().find(|li| li.has_attr("class")).unwrap();
+    /// assert_eq!(x.text(), "B");
+    /// ```
+    pub fn children_iter(&self) -> Children<'a> {
+        Children::new(self)
     }
 
     /// 获取元素子节点（仅元素，不包括文本节点）
@@ -317,16 +351,14 @@ impl<'a> SelectedNode<'a> {
     /// assert_eq!(elements.len(), 2);
     /// ```
     pub fn element_children(&self) -> Vec<SelectedNode<'a>> {
-        self.children()
-            .into_iter()
+        self.children_iter()
             .filter(|n| n.node_type().is_element())
             .collect()
     }
 
     /// 获取文本子节点内容
     pub fn text_children(&self) -> Vec<String> {
-        self.children()
-            .into_iter()
+        self.children_iter()
             .filter(|n| n.node_type().is_text())
             .map(|n| n.text())
             .collect()
@@ -377,31 +409,114 @@ impl<'a> SelectedNode<'a> {
 
     /// 获取所有兄弟节点（不包括自身）
     pub fn siblings(&self) -> Vec<SelectedNode<'a>> {
-        let mut result = Vec::new();
+        let mut result: Vec<_> = self.preceding_siblings().collect();
+        result.reverse();
+        result.extend(self.following_siblings());
+        result
+    }
 
-        // SAFETY: node_ptr 在节点存活期间始终有效
-        // 使用 libxml2-sys 提供的安全封装函数
-        // 向前遍历
-        unsafe {
-            let mut prev = node_get_prev_sibling(self.node_ptr);
-            while !prev.is_null() {
-                result.push(Self::from_raw(prev));
-                prev = node_get_prev_sibling(prev);
-            }
-        }
+    /// 逐个返回后代节点（按文档顺序，不包括自身），不分配 `Vec`
+    ///
+    /// 与 XPath 的 `descendant::node()` 相同，包括文本和注释节点。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<div><p>A <b>B</b></p><p>C</p></div>").unwrap();
+    /// let div = &doc.select("//div").unwrap()[0];
+    /// let tags: Vec<_> = div
+    ///     .descendants()
+    ///     .filter(|n| n.node_type().is_element())
+    ///     .map(|n| n.tag_name())
+    ///     .collect();
+    /// assert_eq!(tags, ["p", "b", "p"]);
+    /// ```
+    pub fn descendants(&self) -> Descendants<'a> {
+        Descendants::new(self)
+    }
 
-        result.reverse();
+    /// 逐个返回祖先元素（由近及远，不包括文档节点），不分配 `Vec`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<table><tr><td><b>x</b></td></tr></table>").unwrap();
+    /// let b = &doc.select("//b").unwrap()[0];
+    /// let table = b.ancestors().find(|n| n.tag_name() == "table").unwrap();
+    /// assert_eq!(table.path(), "/html/body/table");
+    /// ```
+    pub fn ancestors(&self) -> Ancestors<'a> {
+        Ancestors::new(self)
+    }
 
-        // 向后遍历
-        unsafe {
-            let mut next = node_get_next_sibling(self.node_ptr);
-            while !next.is_null() {
-                result.push(Self::from_raw(next));
-                next = node_get_next_sibling(next);
-            }
-        }
+    /// 逐个返回后面的兄弟节点，同 XPath 的 `following-sibling::node()`
+    pub fn following_siblings(&self) -> FollowingSiblings<'a> {
+        FollowingSiblings::new(self)
+    }
 
-        result
+    /// 逐个返回前面的兄弟节点（由近及远），同 XPath 的 `preceding-sibling::node()`
+    pub fn preceding_siblings(&self) -> PrecedingSiblings<'a> {
+        PrecedingSiblings::new(self)
+    }
+
+    /// 逐个返回文档中位于当前节点之后的节点（不包括后代），同 XPath 的 `following::node()`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<h2>Specs</h2><div><table id='t'></table></div>").unwrap();
+    /// let h2 = &doc.select("//h2").unwrap()[0];
+    /// let table = h2.following().find(|n| n.tag_name() == "table").unwrap();
+    /// assert_eq!(table.attr("id").as_deref(), Some("t"));
+    /// ```
+    pub fn following(&self) -> Following<'a> {
+        Following::new(self)
+    }
+
+    /// 逐个返回文档中位于当前节点之前的节点（不包括祖先，由近及远），
+    /// 同 XPath 的 `preceding::node()`
+    pub fn preceding(&self) -> Preceding<'a> {
+        Preceding::new(self)
+    }
+
+    /// 深度优先遍历当前节点及其后代，按文档顺序产生进入（[`Edge::Open`]）与离开（[`Edge::Close`]）事件
+    ///
+    /// 每个节点都有一对事件，可以用 [`Traverse::skip_subtree`] 跳过刚进入的节点的子节点。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, Edge};
+    ///
+    /// let doc = Document::parse("<p>A<b>B</b></p>").unwrap();
+    /// let p = &doc.select("//p").unwrap()[0];
+    /// let mut markup = String::new();
+    /// for edge in p.traverse() {
+    ///     match edge {
+    ///         Edge::Open(n) if n.node_type().is_element() => markup += &format!("<{}>", n.tag_name()),
+    ///         Edge::Close(n) if n.node_type().is_element() => markup += &format!("</{}>", n.tag_name()),
+    ///         Edge::Open(n) => markup += &n.text(),
+    ///         Edge::Close(_) => {}
+    ///     }
+    /// }
+    /// assert_eq!(markup, "<p>A<b>B</b></p>");
+    /// ```
+    pub fn traverse(&self) -> Traverse<'a> {
+        Traverse::new(self)
+    }
+
+    /// 用 [`Visitor`] 访问当前节点及其后代
+    ///
+    /// 进入元素时调用 [`Visitor::visit_element`]，其返回值可以跳过子节点或结束遍历；
+    /// 离开元素时调用 [`Visitor::leave_element`]。
+    pub fn walk<V: Visitor<'a> + ?Sized>(&self, visitor: &mut V) {
+        visit::walk(self, visitor)
     }
 
     /// 检查节点是否有子节点
@@ -420,11 +535,13 @@ impl<'a> SelectedNode<'a> {
 
     /// 获取子节点数量
     pub fn child_count(&self) -> usize {
-        self.children().len()
+        self.children_iter().count()
     }
 
     /// 获取节点的内部 HTML（序列化为字符串）
     ///
+    /// 依次以 HTML 语法序列化所有子节点，不包含节点自身。
+    ///
     /// # Example
     ///
     /// ```
@@ -432,9 +549,7 @@ impl<'a> SelectedNode<'a> {
     ///
     /// let doc = Document::parse("<div><p>Hello</p><span>World</span></div>").unwrap();
     /// let div = &doc.select("//div").unwrap()[0];
-    /// let html = div.inner_html();
-    /// assert!(html.contains("<p>"));
-    /// assert!(html.contains("<span>"));
+    /// assert_eq!(div.inner_html(), "<p>Hello</p><span>World</span>");
     /// ```
     pub fn inner_html(&self) -> String {
         let children = self.children();
@@ -443,36 +558,50 @@ impl<'a> SelectedNode<'a> {
 
     /// 获取节点的外部 HTML（包含节点自身）
     ///
+    /// 通过 libxml2 的 HTML 序列化器输出：保留属性顺序，正确转义文本与属性值，
+    /// 保留注释，空元素（如 `<br>`）不输出结束标签。
+    ///
     /// # Example
     ///
     /// ```
     /// use rlibxml::Document;
     ///
-    /// let doc = Document::parse("<div><p>Hello</p></div>").unwrap();
+    /// let doc = Document::parse("<div><p id='a' class='b'>1 &lt; 2<br></p></div>").unwrap();
     /// let p = &doc.select("//p").unwrap()[0];
-    /// let html = p.outer_html();
-    /// assert!(html.starts_with("<p>"));
+    /// assert_eq!(p.outer_html(), r#"<p id="a" class="b">1 &lt; 2<br></p>"#);
     /// ```
     pub fn outer_html(&self) -> String {
-        // 简化实现：拼接标签和内容
-        let tag = self.tag_name();
-        if tag.is_empty() {
-            return self.text();
-        }
+        // SAFETY: node_ptr 及其所属文档在节点存活期间始终有效
+        // 使用 libxml2-sys 提供的安全封装函数
+        unsafe { node_dump_html(node_get_document(self.node_ptr), self.node_ptr) }
+    }
 
-        let attrs = self.attrs();
-        let attr_str = if attrs.is_empty() {
-            String::new()
-        } else {
-            let pairs: Vec<String> = attrs
-                .iter()
-                .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('"', "&quot;")))
-                .collect();
-            format!(" {}", pairs.join(" "))
-        };
+    /// 获取节点的内部 XML（序列化为字符串）
+    ///
+    /// 依次以 XML 语法序列化所有子节点，不包含节点自身。
+    pub fn inner_xml(&self) -> String {
+        let children = self.children();
+        children.iter().map(|c| c.outer_xml()).collect()
+    }
 
-        let inner = self.inner_html();
-        format!("<{}{}>{}</{}>", tag, attr_str, inner, tag)
+    /// 获取节点的外部 XML（包含节点自身）
+    ///
+    /// 通过 libxml2 的 XML 序列化器输出，保留 CDATA、注释和处理指令，
+    /// 空元素输出为自闭合标签。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse_xml("<root><item><![CDATA[a<b]]><?pi x?></item></root>").unwrap();
+    /// let item = &doc.select("//item").unwrap()[0];
+    /// assert_eq!(item.outer_xml(), "<item><![CDATA[a<b]]><?pi x?></item>");
+    /// ```
+    pub fn outer_xml(&self) -> String {
+        // SAFETY: node_ptr 及其所属文档在节点存活期间始终有效
+        // 使用 libxml2-sys 提供的安全封装函数
+        unsafe { node_dump_xml(node_get_document(self.node_ptr), self.node_ptr, false) }
     }
 
     /// 在当前节点上下文中执行 XPath 查询
@@ -492,6 +621,112 @@ impl<'a> SelectedNode<'a> {
         evaluate_xpath_on_node(self.node_ptr, xpath)
     }
 
+    /// 在当前节点上下文中执行 XPath 查询，只返回按文档顺序的第一个匹配
+    ///
+    /// 同 [`Document::select_first`](crate::Document::select_first)，找到第一个匹配后即停止遍历。
+    pub fn select_first(&self, xpath: &str) -> Result<Option<SelectedNode<'a>>> {
+        let Some(first) = first_match_xpath(xpath) else {
+            return Ok(self.select(xpath)?.into_iter().next());
+        };
+        let nodes = evaluate_xpath_on_node(self.node_ptr, &first)
+            .map_err(|e| with_original_xpath(e, xpath))?;
+        Ok(nodes.into_iter().next())
+    }
+
+    /// 使用命名空间前缀在当前节点上下文中执行 XPath 查询
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let xml = r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+    ///     <s:Body><Result>ok</Result></s:Body>
+    /// </s:Envelope>"#;
+    /// let doc = Document::parse_xml(xml).unwrap();
+    /// let root = doc.root().unwrap();
+    /// let ns = [("soap", "http://schemas.xmlsoap.org/soap/envelope/")];
+    /// let body = root.select_ns("soap:Body", &ns).unwrap();
+    /// assert_eq!(body[0].text().trim(), "ok");
+    /// ```
+    pub fn select_ns(
+        &self,
+        xpath: &str,
+        namespaces: &[(&str, &str)],
+    ) -> Result<Vec<SelectedNode<'a>>> {
+        evaluate_xpath_on_node_with(self.node_ptr, xpath, namespaces, &[])
+    }
+
+    /// 绑定变量后在当前节点上下文中执行 XPath 查询
+    ///
+    /// 表达式中以 `$name` 引用变量。节点集合变量中的节点必须来自同一文档。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse("<ul><li>1</li><li>2</li><li>3</li></ul>").unwrap();
+    /// let ul = &doc.select("//ul").unwrap()[0];
+    /// let items = ul.select_with("li[position() > $n]", &[("n", 1.into())]).unwrap();
+    /// assert_eq!(items.len(), 2);
+    /// ```
+    pub fn select_with(
+        &self,
+        xpath: &str,
+        variables: &[(&str, XPathValue<'a>)],
+    ) -> Result<Vec<SelectedNode<'a>>> {
+        evaluate_xpath_on_node_with(self.node_ptr, xpath, &[], variables)
+    }
+
+    /// 在当前节点的后代中使用 CSS 选择器查询
+    ///
+    /// 只匹配后代元素，不包括当前节点本身。参见 [`Document::css`](crate::Document::css)。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse(r#"<div id="a"><p>1</p></div><div id="b"><p>2</p><p>3</p></div>"#).unwrap();
+    /// let b = &doc.css("div#b").unwrap()[0];
+    /// assert_eq!(b.css("p:last-child").unwrap()[0].text(), "3");
+    /// ```
+    pub fn css(&self, selector: &str) -> Result<Vec<SelectedNode<'a>>> {
+        let xpath = css_to_xpath(selector)?;
+        evaluate_xpath_on_node(self.node_ptr, &xpath)
+    }
+
+    /// 以当前元素为上下文解析一段 HTML / XML
+    ///
+    /// HTML 文档中的节点按 HTML 解析，自动闭合规则按当前元素及其祖先生效；
+    /// XML 文档中的节点按 XML 解析，片段可以使用当前元素作用域内的命名空间前缀，
+    /// 也可以引用文档 DTD 中声明的实体。两种情况都沿用文档解析时的选项（如 `no_blanks`）。
+    /// 返回的 [`Fragment`] 与当前文档相互独立，解析不会修改当前文档。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ParseFailed`](crate::Error::ParseFailed) - XML 片段不是格式良好的
+    /// - [`Error::NullByte`](crate::Error::NullByte) - 片段包含空字节
+    /// - [`Error::InputTooLarge`](crate::Error::InputTooLarge) - 片段超过大小限制
+    /// - [`Error::Custom`](crate::Error::Custom) - 当前节点不是元素
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse_xml(r#"<feed xmlns:g="urn:g"><item/></feed>"#)?;
+    /// let item = &doc.select("//item")?[0];
+    /// let fragment = item.parse_in_context("<g:price>1.50</g:price>")?;
+    /// let price = &fragment.nodes()[0];
+    /// assert_eq!(price.select_ns("self::g:price", &[("g", "urn:g")])?.len(), 1);
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn parse_in_context(&self, markup: &str) -> Result<Fragment> {
+        Fragment::parse_in_node(markup, self.node_ptr)
+    }
+
     /// 获取原始节点指针（用于高级用途）
     ///
     /// # Safety
@@ -1,5 +1,7 @@
 //! HTML/XML 解析选项
 
+use libxml2_sys::*;
+
 /// HTML 解析选项
 ///
 /// 控制解析器的行为，特别适合处理真实世界中的脏 HTML。
@@ -12,9 +14,8 @@
 /// let html = "<div>Hello</div>";
 /// let options = ParseOptions {
 ///     recover: true,
-///     no_error: true,
-///     no_warning: true,
 ///     no_blanks: true,
+///     ..Default::default()
 /// };
 /// let doc = Document::parse_html_with_options(html, options).unwrap();
 /// ```
@@ -27,12 +28,20 @@ pub struct ParseOptions {
 
     /// 抑制错误输出
     ///
-    /// 启用后，解析器的错误消息不会输出到 stderr。
+    /// 解析器的错误消息已经不会输出到 stderr，而是统一记录到
+    /// [`Document::diagnostics`](crate::Document::diagnostics)，此选项不再有任何效果。
+    #[deprecated(
+        note = "错误总是记录到 Document::diagnostics，不会输出到 stderr，此选项不再有效果"
+    )]
     pub no_error: bool,
 
     /// 抑制警告输出
     ///
-    /// 启用后，解析器的警告消息不会输出到 stderr。
+    /// 解析器的警告消息已经不会输出到 stderr，而是统一记录到
+    /// [`Document::diagnostics`](crate::Document::diagnostics)，此选项不再有任何效果。
+    #[deprecated(
+        note = "警告总是记录到 Document::diagnostics，不会输出到 stderr，此选项不再有效果"
+    )]
     pub no_warning: bool,
 
     /// 移除空白节点
@@ -43,6 +52,7 @@ pub struct ParseOptions {
 }
 
 impl Default for ParseOptions {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             recover: true,
@@ -57,6 +67,7 @@ impl ParseOptions {
     /// 创建严格模式选项
     ///
     /// 不进行错误恢复，报告所有错误和警告。
+    #[allow(deprecated)]
     pub fn strict() -> Self {
         Self {
             recover: false,
@@ -76,6 +87,7 @@ impl ParseOptions {
     /// 创建紧凑模式选项
     ///
     /// 移除空白节点，生成更简洁的 DOM 树。
+    #[allow(deprecated)]
     pub fn compact() -> Self {
         Self {
             recover: true,
@@ -84,19 +96,80 @@ impl ParseOptions {
             no_blanks: true,
         }
     }
+
+    /// 转换为 libxml2 的 `htmlParserOption` 位掩码
+    ///
+    /// 已弃用的 `no_error` / `no_warning` 不会传给 libxml2：设置后 libxml2 不再调用错误回调，
+    /// 诊断将无法收集。结构化错误回调本身不向 stderr 输出任何内容。
+    pub(crate) fn to_raw(self) -> i32 {
+        let mut raw_options: htmlParserOption = 0;
+        if self.recover {
+            raw_options |= htmlParserOption_HTML_PARSE_RECOVER;
+        }
+        if self.no_blanks {
+            raw_options |= htmlParserOption_HTML_PARSE_NOBLANKS;
+        }
+        raw_options as i32
+    }
+
+    /// 从文档记录的 `htmlParserOption` 位掩码还原
+    ///
+    /// libxml2 不会在 HTML 文档中记录 `HTML_PARSE_RECOVER`，`recover` 保持默认值。
+    pub(crate) fn from_raw(raw: i32) -> Self {
+        Self {
+            no_blanks: raw & htmlParserOption_HTML_PARSE_NOBLANKS as i32 != 0,
+            ..Self::default()
+        }
+    }
 }
 
 /// XML 解析选项
+///
+/// 默认不加载外部 DTD、不替换实体，不会访问文档以外的文件。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, XmlParseOptions};
+///
+/// let xml = r#"<!DOCTYPE item [
+///     <!ELEMENT item (#PCDATA)>
+///     <!ATTLIST item currency CDATA "EUR">
+/// ]><item>12.50</item>"#;
+/// let options = XmlParseOptions {
+///     dtd_valid: true,
+///     dtd_attr: true,
+///     ..Default::default()
+/// };
+/// let doc = Document::parse_xml_with_options(xml, options)?;
+/// assert!(doc.diagnostics().is_empty());
+/// assert_eq!(doc.root().unwrap().attr("currency").as_deref(), Some("EUR"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
 #[derive(Debug, Clone, Copy)]
 pub struct XmlParseOptions {
     /// 移除空白节点
     pub no_blanks: bool,
 
-    /// 不加载外部 DTD
+    /// 不加载外部 DTD（DOCTYPE 中系统标识符指向的文件）
+    ///
+    /// 启用 `dtd_valid` 或 `dtd_attr` 时外部 DTD 总会被加载。
     pub no_dtd: bool,
 
-    /// 不加载外部实体
+    /// 不替换实体引用
+    ///
+    /// 关闭后实体引用会被替换为实体内容，外部实体会从文件加载。
     pub no_ent: bool,
+
+    /// 按文档的 DTD（内部子集与外部 DTD）验证
+    ///
+    /// 有效性错误不会导致解析失败，而是记录到
+    /// [`Document::diagnostics`](crate::Document::diagnostics) 中，
+    /// 可以用 [`Diagnostic::is_validity_error`](crate::Diagnostic::is_validity_error) 区分。
+    pub dtd_valid: bool,
+
+    /// 为元素补上 DTD 中声明了默认值、但文档中缺失的属性
+    pub dtd_attr: bool,
 }
 
 impl Default for XmlParseOptions {
@@ -105,6 +178,31 @@ impl Default for XmlParseOptions {
             no_blanks: false,
             no_dtd: true,
             no_ent: true,
+            dtd_valid: false,
+            dtd_attr: false,
+        }
+    }
+}
+
+impl XmlParseOptions {
+    /// 转换为 libxml2 的 `xmlParserOption` 位掩码
+    pub(crate) fn to_raw(self) -> i32 {
+        let mut raw_options: xmlParserOption = 0;
+        if self.no_blanks {
+            raw_options |= xmlParserOption_XML_PARSE_NOBLANKS;
+        }
+        if !self.no_dtd {
+            raw_options |= xmlParserOption_XML_PARSE_DTDLOAD;
+        }
+        if !self.no_ent {
+            raw_options |= xmlParserOption_XML_PARSE_NOENT;
+        }
+        if self.dtd_valid {
+            raw_options |= xmlParserOption_XML_PARSE_DTDVALID;
+        }
+        if self.dtd_attr {
+            raw_options |= xmlParserOption_XML_PARSE_DTDATTR;
         }
+        raw_options as i32
     }
 }
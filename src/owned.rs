@@ -0,0 +1,275 @@
+//! 不借用文档的节点句柄
+//!
+//! [`SelectedNode`] 借用 [`Document`]，不能与文档一起存入结构体，也不能脱离文档的作用域。
+//! [`OwnedNode`] 持有 [`OwnedDocument`]（`Arc<Document>`）的引用计数，节点存活期间文档不会被释放，
+//! 可以从函数返回、存入结构体或发送到其他线程。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::fragment::Fragment;
+use crate::node::SelectedNode;
+use crate::node_type::NodeType;
+use libxml2_sys::{doc_owns_node, xmlNodePtr};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 可共享的文档，[`OwnedNode`] 通过它保持文档存活
+///
+/// 用 `Arc::new(doc)` 创建，然后使用 [`Document::select_owned`] 等方法获取 [`OwnedNode`]。
+pub type OwnedDocument = Arc<Document>;
+
+/// 持有文档引用计数的节点句柄
+///
+/// 提供与 [`SelectedNode`] 相同的读取、遍历和查询方法，遍历和查询的结果也是 `OwnedNode`。
+/// 与 [`SelectedNode`] 之间可以低成本地相互转换：[`OwnedNode::as_selected`] 借出
+/// [`SelectedNode`]，[`OwnedNode::new`] 把 [`SelectedNode`] 转换回来。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, OwnedNode};
+/// use std::sync::Arc;
+///
+/// // 解析和查询都在函数内部完成，返回的节点自己保持文档存活
+/// fn links(html: &str) -> rlibxml::Result<Vec<OwnedNode>> {
+///     let doc = Arc::new(Document::parse(html)?);
+///     doc.select_owned("//a[@href]")
+/// }
+///
+/// let links = links(r#"<a href="/a">A</a><a href="/b">B</a>"#)?;
+/// let handle = std::thread::spawn(move || {
+///     links.iter().map(|a| a.attr("href").unwrap()).collect::<Vec<_>>()
+/// });
+/// assert_eq!(handle.join().unwrap(), ["/a", "/b"]);
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct OwnedNode {
+    doc: OwnedDocument,
+    node_ptr: xmlNodePtr,
+}
+
+// SAFETY: 节点属于 doc，doc 在句柄存活期间不会被释放；与 SelectedNode 一样只读访问，
+// Document 本身可以跨线程共享
+unsafe impl Send for OwnedNode {}
+unsafe impl Sync for OwnedNode {}
+
+impl OwnedNode {
+    /// 由 `doc` 中的节点指针创建句柄
+    ///
+    /// # Safety
+    ///
+    /// `node_ptr` 必须是属于 `doc` 的有效节点
+    #[inline]
+    pub(crate) unsafe fn from_raw(doc: &OwnedDocument, node_ptr: xmlNodePtr) -> Self {
+        Self {
+            doc: Arc::clone(doc),
+            node_ptr,
+        }
+    }
+
+    /// 把借用 `doc` 的 [`SelectedNode`] 转换为 `OwnedNode`
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Custom`] - 节点不属于 `doc`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, OwnedNode};
+    /// use std::sync::Arc;
+    ///
+    /// let doc = Arc::new(Document::parse("<ul><li>a</li></ul>")?);
+    /// let li = &doc.select("//li")?[0];
+    /// let owned = OwnedNode::new(&doc, li)?;
+    /// assert_eq!(owned.text(), "a");
+    /// # Ok::<(), rlibxml::Error>(())
+    /// ```
+    pub fn new(doc: &OwnedDocument, node: &SelectedNode<'_>) -> Result<Self> {
+        // SAFETY: doc 存活，node 借用的文档同样存活
+        if !unsafe { doc_owns_node(doc.as_ptr(), node.node_ptr) } {
+            return Err(Error::Custom(
+                "node does not belong to this document".to_string(),
+            ));
+        }
+        // SAFETY: 上面已确认节点属于 doc
+        Ok(unsafe { Self::from_raw(doc, node.node_ptr) })
+    }
+
+    /// 借出同一节点的 [`SelectedNode`]
+    #[inline]
+    pub fn as_selected(&self) -> SelectedNode<'_> {
+        // SAFETY: 节点属于 self.doc，在借用期间有效
+        unsafe { SelectedNode::from_raw(self.node_ptr) }
+    }
+
+    /// 节点所属的文档
+    #[inline]
+    pub fn document(&self) -> &OwnedDocument {
+        &self.doc
+    }
+
+    fn wrap(&self, node: SelectedNode<'_>) -> OwnedNode {
+        // SAFETY: node 由 self 遍历或查询得到，属于同一文档
+        unsafe { Self::from_raw(&self.doc, node.node_ptr) }
+    }
+
+    fn wrap_all(&self, nodes: Vec<SelectedNode<'_>>) -> Vec<OwnedNode> {
+        nodes.into_iter().map(|node| self.wrap(node)).collect()
+    }
+
+    /// 获取节点的文本内容，同 [`SelectedNode::text`]
+    pub fn text(&self) -> String {
+        self.as_selected().text()
+    }
+
+    /// 获取节点的标签名，同 [`SelectedNode::tag_name`]
+    pub fn tag_name(&self) -> String {
+        self.as_selected().tag_name()
+    }
+
+    /// 获取节点的 XPath 路径，同 [`SelectedNode::path`]
+    pub fn path(&self) -> String {
+        self.as_selected().path()
+    }
+
+    /// 获取节点的基准 URL，同 [`SelectedNode::base_url`]
+    pub fn base_url(&self) -> Option<String> {
+        self.as_selected().base_url()
+    }
+
+    /// 获取节点的类型
+    pub fn node_type(&self) -> NodeType {
+        self.as_selected().node_type()
+    }
+
+    /// 获取指定属性的值，同 [`SelectedNode::attr`]
+    pub fn attr(&self, name: &str) -> Option<String> {
+        self.as_selected().attr(name)
+    }
+
+    /// 获取所有属性
+    pub fn attrs(&self) -> HashMap<String, String> {
+        self.as_selected().attrs()
+    }
+
+    /// 检查是否具有指定属性
+    pub fn has_attr(&self, name: &str) -> bool {
+        self.as_selected().has_attr(name)
+    }
+
+    /// 获取第一个子节点
+    pub fn first_child(&self) -> Option<OwnedNode> {
+        self.as_selected().first_child().map(|n| self.wrap(n))
+    }
+
+    /// 获取最后一个子节点
+    pub fn last_child(&self) -> Option<OwnedNode> {
+        self.as_selected().last_child().map(|n| self.wrap(n))
+    }
+
+    /// 获取所有子节点
+    pub fn children(&self) -> Vec<OwnedNode> {
+        self.wrap_all(self.as_selected().children())
+    }
+
+    /// 获取元素子节点（仅元素，不包括文本节点）
+    pub fn element_children(&self) -> Vec<OwnedNode> {
+        self.wrap_all(self.as_selected().element_children())
+    }
+
+    /// 获取文本子节点内容
+    pub fn text_children(&self) -> Vec<String> {
+        self.as_selected().text_children()
+    }
+
+    /// 获取父节点
+    pub fn parent(&self) -> Option<OwnedNode> {
+        self.as_selected().parent().map(|n| self.wrap(n))
+    }
+
+    /// 获取下一个兄弟节点
+    pub fn next_sibling(&self) -> Option<OwnedNode> {
+        self.as_selected().next_sibling().map(|n| self.wrap(n))
+    }
+
+    /// 获取上一个兄弟节点
+    pub fn prev_sibling(&self) -> Option<OwnedNode> {
+        self.as_selected().prev_sibling().map(|n| self.wrap(n))
+    }
+
+    /// 获取所有兄弟节点（不包括自身），同 [`SelectedNode::siblings`]
+    pub fn siblings(&self) -> Vec<OwnedNode> {
+        self.wrap_all(self.as_selected().siblings())
+    }
+
+    /// 检查是否有子节点
+    pub fn has_children(&self) -> bool {
+        self.as_selected().has_children()
+    }
+
+    /// 检查是否有父节点
+    pub fn has_parent(&self) -> bool {
+        self.as_selected().has_parent()
+    }
+
+    /// 获取子节点数量
+    pub fn child_count(&self) -> usize {
+        self.as_selected().child_count()
+    }
+
+    /// 获取节点的内部 HTML，同 [`SelectedNode::inner_html`]
+    pub fn inner_html(&self) -> String {
+        self.as_selected().inner_html()
+    }
+
+    /// 获取节点的外部 HTML，同 [`SelectedNode::outer_html`]
+    pub fn outer_html(&self) -> String {
+        self.as_selected().outer_html()
+    }
+
+    /// 获取节点的内部 XML，同 [`SelectedNode::inner_xml`]
+    pub fn inner_xml(&self) -> String {
+        self.as_selected().inner_xml()
+    }
+
+    /// 获取节点的外部 XML，同 [`SelectedNode::outer_xml`]
+    pub fn outer_xml(&self) -> String {
+        self.as_selected().outer_xml()
+    }
+
+    /// 在当前节点上下文中执行 XPath 查询，同 [`SelectedNode::select`]
+    pub fn select(&self, xpath: &str) -> Result<Vec<OwnedNode>> {
+        Ok(self.wrap_all(self.as_selected().select(xpath)?))
+    }
+
+    /// 使用命名空间前缀在当前节点上下文中执行 XPath 查询，同 [`SelectedNode::select_ns`]
+    pub fn select_ns(&self, xpath: &str, namespaces: &[(&str, &str)]) -> Result<Vec<OwnedNode>> {
+        Ok(self.wrap_all(self.as_selected().select_ns(xpath, namespaces)?))
+    }
+
+    /// 在当前节点的后代中使用 CSS 选择器查询，同 [`SelectedNode::css`]
+    pub fn css(&self, selector: &str) -> Result<Vec<OwnedNode>> {
+        Ok(self.wrap_all(self.as_selected().css(selector)?))
+    }
+
+    /// 以当前元素为上下文解析一段 HTML / XML，同 [`SelectedNode::parse_in_context`]
+    pub fn parse_in_context(&self, markup: &str) -> Result<Fragment> {
+        self.as_selected().parse_in_context(markup)
+    }
+}
+
+impl std::fmt::Debug for OwnedNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedNode")
+            .field("node_ptr", &self.node_ptr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for OwnedNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.as_selected(), f)
+    }
+}
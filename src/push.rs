@@ -0,0 +1,189 @@
+//! 推送式解析
+//!
+//! [`PushParser`] 基于 libxml2 的推送解析器（`htmlCreatePushParserCtxt` / `xmlCreatePushParserCtxt`），
+//! 数据到达一块就解析一块，适合边下载边解析的 HTTP 响应体。
+
+use crate::diagnostic::Diagnostic;
+use crate::document::{Document, MAX_INPUT_SIZE};
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use crate::options::{ParseOptions, XmlParseOptions};
+use encoding_rs::{Encoding, UTF_8};
+use libxml2_sys::{ParserContextGuard, doc_free, doc_get_encoding, doc_get_root_element};
+
+/// 推送式解析器
+///
+/// 用 [`PushParser::feed`] 依次提供数据块，全部提供完（或者不再需要后续内容）时调用
+/// [`PushParser::finish`] 得到 [`Document`]。数据块可以在任意位置切分，包括多字节字符和标签的中间。
+///
+/// HTML 数据必须是 UTF-8（与 [`Document::parse_html_with_options`] 相同，忽略 `<meta charset>`）；
+/// XML 的编码由 BOM 和 XML 声明决定，支持 UTF-8、UTF-16 和 ISO-8859-1。
+/// 其他编码（如 GBK）请收集完整内容后使用 [`Document::from_bytes`]。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{ParseOptions, PushParser};
+///
+/// let chunks = ["<html><head><tit", "le>Shop</title></he", "ad><body>", "<p>never needed</p>"];
+///
+/// let mut parser = PushParser::html(ParseOptions::default())?;
+/// for chunk in chunks {
+///     parser.feed(chunk.as_bytes())?;
+///     // `<body>` 已经开始，`<head>` 完整了，不再下载剩余内容
+///     let root = parser.root().unwrap();
+///     if !root.select("body")?.is_empty() {
+///         break;
+///     }
+/// }
+/// let doc = parser.finish()?;
+/// assert_eq!(doc.extract_string("string(//title)")?, "Shop");
+/// assert!(doc.select("//p")?.is_empty());
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct PushParser {
+    guard: ParserContextGuard,
+    html: bool,
+    // XML 已遇到致命错误，之后的数据都会被忽略
+    failed: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// SAFETY: 解析器上下文及其正在构建的文档只被这个值独占使用，
+// 可以整体转移到其他线程；所有访问都需要借用 self，不会并发。
+unsafe impl Send for PushParser {}
+
+impl PushParser {
+    /// 创建 HTML 推送解析器
+    ///
+    /// 与 [`Document::parse_html_with_options`] 使用相同的容错规则，HTML 解析不会因错误中止。
+    ///
+    /// # Errors
+    ///
+    /// 无法创建解析器上下文（内存不足）时返回错误。
+    pub fn html(options: ParseOptions) -> Result<Self> {
+        let guard = ParserContextGuard::new_html_push(options.to_raw())
+            .ok_or_else(|| Error::Custom("failed to create HTML push parser".to_string()))?;
+        Ok(Self::from_guard(guard, true))
+    }
+
+    /// 创建 XML 推送解析器
+    ///
+    /// 选项的含义与 [`Document::parse_xml_with_options`] 相同。
+    ///
+    /// # Errors
+    ///
+    /// 无法创建解析器上下文（内存不足）时返回错误。
+    pub fn xml(options: XmlParseOptions) -> Result<Self> {
+        let guard = ParserContextGuard::new_xml_push(options.to_raw())
+            .ok_or_else(|| Error::Custom("failed to create XML push parser".to_string()))?;
+        Ok(Self::from_guard(guard, false))
+    }
+
+    fn from_guard(guard: ParserContextGuard, html: bool) -> Self {
+        Self {
+            guard,
+            html,
+            failed: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// 提供下一块数据
+    ///
+    /// 解析器会缓存不完整的标签和字符，等待后续数据。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ParseFailed`] - XML 不是格式良好的（之后每次调用都返回同样的错误，
+    ///   [`PushParser::finish`] 也会失败）；HTML 不会返回此错误
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<()> {
+        for part in chunk.chunks(MAX_INPUT_SIZE) {
+            self.parse_chunk(part, false);
+            if self.failed {
+                return Err(Error::parse_failed_with_diagnostics(
+                    self.diagnostics.clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 目前已解析部分的根元素，还没有解析出任何元素时返回 `None`
+    ///
+    /// 用于在数据到齐之前检查已经到达的内容。树中最后一个元素可能尚未结束，
+    /// 其子节点会随后续数据继续增加。
+    pub fn root(&self) -> Option<SelectedNode<'_>> {
+        let doc = self.guard.document();
+        if doc.is_null() {
+            return None;
+        }
+        // SAFETY: doc 由上下文持有，节点在下一次 feed（需要可变借用）之前保持不变
+        unsafe {
+            let root = doc_get_root_element(doc);
+            (!root.is_null()).then(|| SelectedNode::from_raw(root))
+        }
+    }
+
+    /// 截至目前收集到的解析诊断
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// 结束输入并返回解析得到的文档
+    ///
+    /// 可以在数据没有全部提供时提前调用：HTML 会像一次性解析截断的文档那样补全未闭合的元素；
+    /// 截断的 XML 不是格式良好的，会返回错误。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ParseFailed`] - XML 不是格式良好的，或者没有产生任何文档
+    pub fn finish(mut self) -> Result<Document> {
+        self.parse_chunk(&[], true);
+        let (doc, well_formed) = self.guard.take_document();
+        if !self.html && !well_formed {
+            // SAFETY: 文档已从上下文取走，由这里负责释放
+            unsafe { doc_free(doc) };
+            return Err(Error::parse_failed_with_diagnostics(self.diagnostics));
+        }
+        let encoding = if doc.is_null() {
+            UTF_8
+        } else {
+            // SAFETY: doc 非空且有效
+            unsafe { doc_get_encoding(doc) }
+                .and_then(|label| Encoding::for_label(label.as_bytes()))
+                .unwrap_or(UTF_8)
+        };
+        Document::from_parsed(doc, encoding, self.diagnostics)
+    }
+
+    fn parse_chunk(&mut self, chunk: &[u8], terminate: bool) {
+        if self.failed {
+            return;
+        }
+        // chunk 的长度不超过 MAX_INPUT_SIZE
+        let size = chunk.len() as i32;
+        // SAFETY: 上下文由对应的构造函数创建，chunk 指向 size 字节的有效内存
+        let ret = unsafe {
+            if self.html {
+                self.guard
+                    .parse_html_chunk(chunk.as_ptr().cast(), size, terminate)
+            } else {
+                self.guard
+                    .parse_xml_chunk(chunk.as_ptr().cast(), size, terminate)
+            }
+        };
+        let errors = self.guard.take_errors();
+        self.diagnostics
+            .extend(errors.into_iter().map(Diagnostic::from_raw));
+        self.failed = !self.html && ret != 0;
+    }
+}
+
+impl std::fmt::Debug for PushParser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PushParser")
+            .field("html", &self.html)
+            .finish_non_exhaustive()
+    }
+}
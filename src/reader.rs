@@ -0,0 +1,266 @@
+//! 流式 XML 读取
+//!
+//! [`XmlReader`] 基于 libxml2 的 `xmlTextReader`，从任意 [`std::io::Read`] 按需读取数据，
+//! 逐个产生 [`ReaderEvent`]，不构建整棵树，也不受 [`Document::parse_xml`](crate::Document::parse_xml)
+//! 约 2 GB 的输入大小限制。需要对某条记录做复杂查询时，可以把当前元素
+//! [`expand`](XmlReader::expand) 为临时子树，在子树上使用 XPath。
+
+use crate::diagnostic::Diagnostic;
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use crate::options::XmlParseOptions;
+use libxml2_sys::{
+    TextReaderGuard, xmlReaderTypes_XML_READER_TYPE_CDATA, xmlReaderTypes_XML_READER_TYPE_COMMENT,
+    xmlReaderTypes_XML_READER_TYPE_ELEMENT, xmlReaderTypes_XML_READER_TYPE_END_ELEMENT,
+    xmlReaderTypes_XML_READER_TYPE_PROCESSING_INSTRUCTION,
+    xmlReaderTypes_XML_READER_TYPE_SIGNIFICANT_WHITESPACE, xmlReaderTypes_XML_READER_TYPE_TEXT,
+    xmlReaderTypes_XML_READER_TYPE_WHITESPACE,
+};
+use std::io::Read;
+
+/// 流式读取产生的事件
+///
+/// `depth` 为节点的嵌套深度，根元素为 0，根元素的子节点为 1，依此类推。
+/// 元素名和属性名均为限定名（如 `g:price`）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReaderEvent {
+    /// 开始标签
+    StartElement {
+        /// 元素名
+        name: String,
+        /// 属性（按文档顺序，不含 `xmlns` 命名空间声明）
+        attributes: Vec<(String, String)>,
+        /// 深度
+        depth: usize,
+        /// 是否为空元素（`<a/>`），空元素之后没有对应的 [`ReaderEvent::EndElement`]
+        is_empty: bool,
+    },
+    /// 结束标签
+    EndElement {
+        /// 元素名
+        name: String,
+        /// 深度
+        depth: usize,
+    },
+    /// 文本（包括 CDATA 段和空白）
+    Text {
+        /// 文本内容
+        text: String,
+        /// 深度
+        depth: usize,
+    },
+    /// 注释
+    Comment {
+        /// 注释内容
+        text: String,
+        /// 深度
+        depth: usize,
+    },
+    /// 处理指令
+    ProcessingInstruction {
+        /// 目标
+        target: String,
+        /// 数据
+        data: String,
+        /// 深度
+        depth: usize,
+    },
+}
+
+/// 流式 XML 读取器
+///
+/// 读取器只在内存中保留当前节点附近的树结构，内存占用与文档大小无关。
+/// 输入的编码由 BOM 和 XML 声明决定，支持 UTF-8、UTF-16 和 ISO-8859-1。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{ReaderEvent, XmlReader};
+///
+/// let catalog = r#"<catalog>
+///     <product id="p1"><name>Pen</name><price>1.50</price></product>
+///     <product id="p2"><name>Ink</name><price>4.00</price></product>
+/// </catalog>"#;
+///
+/// let mut reader = XmlReader::new(catalog.as_bytes())?;
+/// let mut total = 0.0;
+/// while let Some(event) = reader.next_event()? {
+///     if let ReaderEvent::StartElement { name, .. } = &event
+///         && name == "product"
+///     {
+///         // 读入整条记录并在其上查询，下一次读取会跳过这条记录
+///         let product = reader.expand()?;
+///         total += product.select("price")?[0].text().parse::<f64>().unwrap();
+///     }
+/// }
+/// assert_eq!(total, 5.5);
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct XmlReader<R> {
+    guard: TextReaderGuard<R>,
+    // 当前元素已被展开或要求跳过，下一次读取越过其子树
+    skip_subtree: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<R: Read> XmlReader<R> {
+    /// 使用默认选项创建读取器
+    ///
+    /// 创建时不会读取数据，第一次调用 [`XmlReader::next_event`] 时才开始读取。
+    ///
+    /// # Errors
+    ///
+    /// 无法创建读取器（内存不足）时返回错误。
+    pub fn new(source: R) -> Result<Self> {
+        Self::with_options(source, XmlParseOptions::default())
+    }
+
+    /// 使用自定义选项创建读取器
+    ///
+    /// `dtd_valid` 时有效性错误记录在 [`XmlReader::diagnostics`] 中，不会中断读取。
+    pub fn with_options(source: R, options: XmlParseOptions) -> Result<Self> {
+        let guard = TextReaderGuard::new(source, options.to_raw())
+            .ok_or_else(|| Error::Custom("failed to create XML reader".to_string()))?;
+        Ok(Self {
+            guard,
+            skip_subtree: false,
+            diagnostics: Vec::new(),
+        })
+    }
+
+    /// 读取下一个事件，到达文档末尾时返回 `None`
+    ///
+    /// DOCTYPE 等不属于上述事件的节点会被跳过。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ParseFailed`] - 文档不是格式良好的，附带截至目前的全部诊断
+    /// - [`Error::Io`] - 数据源返回错误
+    pub fn next_event(&mut self) -> Result<Option<ReaderEvent>> {
+        loop {
+            let ret = if std::mem::take(&mut self.skip_subtree) {
+                self.guard.skip_subtree()
+            } else {
+                self.guard.read()
+            };
+            self.collect_diagnostics();
+            if let Some(e) = self.guard.take_io_error() {
+                return Err(e.into());
+            }
+            match ret {
+                1 => {}
+                0 => return Ok(None),
+                _ => {
+                    return Err(Error::parse_failed_with_diagnostics(
+                        self.diagnostics.clone(),
+                    ));
+                }
+            }
+            if let Some(event) = self.current_event() {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    /// 把当前元素连同全部后代读入内存，返回可以查询的临时子树
+    ///
+    /// 子树借用读取器，下一次读取前必须释放；之后的 [`XmlReader::next_event`]
+    /// 会跳过这个元素（不产生其后代和结束标签的事件）。
+    /// 子树仍然连在读取器的内部文档上，XPath 请使用相对路径（如 `price`、`.//name`），
+    /// `//` 开头的绝对路径会搜索到读取器尚未释放的其他节点。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Custom`] - 当前事件不是 [`ReaderEvent::StartElement`]
+    /// - [`Error::ParseFailed`] / [`Error::Io`] - 读入子树时出错
+    pub fn expand(&mut self) -> Result<SelectedNode<'_>> {
+        if !self.on_start_element() {
+            return Err(Error::Custom(
+                "expand() requires the reader to be positioned on a start element".to_string(),
+            ));
+        }
+        let node = self.guard.expand();
+        self.collect_diagnostics();
+        if let Some(e) = self.guard.take_io_error() {
+            return Err(e.into());
+        }
+        if node.is_null() {
+            return Err(Error::parse_failed_with_diagnostics(
+                self.diagnostics.clone(),
+            ));
+        }
+        self.skip_subtree = true;
+        // SAFETY: 节点在读取器下一次前进前有效，返回值借用 self，期间读取器无法前进
+        Ok(unsafe { SelectedNode::from_raw(node) })
+    }
+
+    /// 跳过当前元素的后代，下一个事件为该元素之后的节点
+    ///
+    /// 用于略过不关心的记录；当前事件不是开始标签时没有效果。
+    pub fn skip(&mut self) {
+        self.skip_subtree = self.on_start_element();
+    }
+
+    /// 截至目前收集到的解析诊断（警告、可恢复的错误和有效性错误）
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    fn on_start_element(&self) -> bool {
+        self.guard.node_type() as u32 == xmlReaderTypes_XML_READER_TYPE_ELEMENT
+    }
+
+    fn collect_diagnostics(&mut self) {
+        let errors = self.guard.take_errors();
+        self.diagnostics
+            .extend(errors.into_iter().map(Diagnostic::from_raw));
+    }
+
+    /// 把读取器当前所在的节点转换为事件
+    fn current_event(&mut self) -> Option<ReaderEvent> {
+        let node_type = self.guard.node_type() as u32;
+        let depth = self.guard.depth().max(0) as usize;
+        let event = if node_type == xmlReaderTypes_XML_READER_TYPE_ELEMENT {
+            ReaderEvent::StartElement {
+                name: self.guard.name(),
+                attributes: self.guard.attributes(),
+                depth,
+                is_empty: self.guard.is_empty_element(),
+            }
+        } else if node_type == xmlReaderTypes_XML_READER_TYPE_END_ELEMENT {
+            ReaderEvent::EndElement {
+                name: self.guard.name(),
+                depth,
+            }
+        } else if node_type == xmlReaderTypes_XML_READER_TYPE_TEXT
+            || node_type == xmlReaderTypes_XML_READER_TYPE_CDATA
+            || node_type == xmlReaderTypes_XML_READER_TYPE_WHITESPACE
+            || node_type == xmlReaderTypes_XML_READER_TYPE_SIGNIFICANT_WHITESPACE
+        {
+            ReaderEvent::Text {
+                text: self.guard.value().unwrap_or_default(),
+                depth,
+            }
+        } else if node_type == xmlReaderTypes_XML_READER_TYPE_COMMENT {
+            ReaderEvent::Comment {
+                text: self.guard.value().unwrap_or_default(),
+                depth,
+            }
+        } else if node_type == xmlReaderTypes_XML_READER_TYPE_PROCESSING_INSTRUCTION {
+            ReaderEvent::ProcessingInstruction {
+                target: self.guard.name(),
+                data: self.guard.value().unwrap_or_default(),
+                depth,
+            }
+        } else {
+            return None;
+        };
+        Some(event)
+    }
+}
+
+impl<R> std::fmt::Debug for XmlReader<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XmlReader").finish_non_exhaustive()
+    }
+}
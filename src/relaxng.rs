@@ -0,0 +1,109 @@
+//! RELAX NG 验证
+//!
+//! 需要启用 `schemas` feature。只接受 XML 语法的 RELAX NG，
+//! 紧凑语法（`.rnc`）需要先转换（如用 trang）。编译后的 [`RelaxNg`]
+//! 可以放在 `Arc` 中由多个线程同时用于验证。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::validation::{ValidationError, invalid_schema, path_to_url, source_len};
+use libxml2_sys::{RawParseError, RelaxNgGuard};
+use std::path::Path;
+use std::str::FromStr;
+
+/// 编译后的 RELAX NG 语法
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, RelaxNg};
+///
+/// let rng = RelaxNg::from_str(r#"
+///     <element name="price" xmlns="http://relaxng.org/ns/structure/1.0"
+///              datatypeLibrary="http://www.w3.org/2001/XMLSchema-datatypes">
+///         <data type="decimal"/>
+///     </element>"#)?;
+///
+/// let mut doc = Document::parse_xml("<price>12.50</price>")?;
+/// assert!(rng.validate(&mut doc)?.is_empty());
+///
+/// let mut doc = Document::parse_xml("<price>n/a</price>")?;
+/// let errors = rng.validate(&mut doc)?;
+/// assert_eq!(errors[0].path.as_deref(), Some("/price"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct RelaxNg {
+    guard: RelaxNgGuard,
+}
+
+impl RelaxNg {
+    /// 从 RELAX NG 文本编译语法
+    ///
+    /// 文本中相对路径的 `include`/`externalRef` 以当前工作目录为基准，
+    /// 需要引用其他文件时请使用 [`RelaxNg::from_file`]。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - 语法无法编译
+    /// - [`Error::InputTooLarge`] - 输入数据超过大小限制
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(rng: &str) -> Result<Self> {
+        let size = source_len(rng)?;
+        // SAFETY: rng 指向 size 字节的有效内存
+        let (guard, errors) = unsafe { RelaxNgGuard::parse_memory(rng.as_ptr().cast(), size) };
+        Self::from_parts(guard, errors)
+    }
+
+    /// 从文件编译语法
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - 文件无法读取或语法无法编译
+    /// - [`Error::NullByte`] - 路径包含空字节
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let url = path_to_url(path.as_ref())?;
+        // SAFETY: url 是有效的 C 字符串
+        let (guard, errors) = unsafe { RelaxNgGuard::parse_file(url.as_ptr()) };
+        Self::from_parts(guard, errors)
+    }
+
+    fn from_parts(guard: Option<RelaxNgGuard>, errors: Vec<RawParseError>) -> Result<Self> {
+        guard
+            .map(|guard| Self { guard })
+            .ok_or_else(|| invalid_schema(errors))
+    }
+
+    /// 验证文档，返回全部验证错误，文档有效时为空
+    ///
+    /// 与 XML Schema 一样，验证可能把 ID 类型的属性登记到文档中，因此需要可变借用。
+    ///
+    /// # Errors
+    ///
+    /// 验证器内部出错（如内存不足）时返回 [`Error::ValidationFailed`]；文档无效不属于错误。
+    pub fn validate(&self, doc: &mut Document) -> Result<Vec<ValidationError>> {
+        // SAFETY: 文档被独占借用，验证期间不会有其他访问
+        let (ret, errors) = unsafe { self.guard.validate_doc(doc.as_ptr()) };
+        let errors = errors.into_iter().map(ValidationError::from_raw).collect();
+        if ret < 0 {
+            return Err(Error::ValidationFailed {
+                validator: "RELAX NG".to_string(),
+                errors,
+            });
+        }
+        Ok(errors)
+    }
+}
+
+impl FromStr for RelaxNg {
+    type Err = Error;
+
+    fn from_str(rng: &str) -> Result<Self> {
+        RelaxNg::from_str(rng)
+    }
+}
+
+impl std::fmt::Debug for RelaxNg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelaxNg").finish_non_exhaustive()
+    }
+}
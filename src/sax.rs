@@ -0,0 +1,313 @@
+//! SAX 事件解析
+//!
+//! 实现 [`SaxHandler`] 后用 [`sax_parse_xml`] 或 [`sax_parse_html`] 解析，
+//! libxml2 边解析边回调，不构建任何文档树。回调收到的名称、属性和文本直接借用解析器的缓冲区，
+//! 只在回调期间有效，需要保留时请自行复制。
+
+use crate::diagnostic::Diagnostic;
+use crate::document::MAX_INPUT_SIZE;
+use crate::error::{Error, Result};
+use crate::options::{ParseOptions, XmlParseOptions};
+use libxml2_sys::{
+    RawSaxAttributes, RawSaxEvent, RawSaxName, RawSaxOutcome, sax_parse_html_memory,
+    sax_parse_xml_memory, xmlParserOption_XML_PARSE_IGNORE_ENC, xmlParserOption_XML_PARSE_NO_XXE,
+    xmlParserOption_XML_PARSE_NOENT,
+};
+use std::ops::ControlFlow;
+
+/// 把解析器提供的字节转换为字符串
+///
+/// libxml2 传给回调的字符串总是 UTF-8，转换失败只可能是解析器内部错误。
+fn utf8(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).unwrap_or("\u{FFFD}")
+}
+
+/// 元素名或属性名
+///
+/// HTML 没有命名空间，名称全部在 `local_name` 中（已转换为小写）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QName<'a> {
+    /// 本地名
+    pub local_name: &'a str,
+    /// 命名空间前缀
+    pub prefix: Option<&'a str>,
+    /// 命名空间 URI
+    pub namespace: Option<&'a str>,
+}
+
+impl<'a> QName<'a> {
+    fn from_raw(raw: RawSaxName<'a>) -> Self {
+        Self {
+            local_name: utf8(raw.local_name),
+            prefix: raw.prefix.map(utf8),
+            namespace: raw.uri.map(utf8),
+        }
+    }
+
+    /// 是否与限定名（`prefix:local` 或不带前缀的 `local`）相同
+    pub fn matches(&self, qualified_name: &str) -> bool {
+        match (qualified_name.split_once(':'), self.prefix) {
+            (Some((prefix, local)), Some(own)) => prefix == own && local == self.local_name,
+            (None, None) => qualified_name == self.local_name,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for QName<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.prefix {
+            Some(prefix) => write!(f, "{}:{}", prefix, self.local_name),
+            None => f.write_str(self.local_name),
+        }
+    }
+}
+
+/// 一个属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    /// 属性名
+    pub name: QName<'a>,
+    /// 属性值（实体和字符引用已替换；HTML 布尔属性为空字符串）
+    pub value: &'a str,
+}
+
+/// 开始标签上的属性列表
+///
+/// XML 中包括 DTD 声明的默认属性，不含 `xmlns` 命名空间声明。
+#[derive(Debug, Clone, Copy)]
+pub struct Attributes<'a> {
+    raw: RawSaxAttributes<'a>,
+}
+
+impl<'a> Attributes<'a> {
+    /// 属性个数
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// 是否没有属性
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// 按限定名（如 `id`、`xlink:href`）查找属性值
+    pub fn get(&self, qualified_name: &str) -> Option<&'a str> {
+        self.iter()
+            .find(|attr| attr.name.matches(qualified_name))
+            .map(|attr| attr.value)
+    }
+
+    /// 按文档顺序遍历属性
+    pub fn iter(&self) -> impl Iterator<Item = Attribute<'a>> + use<'a> {
+        let raw = self.raw;
+        (0..raw.len()).filter_map(move |index| {
+            raw.get(index).map(|(name, value)| Attribute {
+                name: QName::from_raw(name),
+                value: utf8(value),
+            })
+        })
+    }
+}
+
+/// SAX 事件处理器
+///
+/// 所有方法都有什么也不做的默认实现，只需实现关心的事件。
+/// 返回 [`ControlFlow::Break`] 会立即停止解析。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Attributes, QName, SaxHandler, XmlParseOptions, sax_parse_xml};
+/// use std::ops::ControlFlow;
+///
+/// /// 统计价格总和，遇到第一个缺少 SKU 的商品时停止
+/// #[derive(Default)]
+/// struct Prices {
+///     in_price: bool,
+///     total: f64,
+/// }
+///
+/// impl SaxHandler for Prices {
+///     fn start_element(&mut self, name: QName<'_>, attributes: Attributes<'_>) -> ControlFlow<()> {
+///         if name.local_name == "product" && attributes.get("sku").is_none() {
+///             return ControlFlow::Break(());
+///         }
+///         self.in_price = name.local_name == "price";
+///         ControlFlow::Continue(())
+///     }
+///
+///     fn characters(&mut self, text: &str) -> ControlFlow<()> {
+///         if self.in_price {
+///             self.total += text.trim().parse::<f64>().unwrap_or(0.0);
+///         }
+///         ControlFlow::Continue(())
+///     }
+///
+///     fn end_element(&mut self, _name: QName<'_>) -> ControlFlow<()> {
+///         self.in_price = false;
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// let xml = r#"<feed>
+///     <product sku="a"><price>1.50</price></product>
+///     <product sku="b"><price>4.00</price></product>
+///     <product><price>99</price></product>
+/// </feed>"#;
+///
+/// let mut prices = Prices::default();
+/// let flow = sax_parse_xml(xml, XmlParseOptions::default(), &mut prices)?;
+/// assert!(flow.is_break());
+/// assert_eq!(prices.total, 5.5);
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub trait SaxHandler {
+    /// 开始标签
+    fn start_element(&mut self, name: QName<'_>, attributes: Attributes<'_>) -> ControlFlow<()> {
+        let _ = (name, attributes);
+        ControlFlow::Continue(())
+    }
+
+    /// 结束标签（空元素 `<a/>` 也会产生）
+    fn end_element(&mut self, name: QName<'_>) -> ControlFlow<()> {
+        let _ = name;
+        ControlFlow::Continue(())
+    }
+
+    /// 文本（包括 CDATA 段）
+    ///
+    /// 同一段连续文本可能分多次回调，例如跨越解析器缓冲区或中间有实体引用时。
+    fn characters(&mut self, text: &str) -> ControlFlow<()> {
+        let _ = text;
+        ControlFlow::Continue(())
+    }
+
+    /// 注释
+    fn comment(&mut self, text: &str) -> ControlFlow<()> {
+        let _ = text;
+        ControlFlow::Continue(())
+    }
+
+    /// 处理指令
+    fn processing_instruction(&mut self, target: &str, data: &str) -> ControlFlow<()> {
+        let _ = (target, data);
+        ControlFlow::Continue(())
+    }
+
+    /// 解析错误或警告
+    ///
+    /// XML 的致命错误之后解析即结束，[`sax_parse_xml`] 返回 [`Error::ParseFailed`]；
+    /// HTML 解析总会尝试恢复。
+    fn error(&mut self, diagnostic: &Diagnostic) -> ControlFlow<()> {
+        let _ = diagnostic;
+        ControlFlow::Continue(())
+    }
+}
+
+/// 以 SAX 方式解析 XML
+///
+/// 内部实体总会被展开后作为文本报告（SAX 中没有实体节点）。`options.no_ent` 为 `true`（默认）时
+/// 不加载任何外部内容（外部实体和外部 DTD），为 `false` 时外部实体也会从文件加载。
+/// `options.no_blanks` 为 `true` 时不报告只含空白的文本（SAX 没有树结构，无法像 DOM 解析那样
+/// 区分混合内容，元素之间的空白一律丢弃）。
+///
+/// 返回 [`ControlFlow::Break`] 表示被处理器中止，[`ControlFlow::Continue`] 表示解析完整个文档。
+///
+/// # Errors
+///
+/// - [`Error::ParseFailed`] - 文档不是格式良好的（错误同时已通过 [`SaxHandler::error`] 报告）
+/// - [`Error::InputTooLarge`] - 输入数据超过大小限制
+pub fn sax_parse_xml<H: SaxHandler + ?Sized>(
+    xml: &str,
+    options: XmlParseOptions,
+    handler: &mut H,
+) -> Result<ControlFlow<()>> {
+    // 字符串总是 UTF-8，忽略 XML 声明中的编码
+    let mut raw_options = options.to_raw()
+        | xmlParserOption_XML_PARSE_NOENT as i32
+        | xmlParserOption_XML_PARSE_IGNORE_ENC as i32;
+    if options.no_ent {
+        raw_options |= xmlParserOption_XML_PARSE_NO_XXE as i32;
+    }
+    let size = input_len(xml)?;
+    let mut sink = |event: RawSaxEvent<'_>| match event {
+        RawSaxEvent::Characters(text)
+            if options.no_blanks && text.iter().all(u8::is_ascii_whitespace) =>
+        {
+            true
+        }
+        event => dispatch(handler, event),
+    };
+    // SAFETY: xml 指向 size 字节的有效内存
+    let outcome =
+        unsafe { sax_parse_xml_memory(xml.as_ptr().cast(), size, raw_options, &mut sink) };
+    finish(outcome)
+}
+
+/// 以 SAX 方式解析 HTML
+///
+/// 与 [`crate::Document::parse`] 使用相同的容错规则，解析器补全的元素（如 `html`、`body`）
+/// 同样会产生事件。
+///
+/// # Errors
+///
+/// - [`Error::ParseFailed`] - 解析器无法恢复
+/// - [`Error::InputTooLarge`] - 输入数据超过大小限制
+pub fn sax_parse_html<H: SaxHandler + ?Sized>(
+    html: &str,
+    options: ParseOptions,
+    handler: &mut H,
+) -> Result<ControlFlow<()>> {
+    let size = input_len(html)?;
+    let mut sink = |event: RawSaxEvent<'_>| dispatch(handler, event);
+    // SAFETY: html 指向 size 字节的有效内存
+    let outcome =
+        unsafe { sax_parse_html_memory(html.as_ptr().cast(), size, options.to_raw(), &mut sink) };
+    finish(outcome)
+}
+
+fn input_len(input: &str) -> Result<i32> {
+    let size = input.len();
+    if size > MAX_INPUT_SIZE {
+        return Err(Error::InputTooLarge {
+            size,
+            max: MAX_INPUT_SIZE,
+        });
+    }
+    Ok(size as i32)
+}
+
+/// 把原始事件转发给处理器，返回是否继续解析
+fn dispatch<H: SaxHandler + ?Sized>(handler: &mut H, event: RawSaxEvent<'_>) -> bool {
+    let flow = match event {
+        RawSaxEvent::StartElement { name, attributes } => {
+            handler.start_element(QName::from_raw(name), Attributes { raw: attributes })
+        }
+        RawSaxEvent::EndElement { name } => handler.end_element(QName::from_raw(name)),
+        RawSaxEvent::Characters(text) => handler.characters(utf8(text)),
+        RawSaxEvent::Comment(text) => handler.comment(utf8(text)),
+        RawSaxEvent::ProcessingInstruction { target, data } => {
+            handler.processing_instruction(utf8(target), utf8(data))
+        }
+        RawSaxEvent::Error(raw) => handler.error(&Diagnostic::from_raw(raw.clone())),
+    };
+    flow.is_continue()
+}
+
+fn finish(outcome: Option<RawSaxOutcome>) -> Result<ControlFlow<()>> {
+    let outcome =
+        outcome.ok_or_else(|| Error::Custom("failed to create SAX parser context".to_string()))?;
+    if outcome.stopped {
+        return Ok(ControlFlow::Break(()));
+    }
+    if !outcome.parsed {
+        let diagnostics = outcome
+            .errors
+            .into_iter()
+            .map(Diagnostic::from_raw)
+            .collect();
+        return Err(Error::parse_failed_with_diagnostics(diagnostics));
+    }
+    Ok(ControlFlow::Continue(()))
+}
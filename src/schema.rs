@@ -0,0 +1,106 @@
+//! XML Schema (XSD) 验证
+//!
+//! 需要启用 `schemas` feature。编译后的 [`Schema`] 不绑定任何文档，
+//! 可以放在 `Arc` 中由多个线程同时用于验证。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::validation::{ValidationError, invalid_schema, path_to_url, source_len};
+use libxml2_sys::{RawParseError, SchemaGuard};
+use std::path::Path;
+use std::str::FromStr;
+
+/// 编译后的 XML Schema
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, Schema};
+///
+/// let schema = Schema::from_str(r#"
+///     <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+///         <xs:element name="price" type="xs:decimal"/>
+///     </xs:schema>"#)?;
+///
+/// let mut doc = Document::parse_xml("<price>12.50</price>")?;
+/// assert!(doc.validate(&schema)?.is_empty());
+///
+/// let mut doc = Document::parse_xml("<price>n/a</price>")?;
+/// let errors = doc.validate(&schema)?;
+/// assert_eq!(errors[0].line, 1);
+/// assert_eq!(errors[0].path.as_deref(), Some("/price"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct Schema {
+    guard: SchemaGuard,
+}
+
+impl Schema {
+    /// 从 XSD 文本编译 schema
+    ///
+    /// 文本中相对路径的 `xs:include`/`xs:import` 以当前工作目录为基准，
+    /// 需要引用其他文件时请使用 [`Schema::from_file`]。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - XSD 无法编译
+    /// - [`Error::InputTooLarge`] - 输入数据超过大小限制
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(xsd: &str) -> Result<Self> {
+        let size = source_len(xsd)?;
+        // SAFETY: xsd 指向 size 字节的有效内存
+        let (guard, errors) = unsafe { SchemaGuard::parse_memory(xsd.as_ptr().cast(), size) };
+        Self::from_parts(guard, errors)
+    }
+
+    /// 从文件编译 schema
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - 文件无法读取或 XSD 无法编译
+    /// - [`Error::NullByte`] - 路径包含空字节
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let url = path_to_url(path.as_ref())?;
+        // SAFETY: url 是有效的 C 字符串
+        let (guard, errors) = unsafe { SchemaGuard::parse_file(url.as_ptr()) };
+        Self::from_parts(guard, errors)
+    }
+
+    fn from_parts(guard: Option<SchemaGuard>, errors: Vec<RawParseError>) -> Result<Self> {
+        guard
+            .map(|guard| Self { guard })
+            .ok_or_else(|| invalid_schema(errors))
+    }
+
+    /// 验证文档，返回全部验证错误，与 [`Document::validate`] 相同
+    ///
+    /// # Errors
+    ///
+    /// 验证器内部出错（如内存不足）时返回 [`Error::ValidationFailed`]；文档无效不属于错误。
+    pub fn validate(&self, doc: &mut Document) -> Result<Vec<ValidationError>> {
+        // SAFETY: 文档被独占借用，验证期间不会有其他访问
+        let (ret, errors) = unsafe { self.guard.validate_doc(doc.as_ptr()) };
+        let errors = errors.into_iter().map(ValidationError::from_raw).collect();
+        if ret < 0 {
+            return Err(Error::ValidationFailed {
+                validator: "XML Schema".to_string(),
+                errors,
+            });
+        }
+        Ok(errors)
+    }
+}
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(xsd: &str) -> Result<Self> {
+        Schema::from_str(xsd)
+    }
+}
+
+impl std::fmt::Debug for Schema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Schema").finish_non_exhaustive()
+    }
+}
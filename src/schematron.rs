@@ -0,0 +1,122 @@
+//! Schematron 验证
+//!
+//! 需要启用 `schemas` feature。支持 ISO Schematron 与旧版
+//! `http://www.ascc.net/xml/schematron` 命名空间的规则文件，
+//! 未通过的 `assert` 与触发的 `report` 都作为 [`ValidationError`] 报告，
+//! 断言文本保存在 [`ValidationError::assertion`] 中。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::validation::{ValidationError, invalid_schema, path_to_url, source_len};
+use libxml2_sys::{RawParseError, SchematronGuard};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// 编译后的 Schematron 规则
+///
+/// 规则中的 XPath 编译结果在求值时会被 libxml2 写入缓存，
+/// 因此同一个 `Schematron` 上的验证会串行执行；需要并行验证时可以编译多份。
+///
+/// libxml2 要求每个 `pattern` 都有 `id`（或旧版的 `name`），否则验证会失败。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, Schematron};
+///
+/// let rules = Schematron::from_str(r#"
+///     <schema xmlns="http://purl.oclc.org/dsdl/schematron">
+///         <pattern id="items">
+///             <rule context="item">
+///                 <assert test="@sku">Item must have a SKU</assert>
+///             </rule>
+///         </pattern>
+///     </schema>"#)?;
+///
+/// let doc = Document::parse_xml(r#"<feed><item sku="a"/><item/></feed>"#)?;
+/// let errors = rules.validate(&doc)?;
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].path.as_deref(), Some("/feed/item[2]"));
+/// assert_eq!(errors[0].assertion.as_deref(), Some("Item must have a SKU"));
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct Schematron {
+    guard: Mutex<SchematronGuard>,
+}
+
+impl Schematron {
+    /// 从 Schematron 文本编译规则
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - 规则无法编译（如不是 Schematron 文档或没有 `pattern`）
+    /// - [`Error::InputTooLarge`] - 输入数据超过大小限制
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(rules: &str) -> Result<Self> {
+        let size = source_len(rules)?;
+        // SAFETY: rules 指向 size 字节的有效内存
+        let (guard, errors) = unsafe { SchematronGuard::parse_memory(rules.as_ptr().cast(), size) };
+        Self::from_parts(guard, errors)
+    }
+
+    /// 从文件编译规则
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSchema`] - 文件无法读取或规则无法编译
+    /// - [`Error::NullByte`] - 路径包含空字节
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let url = path_to_url(path.as_ref())?;
+        // SAFETY: url 是有效的 C 字符串
+        let (guard, errors) = unsafe { SchematronGuard::parse_file(url.as_ptr()) };
+        Self::from_parts(guard, errors)
+    }
+
+    fn from_parts(guard: Option<SchematronGuard>, errors: Vec<RawParseError>) -> Result<Self> {
+        guard
+            .map(|guard| Self {
+                guard: Mutex::new(guard),
+            })
+            .ok_or_else(|| invalid_schema(errors))
+    }
+
+    /// 验证文档，返回全部违规，文档有效时为空
+    ///
+    /// Schematron 只对文档求值 XPath，不会修改文档。
+    ///
+    /// # Errors
+    ///
+    /// 验证器内部出错（如内存不足、`pattern` 缺少 `id`）时返回 [`Error::ValidationFailed`]；
+    /// 文档无效不属于错误。
+    pub fn validate(&self, doc: &Document) -> Result<Vec<ValidationError>> {
+        let mut guard = self
+            .guard
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // SAFETY: 文档指针有效，验证只读取文档
+        let (ret, errors) = unsafe { guard.validate_doc(doc.as_ptr()) };
+        let errors = errors.into_iter().map(ValidationError::from_raw).collect();
+        if ret < 0 {
+            return Err(Error::ValidationFailed {
+                validator: "Schematron".to_string(),
+                errors,
+            });
+        }
+        Ok(errors)
+    }
+}
+
+impl FromStr for Schematron {
+    type Err = Error;
+
+    fn from_str(rules: &str) -> Result<Self> {
+        Schematron::from_str(rules)
+    }
+}
+
+impl std::fmt::Debug for Schematron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Schematron").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,83 @@
+//! 验证错误
+//!
+//! 文档按 XML Schema、RELAX NG 或 Schematron 规则验证时，每条违规记录为一个
+//! [`ValidationError`]，附带出错的行号和元素路径。
+
+use crate::diagnostic::DiagnosticLevel;
+use crate::document::MAX_INPUT_SIZE;
+use crate::error::{Error, Result};
+use libxml2_sys::{RawParseError, xmlErrorDomain_XML_FROM_SCHEMATRONV};
+use std::ffi::CString;
+use std::path::Path;
+
+/// 一条验证错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// 错误级别
+    pub level: DiagnosticLevel,
+    /// libxml2 错误码（`xmlParserErrors`）
+    pub code: i32,
+    /// 错误消息
+    pub message: String,
+    /// 出错节点所在的行号（从 1 开始，未知时为 0）
+    pub line: usize,
+    /// 出错元素的路径，如 `/feed/item[2]`
+    pub path: Option<String>,
+    /// Schematron 中未通过的 `assert`（或触发的 `report`）的文本，其他验证为 `None`
+    pub assertion: Option<String>,
+}
+
+impl ValidationError {
+    /// 从 libxml2-sys 的原始错误转换
+    pub(crate) fn from_raw(raw: RawParseError) -> Self {
+        let [_, _, text] = raw.args;
+        // Schematron 的消息形如 "<路径> line <行号>: <断言文本>"，路径和行号已单独保存
+        let assertion = (raw.domain == xmlErrorDomain_XML_FROM_SCHEMATRONV as i32)
+            .then(|| text.map(|t| t.trim().to_string()))
+            .flatten();
+        Self {
+            level: DiagnosticLevel::from_raw(raw.level),
+            code: raw.code,
+            message: assertion.clone().unwrap_or(raw.message),
+            line: raw.line.max(0) as usize,
+            path: raw.path,
+            assertion,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.line)?;
+        if let Some(path) = &self.path {
+            write!(f, " {}:", path)?;
+        }
+        write!(f, " {}: {}", self.level, self.message)
+    }
+}
+
+/// 检查规则文本（schema、DTD 等）的长度，返回传给 libxml2 的字节数
+pub(crate) fn source_len(text: &str) -> Result<i32> {
+    if text.len() > MAX_INPUT_SIZE {
+        return Err(Error::InputTooLarge {
+            size: text.len(),
+            max: MAX_INPUT_SIZE,
+        });
+    }
+    Ok(text.len() as i32)
+}
+
+/// 把规则文件路径转换为 libxml2 接受的 C 字符串
+pub(crate) fn path_to_url(path: &Path) -> Result<CString> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| Error::Custom(format!("path is not valid UTF-8: {}", path.display())))?;
+    CString::new(path).map_err(|_| Error::NullByte)
+}
+
+/// 把编译规则时收集到的错误转换为 [`Error::InvalidSchema`]
+pub(crate) fn invalid_schema(errors: Vec<RawParseError>) -> Error {
+    Error::InvalidSchema {
+        errors: errors.into_iter().map(ValidationError::from_raw).collect(),
+    }
+}
@@ -0,0 +1,111 @@
+//! 基于回调的深度优先遍历
+//!
+//! [`Visitor`] 建立在 [`Traverse`](crate::Traverse) 之上，按节点类型分发进入、离开事件，
+//! 适合编写自定义的文本渲染器：例如在进入 `<li>` 时输出 `- `，离开块级元素时换行。
+
+use crate::iter::Edge;
+use crate::node::SelectedNode;
+use crate::node_type::NodeType;
+
+/// [`Visitor::visit_element`] 的返回值，控制遍历如何继续
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// 继续访问元素的子节点
+    Continue,
+    /// 跳过元素的子节点；仍然会调用该元素的 [`Visitor::leave_element`]
+    SkipChildren,
+    /// 立即结束遍历，不再调用任何方法
+    Stop,
+}
+
+/// 节点访问器，由 [`SelectedNode::walk`] 驱动
+///
+/// 所有方法都有空的默认实现，只需实现关心的节点类型。CDATA 节点按文本处理，
+/// 处理指令等其他类型的节点不调用任何方法。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, SelectedNode, Visit, Visitor};
+///
+/// /// 把列表渲染为纯文本，忽略脚本
+/// #[derive(Default)]
+/// struct Renderer {
+///     out: String,
+/// }
+///
+/// impl<'a> Visitor<'a> for Renderer {
+///     fn visit_element(&mut self, node: &SelectedNode<'a>) -> Visit {
+///         match node.tag_name().as_str() {
+///             "script" => Visit::SkipChildren,
+///             "li" => {
+///                 self.out.push_str("- ");
+///                 Visit::Continue
+///             }
+///             _ => Visit::Continue,
+///         }
+///     }
+///
+///     fn leave_element(&mut self, node: &SelectedNode<'a>) {
+///         if node.tag_name() == "li" {
+///             self.out.push('\n');
+///         }
+///     }
+///
+///     fn visit_text(&mut self, node: &SelectedNode<'a>) {
+///         self.out.push_str(node.text().trim());
+///     }
+/// }
+///
+/// let doc = Document::parse("<ul><li>One</li><script>x()</script><li>Two</li></ul>").unwrap();
+/// let ul = &doc.select("//ul").unwrap()[0];
+/// let mut renderer = Renderer::default();
+/// ul.walk(&mut renderer);
+/// assert_eq!(renderer.out, "- One\n- Two\n");
+/// ```
+pub trait Visitor<'a> {
+    /// 进入元素，返回值决定是否访问它的子节点
+    fn visit_element(&mut self, node: &SelectedNode<'a>) -> Visit {
+        let _ = node;
+        Visit::Continue
+    }
+
+    /// 离开元素，它的子节点都已经访问过（或被跳过）
+    fn leave_element(&mut self, node: &SelectedNode<'a>) {
+        let _ = node;
+    }
+
+    /// 文本或 CDATA 节点
+    fn visit_text(&mut self, node: &SelectedNode<'a>) {
+        let _ = node;
+    }
+
+    /// 注释节点
+    fn visit_comment(&mut self, node: &SelectedNode<'a>) {
+        let _ = node;
+    }
+}
+
+/// 用 `visitor` 访问 `node` 及其后代，见 [`SelectedNode::walk`]
+pub(crate) fn walk<'a, V: Visitor<'a> + ?Sized>(node: &SelectedNode<'a>, visitor: &mut V) {
+    let mut traverse = node.traverse();
+    while let Some(edge) = traverse.next() {
+        match edge {
+            Edge::Open(node) => match node.node_type() {
+                NodeType::Element => match visitor.visit_element(&node) {
+                    Visit::Continue => {}
+                    Visit::SkipChildren => traverse.skip_subtree(),
+                    Visit::Stop => return,
+                },
+                kind if kind.is_text() => visitor.visit_text(&node),
+                NodeType::Comment => visitor.visit_comment(&node),
+                _ => {}
+            },
+            Edge::Close(node) => {
+                if node.node_type() == NodeType::Element {
+                    visitor.leave_element(&node);
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 //! XPath 查询与结果处理
 
+use crate::document::Document;
 use crate::error::{Error, Result};
 use crate::node::SelectedNode;
 use libxml2_sys::*;
 use std::ffi::CString;
+use std::ptr;
+use std::sync::Mutex;
 
 /// XPath 查询结果
 ///
@@ -105,6 +108,9 @@ impl<'a> XPathResult<'a> {
     }
 
     /// 获取数字值，如果类型不匹配则进行转换
+    ///
+    /// 无法解析的字符串得到 `0.0`；需要在转换失败时报错请使用
+    /// [`Document::eval`](crate::Document::eval)。
     pub fn as_number(&self) -> f64 {
         match self {
             XPathResult::Number(n) => *n,
@@ -139,24 +145,333 @@ impl<'a> XPathResult<'a> {
     }
 }
 
-/// 执行 XPath 查询的内部实现
+/// 绑定到 XPath 变量的值
 ///
-/// 此函数使用 libxml2-sys 提供的安全封装，对外暴露安全接口。
+/// 通过变量传值无需拼接表达式字符串，也就不必处理引号转义。
+/// 常见类型都实现了 `From`，可以直接 `.into()`。
 ///
-/// # Arguments
+/// # Example
 ///
-/// * `doc_ptr` - 有效的文档指针
-/// * `xpath` - XPath 表达式字符串
-pub(crate) fn evaluate_xpath<'a>(doc_ptr: xmlDocPtr, xpath: &str) -> Result<XPathResult<'a>> {
+/// ```
+/// use rlibxml::{Document, XPathValue};
+///
+/// let doc = Document::parse(r#"<p title="O'Reilly">book</p>"#).unwrap();
+/// let nodes = doc
+///     .select_with("//p[@title = $title]", &[("title", "O'Reilly".into())])
+///     .unwrap();
+/// assert_eq!(nodes.len(), 1);
+///
+/// let value: XPathValue = 3.into();
+/// assert!(matches!(value, XPathValue::Number(n) if n == 3.0));
+/// ```
+#[derive(Debug, Clone)]
+pub enum XPathValue<'a> {
+    /// 字符串
+    String(std::string::String),
+    /// 数字（浮点数）
+    Number(f64),
+    /// 布尔值
+    Boolean(bool),
+    /// 节点集合（节点必须来自被查询的文档）
+    NodeSet(Vec<SelectedNode<'a>>),
+}
+
+impl From<&str> for XPathValue<'_> {
+    fn from(value: &str) -> Self {
+        XPathValue::String(value.to_string())
+    }
+}
+
+impl From<std::string::String> for XPathValue<'_> {
+    fn from(value: std::string::String) -> Self {
+        XPathValue::String(value)
+    }
+}
+
+impl From<f64> for XPathValue<'_> {
+    fn from(value: f64) -> Self {
+        XPathValue::Number(value)
+    }
+}
+
+impl From<i32> for XPathValue<'_> {
+    fn from(value: i32) -> Self {
+        XPathValue::Number(value as f64)
+    }
+}
+
+impl From<i64> for XPathValue<'_> {
+    fn from(value: i64) -> Self {
+        XPathValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for XPathValue<'_> {
+    fn from(value: usize) -> Self {
+        XPathValue::Number(value as f64)
+    }
+}
+
+impl From<bool> for XPathValue<'_> {
+    fn from(value: bool) -> Self {
+        XPathValue::Boolean(value)
+    }
+}
+
+impl<'a> From<SelectedNode<'a>> for XPathValue<'a> {
+    fn from(value: SelectedNode<'a>) -> Self {
+        XPathValue::NodeSet(vec![value])
+    }
+}
+
+impl<'a> From<&SelectedNode<'a>> for XPathValue<'a> {
+    fn from(value: &SelectedNode<'a>) -> Self {
+        XPathValue::NodeSet(vec![value.clone()])
+    }
+}
+
+impl<'a> From<Vec<SelectedNode<'a>>> for XPathValue<'a> {
+    fn from(value: Vec<SelectedNode<'a>>) -> Self {
+        XPathValue::NodeSet(value)
+    }
+}
+
+impl<'a> From<&[SelectedNode<'a>]> for XPathValue<'a> {
+    fn from(value: &[SelectedNode<'a>]) -> Self {
+        XPathValue::NodeSet(value.to_vec())
+    }
+}
+
+/// 预编译的 XPath 表达式
+///
+/// 编译一次、在任意文档或节点上反复求值，省去每次查询时解析表达式的开销。
+/// `XPath` 是 `Send + Sync` 的，可以存放在 `static` 中供多个线程共享。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, XPath};
+/// use std::sync::LazyLock;
+///
+/// static TITLES: LazyLock<XPath> = LazyLock::new(|| XPath::compile("//h1/text()").unwrap());
+///
+/// let doc = Document::parse("<h1>Hello</h1>").unwrap();
+/// let titles = TITLES.select(&doc).unwrap();
+/// assert_eq!(titles[0].text(), "Hello");
+/// ```
+pub struct XPath {
+    expression: String,
+    // libxml2 求值时会把函数查找结果缓存进编译结果，同一份编译结果不能并发求值。
+    // 这里按需保留多份编译结果，每次求值独占其中一份。
+    pool: Mutex<Vec<CompiledXPathGuard>>,
+    // 缓存中带前缀的函数的命名空间 URI 指向求值上下文自己的命名空间表，上下文释放后即失效，
+    // 扩展函数也可能只在部分上下文中注册。调用扩展函数的表达式每次求值都重新编译，不放回池中。
+    reusable: bool,
+}
+
+impl XPath {
+    /// 编译 XPath 表达式
+    ///
+    /// 语法错误在编译时立即报告；命名空间前缀、变量和函数在求值时才解析。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::XPathSyntax`] - 表达式有语法错误，附带出错位置
+    /// - [`Error::NullByte`] - 表达式包含空字节
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Error, XPath};
+    ///
+    /// let err = XPath::compile("//div[@class='x' and ]").unwrap_err();
+    /// assert!(matches!(err, Error::XPathSyntax { position: 21, .. }));
+    /// ```
+    pub fn compile(xpath: &str) -> Result<Self> {
+        // 编译不需要文档，使用空上下文即可
+        // SAFETY: 仅用于编译的上下文允许文档为 null
+        let mut ctx =
+            unsafe { XPathContextGuard::new(ptr::null_mut()) }.ok_or(Error::XPathContextFailed)?;
+        let comp = compile_in_context(&mut ctx, xpath)?;
+
+        Ok(Self {
+            expression: xpath.to_string(),
+            pool: Mutex::new(vec![comp]),
+            reusable: !calls_extension_function(xpath),
+        })
+    }
+
+    /// 获取表达式原文
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    /// 以文档为上下文求值
+    pub fn evaluate<'a>(&self, doc: &'a Document) -> Result<XPathResult<'a>> {
+        // SAFETY: doc 在返回结果的生命周期内有效
+        let mut ctx = new_context(unsafe { doc.as_ptr() })?;
+        evaluate_compiled_in_context(&mut ctx, self)
+    }
+
+    /// 以文档为上下文求值并返回匹配的节点
+    ///
+    /// 如果 XPath 返回非节点类型，返回空向量。
+    pub fn select<'a>(&self, doc: &'a Document) -> Result<Vec<SelectedNode<'a>>> {
+        match self.evaluate(doc)? {
+            XPathResult::NodeSet(nodes) => Ok(nodes),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 以指定节点为上下文求值
+    pub fn evaluate_from<'a>(&self, node: &SelectedNode<'a>) -> Result<XPathResult<'a>> {
+        // SAFETY: node_ptr 在节点存活期间有效
+        let doc_ptr = unsafe { node_get_document(node.node_ptr) };
+        if doc_ptr.is_null() {
+            return Err(Error::invalid_xpath(&self.expression));
+        }
+
+        let mut ctx = new_context(doc_ptr)?;
+        // SAFETY: node_ptr 属于 doc_ptr 文档
+        if !unsafe { ctx.set_context_node(node.node_ptr) } {
+            return Err(Error::invalid_xpath(&self.expression));
+        }
+        evaluate_compiled_in_context(&mut ctx, self)
+    }
+
+    /// 以指定节点为上下文求值并返回匹配的节点
+    ///
+    /// 如果 XPath 返回非节点类型，返回空向量。
+    pub fn select_from<'a>(&self, node: &SelectedNode<'a>) -> Result<Vec<SelectedNode<'a>>> {
+        match self.evaluate_from(node)? {
+            XPathResult::NodeSet(nodes) => Ok(nodes),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 取出一份编译结果，池为空时重新编译
+    fn checkout(&self, ctx: &mut XPathContextGuard) -> Result<CompiledXPathGuard> {
+        let pooled = self
+            .pool
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop();
+        match pooled {
+            Some(comp) => Ok(comp),
+            None => compile_in_context(ctx, &self.expression),
+        }
+    }
+
+    /// 归还编译结果；调用扩展函数的编译结果直接释放
+    fn checkin(&self, comp: CompiledXPathGuard) {
+        if !self.reusable {
+            return;
+        }
+        self.pool
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(comp);
+    }
+}
+
+impl std::fmt::Debug for XPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("XPath").field(&self.expression).finish()
+    }
+}
+
+impl std::fmt::Display for XPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// 在上下文中编译 XPath 表达式
+fn compile_in_context(ctx: &mut XPathContextGuard, xpath: &str) -> Result<CompiledXPathGuard> {
+    let c_xpath = CString::new(xpath).map_err(|_| Error::NullByte)?;
+
+    // SAFETY: ctx 有效，c_xpath 是有效的 CString
+    unsafe { ctx.compile(c_xpath.as_ptr() as *const xmlChar) }.ok_or_else(|| {
+        match ctx.last_error() {
+            Some(error) => Error::XPathSyntax {
+                xpath: xpath.to_string(),
+                message: error.message,
+                position: error.offset,
+            },
+            None => Error::invalid_xpath(xpath),
+        }
+    })
+}
+
+/// 为文档创建 XPath 上下文
+///
+/// 库内所有 XPath 求值都通过这里创建上下文，便于统一注册扩展。
+/// 启用 `exslt` feature 时，EXSLT 函数和标准前缀在这里注册。
+pub(crate) fn new_context(doc_ptr: xmlDocPtr) -> Result<XPathContextGuard> {
+    // SAFETY: 调用者保证 doc_ptr 是有效的文档指针
+    #[cfg_attr(not(feature = "exslt"), allow(unused_mut))]
+    let mut ctx = unsafe { XPathContextGuard::new(doc_ptr) }.ok_or(Error::XPathContextFailed)?;
+    #[cfg(feature = "exslt")]
+    crate::exslt::register(&mut ctx)?;
+    Ok(ctx)
+}
+
+/// 在已有上下文中求值 XPath 表达式
+///
+/// 上下文节点由调用者预先设置。
+pub(crate) fn evaluate_in_context<'a>(
+    ctx: &mut XPathContextGuard,
+    xpath: &str,
+) -> Result<XPathResult<'a>> {
     let c_xpath = CString::new(xpath).map_err(|_| Error::NullByte)?;
 
-    // SAFETY: doc_ptr 是有效的文档指针，c_xpath 是有效的 CString
+    // SAFETY: ctx 有效，c_xpath 是有效的 CString
     // 使用 libxml2-sys 提供的安全封装
-    unsafe {
-        let raw_result = xpath_evaluate(doc_ptr, c_xpath.as_ptr() as *const xmlChar)
-            .ok_or_else(|| Error::invalid_xpath(xpath))?;
+    let raw_result = unsafe { ctx.evaluate(c_xpath.as_ptr() as *const xmlChar) };
+    into_result(ctx, raw_result, xpath)
+}
 
-        let result = match raw_result.result_type {
+/// 在已有上下文中求值编译后的 XPath 表达式
+pub(crate) fn evaluate_compiled_in_context<'a>(
+    ctx: &mut XPathContextGuard,
+    xpath: &XPath,
+) -> Result<XPathResult<'a>> {
+    let comp = xpath.checkout(ctx)?;
+    // SAFETY: ctx 关联的文档和上下文节点由调用者保证有效
+    let raw_result = unsafe { ctx.evaluate_compiled(&comp) };
+    xpath.checkin(comp);
+    into_result(ctx, raw_result, &xpath.expression)
+}
+
+/// 把原始求值结果转换为 [`XPathResult`]
+///
+/// 求值失败时从上下文中取出错误原因。
+fn into_result<'a>(
+    ctx: &XPathContextGuard,
+    raw_result: Option<RawXPathResult>,
+    xpath: &str,
+) -> Result<XPathResult<'a>> {
+    let Some(raw_result) = raw_result else {
+        return Err(match ctx.last_error() {
+            Some(error) => Error::invalid_xpath_with_reason(xpath, error.message),
+            None => Error::invalid_xpath(xpath),
+        });
+    };
+
+    // SAFETY: raw_result 由 libxml2 返回，节点属于上下文关联的文档
+    Ok(unsafe { convert_raw(&raw_result) })
+}
+
+/// 把 libxml2 的 XPath 对象复制为 [`XPathResult`]
+///
+/// # Safety
+///
+/// 节点集合中的节点必须在 `'a` 期间有效
+unsafe fn convert_raw<'a>(raw_result: &RawXPathResult) -> XPathResult<'a> {
+    // SAFETY: 类型已检查，调用者保证节点有效
+    unsafe {
+        match raw_result.result_type {
             XPATH_NODESET => {
                 let node_ptrs = raw_result.as_nodeset();
                 let nodes = node_ptrs
@@ -169,9 +484,292 @@ pub(crate) fn evaluate_xpath<'a>(doc_ptr: xmlDocPtr, xpath: &str) -> Result<XPat
             XPATH_NUMBER => XPathResult::Number(raw_result.as_number()),
             XPATH_STRING => XPathResult::String(raw_result.as_string()),
             _ => XPathResult::Empty,
-        };
+        }
+    }
+}
+
+/// 把 [`XPathValue`] 转换为新建的 libxml2 XPath 对象
+///
+/// 节点集合中的节点必须属于 `doc_ptr` 文档（包括其片段存储）。返回的对象由调用者负责释放或转交。
+fn new_object(value: &XPathValue<'_>, doc_ptr: xmlDocPtr) -> Result<*mut xmlXPathObject> {
+    let object = match value {
+        XPathValue::String(s) => {
+            let c_value = CString::new(s.as_str()).map_err(|_| Error::NullByte)?;
+            // SAFETY: c_value 是有效的 CString，libxml2 会复制它
+            unsafe { xpath_new_string(c_value.as_ptr() as *const xmlChar) }
+        }
+        XPathValue::Number(n) => xpath_new_number(*n),
+        XPathValue::Boolean(b) => xpath_new_boolean(*b),
+        XPathValue::NodeSet(nodes) => {
+            let node_ptrs: Vec<xmlNodePtr> = nodes.iter().map(|node| node.node_ptr).collect();
+            // SAFETY: 节点在文档存活期间有效
+            if node_ptrs
+                .iter()
+                .any(|&ptr| !unsafe { doc_owns_node(doc_ptr, ptr) })
+            {
+                return Err(Error::Custom(
+                    "node belongs to another document".to_string(),
+                ));
+            }
+            // SAFETY: 所有节点都属于 doc_ptr 文档
+            unsafe { xpath_new_nodeset(&node_ptrs) }
+        }
+    };
+    if object.is_null() {
+        return Err(Error::Custom("failed to allocate XPath object".to_string()));
+    }
+    Ok(object)
+}
+
+/// 执行 XPath 查询的内部实现
+///
+/// # Arguments
+///
+/// * `doc_ptr` - 有效的文档指针
+/// * `xpath` - XPath 表达式字符串
+pub(crate) fn evaluate_xpath<'a>(doc_ptr: xmlDocPtr, xpath: &str) -> Result<XPathResult<'a>> {
+    let mut ctx = new_context(doc_ptr)?;
+    evaluate_in_context(&mut ctx, xpath)
+}
+
+/// 在上下文中注册命名空间前缀
+pub(crate) fn register_namespace(
+    ctx: &mut XPathContextGuard,
+    prefix: &str,
+    uri: &str,
+) -> Result<()> {
+    let c_prefix = CString::new(prefix).map_err(|_| Error::NullByte)?;
+    let c_uri = CString::new(uri).map_err(|_| Error::NullByte)?;
+
+    // SAFETY: c_prefix 和 c_uri 是有效的 CString，libxml2 会复制它们
+    let registered = unsafe {
+        ctx.register_namespace(
+            c_prefix.as_ptr() as *const xmlChar,
+            c_uri.as_ptr() as *const xmlChar,
+        )
+    };
+    if registered {
+        Ok(())
+    } else {
+        Err(Error::InvalidNamespace {
+            prefix: prefix.to_string(),
+            uri: uri.to_string(),
+        })
+    }
+}
+
+/// 在上下文中注册变量
+///
+/// 节点集合中的节点必须属于上下文关联的文档。
+pub(crate) fn register_variable(
+    ctx: &mut XPathContextGuard,
+    name: &str,
+    value: &XPathValue<'_>,
+) -> Result<()> {
+    let c_name = CString::new(name).map_err(|_| Error::NullByte)?;
+    let invalid = |reason: String| Error::InvalidVariable {
+        name: name.to_string(),
+        reason,
+    };
+
+    // SAFETY: ctx 在调用期间有效
+    let doc_ptr = unsafe { (*ctx.as_ptr()).doc };
+    let object = new_object(value, doc_ptr).map_err(|e| match e {
+        Error::NullByte => Error::NullByte,
+        other => invalid(other.to_string()),
+    })?;
+
+    // SAFETY: c_name 是有效的 CString；object 的所有权转交给上下文
+    if unsafe { ctx.register_variable(c_name.as_ptr() as *const xmlChar, object) } {
+        Ok(())
+    } else {
+        Err(invalid("failed to register variable".to_string()))
+    }
+}
+
+/// 在上下文中注册自定义函数
+///
+/// 实参被转换为 [`XPathResult`]，返回值从 [`XPathValue`] 转换回 libxml2 对象。
+/// 回调返回的错误或发生的 panic 都会终止本次求值，并作为 [`Error::InvalidXPath`] 的原因报告。
+pub(crate) fn register_function<F>(
+    ctx: &mut XPathContextGuard,
+    ns_uri: &str,
+    name: &str,
+    function: F,
+) -> Result<()>
+where
+    F: for<'n> Fn(&[XPathResult<'n>]) -> Result<XPathValue<'n>> + 'static,
+{
+    register_raw_function(ctx, ns_uri, name, move |_, args| function(args))
+}
+
+/// 在上下文中注册需要访问文档指针的自定义函数
+///
+/// 与 [`register_function`] 相同，但回调额外收到上下文关联的文档，
+/// 可以用来在片段存储中创建结果节点。
+pub(crate) fn register_raw_function<F>(
+    ctx: &mut XPathContextGuard,
+    ns_uri: &str,
+    name: &str,
+    function: F,
+) -> Result<()>
+where
+    F: for<'n> Fn(xmlDocPtr, &[XPathResult<'n>]) -> Result<XPathValue<'n>> + 'static,
+{
+    let c_uri = CString::new(ns_uri).map_err(|_| Error::NullByte)?;
+    let c_name = CString::new(name).map_err(|_| Error::NullByte)?;
+
+    let callback: XPathFunctionCallback = Box::new(move |doc_ptr, raw_args| {
+        // SAFETY: 实参在回调期间有效，节点属于上下文关联的文档
+        let args: Vec<XPathResult<'_>> = raw_args
+            .iter()
+            .map(|raw| unsafe { convert_raw(raw) })
+            .collect();
+        let value = function(doc_ptr, &args).map_err(|e| e.to_string())?;
+        new_object(&value, doc_ptr).map_err(|e| e.to_string())
+    });
+
+    if ctx.register_function(&c_uri, &c_name, callback) {
+        Ok(())
+    } else {
+        Err(Error::InvalidFunction {
+            name: name.to_string(),
+            uri: ns_uri.to_string(),
+        })
+    }
+}
+
+/// 使用变量执行 XPath 查询
+///
+/// # Arguments
+///
+/// * `doc_ptr` - 有效的文档指针
+/// * `xpath` - XPath 表达式字符串
+/// * `variables` - `(变量名, 值)` 列表
+pub(crate) fn evaluate_xpath_with<'a>(
+    doc_ptr: xmlDocPtr,
+    xpath: &str,
+    variables: &[(&str, XPathValue<'a>)],
+) -> Result<XPathResult<'a>> {
+    let mut ctx = new_context(doc_ptr)?;
+    for (name, value) in variables {
+        register_variable(&mut ctx, name, value)?;
+    }
+    evaluate_in_context(&mut ctx, xpath)
+}
+
+/// XPath 1.0 核心函数库（以及 libxml2 内置的 `escape-uri`），它们的函数指针在所有上下文中相同
+const CORE_FUNCTIONS: &[&str] = &[
+    "last",
+    "position",
+    "count",
+    "id",
+    "local-name",
+    "namespace-uri",
+    "name",
+    "string",
+    "concat",
+    "starts-with",
+    "contains",
+    "substring-before",
+    "substring-after",
+    "substring",
+    "string-length",
+    "normalize-space",
+    "translate",
+    "boolean",
+    "not",
+    "true",
+    "false",
+    "lang",
+    "number",
+    "sum",
+    "floor",
+    "ceiling",
+    "round",
+    "escape-uri",
+];
+
+/// 后面可以紧跟 `(` 但不是函数调用的名称：节点类型测试和运算符
+const NOT_FUNCTIONS: &[&str] = &[
+    "node",
+    "text",
+    "comment",
+    "processing-instruction",
+    "and",
+    "or",
+    "div",
+    "mod",
+];
+
+/// 表达式是否可能调用核心函数库以外的函数（带前缀的函数、EXSLT 或自定义函数）
+///
+/// 按词法扫描字符串字面量以外、后面跟着 `(` 的名称。宁可误判为调用扩展函数：
+/// 误判只会让 [`XPath`] 放弃复用编译结果。
+fn calls_extension_function(xpath: &str) -> bool {
+    let chars: Vec<char> = xpath.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' || c == '"' {
+            // 跳过字符串字面量
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || matches!(chars[i], '-' | '_' | '.' | ':'))
+            {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            // `child::node` 这样的轴与节点测试
+            let name = name.rsplit("::").next().unwrap_or_default();
+            let next = chars[i..].iter().find(|c| !c.is_whitespace());
+            if next == Some(&'(')
+                && !CORE_FUNCTIONS.contains(&name)
+                && !NOT_FUNCTIONS.contains(&name)
+            {
+                return true;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    false
+}
 
-        Ok(result)
+/// 把 `xpath` 改写为只取第一个匹配的 `(xpath)[1]`
+///
+/// libxml2 对这种形式只收集到第一个节点就停止。括号不配对的表达式单独求值时本就无效，
+/// 包进括号后却可能变得有效，这时返回 `None`，由调用者按原表达式求值并报告错误。
+pub(crate) fn first_match_xpath(xpath: &str) -> Option<String> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for c in xpath.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+    }
+    (depth == 0 && quote.is_none()).then(|| format!("({xpath})[1]"))
+}
+
+/// 让改写后的表达式的求值错误报告调用者传入的原表达式
+pub(crate) fn with_original_xpath(error: Error, xpath: &str) -> Error {
+    match error {
+        Error::InvalidXPath { reason, .. } => Error::InvalidXPath {
+            xpath: xpath.to_string(),
+            reason,
+        },
+        other => other,
     }
 }
 
@@ -185,23 +783,43 @@ pub(crate) fn evaluate_xpath_on_node<'a>(
     node_ptr: xmlNodePtr,
     xpath: &str,
 ) -> Result<Vec<SelectedNode<'a>>> {
-    let c_xpath = CString::new(xpath).map_err(|_| Error::NullByte)?;
+    evaluate_xpath_on_node_with(node_ptr, xpath, &[], &[])
+}
 
-    // SAFETY: node_ptr 是有效的节点指针，c_xpath 是有效的 CString
-    // 使用 libxml2-sys 提供的安全封装
-    unsafe {
-        let raw_result = xpath_evaluate_on_node(node_ptr, c_xpath.as_ptr() as *const xmlChar)
-            .ok_or_else(|| Error::invalid_xpath(xpath))?;
-
-        if raw_result.result_type == XPATH_NODESET {
-            let node_ptrs = raw_result.as_nodeset();
-            let nodes = node_ptrs
-                .into_iter()
-                .map(|ptr| SelectedNode::from_raw(ptr))
-                .collect();
-            Ok(nodes)
-        } else {
-            Ok(Vec::new())
-        }
+/// 在节点上下文中使用命名空间前缀和变量执行 XPath 查询
+///
+/// # Arguments
+///
+/// * `node_ptr` - 有效的节点指针
+/// * `xpath` - XPath 表达式字符串
+/// * `namespaces` - `(前缀, URI)` 列表
+/// * `variables` - `(变量名, 值)` 列表
+pub(crate) fn evaluate_xpath_on_node_with<'a>(
+    node_ptr: xmlNodePtr,
+    xpath: &str,
+    namespaces: &[(&str, &str)],
+    variables: &[(&str, XPathValue<'a>)],
+) -> Result<Vec<SelectedNode<'a>>> {
+    // SAFETY: node_ptr 是有效的节点指针
+    let doc_ptr = unsafe { node_get_document(node_ptr) };
+    if doc_ptr.is_null() {
+        return Err(Error::invalid_xpath(xpath));
+    }
+
+    let mut ctx = new_context(doc_ptr)?;
+    for (prefix, uri) in namespaces {
+        register_namespace(&mut ctx, prefix, uri)?;
+    }
+    for (name, value) in variables {
+        register_variable(&mut ctx, name, value)?;
+    }
+    // SAFETY: node_ptr 属于 doc_ptr 文档
+    if !unsafe { ctx.set_context_node(node_ptr) } {
+        return Err(Error::invalid_xpath(xpath));
+    }
+
+    match evaluate_in_context(&mut ctx, xpath)? {
+        XPathResult::NodeSet(nodes) => Ok(nodes),
+        _ => Ok(Vec::new()),
     }
 }
@@ -0,0 +1,281 @@
+//! 可复用的 XPath 上下文
+//!
+//! [`Document::select`] 等便捷方法每次都会新建一个空白上下文。
+//! 查询带命名空间的 XML（Atom、SOAP、sitemap 等）时，需要先把前缀注册到上下文中，
+//! [`XPathContext`] 负责保存这些注册信息并在多次查询之间复用。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::node::SelectedNode;
+use crate::xpath::{
+    XPath, XPathResult, XPathValue, evaluate_compiled_in_context, evaluate_in_context, new_context,
+    register_function, register_namespace, register_variable,
+};
+use libxml2_sys::*;
+use std::marker::PhantomData;
+
+/// 绑定到文档的 XPath 上下文
+///
+/// 上下文持有已注册的命名空间前缀、变量和扩展函数，可以在同一文档上反复求值。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, XPathContext};
+///
+/// let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+///     <entry><title>First</title></entry>
+///     <entry><title>Second</title></entry>
+/// </feed>"#;
+/// let doc = Document::parse_xml(xml).unwrap();
+///
+/// let mut ctx = XPathContext::new(&doc).unwrap();
+/// ctx.register_namespace("atom", "http://www.w3.org/2005/Atom").unwrap();
+///
+/// let titles = ctx.select("//atom:entry/atom:title").unwrap();
+/// assert_eq!(titles.len(), 2);
+/// assert_eq!(titles[0].text(), "First");
+/// ```
+pub struct XPathContext<'a> {
+    guard: XPathContextGuard,
+    _marker: PhantomData<&'a Document>,
+}
+
+impl<'a> XPathContext<'a> {
+    /// 为文档创建新的 XPath 上下文
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::XPathContextFailed`] - 上下文创建失败（通常表示内存不足）
+    pub fn new(doc: &'a Document) -> Result<Self> {
+        // SAFETY: doc 在上下文存活期间始终有效（由生命周期 'a 保证）
+        let guard = new_context(unsafe { doc.as_ptr() })?;
+        Ok(Self {
+            guard,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 注册命名空间前缀
+    ///
+    /// 同一前缀重复注册时，以最后一次注册的 URI 为准。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NullByte`] - 前缀或 URI 包含空字节
+    /// - [`Error::InvalidNamespace`] - 前缀为空，libxml2 拒绝注册
+    pub fn register_namespace(&mut self, prefix: &str, uri: &str) -> Result<()> {
+        register_namespace(&mut self.guard, prefix, uri)
+    }
+
+    /// 批量注册命名空间前缀
+    ///
+    /// # Arguments
+    ///
+    /// * `namespaces` - `(前缀, URI)` 列表
+    pub fn register_namespaces(&mut self, namespaces: &[(&str, &str)]) -> Result<()> {
+        for (prefix, uri) in namespaces {
+            self.register_namespace(prefix, uri)?;
+        }
+        Ok(())
+    }
+
+    /// 设置变量
+    ///
+    /// 变量在上下文中保留，之后的每次求值都可以通过 `$name` 引用；同名变量会被替换。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidVariable`] - 节点集合包含其他文档的节点
+    /// - [`Error::NullByte`] - 变量名或字符串值包含空字节
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::Document;
+    ///
+    /// let doc = Document::parse(r#"<a href="/x">X</a><a href="/y">Y</a>"#).unwrap();
+    /// let mut ctx = doc.xpath_context().unwrap();
+    ///
+    /// for href in ["/x", "/y"] {
+    ///     ctx.set_variable("href", href).unwrap();
+    ///     assert_eq!(ctx.select("//a[@href = $href]").unwrap().len(), 1);
+    /// }
+    /// ```
+    pub fn set_variable(&mut self, name: &str, value: impl Into<XPathValue<'a>>) -> Result<()> {
+        register_variable(&mut self.guard, name, &value.into())
+    }
+
+    /// 注册用 Rust 实现的 XPath 扩展函数
+    ///
+    /// 函数注册在 `ns_uri` 命名空间下，表达式中通过已注册的前缀调用（`ns_uri` 为空时无需前缀）。
+    /// 实参以 [`XPathResult`] 传入，返回值可以是任意 [`XPathValue`]；返回的节点必须来自被查询的文档。
+    ///
+    /// 回调返回 `Err` 或发生 panic 时，本次求值失败并返回 [`Error::InvalidXPath`]，
+    /// 其 `reason` 为回调的错误消息。panic 不会跨越 libxml2 的 C 栈展开。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NullByte`] - 命名空间或函数名包含空字节
+    /// - [`Error::InvalidFunction`] - libxml2 拒绝注册，例如同名函数已经注册
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, XPathValue};
+    ///
+    /// let doc = Document::parse(r#"<span class="price">$1,299.00</span>"#).unwrap();
+    /// let mut ctx = doc.xpath_context().unwrap();
+    /// ctx.register_namespace("my", "urn:my-functions").unwrap();
+    /// ctx.register_function("urn:my-functions", "normalize-price", |args| {
+    ///     let raw = args.first().map(|a| a.as_string()).unwrap_or_default();
+    ///     let digits: String = raw.chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+    ///     Ok(XPathValue::Number(digits.parse().unwrap_or(f64::NAN)))
+    /// })
+    /// .unwrap();
+    ///
+    /// let price = ctx.evaluate("my:normalize-price(//span)").unwrap();
+    /// assert_eq!(price.as_number(), 1299.0);
+    /// ```
+    pub fn register_function<F>(&mut self, ns_uri: &str, name: &str, function: F) -> Result<()>
+    where
+        F: for<'n> Fn(&[XPathResult<'n>]) -> Result<XPathValue<'n>> + 'static,
+    {
+        register_function(&mut self.guard, ns_uri, name, function)
+    }
+
+    /// 自动注册根元素作用域内的命名空间声明
+    ///
+    /// 带前缀的声明按原前缀注册。XPath 1.0 无法直接匹配默认命名空间，
+    /// 因此默认命名空间（`xmlns="..."`）只有在提供 `default_prefix` 时才会以该前缀注册。
+    /// 文档没有根元素时不做任何事。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, XPathContext};
+    ///
+    /// let xml = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+    ///                      xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+    ///     <url><loc>https://example.com/</loc><image:image/></url>
+    /// </urlset>"#;
+    /// let doc = Document::parse_xml(xml).unwrap();
+    ///
+    /// let mut ctx = XPathContext::new(&doc).unwrap();
+    /// ctx.register_root_namespaces(Some("sm")).unwrap();
+    ///
+    /// assert_eq!(ctx.select("//sm:url/sm:loc").unwrap().len(), 1);
+    /// assert_eq!(ctx.select("//image:image").unwrap().len(), 1);
+    /// ```
+    pub fn register_root_namespaces(&mut self, default_prefix: Option<&str>) -> Result<()> {
+        // SAFETY: 上下文中的文档指针在 'a 期间有效
+        let namespaces = unsafe {
+            let root = doc_get_root_element((*self.guard.as_ptr()).doc);
+            if root.is_null() {
+                return Ok(());
+            }
+            node_get_in_scope_namespaces(root)
+        };
+
+        for (prefix, uri) in namespaces {
+            match prefix.as_deref().or(default_prefix) {
+                Some(prefix) => self.register_namespace(prefix, &uri)?,
+                None => continue,
+            }
+        }
+        Ok(())
+    }
+
+    /// 以文档为上下文执行 XPath 查询
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidXPath`] - XPath 表达式无效（包括使用了未注册的前缀）
+    /// - [`Error::NullByte`] - XPath 包含空字节
+    pub fn evaluate(&mut self, xpath: &str) -> Result<XPathResult<'a>> {
+        self.guard.clear_context_node();
+        evaluate_in_context(&mut self.guard, xpath)
+    }
+
+    /// 以文档为上下文执行 XPath 查询并返回匹配的节点
+    ///
+    /// 如果 XPath 返回非节点类型，返回空向量。
+    pub fn select(&mut self, xpath: &str) -> Result<Vec<SelectedNode<'a>>> {
+        match self.evaluate(xpath)? {
+            XPathResult::NodeSet(nodes) => Ok(nodes),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 以文档为上下文求值预编译的 XPath 表达式
+    ///
+    /// 表达式中的命名空间前缀在求值时解析，因此可以使用本上下文注册的前缀。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rlibxml::{Document, XPath};
+    ///
+    /// let entries = XPath::compile("//atom:entry").unwrap();
+    ///
+    /// let doc = Document::parse_xml(r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry/></feed>"#).unwrap();
+    /// let mut ctx = doc.xpath_context().unwrap();
+    /// ctx.register_root_namespaces(Some("atom")).unwrap();
+    /// assert_eq!(ctx.select_compiled(&entries).unwrap().len(), 1);
+    /// ```
+    pub fn evaluate_compiled(&mut self, xpath: &XPath) -> Result<XPathResult<'a>> {
+        self.guard.clear_context_node();
+        evaluate_compiled_in_context(&mut self.guard, xpath)
+    }
+
+    /// 以文档为上下文求值预编译的 XPath 表达式并返回匹配的节点
+    ///
+    /// 如果 XPath 返回非节点类型，返回空向量。
+    pub fn select_compiled(&mut self, xpath: &XPath) -> Result<Vec<SelectedNode<'a>>> {
+        match self.evaluate_compiled(xpath)? {
+            XPathResult::NodeSet(nodes) => Ok(nodes),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 以指定节点为上下文执行 XPath 查询
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidXPath`] - XPath 表达式无效，或节点不属于此上下文的文档
+    /// - [`Error::NullByte`] - XPath 包含空字节
+    pub fn evaluate_from(
+        &mut self,
+        node: &SelectedNode<'a>,
+        xpath: &str,
+    ) -> Result<XPathResult<'a>> {
+        // SAFETY: node 的生命周期与上下文绑定到同一文档，libxml2 会拒绝其他文档的节点
+        if !unsafe { self.guard.set_context_node(node.node_ptr) } {
+            return Err(Error::invalid_xpath_with_reason(
+                xpath,
+                "context node belongs to another document",
+            ));
+        }
+        evaluate_in_context(&mut self.guard, xpath)
+    }
+
+    /// 以指定节点为上下文执行 XPath 查询并返回匹配的节点
+    ///
+    /// 如果 XPath 返回非节点类型，返回空向量。
+    pub fn select_from(
+        &mut self,
+        node: &SelectedNode<'a>,
+        xpath: &str,
+    ) -> Result<Vec<SelectedNode<'a>>> {
+        match self.evaluate_from(node, xpath)? {
+            XPathResult::NodeSet(nodes) => Ok(nodes),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+impl std::fmt::Debug for XPathContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XPathContext").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,128 @@
+//! XSLT 1.0 转换
+//!
+//! 需要启用 `xslt` feature（同时编译 libxslt）。样式表本身是一个 XML 文档，
+//! 先用 [`Document::parse_xml`] 解析，再编译为 [`Stylesheet`]。
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use encoding_rs::UTF_8;
+use libxml2_sys::StylesheetGuard;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+use std::sync::Mutex;
+
+/// 编译后的 XSLT 样式表
+///
+/// 样式表中的 XPath 编译结果在求值时会被 libxml2 写入缓存，
+/// 因此同一个 `Stylesheet` 上的转换会串行执行；需要并行转换时可以编译多份。
+///
+/// 转换禁止写文件、创建目录和访问网络，`document()` 仍然可以读取本地文件。
+///
+/// # Example
+///
+/// ```
+/// use rlibxml::{Document, Stylesheet};
+///
+/// let xsl = Document::parse_xml(r#"
+///     <xsl:stylesheet version="1.0" xmlns:xsl="http://www.w3.org/1999/XSL/Transform">
+///         <xsl:output method="xml" omit-xml-declaration="yes"/>
+///         <xsl:param name="source"/>
+///         <xsl:template match="/">
+///             <products source="{$source}">
+///                 <xsl:for-each select="//item">
+///                     <product sku="{@id}"><xsl:value-of select="name"/></product>
+///                 </xsl:for-each>
+///             </products>
+///         </xsl:template>
+///     </xsl:stylesheet>"#)?;
+/// let stylesheet = Stylesheet::parse(&xsl)?;
+///
+/// let vendor = Document::parse_xml(r#"<feed><item id="a1"><name>Pen</name></item></feed>"#)?;
+/// let result = stylesheet.transform(&vendor, &[("source", "acme")])?;
+///
+/// let output = stylesheet.serialize(&result)?;
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<products source=\"acme\"><product sku=\"a1\">Pen</product></products>\n"
+/// );
+/// # Ok::<(), rlibxml::Error>(())
+/// ```
+pub struct Stylesheet {
+    guard: Mutex<StylesheetGuard>,
+}
+
+impl Stylesheet {
+    /// 从已解析的样式表文档编译
+    ///
+    /// 编译使用文档的副本，之后修改或释放原文档不影响样式表。
+    /// `xsl:import`/`xsl:include` 的相对路径以文档的 URL 为基准，
+    /// 从字符串解析的文档以当前工作目录为基准。
+    ///
+    /// # Errors
+    ///
+    /// 返回 [`Error::InvalidStylesheet`]，例如文档不是 XSLT 样式表或引用了不存在的文件。
+    pub fn parse(doc: &Document) -> Result<Self> {
+        // SAFETY: 文档指针有效，编译只读取文档
+        let (guard, messages) = unsafe { StylesheetGuard::parse(doc.as_ptr()) };
+        match guard {
+            Some(guard) => Ok(Self {
+                guard: Mutex::new(guard),
+            }),
+            None => Err(Error::InvalidStylesheet { messages }),
+        }
+    }
+
+    /// 对文档执行转换，返回结果文档
+    ///
+    /// `params` 为顶层 `xsl:param` 的取值，按字符串传入（不作为 XPath 表达式求值）。
+    /// 转换使用源文档的副本，源文档不会被修改。
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TransformFailed`] - 转换出错或被 `xsl:message terminate="yes"` 终止
+    /// - [`Error::NullByte`] - 参数名或参数值包含空字节
+    pub fn transform(&self, doc: &Document, params: &[(&str, &str)]) -> Result<Document> {
+        let strings = params
+            .iter()
+            .flat_map(|(name, value)| [*name, *value])
+            .map(|s| CString::new(s).map_err(|_| Error::NullByte))
+            .collect::<Result<Vec<_>>>()?;
+        let mut raw_params: Vec<*const c_char> = strings.iter().map(|s| s.as_ptr()).collect();
+        raw_params.push(ptr::null());
+
+        let mut guard = self
+            .guard
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // SAFETY: 文档指针有效；raw_params 以 null 结尾，指向的 strings 在调用期间存活
+        let (result, messages) = unsafe { guard.transform(doc.as_ptr(), raw_params.as_ptr()) };
+        if result.is_null() {
+            return Err(Error::TransformFailed { messages });
+        }
+        Document::from_parsed(result, UTF_8, Vec::new())
+    }
+
+    /// 按样式表的 `xsl:output` 设置（方法、编码、缩进、DOCTYPE 等）序列化转换结果
+    ///
+    /// 返回的字节使用 `xsl:output` 声明的编码，未声明时为 UTF-8。
+    ///
+    /// # Errors
+    ///
+    /// 序列化失败（如不支持的输出编码）时返回错误。
+    pub fn serialize(&self, result: &Document) -> Result<Vec<u8>> {
+        let guard = self
+            .guard
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // SAFETY: 文档指针有效，序列化只读取文档
+        unsafe { guard.save_result(result.as_ptr()) }
+            .ok_or_else(|| Error::Custom("failed to serialize transformation result".to_string()))
+    }
+}
+
+impl std::fmt::Debug for Stylesheet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Stylesheet").finish_non_exhaustive()
+    }
+}
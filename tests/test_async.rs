@@ -0,0 +1,214 @@
+//! 异步解析测试
+#![cfg(feature = "tokio")]
+
+use rlibxml::{
+    AsyncOptions, AsyncXmlReader, Document, Error, ParseOptions, ReaderEvent, XmlParseOptions,
+    XmlReader,
+};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, DuplexStream, ReadBuf};
+use tokio::task::JoinHandle;
+
+/// 在后台任务中把 `data` 分成小块写入管道，模拟逐块到达的响应体
+fn serve(data: Vec<u8>, piece: usize) -> (DuplexStream, JoinHandle<io::Result<()>>) {
+    let (client, mut server) = tokio::io::duplex(1024);
+    let writer = tokio::spawn(async move {
+        for chunk in data.chunks(piece) {
+            server.write_all(chunk).await?;
+            tokio::task::yield_now().await;
+        }
+        server.shutdown().await
+    });
+    (client, writer)
+}
+
+fn catalog(count: usize) -> Vec<u8> {
+    let mut xml = String::from("<catalog>\n");
+    for i in 1..=count {
+        xml.push_str(&format!(
+            "<product id=\"p{i}\"><name>Item {i}</name><price>{}.25</price></product>\n",
+            i % 10
+        ));
+    }
+    xml.push_str("</catalog>");
+    xml.into_bytes()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_parse_async() {
+    let html = "<html><head><title>商品</title></head><body>".to_string()
+        + &"<p class=\"row\">Pen — 1.50</p>".repeat(2_000)
+        + "</body></html>";
+    let expected = Document::parse(&html).unwrap().to_html();
+
+    // 当前线程运行时：写入任务与解析交替执行，解析不会阻塞运行时
+    let (body, writer) = serve(html.clone().into_bytes(), 333);
+    let doc = Document::parse_async(body).await.unwrap();
+    writer.await.unwrap().unwrap();
+    assert_eq!(doc.to_html(), expected);
+    assert_eq!(doc.select("//p").unwrap().len(), 2_000);
+
+    // 不使用阻塞线程池，小块读取
+    let (body, writer) = serve(html.into_bytes(), 100);
+    let async_options = AsyncOptions {
+        chunk_size: 7,
+        spawn_blocking: false,
+    };
+    let doc = Document::parse_html_async_with_options(body, ParseOptions::default(), async_options)
+        .await
+        .unwrap();
+    writer.await.unwrap().unwrap();
+    assert_eq!(doc.to_html(), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_parse_xml_async() {
+    let data = catalog(5_000);
+    let expected = Document::parse_xml(std::str::from_utf8(&data).unwrap())
+        .unwrap()
+        .to_xml();
+
+    let (body, writer) = serve(data, 4096);
+    let doc = Document::parse_xml_async(body).await.unwrap();
+    writer.await.unwrap().unwrap();
+    assert_eq!(doc.to_xml(), expected);
+
+    let options = XmlParseOptions {
+        no_blanks: true,
+        ..Default::default()
+    };
+    let doc = Document::parse_xml_async_with_options(
+        b"<feed>\n  <item/>\n</feed>".as_slice(),
+        options,
+        AsyncOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert!(doc.to_xml().ends_with("<feed><item/></feed>\n"));
+}
+
+#[tokio::test]
+async fn test_parse_async_errors() {
+    let err = Document::parse_xml_async(b"<feed><item>a</feed>".as_slice())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::ParseFailed { .. }));
+
+    let source = b"<feed><item>".as_slice().chain(FailingRead);
+    match Document::parse_async(source).await.unwrap_err() {
+        Error::Io { kind, message } => {
+            assert_eq!(kind, io::ErrorKind::ConnectionReset);
+            assert!(message.contains("body dropped"));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+struct FailingRead;
+
+impl AsyncRead for FailingRead {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Err(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "body dropped",
+        )))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_async_reader_events() {
+    let xml = r#"<feed xmlns:g="urn:g" version="2"><!-- note --><item g:id="a">Pen <![CDATA[& ink]]></item><empty/></feed>"#;
+    let mut reader = XmlReader::new(xml.as_bytes()).unwrap();
+    let mut expected = Vec::new();
+    while let Some(event) = reader.next_event().unwrap() {
+        expected.push(event);
+    }
+
+    let (body, _writer) = serve(xml.as_bytes().to_vec(), 5);
+    let mut reader = AsyncXmlReader::new(body);
+    let mut events = Vec::new();
+    while let Some(event) = reader.next_event().await.unwrap() {
+        events.push(event);
+    }
+    assert_eq!(events, expected);
+    assert!(reader.next_event().await.unwrap().is_none());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_async_reader_records() {
+    let (body, writer) = serve(catalog(20_000), 1000);
+    let async_options = AsyncOptions {
+        chunk_size: 512,
+        ..Default::default()
+    };
+    let mut reader = AsyncXmlReader::with_options(body, XmlParseOptions::default(), async_options);
+
+    let mut count = 0;
+    let mut in_price = false;
+    let mut total = 0.0;
+    while let Some(event) = reader.next_event().await.unwrap() {
+        match event {
+            ReaderEvent::StartElement { name, .. } => {
+                count += (name == "product") as usize;
+                in_price = name == "price";
+            }
+            ReaderEvent::Text { text, .. } if in_price => total += text.parse::<f64>().unwrap(),
+            ReaderEvent::EndElement { .. } => in_price = false,
+            _ => {}
+        }
+    }
+    writer.await.unwrap().unwrap();
+    assert_eq!(count, 20_000);
+    assert_eq!(total, 2_000.0 * 45.0 + 20_000.0 * 0.25);
+}
+
+#[tokio::test]
+async fn test_async_reader_malformed() {
+    let mut xml = "<feed>\n".to_string();
+    xml.push_str(&"<item>a</item>\n".repeat(500));
+    xml.push_str("<item>b</feed>");
+
+    let mut reader = AsyncXmlReader::new(io::Cursor::new(xml.into_bytes()));
+    let err = loop {
+        match reader.next_event().await {
+            Ok(Some(_)) => {}
+            Ok(None) => panic!("malformed input should fail"),
+            Err(e) => break e,
+        }
+    };
+    assert!(matches!(err, Error::ParseFailed { .. }));
+    assert!(reader.next_event().await.unwrap().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_async_reader_drop_stops_background_thread() {
+    // 写入端不断产生数据，读取端读到几个事件后丢弃
+    let (client, mut server) = tokio::io::duplex(1024);
+    let writer = tokio::spawn(async move {
+        server.write_all(b"<feed>").await.unwrap();
+        loop {
+            if let Err(e) = server.write_all(b"<item>endless</item>").await {
+                break e;
+            }
+        }
+    });
+
+    let mut reader = AsyncXmlReader::new(client);
+    for _ in 0..10 {
+        reader.next_event().await.unwrap().unwrap();
+    }
+    drop(reader);
+
+    // 后台线程停止后释放管道，写入端收到错误
+    let err = tokio::time::timeout(std::time::Duration::from_secs(10), writer)
+        .await
+        .expect("background reader did not stop")
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
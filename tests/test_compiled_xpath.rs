@@ -0,0 +1,162 @@
+//! 预编译 XPath 测试
+
+use rlibxml::{Document, Error, XPath, XPathResult, XPathValue};
+use std::sync::{Arc, LazyLock};
+use std::thread;
+
+static LINKS: LazyLock<XPath> = LazyLock::new(|| XPath::compile("//a/@href").unwrap());
+
+#[test]
+fn test_compiled_xpath_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<XPath>();
+}
+
+#[test]
+fn test_reuse_across_documents() {
+    let xpath = XPath::compile("count(//li)").unwrap();
+    assert_eq!(xpath.as_str(), "count(//li)");
+
+    for n in 1..=3 {
+        let html = format!("<ul>{}</ul>", "<li>x</li>".repeat(n));
+        let doc = Document::parse(&html).unwrap();
+        assert_eq!(xpath.evaluate(&doc).unwrap().as_number(), n as f64);
+    }
+}
+
+#[test]
+fn test_result_types() {
+    let doc = Document::parse("<div><p>A</p><p>B</p></div>").unwrap();
+
+    let nodes = XPath::compile("//p").unwrap().select(&doc).unwrap();
+    assert_eq!(nodes.len(), 2);
+
+    let result = XPath::compile("string(//p)")
+        .unwrap()
+        .evaluate(&doc)
+        .unwrap();
+    assert!(matches!(result, XPathResult::String(ref s) if s == "A"));
+
+    let result = XPath::compile("count(//p) > 1")
+        .unwrap()
+        .evaluate(&doc)
+        .unwrap();
+    assert!(matches!(result, XPathResult::Boolean(true)));
+
+    // 非节点结果的 select 返回空向量
+    let nodes = XPath::compile("count(//p)").unwrap().select(&doc).unwrap();
+    assert!(nodes.is_empty());
+}
+
+#[test]
+fn test_evaluate_from_node() {
+    let doc = Document::parse(
+        r#"<div class="a"><span>1</span></div><div class="b"><span>2</span><span>3</span></div>"#,
+    )
+    .unwrap();
+    let spans = XPath::compile("span").unwrap();
+
+    let divs = doc.select("//div").unwrap();
+    assert_eq!(spans.select_from(&divs[0]).unwrap().len(), 1);
+    assert_eq!(spans.select_from(&divs[1]).unwrap().len(), 2);
+    assert_eq!(
+        XPath::compile("string(@class)")
+            .unwrap()
+            .evaluate_from(&divs[1])
+            .unwrap()
+            .as_string(),
+        "b"
+    );
+}
+
+#[test]
+fn test_syntax_error_position() {
+    let err = XPath::compile("//div[@class='x' and ]").unwrap_err();
+    match err {
+        Error::XPathSyntax {
+            xpath,
+            message,
+            position,
+        } => {
+            assert_eq!(xpath, "//div[@class='x' and ]");
+            assert!(!message.is_empty());
+            assert_eq!(position, 21);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    assert!(matches!(
+        XPath::compile("count(//p"),
+        Err(Error::XPathSyntax { position: 9, .. })
+    ));
+    assert!(matches!(XPath::compile("//p\0"), Err(Error::NullByte)));
+}
+
+#[test]
+fn test_unresolved_prefix_fails_at_evaluation() {
+    // 前缀在求值时才解析，编译可以成功
+    let xpath = XPath::compile("//atom:entry").unwrap();
+    let doc = Document::parse_xml(r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry/></feed>"#)
+        .unwrap();
+
+    match xpath.evaluate(&doc) {
+        Err(Error::InvalidXPath { reason, .. }) => assert!(reason.is_some()),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_namespace("atom", "http://www.w3.org/2005/Atom")
+        .unwrap();
+    assert_eq!(ctx.select_compiled(&xpath).unwrap().len(), 1);
+}
+
+#[test]
+fn test_extension_function_across_contexts() {
+    // libxml2 会把函数查找结果缓存在编译结果中，其中的命名空间 URI 属于首次求值的上下文。
+    // 同一个表达式在多个短生命周期的上下文中反复求值，每次都必须调用当前上下文注册的函数
+    let xpath = XPath::compile("t:scale(count(//li))").unwrap();
+    let docs = [
+        Document::parse("<ul><li>1</li></ul>").unwrap(),
+        Document::parse("<ul><li>1</li><li>2</li></ul>").unwrap(),
+    ];
+
+    for factor in [10.0, 100.0] {
+        for (doc, count) in docs.iter().zip([1.0, 2.0]) {
+            let mut ctx = doc.xpath_context().unwrap();
+            ctx.register_namespace("t", "urn:test").unwrap();
+            ctx.register_function("urn:test", "scale", move |args| {
+                Ok(XPathValue::Number(args[0].as_number() * factor))
+            })
+            .unwrap();
+            for _ in 0..2 {
+                let result = ctx.evaluate_compiled(&xpath).unwrap();
+                assert_eq!(result.as_number(), count * factor);
+            }
+        }
+    }
+
+    // 没有注册该函数的上下文报错，而不是调用其他上下文的函数
+    assert!(xpath.evaluate(&docs[0]).is_err());
+}
+
+#[test]
+fn test_static_xpath_across_threads() {
+    let doc = Arc::new(
+        Document::parse(r#"<a href="/1">1</a><a href="/2">2</a><a href="/3">3</a>"#).unwrap(),
+    );
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let doc = Arc::clone(&doc);
+            thread::spawn(move || {
+                (0..200)
+                    .map(|_| LINKS.select(&doc).unwrap().len())
+                    .sum::<usize>()
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 600);
+    }
+}
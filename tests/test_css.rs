@@ -0,0 +1,219 @@
+//! CSS 选择器测试
+
+use rlibxml::{Document, Error, css_to_xpath};
+
+const HTML: &str = r#"
+<html><body>
+<div id="main" class="content  wide">
+    <h1 lang="en-US">Title</h1>
+    <ul class="list">
+        <li class="item first"><a href="http://a.example">A</a></li>
+        <li class="item"><a href="/b" data-x="it's &quot;quoted&quot;">B</a></li>
+        <li class="item special"><a href="https://c.example/doc.PDF">C</a></li>
+        <li class="item"><span>D</span></li>
+        <li class="item last"><a href="mailto:e@example">E</a></li>
+    </ul>
+    <p>First paragraph</p>
+    <p class="note">Second <em>paragraph</em></p>
+    <p></p>
+    <form>
+        <input type="checkbox" checked>
+        <input type="text" disabled>
+        <select><option selected>x</option><option>y</option></select>
+    </form>
+</div>
+</body></html>"#;
+
+fn texts(nodes: &[rlibxml::SelectedNode<'_>]) -> Vec<String> {
+    nodes.iter().map(|n| n.text()).collect()
+}
+
+#[test]
+fn test_type_class_id() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(doc.css("li").unwrap().len(), 5);
+    assert_eq!(doc.css(".item").unwrap().len(), 5);
+    assert_eq!(doc.css("li.item.special").unwrap().len(), 1);
+    assert_eq!(doc.css("#main").unwrap()[0].tag_name(), "div");
+    // class 属性中多余的空白不影响匹配
+    assert_eq!(doc.css("div.wide").unwrap().len(), 1);
+    assert_eq!(
+        doc.css("*").unwrap().len(),
+        doc.select("//*").unwrap().len()
+    );
+}
+
+#[test]
+fn test_combinators() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(doc.css("div a").unwrap().len(), 4);
+    assert_eq!(doc.css("div > a").unwrap().len(), 0);
+    assert_eq!(texts(&doc.css("li.first + li").unwrap()), ["B"]);
+    assert_eq!(doc.css("li.special ~ li").unwrap().len(), 2);
+    assert_eq!(texts(&doc.css("ul>li:first-child>a").unwrap()), ["A"]);
+
+    // 选择器列表按文档顺序合并且去重
+    let nodes = doc.css("p.note, h1, li.first a, h1").unwrap();
+    assert_eq!(texts(&nodes), ["Title", "A", "Second paragraph"]);
+}
+
+#[test]
+fn test_request_example() {
+    let doc = Document::parse(HTML).unwrap();
+    let links = doc
+        .css("ul > li.item:nth-child(2n+1) a[href^=http]")
+        .unwrap();
+    assert_eq!(texts(&links), ["A", "C"]);
+}
+
+#[test]
+fn test_attribute_selectors() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(doc.css("a[href]").unwrap().len(), 4);
+    assert_eq!(texts(&doc.css("a[href='/b']").unwrap()), ["B"]);
+    assert_eq!(texts(&doc.css("a[href$=\".PDF\"]").unwrap()), ["C"]);
+    assert_eq!(texts(&doc.css("a[href$='.pdf' i]").unwrap()), ["C"]);
+    assert_eq!(doc.css("a[href*=example]").unwrap().len(), 3);
+    assert_eq!(doc.css("li[class~=item]").unwrap().len(), 5);
+    assert_eq!(doc.css("li[class~='it']").unwrap().len(), 0);
+    assert_eq!(doc.css("h1[lang|=en]").unwrap().len(), 1);
+    assert_eq!(doc.css("a[href^='']").unwrap().len(), 0);
+
+    // 同时包含单双引号的值
+    let quoted = doc.css(r#"a[data-x="it's \"quoted\""]"#).unwrap();
+    assert_eq!(texts(&quoted), ["B"]);
+}
+
+#[test]
+fn test_structural_pseudo_classes() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(texts(&doc.css("li:last-child").unwrap()), ["E"]);
+    assert_eq!(texts(&doc.css("li:nth-child(2)").unwrap()), ["B"]);
+    assert_eq!(
+        texts(&doc.css("li:nth-child(odd)").unwrap()),
+        ["A", "C", "E"]
+    );
+    assert_eq!(texts(&doc.css("li:nth-child(even)").unwrap()), ["B", "D"]);
+    assert_eq!(texts(&doc.css("li:nth-child(-n+2)").unwrap()), ["A", "B"]);
+    assert_eq!(texts(&doc.css("li:nth-child(n+4)").unwrap()), ["D", "E"]);
+    assert_eq!(texts(&doc.css("li:nth-last-child(2)").unwrap()), ["D"]);
+    assert_eq!(doc.css("li:only-child").unwrap().len(), 0);
+    assert_eq!(doc.css("a:only-child").unwrap().len(), 4);
+
+    assert_eq!(
+        texts(&doc.css("p:first-of-type").unwrap()),
+        ["First paragraph"]
+    );
+    assert_eq!(doc.css("p:last-of-type").unwrap()[0].text(), "");
+    assert_eq!(
+        texts(&doc.css("p:nth-of-type(2)").unwrap()),
+        ["Second paragraph"]
+    );
+    assert_eq!(doc.css("h1:only-of-type").unwrap().len(), 1);
+
+    assert_eq!(doc.css("p:empty").unwrap().len(), 1);
+    assert_eq!(doc.css("html:root").unwrap().len(), 1);
+    assert_eq!(doc.css("body:root").unwrap().len(), 0);
+}
+
+#[test]
+fn test_not_has_contains() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(doc.css("li:not(.first):not(.last)").unwrap().len(), 3);
+    assert_eq!(doc.css("li:not(.first, .last)").unwrap().len(), 3);
+    assert_eq!(texts(&doc.css("li:has(span)").unwrap()), ["D"]);
+    assert_eq!(doc.css("li:has(> a[href^=http])").unwrap().len(), 2);
+    assert_eq!(texts(&doc.css("li:has(+ li.last)").unwrap()), ["D"]);
+    assert_eq!(doc.css("ul:has(~ form)").unwrap().len(), 1);
+    assert_eq!(
+        texts(&doc.css("p:contains('paragraph')").unwrap()),
+        ["First paragraph", "Second paragraph"]
+    );
+    assert_eq!(doc.css("p:contains(Second)").unwrap().len(), 1);
+    assert_eq!(
+        texts(&doc.css(":is(h1, li.first)").unwrap()),
+        ["Title", "A"]
+    );
+}
+
+#[test]
+fn test_form_pseudo_classes() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(doc.css(":checked").unwrap().len(), 2);
+    assert_eq!(doc.css("input:disabled").unwrap().len(), 1);
+    assert_eq!(doc.css("input:enabled").unwrap().len(), 1);
+}
+
+#[test]
+fn test_node_css() {
+    let doc = Document::parse(HTML).unwrap();
+    let ul = &doc.css("ul.list").unwrap()[0];
+
+    assert_eq!(ul.css("a").unwrap().len(), 4);
+    // 只匹配后代，不包括节点本身
+    assert_eq!(ul.css("ul").unwrap().len(), 0);
+    assert_eq!(ul.css("li:first-child a").unwrap()[0].text(), "A");
+}
+
+#[test]
+fn test_translation() {
+    assert_eq!(css_to_xpath("ul > li").unwrap(), "descendant::ul/li");
+    assert_eq!(
+        css_to_xpath("h1 + p").unwrap(),
+        "descendant::h1/following-sibling::*[1]/self::p"
+    );
+    assert_eq!(
+        css_to_xpath("a, b").unwrap(),
+        "descendant::a | descendant::b"
+    );
+    assert_eq!(
+        css_to_xpath(r"#\31 23").unwrap(),
+        "descendant::*[@id = '123']"
+    );
+}
+
+#[test]
+fn test_syntax_errors() {
+    let position = |selector: &str| match css_to_xpath(selector) {
+        Err(Error::CssSyntax { position, .. }) => position,
+        other => panic!("unexpected result for {selector:?}: {other:?}"),
+    };
+
+    assert_eq!(position("ul > "), 5);
+    assert_eq!(position("li..item"), 3);
+    assert_eq!(position("a[href"), 6);
+    assert_eq!(position("a[href=]"), 7);
+    assert_eq!(position("a[href='x]"), 7);
+    assert_eq!(position("li:nth-child(2x)"), 13);
+    // 系数和偏移量的运算溢出
+    assert_eq!(position("li:nth-child(-9223372036854775808)"), 13);
+    assert_eq!(position("li:nth-child(-9223372036854775807)"), 13);
+    assert_eq!(position("li:nth-child(-9223372036854775808n+1)"), 13);
+    assert_eq!(position("li:nth-child(99999999999999999999)"), 13);
+    assert_eq!(position("li:hover"), 2);
+    assert_eq!(position("p::before"), 1);
+    assert_eq!(position("div, "), 5);
+    assert_eq!(position("svg|rect"), 3);
+    assert_eq!(position("li:not(a b)"), 9);
+    assert_eq!(position(":first-of-type"), 0);
+    assert_eq!(position("a)"), 1);
+
+    match Document::parse(HTML).unwrap().css("li!") {
+        Err(Error::CssSyntax {
+            selector,
+            message,
+            position,
+        }) => {
+            assert_eq!(selector, "li!");
+            assert_eq!(position, 2);
+            assert!(message.contains('!'));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
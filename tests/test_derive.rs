@@ -0,0 +1,185 @@
+//! #[derive(FromNode)] 测试
+#![cfg(feature = "derive")]
+
+use rlibxml::{Document, Error, FromNode};
+
+#[derive(Debug, FromNode)]
+struct Offer {
+    #[xpath("./span")]
+    price: f64,
+    #[attr("data-currency")]
+    currency: Option<String>,
+}
+
+#[derive(Debug, FromNode)]
+struct Product {
+    #[attr("id")]
+    id: u32,
+    #[xpath("./h2/text()")]
+    name: String,
+    #[xpath("./a")]
+    #[attr("href")]
+    link: Option<String>,
+    #[xpath("./ul/li")]
+    tags: Vec<String>,
+    #[xpath("./div[@class='offer']", nested)]
+    offers: Vec<Offer>,
+    #[xpath("./div[@class='offer'][1]", nested)]
+    best: Option<Offer>,
+}
+
+#[derive(Debug, FromNode)]
+struct Page {
+    #[xpath("//title")]
+    title: String,
+    #[xpath("//div[@class='product']", nested)]
+    products: Vec<Product>,
+    #[xpath("//link[@rel='next']/@href")]
+    next: Option<String>,
+}
+
+const HTML: &str = r#"
+<html><head><title> Catalog </title></head><body>
+  <div class="product" id="1">
+    <h2>Pen</h2>
+    <a href="/pen">details</a>
+    <ul><li>office</li><li>blue</li></ul>
+    <div class="offer" data-currency="EUR"><span> 1.50 </span></div>
+    <div class="offer"><span>2</span></div>
+  </div>
+  <div class="product" id="2">
+    <h2>Paper</h2>
+  </div>
+</body></html>
+"#;
+
+#[test]
+fn test_extract_document() {
+    let doc = Document::parse(HTML).unwrap();
+    let page: Page = doc.extract().unwrap();
+    assert_eq!(page.title, "Catalog");
+    assert_eq!(page.next, None);
+    assert_eq!(page.products.len(), 2);
+
+    let pen = &page.products[0];
+    assert_eq!(pen.id, 1);
+    assert_eq!(pen.name, "Pen");
+    assert_eq!(pen.link.as_deref(), Some("/pen"));
+    assert_eq!(pen.tags, ["office", "blue"]);
+    assert_eq!(pen.offers.len(), 2);
+    assert_eq!(pen.offers[0].price, 1.5);
+    assert_eq!(pen.offers[0].currency.as_deref(), Some("EUR"));
+    assert_eq!(pen.offers[1].currency, None);
+    assert_eq!(pen.best.as_ref().unwrap().price, 1.5);
+
+    let paper = &page.products[1];
+    assert_eq!(paper.link, None);
+    assert!(paper.tags.is_empty());
+    assert!(paper.offers.is_empty());
+    assert!(paper.best.is_none());
+}
+
+#[test]
+fn test_extract_all_and_from_node() {
+    let doc = Document::parse(HTML).unwrap();
+    let products: Vec<Product> = doc.extract_all("//div[@class='product']").unwrap();
+    assert_eq!(products[1].name, "Paper");
+
+    let offer = &doc.css("div.offer").unwrap()[1];
+    assert_eq!(Offer::from_node(offer).unwrap().price, 2.0);
+}
+
+#[test]
+fn test_missing_field() {
+    #[derive(Debug, FromNode)]
+    struct Item {
+        #[xpath("./b")]
+        _bold: String,
+    }
+
+    let doc = Document::parse("<p><i>x</i></p>").unwrap();
+    match doc.extract_all::<Item>("//p").unwrap_err() {
+        Error::Extract {
+            field,
+            selector,
+            reason,
+        } => {
+            assert_eq!(field, "_bold");
+            assert_eq!(selector, "./b");
+            assert_eq!(reason, "no match");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_error_in_nested_field() {
+    let html = r#"<div class="product" id="7"><h2>Ink</h2>
+        <div class="offer"><span>1</span></div>
+        <div class="offer"><span>free</span></div></div>"#;
+    let doc = Document::parse(html).unwrap();
+    let error = doc
+        .extract_all::<Product>("//div[@class='product']")
+        .unwrap_err();
+    match &error {
+        Error::Extract {
+            field,
+            selector,
+            reason,
+        } => {
+            assert_eq!(field, "offers[1].price");
+            assert_eq!(selector, "./span");
+            assert!(reason.contains("\"free\""), "{reason}");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(error.to_string().contains("offers[1].price"));
+}
+
+#[test]
+fn test_attr_selector_and_invalid_xpath() {
+    #[derive(Debug, FromNode)]
+    struct Link {
+        #[xpath("./a")]
+        #[attr("data-id")]
+        _id: i32,
+    }
+
+    #[derive(Debug, FromNode)]
+    struct Broken {
+        #[xpath("./a[")]
+        _a: Vec<String>,
+    }
+
+    let doc = Document::parse(r#"<p><a data-id="x">a</a></p>"#).unwrap();
+    match doc.extract_all::<Link>("//p").unwrap_err() {
+        Error::Extract {
+            field, selector, ..
+        } => {
+            assert_eq!(field, "_id");
+            assert_eq!(selector, "./a/@data-id");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(matches!(
+        doc.extract_all::<Broken>("//p"),
+        Err(Error::Extract { .. })
+    ));
+}
+
+#[test]
+fn test_extract_from_xml() {
+    #[derive(Debug, FromNode)]
+    struct Feed {
+        #[attr("version")]
+        version: String,
+        #[xpath("./entry/@id")]
+        ids: Vec<u64>,
+    }
+
+    let doc =
+        Document::parse_xml(r#"<feed version="2"><entry id="3"/><entry id="5"/></feed>"#).unwrap();
+    let feed: Feed = doc.extract().unwrap();
+    assert_eq!(feed.version, "2");
+    assert_eq!(feed.ids, [3, 5]);
+}
@@ -0,0 +1,98 @@
+//! 解析诊断测试
+
+use rlibxml::{DiagnosticLevel, Document, Error, ParseOptions, XmlParseOptions};
+
+#[test]
+fn test_clean_document_has_no_diagnostics() {
+    let doc = Document::parse("<html><body><p>ok</p></body></html>").unwrap();
+    assert!(doc.diagnostics().is_empty());
+
+    let doc = Document::parse_xml("<root><item/></root>").unwrap();
+    assert!(doc.diagnostics().is_empty());
+}
+
+#[test]
+fn test_recovered_html_keeps_diagnostics() {
+    let html = "<div>\n  <p>text</span>\n</div>";
+    let doc = Document::parse(html).unwrap();
+
+    // 容错解析成功，但错误被记录下来
+    assert!(!doc.is_empty());
+    let diags = doc.diagnostics();
+    assert_eq!(diags.len(), 1);
+
+    let diag = &diags[0];
+    assert_eq!(diag.level, DiagnosticLevel::Error);
+    assert!(diag.is_error());
+    assert_eq!(diag.line, 2);
+    assert!(diag.column > 0);
+    assert!(diag.message.contains("span"));
+    assert!(!diag.message.ends_with('\n'));
+    assert!(diag.file.is_none());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_diagnostics_independent_of_silence_flags() {
+    let html = "<div><p>text</span></div>";
+
+    let silent = Document::parse_html_with_options(html, ParseOptions::default()).unwrap();
+    let loud = Document::parse_html_with_options(
+        html,
+        ParseOptions {
+            no_error: false,
+            no_warning: false,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(silent.diagnostics(), loud.diagnostics());
+}
+
+#[test]
+fn test_xml_parse_failure_carries_diagnostics() {
+    let result = Document::parse_xml("<root>\n<a><b></a>\n</root>");
+
+    match result.unwrap_err() {
+        Error::ParseFailed {
+            detail,
+            diagnostics,
+            ..
+        } => {
+            assert!(!diagnostics.is_empty());
+            let fatal = diagnostics
+                .iter()
+                .find(|d| d.level == DiagnosticLevel::Fatal)
+                .unwrap();
+            assert_eq!(fatal.line, 2);
+            assert!(fatal.message.contains("mismatch"));
+            assert!(detail.unwrap().contains("mismatch"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_xml_recoverable_error_is_recorded() {
+    let xml = r#"<root xmlns:a="not a uri"><a:item/></root>"#;
+    let doc = Document::parse_xml_with_options(xml, XmlParseOptions::default()).unwrap();
+
+    // 命名空间 URI 非法属于可恢复错误，文档仍然可用
+    assert_eq!(doc.select("//*").unwrap().len(), 2);
+
+    let diags = doc.diagnostics();
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].level, DiagnosticLevel::Error);
+    assert!(diags[0].message.contains("not a valid URI"));
+    assert_eq!(diags[0].column, 26);
+}
+
+#[test]
+fn test_diagnostic_display() {
+    let doc = Document::parse("<div><p>text</span></div>").unwrap();
+    let display = doc.diagnostics()[0].to_string();
+
+    assert!(display.starts_with("1:"));
+    assert!(display.contains("error"));
+}
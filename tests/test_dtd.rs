@@ -0,0 +1,157 @@
+//! DTD 验证与 DOCTYPE 测试
+
+use rlibxml::{Document, Dtd, Error, XmlParseOptions};
+use std::ffi::c_void;
+
+const FEED_DTD: &str = r#"<!ELEMENT feed (item+)>
+<!ELEMENT item (#PCDATA)>
+<!ATTLIST item id ID #REQUIRED kind CDATA "plain">"#;
+
+const INVALID: &str = r#"<feed>
+<item id="a">x</item>
+<item>y</item>
+<bad/>
+</feed>"#;
+
+#[test]
+fn test_doctype() {
+    let doc = Document::parse_xml(
+        r#"<!DOCTYPE feed PUBLIC "-//Example//DTD Feed 1.0//EN" "http://example.com/feed.dtd"><feed/>"#,
+    )
+    .unwrap();
+    let doctype = doc.doctype().unwrap();
+    assert_eq!(doctype.name, "feed");
+    assert_eq!(
+        doctype.public_id.as_deref(),
+        Some("-//Example//DTD Feed 1.0//EN")
+    );
+    assert_eq!(
+        doctype.system_id.as_deref(),
+        Some("http://example.com/feed.dtd")
+    );
+
+    let html = Document::parse("<!DOCTYPE html><html><body></body></html>").unwrap();
+    let doctype = html.doctype().unwrap();
+    assert_eq!(doctype.name, "html");
+    assert_eq!(doctype.public_id, None);
+
+    assert!(Document::parse_xml("<feed/>").unwrap().doctype().is_none());
+}
+
+#[test]
+fn test_parse_with_internal_dtd() {
+    let xml = format!("<!DOCTYPE feed [\n{FEED_DTD}\n]>\n{INVALID}");
+
+    // 默认既不验证也不补默认属性
+    let doc = Document::parse_xml(&xml).unwrap();
+    assert!(doc.diagnostics().is_empty());
+    assert_eq!(doc.select("//item[@kind]").unwrap().len(), 0);
+
+    let options = XmlParseOptions {
+        dtd_valid: true,
+        dtd_attr: true,
+        ..Default::default()
+    };
+    let doc = Document::parse_xml_with_options(&xml, options).unwrap();
+    let invalid: Vec<_> = doc
+        .diagnostics()
+        .iter()
+        .filter(|d| d.is_validity_error())
+        .map(|d| d.line)
+        .collect();
+    assert_eq!(invalid.len(), 3);
+    assert!(invalid.iter().all(|&line| line > 4));
+    assert_eq!(
+        doc.extract_texts("//item/@kind").unwrap(),
+        ["plain", "plain"]
+    );
+}
+
+#[test]
+fn test_entities() {
+    let xml = r#"<!DOCTYPE r [<!ENTITY co "Example Corp">]><r>&co;</r>"#;
+
+    let doc = Document::parse_xml(xml).unwrap();
+    assert_eq!(doc.root().unwrap().inner_xml(), "&co;");
+
+    let options = XmlParseOptions {
+        no_ent: false,
+        ..Default::default()
+    };
+    let doc = Document::parse_xml_with_options(xml, options).unwrap();
+    assert_eq!(doc.root().unwrap().inner_xml(), "Example Corp");
+}
+
+#[test]
+fn test_validate_against_dtd() {
+    let dtd = Dtd::parse(FEED_DTD).unwrap();
+
+    let mut doc = Document::parse_xml(INVALID).unwrap();
+    let errors = dtd.validate(&mut doc).unwrap();
+    let summary: Vec<_> = errors
+        .iter()
+        .map(|e| (e.line, e.path.as_deref().unwrap()))
+        .collect();
+    assert_eq!(
+        summary,
+        [(1, "/feed"), (3, "/feed/item[2]"), (4, "/feed/bad")]
+    );
+    assert!(errors[1].message.contains("attribute id"));
+
+    let mut doc = Document::parse_xml(r#"<feed><item id="a">x</item></feed>"#).unwrap();
+    assert!(dtd.validate(&mut doc).unwrap().is_empty());
+
+    // 文档自身的 DOCTYPE 被忽略
+    let mut doc = Document::parse_xml(
+        r#"<!DOCTYPE feed [<!ELEMENT feed EMPTY>]><feed><item id="a">x</item></feed>"#,
+    )
+    .unwrap();
+    assert!(dtd.validate(&mut doc).unwrap().is_empty());
+    assert!(doc.doctype().is_some());
+}
+
+#[test]
+fn test_invalid_dtd() {
+    match Dtd::parse("<!ELEMENT feed (item+>") {
+        Err(Error::InvalidSchema { errors }) => {
+            assert_eq!(errors[0].line, 1);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(matches!(
+        Dtd::from_file("/nonexistent/feed.dtd"),
+        Err(Error::InvalidSchema { .. })
+    ));
+}
+
+/// 使用方应用自己安装的全局错误回调，统计收到的错误数
+unsafe extern "C" fn count_errors(data: *mut c_void, _error: *const libxml2_sys::xmlError) {
+    // SAFETY: data 指向测试中的计数器
+    unsafe { *data.cast::<usize>() += 1 };
+}
+
+#[test]
+fn test_application_error_handler_restored() {
+    let mut count = 0usize;
+    let context: *mut c_void = (&raw mut count).cast();
+    // SAFETY: count 在回调注销前一直存活
+    unsafe { libxml2_sys::xmlSetStructuredErrorFunc(context, Some(count_errors)) };
+
+    let result = Dtd::parse("<!ELEMENT feed (item+>");
+    // SAFETY: 读取并重置当前线程的全局回调
+    let (handler, restored) = unsafe {
+        let handler = *libxml2_sys::__xmlStructuredError();
+        let restored = *libxml2_sys::__xmlStructuredErrorContext();
+        libxml2_sys::xmlSetStructuredErrorFunc(std::ptr::null_mut(), None);
+        (handler, restored)
+    };
+
+    // 解析期间的错误被收集，之后原来的回调恢复
+    assert!(matches!(result, Err(Error::InvalidSchema { .. })));
+    assert_eq!(count, 0);
+    assert_eq!(
+        handler.map(|f| f as *const ()),
+        Some(count_errors as *const ())
+    );
+    assert_eq!(restored, context);
+}
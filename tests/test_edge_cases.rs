@@ -149,6 +149,7 @@ fn test_document_boundary_states() {
 
 /// 测试解析选项边界
 #[test]
+#[allow(deprecated)]
 fn test_parse_options_boundaries() {
     let html = "<div>test</div>";
 
@@ -0,0 +1,153 @@
+//! 字符编码探测测试
+
+use rlibxml::{Document, ParseOptions, XmlParseOptions};
+
+/// "中文" 的 GBK 编码
+const GBK_ZHONGWEN: &[u8] = &[0xD6, 0xD0, 0xCE, 0xC4];
+
+/// "日本" 的 Shift_JIS 编码
+const SJIS_NIHON: &[u8] = &[0x93, 0xFA, 0x96, 0x7B];
+
+fn html_with_body(head: &str, body: &[u8]) -> Vec<u8> {
+    let mut html = format!("<html><head>{}</head><body><p>", head).into_bytes();
+    html.extend_from_slice(body);
+    html.extend_from_slice(b"</p></body></html>");
+    html
+}
+
+#[test]
+fn test_meta_charset() {
+    let html = html_with_body(r#"<meta charset="gbk">"#, GBK_ZHONGWEN);
+    let doc = Document::from_bytes(&html, ParseOptions::default()).unwrap();
+
+    assert_eq!(doc.encoding(), "GBK");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+}
+
+#[test]
+fn test_meta_http_equiv() {
+    let html = html_with_body(
+        r#"<title>t</title><META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=Shift_JIS">"#,
+        SJIS_NIHON,
+    );
+    let doc = Document::from_bytes(&html, ParseOptions::default()).unwrap();
+
+    assert_eq!(doc.encoding(), "Shift_JIS");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "日本");
+}
+
+#[test]
+fn test_meta_inside_comment_ignored() {
+    let html = html_with_body(r#"<!-- <meta charset="gbk"> -->"#, "é".as_bytes());
+    let doc = Document::from_bytes(&html, ParseOptions::default()).unwrap();
+
+    assert_eq!(doc.encoding(), "UTF-8");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "é");
+}
+
+#[test]
+fn test_html_xml_declaration() {
+    let mut html = br#"<?xml version="1.0" encoding="Shift_JIS"?>"#.to_vec();
+    html.extend(html_with_body("<title>t</title>", SJIS_NIHON));
+    let doc = Document::from_bytes(&html, ParseOptions::default()).unwrap();
+
+    assert_eq!(doc.encoding(), "Shift_JIS");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "日本");
+
+    // 同时存在时以 <meta> 为准
+    let mut html = br#"<?xml version="1.0" encoding="Shift_JIS"?>"#.to_vec();
+    html.extend(html_with_body(r#"<meta charset="gbk">"#, GBK_ZHONGWEN));
+    let doc = Document::from_bytes(&html, ParseOptions::default()).unwrap();
+
+    assert_eq!(doc.encoding(), "GBK");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+}
+
+#[test]
+fn test_hint_overrides_meta() {
+    // 文档声明 UTF-8，但传输层声明 GBK，以传输层为准
+    let html = html_with_body(r#"<meta charset="utf-8">"#, GBK_ZHONGWEN);
+
+    let doc = Document::from_bytes_with_hint(
+        &html,
+        Some("text/html; charset=\"GB2312\""),
+        ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(doc.encoding(), "GBK");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+
+    // 裸编码标签同样可用
+    let doc = Document::from_bytes_with_hint(&html, Some("gbk"), ParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "GBK");
+
+    // 无法识别的提示被忽略
+    let doc =
+        Document::from_bytes_with_hint(&html, Some("text/html"), ParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "UTF-8");
+}
+
+#[test]
+fn test_bom_overrides_hint() {
+    let mut html = vec![0xFF, 0xFE];
+    for unit in "<p>中文</p>".encode_utf16() {
+        html.extend_from_slice(&unit.to_le_bytes());
+    }
+
+    let doc = Document::from_bytes_with_hint(&html, Some("gbk"), ParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "UTF-16LE");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+}
+
+#[test]
+fn test_undeclared_fallback() {
+    // 合法 UTF-8
+    let doc = Document::from_bytes("<p>中文</p>".as_bytes(), ParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "UTF-8");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+
+    // 非法 UTF-8 回退到 windows-1252
+    let doc = Document::from_bytes(b"<p>caf\xE9</p>", ParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "windows-1252");
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "café");
+}
+
+#[test]
+fn test_xml_declaration_encoding() {
+    let mut xml = br#"<?xml version="1.0" encoding="GBK"?><root><item>"#.to_vec();
+    xml.extend_from_slice(GBK_ZHONGWEN);
+    xml.extend_from_slice(b"</item></root>");
+
+    let doc = Document::from_xml_bytes(&xml, XmlParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "GBK");
+    assert_eq!(doc.extract_texts("//item").unwrap(), vec!["中文"]);
+}
+
+#[test]
+fn test_xml_str_ignores_declared_encoding() {
+    // &str 已经是 UTF-8，XML 声明中的编码不再生效
+    for label in ["ISO-8859-1", "GBK"] {
+        let xml = format!(r#"<?xml version="1.0" encoding="{label}"?><root>café 中文</root>"#);
+        let doc = Document::parse_xml(&xml).unwrap();
+        assert_eq!(doc.encoding(), "UTF-8");
+        assert_eq!(doc.root().unwrap().text(), "café 中文");
+    }
+}
+
+#[test]
+fn test_xml_utf16_without_bom() {
+    let mut xml = Vec::new();
+    for unit in r#"<?xml version="1.0" encoding="UTF-16"?><root>日本</root>"#.encode_utf16() {
+        xml.extend_from_slice(&unit.to_be_bytes());
+    }
+
+    let doc = Document::from_xml_bytes(&xml, XmlParseOptions::default()).unwrap();
+    assert_eq!(doc.encoding(), "UTF-16BE");
+    assert_eq!(doc.root().unwrap().text(), "日本");
+}
+
+#[test]
+fn test_str_parse_reports_utf8() {
+    let doc = Document::parse_xml("<root/>").unwrap();
+    assert_eq!(doc.encoding(), "UTF-8");
+}
@@ -0,0 +1,178 @@
+//! 类型化 XPath 求值（Document::eval）测试
+
+use rlibxml::{Document, Error, FromXPathResult, IsoDate, IsoDateTime, SelectedNode, XPathResult};
+
+const HTML: &str = r#"
+<table>
+  <tr><td class="qty"> 3 </td><td class="price">1.50</td><td class="ok">true</td></tr>
+  <tr><td class="qty">-7</td><td class="price">n/a</td><td class="ok">yes</td></tr>
+</table>
+<time datetime="2024-03-15T08:30:00Z">Friday</time>
+<time>2024-02-30</time>
+"#;
+
+fn conversion(error: Error) -> (Option<String>, String, String) {
+    match error {
+        Error::XPathConversion {
+            xpath,
+            expected,
+            found,
+        } => (xpath, expected, found),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn test_numbers() {
+    let doc = Document::parse(HTML).unwrap();
+    assert_eq!(doc.eval::<i64>("count(//td)").unwrap(), 6);
+    assert_eq!(doc.eval::<u32>("count(//tr)").unwrap(), 2);
+    assert_eq!(doc.eval::<i64>("(//td[@class='qty'])[1]").unwrap(), 3);
+    assert_eq!(
+        doc.eval::<i32>("string((//td[@class='qty'])[2])").unwrap(),
+        -7
+    );
+    assert_eq!(doc.eval::<f64>("(//td[@class='price'])[1]").unwrap(), 1.5);
+    assert_eq!(doc.eval::<f64>("sum(//td[@class='qty'])").unwrap(), -4.0);
+
+    // 非整数、超出范围和负数都会报错
+    let (xpath, expected, found) = conversion(doc.eval::<i64>("count(//td) div 4").unwrap_err());
+    assert_eq!(xpath.as_deref(), Some("count(//td) div 4"));
+    assert_eq!(expected, "i64");
+    assert_eq!(found, "number 1.5");
+    assert!(doc.eval::<u8>("256").is_err());
+    assert!(doc.eval::<u32>("(//td[@class='qty'])[2]").is_err());
+    assert_eq!(doc.eval::<u64>("4294967296").unwrap(), 4294967296);
+    assert_eq!(doc.eval::<i8>("-128").unwrap(), -128);
+    assert!(doc.eval::<i8>("-129").is_err());
+    assert!(doc.eval::<i64>("1 div 0").is_err());
+}
+
+#[test]
+fn test_no_default_fallback() {
+    let doc = Document::parse(HTML).unwrap();
+    // extract_number 把无法解析的文本当作 0，eval 报错
+    assert_eq!(
+        doc.extract_number("string((//td[@class='price'])[2])")
+            .unwrap(),
+        0.0
+    );
+    let (_, expected, found) =
+        conversion(doc.eval::<f64>("(//td[@class='price'])[2]").unwrap_err());
+    assert_eq!(expected, "f64");
+    assert_eq!(found, "string \"n/a\"");
+
+    // NaN 不是有效的数字结果
+    assert!(doc.eval::<f64>("number('x')").is_err());
+    // 多个节点不能转换为单个值
+    let (_, _, found) = conversion(doc.eval::<String>("//td").unwrap_err());
+    assert_eq!(found, "node-set of 6 nodes");
+    // 空节点集合只能转换为 Option / Vec
+    assert!(doc.eval::<String>("//h1").is_err());
+    assert_eq!(doc.eval::<Option<String>>("//h1").unwrap(), None);
+    assert!(doc.eval::<Vec<String>>("//h1").unwrap().is_empty());
+    // 类型不匹配
+    assert!(doc.eval::<bool>("count(//td)").is_err());
+    assert!(doc.eval::<String>("1 + 1").is_err());
+    assert!(doc.eval::<Vec<i64>>("count(//td)").is_err());
+}
+
+#[test]
+fn test_bool_and_strings() {
+    let doc = Document::parse(HTML).unwrap();
+    assert!(doc.eval::<bool>("count(//tr) = 2").unwrap());
+    assert!(doc.eval::<bool>("(//td[@class='ok'])[1]").unwrap());
+    assert!(doc.eval::<bool>("(//td[@class='ok'])[2]").is_err());
+
+    assert_eq!(doc.eval::<String>("//time[1]").unwrap(), "Friday");
+    assert_eq!(
+        doc.eval::<Option<String>>("//time[1]/@datetime")
+            .unwrap()
+            .as_deref(),
+        Some("2024-03-15T08:30:00Z")
+    );
+    assert_eq!(
+        doc.eval::<Vec<String>>("//td[@class='qty']").unwrap(),
+        [" 3 ", "-7"]
+    );
+    assert_eq!(doc.eval::<Vec<i64>>("//td[@class='qty']").unwrap(), [3, -7]);
+    assert!(doc.eval::<Vec<f64>>("//td[@class='price']").is_err());
+}
+
+#[test]
+fn test_dates() {
+    let doc = Document::parse(HTML).unwrap();
+    let time: IsoDateTime = doc.eval("//time[1]/@datetime").unwrap();
+    assert_eq!(
+        time.date,
+        IsoDate {
+            year: 2024,
+            month: 3,
+            day: 15
+        }
+    );
+    assert_eq!(
+        (time.hour, time.minute, time.offset_minutes),
+        (8, 30, Some(0))
+    );
+    assert_eq!(time.to_string(), "2024-03-15T08:30:00Z");
+
+    let (_, expected, _) = conversion(doc.eval::<IsoDate>("//time[2]").unwrap_err());
+    assert_eq!(expected, "date");
+    assert!(doc.eval::<IsoDate>("//time[1]/@datetime").is_err());
+
+    for valid in ["2024-03-15 08:30", "2024-03-15T23:59:59.123456789-05:30"] {
+        let parsed: IsoDateTime = valid.parse().unwrap();
+        assert_eq!(parsed.date.day, 15);
+    }
+    for invalid in [
+        "2024-3-15T08:30",
+        "2024-03-15T24:00",
+        "2024-03-15T08:30:00.",
+        "2024-03-15T08:30+8",
+        "2024-03-15",
+    ] {
+        assert!(invalid.parse::<IsoDateTime>().is_err(), "{invalid}");
+    }
+    let date: IsoDate = "2000-02-29".parse().unwrap();
+    assert!(date < "2000-03-01".parse().unwrap());
+    assert!("1900-02-29".parse::<IsoDate>().is_err());
+}
+
+#[test]
+fn test_nodes_and_user_types() {
+    #[derive(Debug)]
+    struct Quantity(u32);
+
+    impl<'a> FromXPathResult<'a> for Quantity {
+        fn from_xpath_result(result: XPathResult<'a>) -> rlibxml::Result<Self> {
+            u32::from_xpath_result(result).map(Quantity)
+        }
+    }
+
+    let doc = Document::parse(HTML).unwrap();
+    let node: SelectedNode = doc.eval("//time[1]").unwrap();
+    assert_eq!(
+        node.attr("datetime").as_deref(),
+        Some("2024-03-15T08:30:00Z")
+    );
+    assert_eq!(doc.eval::<Vec<SelectedNode>>("//tr").unwrap().len(), 2);
+    assert!(doc.eval::<SelectedNode>("//tr").is_err());
+    assert!(doc.eval::<XPathResult>("//tr").unwrap().is_nodeset());
+
+    assert_eq!(
+        doc.eval::<Quantity>("(//td[@class='qty'])[1]").unwrap().0,
+        3
+    );
+    // 自定义类型的错误同样带上表达式
+    let (xpath, expected, _) =
+        conversion(doc.eval::<Quantity>("(//td[@class='qty'])[2]").unwrap_err());
+    assert_eq!(xpath.as_deref(), Some("(//td[@class='qty'])[2]"));
+    assert_eq!(expected, "u32");
+
+    // 无效表达式仍然是 InvalidXPath
+    assert!(matches!(
+        doc.eval::<i64>("count(//td"),
+        Err(Error::InvalidXPath { .. })
+    ));
+}
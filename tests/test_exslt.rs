@@ -0,0 +1,254 @@
+//! EXSLT 扩展函数测试
+#![cfg(feature = "exslt")]
+
+use rlibxml::{Document, Error, XPath, XPathResult};
+
+const HTML: &str = r#"
+<table>
+    <tr class="row"><td class="sku">AB-1024</td><td class="price">12.5</td><td class="tags">red, large</td></tr>
+    <tr class="row"><td class="sku">cd-77</td><td class="price">3</td><td class="tags">blue</td></tr>
+    <tr class="row"><td class="sku">XY-9</td><td class="price">40</td><td class="tags">red</td></tr>
+    <tr class="total"><td>2024-02-29T13:45:30Z</td></tr>
+</table>"#;
+
+#[test]
+fn test_regexp_functions() {
+    let doc = Document::parse(HTML).unwrap();
+
+    let skus = doc
+        .select(r"//td[regexp:test(., '^[a-z]{2}-\d+$', 'i')]")
+        .unwrap();
+    assert_eq!(skus.len(), 3);
+    let lower = doc
+        .select(r"//td[regexp:test(., '^[a-z]{2}-\d+$')]")
+        .unwrap();
+    assert_eq!(lower.len(), 1);
+
+    let replaced = doc
+        .evaluate(r"regexp:replace(string(//td[@class='sku']), '(\w+)-(\d+)', '', '$2-$1')")
+        .unwrap();
+    assert_eq!(replaced.as_string(), "1024-AB");
+
+    let digits = doc
+        .evaluate(r"regexp:replace('a1b22c333', '\d', 'g', '#')")
+        .unwrap();
+    assert_eq!(digits.as_string(), "a#b##c###");
+}
+
+#[test]
+fn test_regexp_match_returns_nodes() {
+    let doc = Document::parse(HTML).unwrap();
+
+    // 非全局匹配：整体匹配加各分组
+    let groups = doc
+        .select(r"regexp:match('AB-1024', '([A-Z]+)-(\d+)')")
+        .unwrap();
+    let texts: Vec<_> = groups.iter().map(|n| n.text()).collect();
+    assert_eq!(texts, ["AB-1024", "AB", "1024"]);
+    assert_eq!(groups[0].tag_name(), "match");
+
+    // 全局匹配：每处整体匹配，顺序保持不变
+    let all = doc
+        .evaluate(r"count(regexp:match('3 + 14 = 17', '\d+', 'g'))")
+        .unwrap();
+    assert_eq!(all.as_number(), 3.0);
+    let second = doc
+        .evaluate(r"string(regexp:match('3 + 14 = 17', '\d+', 'g')[2])")
+        .unwrap();
+    assert_eq!(second.as_string(), "14");
+}
+
+#[test]
+fn test_string_functions() {
+    let doc = Document::parse(HTML).unwrap();
+
+    let tokens = doc
+        .select("str:tokenize(//tr[1]/td[@class='tags'], ', ')")
+        .unwrap();
+    let texts: Vec<_> = tokens.iter().map(|n| n.text()).collect();
+    assert_eq!(texts, ["red", "large"]);
+
+    let parts = doc.select("str:split('a--b----c', '--')").unwrap();
+    let texts: Vec<_> = parts.iter().map(|n| n.text()).collect();
+    assert_eq!(texts, ["a", "b", "c"]);
+
+    // 新建的节点可以继续参与路径运算
+    let red = doc
+        .evaluate("count(//td[@class='tags'][str:tokenize(., ', ') = 'red'])")
+        .unwrap();
+    assert_eq!(red.as_number(), 2.0);
+
+    let result = doc.evaluate("str:replace('a.b.c', '.', '::')").unwrap();
+    assert_eq!(result.as_string(), "a::b::c");
+    let result = doc.evaluate("str:concat(//td[@class='sku'])").unwrap();
+    assert_eq!(result.as_string(), "AB-1024cd-77XY-9");
+    let result = doc.evaluate("str:padding(5, 'ab')").unwrap();
+    assert_eq!(result.as_string(), "ababa");
+}
+
+#[test]
+fn test_set_functions() {
+    let doc = Document::parse(HTML).unwrap();
+
+    let distinct = doc
+        .evaluate("count(set:distinct(//td[@class='tags']/text()[starts-with(., 'red')] | //td[@class='tags'][. = 'red']))")
+        .unwrap();
+    assert_eq!(distinct.as_number(), 2.0);
+
+    let rows = "//tr[@class='row']";
+    let result = doc
+        .evaluate(&format!("count(set:difference({rows}, {rows}[2]))"))
+        .unwrap();
+    assert_eq!(result.as_number(), 2.0);
+    let result = doc
+        .evaluate(&format!("count(set:intersection({rows}, //tr[td = '3']))"))
+        .unwrap();
+    assert_eq!(result.as_number(), 1.0);
+    let result = doc
+        .evaluate(&format!("set:has-same-node({rows}, //tr[last()])"))
+        .unwrap();
+    assert!(matches!(result, XPathResult::Boolean(false)));
+
+    let leading = doc
+        .select(&format!("set:leading({rows}, {rows}[3])"))
+        .unwrap();
+    assert_eq!(leading.len(), 2);
+    let trailing = doc
+        .select(&format!("set:trailing({rows}, {rows}[1])/td[@class='sku']"))
+        .unwrap();
+    let skus: Vec<_> = trailing.iter().map(|n| n.text()).collect();
+    assert_eq!(skus, ["cd-77", "XY-9"]);
+}
+
+#[test]
+fn test_math_functions() {
+    let doc = Document::parse(HTML).unwrap();
+
+    assert_eq!(
+        doc.evaluate("math:max(//td[@class='price'])")
+            .unwrap()
+            .as_number(),
+        40.0
+    );
+    assert_eq!(
+        doc.evaluate("math:min(//td[@class='price'])")
+            .unwrap()
+            .as_number(),
+        3.0
+    );
+    assert!(
+        doc.evaluate("math:min(//td[@class='sku'])")
+            .unwrap()
+            .as_number()
+            .is_nan()
+    );
+
+    let cheapest = doc
+        .select("math:lowest(//td[@class='price'])/../td[@class='sku']")
+        .unwrap();
+    assert_eq!(cheapest[0].text(), "cd-77");
+    let highest = doc.select("math:highest(//td[@class='price'])").unwrap();
+    assert_eq!(highest[0].text(), "40");
+
+    assert_eq!(
+        doc.evaluate("math:power(2, 10)").unwrap().as_number(),
+        1024.0
+    );
+    assert_eq!(doc.evaluate("math:abs(-2.5)").unwrap().as_number(), 2.5);
+    assert_eq!(doc.evaluate("math:sqrt(81)").unwrap().as_number(), 9.0);
+}
+
+#[test]
+fn test_date_functions() {
+    let doc = Document::parse(HTML).unwrap();
+    let number = |xpath: &str| doc.evaluate(xpath).unwrap().as_number();
+    let string = |xpath: &str| doc.evaluate(xpath).unwrap().as_string();
+
+    assert_eq!(number("date:year(//tr[@class='total']/td)"), 2024.0);
+    assert_eq!(number("date:month-in-year(//tr[@class='total']/td)"), 2.0);
+    assert_eq!(number("date:day-in-month(//tr[@class='total']/td)"), 29.0);
+    // 2024-02-29 是星期四
+    assert_eq!(number("date:day-in-week(//tr[@class='total']/td)"), 5.0);
+    assert_eq!(number("date:hour-in-day(//tr[@class='total']/td)"), 13.0);
+    assert_eq!(number("date:minute-in-hour(//tr[@class='total']/td)"), 45.0);
+    assert_eq!(
+        number("date:second-in-minute(//tr[@class='total']/td)"),
+        30.0
+    );
+    assert_eq!(string("date:date(//tr[@class='total']/td)"), "2024-02-29Z");
+    assert_eq!(
+        string("date:time('2024-02-29T08:00:00+02:00')"),
+        "08:00:00+02:00"
+    );
+    assert_eq!(number("date:year('1999-12')"), 1999.0);
+
+    // 非法日期
+    assert!(number("date:year('2023-02-29')").is_nan());
+    assert_eq!(string("date:date('yesterday')"), "");
+
+    let now = string("date:date-time()");
+    assert_eq!(now.len(), 20);
+    assert!(now.ends_with('Z'));
+    assert!(number("date:year()") >= 2024.0);
+}
+
+#[test]
+fn test_exslt_in_node_and_compiled_queries() {
+    let doc = Document::parse(HTML).unwrap();
+    let table = &doc.select("//table").unwrap()[0];
+
+    let rows = table
+        .select("tr[regexp:test(td[@class='sku'], '^[A-Z]')]")
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+
+    // 同一个编译结果在多个文档上反复求值
+    let xpath = XPath::compile("count(str:tokenize(string(//p)))").unwrap();
+    let docs = [
+        Document::parse("<p>a b c</p>").unwrap(),
+        Document::parse("<p>a b c d</p>").unwrap(),
+    ];
+    for _ in 0..2 {
+        assert_eq!(xpath.evaluate(&docs[0]).unwrap().as_number(), 3.0);
+        assert_eq!(xpath.evaluate(&docs[1]).unwrap().as_number(), 4.0);
+        assert_eq!(xpath.evaluate_from(table).unwrap().as_number(), 0.0);
+    }
+
+    // 自定义前缀仍然可以覆盖标准前缀
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_namespace("str", "urn:other").unwrap();
+    assert!(ctx.evaluate("str:tokenize('a b')").is_err());
+}
+
+#[test]
+fn test_exslt_errors() {
+    let doc = Document::parse(HTML).unwrap();
+
+    match doc.evaluate("regexp:test('x', '(')") {
+        Err(Error::InvalidXPath { reason, .. }) => {
+            assert!(reason.unwrap().contains("invalid regular expression"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match doc.evaluate("set:distinct('not a node-set')") {
+        Err(Error::InvalidXPath { reason, .. }) => {
+            assert!(reason.unwrap().contains("node-set"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(doc.evaluate("str:padding()").is_err());
+
+    // 过长的填充返回错误，而不是耗尽内存
+    for length in ["1 div 0", "1e12"] {
+        match doc.evaluate(&format!("str:padding({length})")) {
+            Err(Error::InvalidXPath { reason, .. }) => {
+                assert!(reason.unwrap().contains("exceeds the limit"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+    let padded = doc
+        .evaluate("string-length(str:padding(16777216, 'ab'))")
+        .unwrap();
+    assert_eq!(padded.as_number(), 16777216.0);
+}
@@ -0,0 +1,220 @@
+//! 从文件解析测试
+
+use rlibxml::{Document, Error, XmlParseOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 在临时目录中创建测试专用的子目录
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rlibxml-file-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn test_parse_html_file() {
+    let dir = temp_dir("html");
+    let utf8 = dir.join("index.html");
+    std::fs::write(
+        &utf8,
+        "<html><head><title>商品</title></head><body><a href=\"item.html\">Pen</a></body></html>",
+    )
+    .unwrap();
+    // "中文" 的 GBK 编码
+    let gbk = dir.join("gbk.html");
+    let mut body = b"<html><head><meta charset=\"gbk\"></head><body><p>".to_vec();
+    body.extend_from_slice(&[0xD6, 0xD0, 0xCE, 0xC4]);
+    body.extend_from_slice(b"</p></body></html>");
+    std::fs::write(&gbk, body).unwrap();
+
+    let doc = Document::parse_html_file(&utf8);
+    let gbk_doc = Document::parse_html_file(&gbk);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let doc = doc.unwrap();
+    assert_eq!(doc.encoding(), "UTF-8");
+    assert_eq!(doc.url().as_deref(), Some(path_str(&utf8)));
+    assert_eq!(doc.extract_string("string(//title)").unwrap(), "商品");
+    let link = &doc.select("//a").unwrap()[0];
+    assert_eq!(link.base_url().as_deref(), Some(path_str(&utf8)));
+
+    // 非 UTF-8 文件转码后解析，同样记录 URL
+    let doc = gbk_doc.unwrap();
+    assert_eq!(doc.encoding(), "GBK");
+    assert_eq!(doc.url().as_deref(), Some(path_str(&gbk)));
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "中文");
+}
+
+#[test]
+fn test_parse_html_file_undeclared_encoding() {
+    let dir = temp_dir("undeclared");
+    // 没有任何编码声明的 Latin-1 文件，非 ASCII 字符出现在前 1024 字节之后
+    let latin1 = dir.join("latin1.html");
+    let mut body = format!("<html><body><p>{}</p><p>", "x".repeat(2000)).into_bytes();
+    body.extend_from_slice(b"caf\xE9</p></body></html>");
+    std::fs::write(&latin1, body).unwrap();
+    // 多字节字符跨越读取分块的边界
+    let utf8 = dir.join("utf8.html");
+    std::fs::write(&utf8, format!("<p>{}</p>", "é中".repeat(5000))).unwrap();
+
+    let doc = Document::parse_html_file(&latin1);
+    let utf8_doc = Document::parse_html_file(&utf8);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let doc = doc.unwrap();
+    assert_eq!(doc.encoding(), "windows-1252");
+    assert_eq!(doc.extract_string("string(//p[2])").unwrap(), "café");
+    assert_eq!(doc.url().as_deref(), Some(path_str(&latin1)));
+
+    let doc = utf8_doc.unwrap();
+    assert_eq!(doc.encoding(), "UTF-8");
+    assert_eq!(
+        doc.extract_string("string(//p)").unwrap(),
+        "é中".repeat(5000)
+    );
+}
+
+#[test]
+fn test_parse_xml_file_encodings() {
+    let dir = temp_dir("xml");
+    let utf16 = dir.join("utf16.xml");
+    let mut data = vec![0xFF, 0xFE];
+    for unit in "<p>日本</p>".encode_utf16() {
+        data.extend_from_slice(&unit.to_le_bytes());
+    }
+    std::fs::write(&utf16, data).unwrap();
+    let latin1 = dir.join("latin1.xml");
+    std::fs::write(
+        &latin1,
+        b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><p>caf\xE9</p>",
+    )
+    .unwrap();
+
+    let utf16_doc = Document::parse_xml_file(&utf16);
+    let latin1_doc = Document::parse_xml_file(&latin1);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let doc = utf16_doc.unwrap();
+    assert_eq!(doc.encoding(), "UTF-16LE");
+    assert_eq!(doc.root().unwrap().text(), "日本");
+    assert_eq!(doc.url().as_deref(), Some(path_str(&utf16)));
+
+    let doc = latin1_doc.unwrap();
+    assert_eq!(doc.encoding(), "windows-1252");
+    assert_eq!(doc.root().unwrap().text(), "café");
+    assert_eq!(doc.url().as_deref(), Some(path_str(&latin1)));
+}
+
+#[test]
+fn test_external_dtd_relative_to_file() {
+    let dir = temp_dir("dtd");
+    std::fs::write(
+        dir.join("item.dtd"),
+        "<!ELEMENT item (#PCDATA)><!ATTLIST item currency CDATA \"EUR\">",
+    )
+    .unwrap();
+    let utf8 = dir.join("item.xml");
+    std::fs::write(
+        &utf8,
+        "<!DOCTYPE item SYSTEM \"item.dtd\"><item>12.50</item>",
+    )
+    .unwrap();
+    let latin1 = dir.join("item-latin1.xml");
+    std::fs::write(
+        &latin1,
+        b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n\
+          <!DOCTYPE item SYSTEM \"item.dtd\"><item>12,50 \xA4</item>",
+    )
+    .unwrap();
+
+    let options = XmlParseOptions {
+        dtd_attr: true,
+        ..Default::default()
+    };
+    let docs = [&utf8, &latin1].map(|path| Document::parse_xml_file_with_options(path, options));
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // 无论是否经过转码，外部 DTD 都相对于文件所在目录加载
+    for doc in docs {
+        let doc = doc.unwrap();
+        assert!(doc.diagnostics().is_empty());
+        assert_eq!(doc.root().unwrap().attr("currency").as_deref(), Some("EUR"));
+    }
+}
+
+#[test]
+fn test_xml_base() {
+    let dir = temp_dir("base");
+    let path = dir.join("feed.xml");
+    std::fs::write(
+        &path,
+        r#"<feed><item xml:base="2024/"><link/></item><item xml:base="http://example.com/a/"><link/></item></feed>"#,
+    )
+    .unwrap();
+    let doc = Document::parse_xml_file(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let doc = doc.unwrap();
+    let links = doc.select("//link").unwrap();
+    assert_eq!(
+        links[0].base_url().as_deref(),
+        Some(path_str(&dir.join("2024/")))
+    );
+    assert_eq!(
+        links[1].base_url().as_deref(),
+        Some("http://example.com/a/")
+    );
+    assert_eq!(
+        doc.root().unwrap().base_url().as_deref(),
+        Some(path_str(&path))
+    );
+
+    // 从字符串解析的文档没有 URL
+    let doc = Document::parse_xml("<feed><item/></feed>").unwrap();
+    assert_eq!(doc.url(), None);
+    assert_eq!(doc.root().unwrap().base_url(), None);
+}
+
+#[test]
+fn test_file_errors() {
+    match Document::parse_html_file("/nonexistent/index.html").unwrap_err() {
+        Error::Io { kind, .. } => assert_eq!(kind, io::ErrorKind::NotFound),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(matches!(
+        Document::parse_xml_file("/nonexistent/feed.xml"),
+        Err(Error::Io { .. })
+    ));
+
+    let dir = temp_dir("errors");
+    let malformed = dir.join("malformed.xml");
+    std::fs::write(&malformed, "<feed><item>a</feed>").unwrap();
+    let result = Document::parse_xml_file(&malformed);
+    std::fs::remove_dir_all(&dir).unwrap();
+    match result.unwrap_err() {
+        Error::ParseFailed { diagnostics, .. } => {
+            assert!(diagnostics.iter().any(|d| d.is_error()));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_path() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = temp_dir("non-utf8");
+    let path = dir.join(std::ffi::OsStr::from_bytes(b"caf\xE9.html"));
+    std::fs::write(&path, "<p>x</p>").unwrap();
+    let result = Document::parse_html_file(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+    match result.unwrap_err() {
+        Error::Io { kind, .. } => assert_eq!(kind, io::ErrorKind::InvalidInput),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
@@ -0,0 +1,210 @@
+//! 片段解析测试
+
+use rlibxml::{Document, Error, Fragment, NodeType, ParseOptions, XmlParseOptions};
+
+#[test]
+fn test_table_cells() {
+    let fragment = Document::parse_fragment("<td>Pen</td><td>1.50<td>2", "tr").unwrap();
+    assert_eq!(fragment.len(), 3);
+    assert_eq!(fragment.to_html(), "<td>Pen</td><td>1.50</td><td>2</td>");
+    assert_eq!(fragment.text(), "Pen1.502");
+    assert_eq!(fragment.select("td[2]").unwrap()[0].text(), "1.50");
+
+    // 整文档解析会补上外层结构
+    let doc = Document::parse("<td>Pen</td>").unwrap();
+    assert!(doc.to_html().contains("<body>"));
+}
+
+#[test]
+fn test_top_level_text() {
+    let fragment = Document::parse_fragment("Hello <b>world</b><!-- c -->", "div").unwrap();
+    let nodes = fragment.nodes();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0].node_type(), NodeType::Text);
+    assert_eq!(nodes[0].text(), "Hello ");
+    assert_eq!(nodes[1].tag_name(), "b");
+    assert_eq!(nodes[2].node_type(), NodeType::Comment);
+    assert_eq!(fragment.to_html(), "Hello <b>world</b><!-- c -->");
+
+    // body 上下文同样不补 <p>
+    let fragment = Document::parse_fragment("CMS <i>body</i> text", "BODY").unwrap();
+    assert_eq!(fragment.to_html(), "CMS <i>body</i> text");
+}
+
+#[test]
+fn test_context_rules() {
+    // 未闭合的 <li> 由下一个 <li> 闭合；闭合上下文后的内容仍属于片段
+    let fragment = Document::parse_fragment("<li>one<li>two</ul>after", "ul").unwrap();
+    assert_eq!(fragment.to_html(), "<li>one</li><li>two</li>after");
+    assert_eq!(fragment.select("li").unwrap().len(), 2);
+    assert_eq!(fragment.css("li:last-child").unwrap()[0].text(), "two");
+
+    let fragment = Document::parse_fragment("<p>a<p>b <a href='/x'>x</a>", "div").unwrap();
+    assert_eq!(fragment.len(), 2);
+    assert_eq!(
+        fragment.select(".//a").unwrap()[0].attr("href").as_deref(),
+        Some("/x")
+    );
+
+    // 空片段
+    let fragment = Document::parse_fragment("", "div").unwrap();
+    assert!(fragment.is_empty());
+    assert_eq!(fragment.len(), 0);
+    assert_eq!(fragment.to_html(), "");
+}
+
+#[test]
+fn test_parse_in_html_context() {
+    let doc = Document::parse("<table><tr id='r'><td>old</td></tr></table>").unwrap();
+    let before = doc.to_html();
+    let row = &doc.css("tr#r").unwrap()[0];
+
+    let fragment = row.parse_in_context("<td>a<td>b").unwrap();
+    assert_eq!(fragment.to_html(), "<td>a</td><td>b</td>");
+    // 原文档保持不变
+    assert_eq!(doc.to_html(), before);
+
+    // 片段独立于原文档
+    drop(doc);
+    assert_eq!(fragment.len(), 2);
+}
+
+#[test]
+fn test_parse_in_xml_context() {
+    let xml = r#"<feed xmlns="urn:atom" xmlns:g="urn:g"><entry/></feed>"#;
+    let doc = Document::parse_xml(xml).unwrap();
+    let entry = &doc.root().unwrap().element_children()[0];
+
+    let fragment = entry
+        .parse_in_context("<title>Pen</title>\n<g:price>1.50</g:price>")
+        .unwrap();
+    assert_eq!(fragment.len(), 3);
+    assert_eq!(
+        fragment.to_xml(),
+        "<title>Pen</title>\n<g:price>1.50</g:price>"
+    );
+    // 继承上下文的默认命名空间和前缀
+    let ns = [("a", "urn:atom"), ("g", "urn:g")];
+    assert_eq!(
+        fragment.nodes()[0]
+            .select_ns("self::a:title", &ns)
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        fragment.nodes()[2]
+            .select_ns("self::g:price", &ns)
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(fragment.diagnostics().is_empty());
+}
+
+#[test]
+fn test_xml_fragment_uses_document_dtd_and_options() {
+    let xml = r#"<!DOCTYPE feed [<!ENTITY shop "Pen Shop">]>
+<feed xmlns:g="urn:g"><g:entry/></feed>"#;
+    let doc = Document::parse_xml(xml).unwrap();
+    let entry = &doc.root().unwrap().element_children()[0];
+
+    // 引用文档 DTD 中声明的实体，上下文元素本身带前缀
+    let fragment = entry
+        .parse_in_context("<g:title>&shop;</g:title>\n<g:price>1.50</g:price>")
+        .unwrap();
+    assert_eq!(fragment.len(), 3);
+    assert_eq!(fragment.nodes()[0].text(), "Pen Shop");
+    assert!(fragment.diagnostics().is_empty());
+    assert!(matches!(
+        entry.parse_in_context("<g:title>&missing;</g:title>"),
+        Err(Error::ParseFailed { .. })
+    ));
+
+    // 沿用文档的解析选项
+    let options = XmlParseOptions {
+        no_blanks: true,
+        ..Default::default()
+    };
+    let doc = Document::parse_xml_with_options(xml, options).unwrap();
+    let entry = &doc.root().unwrap().element_children()[0];
+    let fragment = entry
+        .parse_in_context("<g:title>&shop;</g:title>\n<g:price>1.50</g:price>")
+        .unwrap();
+    assert_eq!(fragment.len(), 2);
+}
+
+#[test]
+fn test_html_fragment_uses_document_options() {
+    // libxml2 的 no_blanks 只移除 head 等元素中的空白
+    let html = "<head><title>Shop</title></head>";
+    let markup = "<title>Pen</title>\n<meta charset=\"utf-8\">";
+    let doc = Document::parse_html(html).unwrap();
+    let head = &doc.select("//head").unwrap()[0];
+    assert_eq!(head.parse_in_context(markup).unwrap().len(), 3);
+
+    let doc = Document::parse_html_with_options(html, ParseOptions::compact()).unwrap();
+    let head = &doc.select("//head").unwrap()[0];
+    let fragment = head.parse_in_context(markup).unwrap();
+    assert_eq!(fragment.len(), 2);
+    assert_eq!(
+        fragment.to_html(),
+        "<title>Pen</title><meta charset=\"utf-8\">"
+    );
+}
+
+#[test]
+fn test_malformed_xml_fragment() {
+    let doc = Document::parse_xml("<feed><entry/></feed>").unwrap();
+    let entry = &doc.select("//entry").unwrap()[0];
+
+    assert!(matches!(
+        entry.parse_in_context("<title>"),
+        Err(Error::ParseFailed { .. })
+    ));
+    // 片段不能闭合上下文元素后继续追加内容
+    assert!(matches!(
+        entry.parse_in_context("</entry><entry/>"),
+        Err(Error::ParseFailed { .. })
+    ));
+    // 诊断的位置相对于片段本身
+    match entry.parse_in_context("<a></b>").unwrap_err() {
+        Error::ParseFailed { diagnostics, .. } => {
+            let diagnostic = diagnostics.iter().find(|d| d.is_error()).unwrap();
+            assert_eq!(diagnostic.line, 1);
+            assert!(diagnostic.column <= "<a></b>".len() + 1);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn test_fragment_errors() {
+    assert!(matches!(
+        Document::parse_fragment("<p>x</p>", "p><script"),
+        Err(Error::Custom(_))
+    ));
+    assert!(matches!(
+        Document::parse_fragment("a\0b", "div"),
+        Err(Error::NullByte)
+    ));
+
+    let doc = Document::parse("<p>text</p>").unwrap();
+    let text = &doc.select("//p/text()").unwrap()[0];
+    assert!(matches!(
+        text.parse_in_context("<b>x</b>"),
+        Err(Error::Custom(_))
+    ));
+}
+
+#[test]
+fn test_fragment_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Fragment>();
+
+    let fragment = Document::parse_fragment("<li>a</li><li>b</li>", "ul").unwrap();
+    let count = std::thread::spawn(move || fragment.select("li").unwrap().len())
+        .join()
+        .unwrap();
+    assert_eq!(count, 2);
+}
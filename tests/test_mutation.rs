@@ -0,0 +1,213 @@
+//! 文档修改测试
+
+use rlibxml::{Document, Error};
+
+const HTML: &str = r#"<html><head><title>Page</title><style>body{}</style></head><body>
+<script>track()</script>
+<p id="intro">Read <a href="https://t.example/r?u=1" class="track">this <b>article</b></a> now.</p>
+<img src="/img/a.png" alt="a"><img src="logo.png">
+</body></html>"#;
+
+#[test]
+fn test_archive_cleanup() {
+    let mut doc = Document::parse(HTML).unwrap();
+
+    let mut edit = doc.edit();
+    for node in edit.select_ids("//script | //style").unwrap() {
+        edit.remove(node).unwrap();
+    }
+    for link in edit.css_ids("a.track").unwrap() {
+        edit.unwrap(link).unwrap();
+    }
+    for img in edit.select_ids("//img[@src]").unwrap() {
+        let src = edit.node(img).unwrap().attr("src").unwrap();
+        edit.set_attr(
+            img,
+            "src",
+            &format!("archive/{}", src.trim_start_matches('/')),
+        )
+        .unwrap();
+    }
+    let title = edit.select_ids("//title").unwrap()[0];
+    let base = edit.create_element("base").unwrap();
+    edit.set_attr(base, "href", "https://example.com/page")
+        .unwrap();
+    edit.insert_before(title, base).unwrap();
+    drop(edit);
+
+    assert!(doc.select("//script | //style | //a").unwrap().is_empty());
+    let p = &doc.select("//p").unwrap()[0];
+    assert_eq!(p.text(), "Read this article now.");
+    assert_eq!(p.inner_html(), "Read this <b>article</b> now.");
+    assert_eq!(
+        doc.extract_texts("//img/@src").unwrap(),
+        ["archive/img/a.png", "archive/logo.png"]
+    );
+    let head = &doc.select("//head").unwrap()[0];
+    assert_eq!(
+        head.inner_html(),
+        r#"<base href="https://example.com/page"><title>Page</title>"#
+    );
+}
+
+#[test]
+fn test_attributes() {
+    let mut doc = Document::parse(r#"<div a="1" b="2" c="3"></div>"#).unwrap();
+
+    let mut edit = doc.edit();
+    let div = edit.select_ids("//div").unwrap()[0];
+    // 修改已有属性不改变属性顺序
+    edit.set_attr(div, "b", "x < \"y\" & z").unwrap();
+    assert!(edit.remove_attr(div, "a").unwrap());
+    assert!(!edit.remove_attr(div, "missing").unwrap());
+    edit.set_attr(div, "d", "4").unwrap();
+    assert_eq!(
+        edit.node(div).unwrap().attr("b").as_deref(),
+        Some("x < \"y\" & z")
+    );
+
+    assert!(matches!(
+        edit.set_attr(div, "bad name", "v"),
+        Err(Error::InvalidEdit { .. })
+    ));
+    let attr = edit.select_ids("//div/@c").unwrap()[0];
+    assert!(edit.set_attr(attr, "x", "y").is_err());
+    drop(edit);
+
+    assert_eq!(
+        doc.select("//div").unwrap()[0].outer_html(),
+        r#"<div b='x &lt; "y" &amp; z' c="3" d="4"></div>"#
+    );
+}
+
+#[test]
+fn test_set_text_and_create_nodes() {
+    let mut doc =
+        Document::parse_xml("<root><item>old <b>bold</b></item><!--note--></root>").unwrap();
+
+    let mut edit = doc.edit();
+    let item = edit.select_ids("//item").unwrap()[0];
+    let bold = edit.select_ids("//b").unwrap()[0];
+    edit.set_text(item, "<new> & improved").unwrap();
+    // 被替换掉的子节点在会话内仍然可以访问
+    assert_eq!(edit.node(bold).unwrap().text(), "bold");
+    assert!(!edit.node(bold).unwrap().has_parent());
+
+    let comment = edit.select_ids("//comment()").unwrap()[0];
+    edit.set_text(comment, "changed").unwrap();
+
+    let root = edit.root().map(|r| edit.id(&r)).unwrap().unwrap();
+    let extra = edit.create_element("extra").unwrap();
+    let text = edit.create_text("a").unwrap();
+    edit.append_child(extra, text).unwrap();
+    // 相邻文本节点不会被合并，句柄保持有效
+    let more = edit.create_text("b").unwrap();
+    edit.append_child(extra, more).unwrap();
+    edit.append_child(root, extra).unwrap();
+    assert_eq!(edit.node(text).unwrap().text(), "a");
+    assert_eq!(edit.node(extra).unwrap().child_count(), 2);
+
+    // 未插入的节点在会话结束时释放
+    edit.create_element("orphan").unwrap();
+    drop(edit);
+
+    assert!(doc.to_xml().contains(
+        "<root><item>&lt;new&gt; &amp; improved</item><!--changed--><extra>ab</extra></root>"
+    ));
+}
+
+#[test]
+fn test_drop_uninserted_subtree() {
+    let mut doc = Document::parse(HTML).unwrap();
+
+    let mut edit = doc.edit();
+    // 创建后从未插入文档的子树：释放根节点时一并释放登记过的后代
+    let div = edit.create_element("div").unwrap();
+    let text = edit.create_text("x").unwrap();
+    edit.append_child(div, text).unwrap();
+    let span = edit.create_element("span").unwrap();
+    edit.append_child(div, span).unwrap();
+    let inner = edit.create_text("y").unwrap();
+    edit.append_child(span, inner).unwrap();
+    // 从文档中移除的节点再移入未插入的子树
+    let b = edit.select_ids("//b").unwrap()[0];
+    edit.remove(b).unwrap();
+    edit.append_child(span, b).unwrap();
+    assert_eq!(edit.node(div).unwrap().text(), "xyarticle");
+    drop(edit);
+
+    assert!(doc.select("//b").unwrap().is_empty());
+    assert!(doc.select("//div").unwrap().is_empty());
+}
+
+#[test]
+fn test_move_and_replace() {
+    let mut doc = Document::parse_xml("<r><a/><b><c/></b><d/></r>").unwrap();
+
+    let mut edit = doc.edit();
+    let [a, b, c, d] = ["a", "b", "c", "d"].map(|n| edit.select_ids(&format!("//{n}")).unwrap()[0]);
+
+    // 已在树中的节点会被移动
+    edit.append_child(c, a).unwrap();
+    edit.insert_before(b, d).unwrap();
+    assert_eq!(
+        edit.root().unwrap().outer_xml(),
+        "<r><d/><b><c><a/></c></b></r>"
+    );
+
+    let e = edit.create_element("e").unwrap();
+    edit.replace_with(b, e).unwrap();
+    assert_eq!(edit.root().unwrap().outer_xml(), "<r><d/><e/></r>");
+
+    // 被移除的子树可以重新插入
+    edit.append_child(e, b).unwrap();
+    drop(edit);
+
+    assert_eq!(
+        doc.root().unwrap().outer_xml(),
+        "<r><d/><e><b><c><a/></c></b></e></r>"
+    );
+}
+
+#[test]
+fn test_invalid_edits() {
+    let mut doc = Document::parse_xml(r#"<r x="1"><a><b/></a>text</r>"#).unwrap();
+
+    let stale = {
+        let edit = doc.edit();
+        edit.select_ids("//a").unwrap()[0]
+    };
+
+    let mut edit = doc.edit();
+    let [a, b] = ["//a", "//b"].map(|x| edit.select_ids(x).unwrap()[0]);
+    let attr = edit.select_ids("//@x").unwrap()[0];
+    let text = edit.select_ids("//r/text()").unwrap()[0];
+    let root = edit.select_ids("/").unwrap()[0];
+
+    let is_invalid = |r: rlibxml::Result<()>| matches!(r, Err(Error::InvalidEdit { .. }));
+    // 不能把节点插入到自身或其后代中
+    assert!(is_invalid(edit.append_child(b, a)));
+    assert!(is_invalid(edit.append_child(a, a)));
+    assert!(is_invalid(edit.insert_before(b, a)));
+    // 文本节点不能有子节点，属性和文档节点不能被插入
+    assert!(is_invalid(edit.append_child(text, b)));
+    assert!(is_invalid(edit.append_child(a, attr)));
+    assert!(is_invalid(edit.append_child(a, root)));
+    assert!(is_invalid(edit.remove(root)));
+    assert!(is_invalid(edit.unwrap(text)));
+    // 其他会话的句柄
+    assert!(is_invalid(edit.remove(stale)));
+    assert!(edit.node(stale).is_err());
+
+    // 属性节点可以直接移除
+    edit.remove(attr).unwrap();
+    drop(edit);
+
+    // 其他文档的节点
+    let other = Document::parse("<p/>").unwrap();
+    let edit = doc.edit();
+    assert!(edit.id(&other.select("//p").unwrap()[0]).is_err());
+    drop(edit);
+
+    assert_eq!(doc.root().unwrap().outer_xml(), "<r><a><b/></a>text</r>");
+}
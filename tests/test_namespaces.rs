@@ -0,0 +1,135 @@
+//! XPath 命名空间测试
+
+use rlibxml::{Document, Error, XPathContext};
+
+const ATOM: &str = "http://www.w3.org/2005/Atom";
+
+const FEED: &str = r#"<?xml version="1.0"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:media="http://search.yahoo.com/mrss/">
+    <title>Example</title>
+    <entry><title>First</title><media:thumbnail url="a.jpg"/></entry>
+    <entry><title>Second</title><media:thumbnail url="b.jpg"/></entry>
+</feed>"#;
+
+#[test]
+fn test_select_ns() {
+    let doc = Document::parse_xml(FEED).unwrap();
+
+    // 未注册前缀时默认命名空间中的元素无法直接匹配
+    assert!(doc.select("//entry").unwrap().is_empty());
+
+    let entries = doc.select_ns("//atom:entry", &[("atom", ATOM)]).unwrap();
+    assert_eq!(entries.len(), 2);
+}
+
+#[test]
+fn test_unregistered_prefix_is_error() {
+    let doc = Document::parse_xml(FEED).unwrap();
+    assert!(matches!(
+        doc.select("//atom:entry"),
+        Err(Error::InvalidXPath { .. })
+    ));
+}
+
+#[test]
+fn test_context_reuse() {
+    let doc = Document::parse_xml(FEED).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_namespaces(&[("a", ATOM), ("m", "http://search.yahoo.com/mrss/")])
+        .unwrap();
+
+    let titles: Vec<String> = ctx
+        .select("//a:entry/a:title")
+        .unwrap()
+        .iter()
+        .map(|n| n.text())
+        .collect();
+    assert_eq!(titles, vec!["First", "Second"]);
+
+    let urls = ctx.select("//m:thumbnail/@url").unwrap();
+    assert_eq!(urls.len(), 2);
+    assert_eq!(ctx.evaluate("count(//a:entry)").unwrap().as_number(), 2.0);
+}
+
+#[test]
+fn test_context_relative_to_node() {
+    let doc = Document::parse_xml(FEED).unwrap();
+    let mut ctx = XPathContext::new(&doc).unwrap();
+    ctx.register_namespace("atom", ATOM).unwrap();
+
+    let entries = ctx.select("//atom:entry").unwrap();
+    let title = ctx.select_from(&entries[1], "atom:title").unwrap();
+    assert_eq!(title[0].text(), "Second");
+
+    // 节点上下文查询之后，文档级查询不受影响
+    assert_eq!(ctx.select("atom:feed").unwrap().len(), 0);
+    assert_eq!(ctx.select("/atom:feed/atom:title").unwrap().len(), 1);
+}
+
+#[test]
+fn test_context_rejects_foreign_node() {
+    let doc = Document::parse_xml(FEED).unwrap();
+    let other = Document::parse_xml("<root/>").unwrap();
+    let foreign = other.root().unwrap();
+
+    let mut ctx = doc.xpath_context().unwrap();
+    assert!(matches!(
+        ctx.select_from(&foreign, "."),
+        Err(Error::InvalidXPath { .. })
+    ));
+}
+
+#[test]
+fn test_register_root_namespaces() {
+    let doc = Document::parse_xml(FEED).unwrap();
+
+    // 没有默认前缀时只注册带前缀的声明
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_root_namespaces(None).unwrap();
+    assert_eq!(ctx.select("//media:thumbnail").unwrap().len(), 2);
+
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_root_namespaces(Some("atom")).unwrap();
+    assert_eq!(ctx.select("//atom:entry").unwrap().len(), 2);
+    assert_eq!(ctx.select("//media:thumbnail").unwrap().len(), 2);
+}
+
+#[test]
+fn test_register_root_namespaces_without_declarations() {
+    let doc = Document::parse("<p>text</p>").unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_root_namespaces(Some("x")).unwrap();
+    assert_eq!(ctx.select("//p").unwrap().len(), 1);
+}
+
+#[test]
+fn test_node_select_ns() {
+    let xml = r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+        <s:Body><r:Result xmlns:r="urn:result">ok</r:Result></s:Body>
+    </s:Envelope>"#;
+    let doc = Document::parse_xml(xml).unwrap();
+    let root = doc.root().unwrap();
+
+    let ns = [
+        ("soap", "http://schemas.xmlsoap.org/soap/envelope/"),
+        ("res", "urn:result"),
+    ];
+    let result = root.select_ns("soap:Body/res:Result", &ns).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].text(), "ok");
+}
+
+#[test]
+fn test_invalid_namespace_registration() {
+    let doc = Document::parse_xml(FEED).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+
+    assert!(matches!(
+        ctx.register_namespace("", ATOM),
+        Err(Error::InvalidNamespace { .. })
+    ));
+    assert!(matches!(
+        ctx.register_namespace("a\0b", ATOM),
+        Err(Error::NullByte)
+    ));
+}
@@ -20,6 +20,7 @@ fn test_parse_options() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn test_custom_parse_options() {
     let html = r#"<div>Hello</div>"#;
 
@@ -47,6 +48,7 @@ fn test_xml_parse_options() {
         no_blanks: true,
         no_dtd: false,
         no_ent: true,
+        ..Default::default()
     };
 
     let doc = Document::parse_xml_with_options(xml, options).unwrap();
@@ -54,6 +56,7 @@ fn test_xml_parse_options() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn test_broken_html_with_recover() {
     let broken_html = r#"<div><p>Unclosed<div>Nested</div>"#;
 
@@ -0,0 +1,131 @@
+//! 不借用文档的节点句柄测试
+
+use rlibxml::{Document, Error, NodeType, OwnedDocument, OwnedNode};
+use std::sync::Arc;
+
+const HTML: &str = r#"<html><body>
+<ul id="list"><li class="item" data-sku="a1">Pen</li><li class="item" data-sku="b2">Ink <b>blue</b></li></ul>
+<p>Footer</p>
+</body></html>"#;
+
+/// 解析后直接返回节点，文档由节点保持存活
+fn items(html: &str) -> Vec<OwnedNode> {
+    let doc = Arc::new(Document::parse(html).unwrap());
+    doc.css_owned("li.item").unwrap()
+}
+
+/// 与文档一起存入结构体
+struct Listing {
+    doc: OwnedDocument,
+    list: OwnedNode,
+}
+
+impl Listing {
+    fn new(html: &str) -> Self {
+        let doc = Arc::new(Document::parse(html).unwrap());
+        let list = doc.select_owned("//ul").unwrap().remove(0);
+        Self { doc, list }
+    }
+}
+
+#[test]
+fn test_return_from_function() {
+    let items = items(HTML);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].text(), "Pen");
+    assert_eq!(items[1].attr("data-sku").as_deref(), Some("b2"));
+    // 两个节点共享同一文档
+    assert!(Arc::ptr_eq(items[0].document(), items[1].document()));
+    assert_eq!(Arc::strong_count(items[0].document()), 2);
+}
+
+#[test]
+fn test_store_with_document() {
+    let listing = Listing::new(HTML);
+    assert_eq!(listing.list.attr("id").as_deref(), Some("list"));
+    assert!(Arc::ptr_eq(&listing.doc, listing.list.document()));
+    assert_eq!(listing.doc.select("//li").unwrap().len(), 2);
+}
+
+#[test]
+fn test_navigation_and_queries() {
+    let doc = Arc::new(Document::parse(HTML).unwrap());
+    let root = doc.root_owned().unwrap();
+    assert_eq!(root.tag_name(), "html");
+
+    let list = &doc.select_owned("//ul").unwrap()[0];
+    let first = list.first_child().unwrap();
+    let last = list.last_child().unwrap();
+    assert_eq!(first.text(), "Pen");
+    assert_eq!(first.next_sibling().unwrap().text(), last.text());
+    assert_eq!(last.prev_sibling().unwrap().text(), "Pen");
+    assert_eq!(first.siblings().len(), 1);
+    assert_eq!(first.parent().unwrap().attr("id").as_deref(), Some("list"));
+    assert_eq!(list.children().len(), 2);
+    assert_eq!(list.element_children().len(), 2);
+    assert_eq!(list.child_count(), 2);
+    assert!(list.has_children() && list.has_parent());
+    assert_eq!(first.node_type(), NodeType::Element);
+    assert!(first.has_attr("class"));
+    assert_eq!(first.attrs().len(), 2);
+    assert_eq!(last.text_children(), ["Ink "]);
+
+    assert_eq!(list.select("li[2]/b").unwrap()[0].text(), "blue");
+    assert_eq!(list.css("b").unwrap()[0].path(), "/html/body/ul/li[2]/b");
+    assert_eq!(last.inner_html(), "Ink <b>blue</b>");
+    assert_eq!(first.outer_html(), first.as_selected().outer_html());
+    assert_eq!(first.inner_xml(), "Pen");
+    assert!(last.outer_xml().starts_with("<li"));
+    assert_eq!(
+        last.parse_in_context("<i>x</i>").unwrap().to_html(),
+        "<i>x</i>"
+    );
+    assert_eq!(format!("{first}"), format!("{}", first.as_selected()));
+}
+
+#[test]
+fn test_conversion() {
+    let doc = Arc::new(Document::parse(HTML).unwrap());
+    let selected = &doc.select("//p").unwrap()[0];
+    let owned = OwnedNode::new(&doc, selected).unwrap();
+    assert_eq!(owned.text(), "Footer");
+    assert_eq!(owned.as_selected().path(), selected.path());
+
+    // 其他文档的节点不能转换
+    let other = Arc::new(Document::parse(HTML).unwrap());
+    assert!(matches!(
+        OwnedNode::new(&other, selected),
+        Err(Error::Custom(_))
+    ));
+
+    // 命名空间查询
+    let feed = Arc::new(
+        Document::parse_xml(r#"<feed xmlns:g="urn:g"><g:item>1</g:item></feed>"#).unwrap(),
+    );
+    let root = feed.root_owned().unwrap();
+    let items = root.select_ns("g:item", &[("g", "urn:g")]).unwrap();
+    assert_eq!(items[0].text(), "1");
+}
+
+#[test]
+fn test_worker_pool() {
+    let doc = Arc::new(Document::parse(&"<p>row</p>".repeat(100)).unwrap());
+    let nodes = doc.select_owned("//p").unwrap();
+    drop(doc);
+
+    let handles: Vec<_> = nodes
+        .chunks(25)
+        .map(|chunk| {
+            let chunk = chunk.to_vec();
+            std::thread::spawn(move || chunk.iter().map(|p| p.text().len()).sum::<usize>())
+        })
+        .collect();
+    let total: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(total, 300);
+
+    // 最后一个句柄释放后文档才被释放
+    let document = Arc::clone(nodes[0].document());
+    assert_eq!(Arc::strong_count(&document), 101);
+    drop(nodes);
+    assert_eq!(Arc::strong_count(&document), 1);
+}
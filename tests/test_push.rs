@@ -0,0 +1,185 @@
+//! 推送式解析测试
+
+use rlibxml::{Document, Error, ParseOptions, PushParser, XmlParseOptions};
+
+const HTML: &str = r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>商品 &amp; 价格</title></head>
+<body><ul id="list"><li class="item">Pen — 1.50<li class="item">Ink 墨水</ul>
+<p>Trailing <b>bold</p></body></html>"#;
+
+const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<catalog xmlns:g="urn:g"><!-- 目录 --><product g:id="1"><name>Pen &amp; cap</name><price>1.50</price></product><product g:id="2"><name>墨水</name><![CDATA[<raw>]]></product></catalog>"#;
+
+fn push_html(chunks: impl IntoIterator<Item = impl AsRef<[u8]>>) -> Document {
+    let mut parser = PushParser::html(ParseOptions::default()).unwrap();
+    for chunk in chunks {
+        parser.feed(chunk.as_ref()).unwrap();
+    }
+    parser.finish().unwrap()
+}
+
+fn push_xml(chunks: impl IntoIterator<Item = impl AsRef<[u8]>>) -> Result<Document, Error> {
+    let mut parser = PushParser::xml(XmlParseOptions::default()).unwrap();
+    for chunk in chunks {
+        parser.feed(chunk.as_ref())?;
+    }
+    parser.finish()
+}
+
+#[test]
+fn test_html_matches_one_shot() {
+    let expected = Document::parse(HTML).unwrap().to_html();
+
+    // 一次性提供全部数据
+    assert_eq!(push_html([HTML]).to_html(), expected);
+    // 在每个字节处切开，包括多字节字符和实体的中间
+    assert_eq!(push_html(HTML.as_bytes().chunks(1)).to_html(), expected);
+    for size in [2, 3, 7, 64] {
+        assert_eq!(push_html(HTML.as_bytes().chunks(size)).to_html(), expected);
+    }
+}
+
+#[test]
+fn test_xml_matches_one_shot() {
+    let expected = Document::parse_xml(XML).unwrap().to_xml();
+    for size in [1, 2, 5, 1024] {
+        let doc = push_xml(XML.as_bytes().chunks(size)).unwrap();
+        assert_eq!(doc.to_xml(), expected);
+        assert!(doc.diagnostics().is_empty());
+    }
+}
+
+#[test]
+fn test_stop_after_head() {
+    let mut body = String::from("<html><head><title>Shop</title></head><body>");
+    body.push_str(&"<p>filler</p>".repeat(10_000));
+    body.push_str("</body></html>");
+
+    let mut parser = PushParser::html(ParseOptions::default()).unwrap();
+    let mut fed = 0;
+    for chunk in body.as_bytes().chunks(16) {
+        parser.feed(chunk).unwrap();
+        fed += chunk.len();
+        let root = parser.root().unwrap();
+        if !root.select("body").unwrap().is_empty() {
+            break;
+        }
+    }
+    assert!(fed < 100);
+
+    // 结束时只补全已经收到的内容
+    let doc = parser.finish().unwrap();
+    assert_eq!(doc.extract_string("string(//title)").unwrap(), "Shop");
+    assert!(doc.select("//p").unwrap().len() <= 1);
+}
+
+#[test]
+fn test_root_before_any_element() {
+    let mut parser = PushParser::xml(XmlParseOptions::default()).unwrap();
+    assert!(parser.root().is_none());
+    parser.feed(b"<?xml version=\"1.0\"?>\n").unwrap();
+    assert!(parser.root().is_none());
+    parser.feed(b"<feed><item>a</item>").unwrap();
+    let root = parser.root().unwrap();
+    assert_eq!(root.tag_name(), "feed");
+    assert_eq!(root.select("item").unwrap().len(), 1);
+}
+
+#[test]
+fn test_malformed_xml() {
+    let mut parser = PushParser::xml(XmlParseOptions::default()).unwrap();
+    parser.feed(b"<feed><item>a</item>").unwrap();
+    let err = parser.feed(b"<item>b</feed>").unwrap_err();
+    match err {
+        Error::ParseFailed { diagnostics, .. } => {
+            assert!(diagnostics.iter().any(|d| d.is_error()));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    // 致命错误之后继续提供数据仍然失败
+    assert!(parser.feed(b"<more/>").is_err());
+    assert!(!parser.diagnostics().is_empty());
+    assert!(matches!(parser.finish(), Err(Error::ParseFailed { .. })));
+
+    // 截断的 XML 在结束时报错
+    assert!(matches!(
+        push_xml(["<feed><item>"]),
+        Err(Error::ParseFailed { .. })
+    ));
+    // 没有任何数据
+    assert!(matches!(
+        push_xml(Vec::<&[u8]>::new()),
+        Err(Error::ParseFailed { .. })
+    ));
+}
+
+#[test]
+fn test_truncated_html() {
+    // HTML 提前结束时补全未闭合的元素，与一次性解析相同
+    let truncated = &HTML[..HTML.find("Ink").unwrap()];
+    let doc = push_html([truncated]);
+    assert_eq!(doc.to_html(), Document::parse(truncated).unwrap().to_html());
+    assert_eq!(doc.select("//li").unwrap().len(), 2);
+}
+
+#[test]
+fn test_options() {
+    let xml = "<feed>\n  <item>a</item>\n</feed>";
+    let options = XmlParseOptions {
+        no_blanks: true,
+        ..Default::default()
+    };
+    let mut parser = PushParser::xml(options).unwrap();
+    parser.feed(xml.as_bytes()).unwrap();
+    let doc = parser.finish().unwrap();
+    assert!(doc.to_xml().ends_with("\n<feed><item>a</item></feed>\n"));
+
+    // 默认不替换实体，与一次性解析一致
+    let xml = r#"<!DOCTYPE r [<!ENTITY e "EE">]><r>&e;</r>"#;
+    let doc = push_xml([xml]).unwrap();
+    assert_eq!(doc.to_xml(), Document::parse_xml(xml).unwrap().to_xml());
+}
+
+#[test]
+fn test_encodings() {
+    // 按 XML 声明转换 Latin-1
+    let latin1 = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><p>caf\xE9</p>";
+    let doc = push_xml(latin1.chunks(3)).unwrap();
+    assert_eq!(doc.root().unwrap().text(), "café");
+    assert_eq!(doc.encoding(), "windows-1252");
+
+    // 带 BOM 的 UTF-16LE XML
+    let mut utf16 = vec![0xFF, 0xFE];
+    for unit in "<p>日本</p>".encode_utf16() {
+        utf16.extend_from_slice(&unit.to_le_bytes());
+    }
+    let doc = push_xml(utf16.chunks(3)).unwrap();
+    assert_eq!(doc.root().unwrap().text(), "日本");
+
+    // HTML 总是按 UTF-8 解析，忽略 <meta> 声明
+    let html = "<meta charset=\"iso-8859-1\"><p>café</p>";
+    let doc = push_html(html.as_bytes().chunks(1));
+    assert_eq!(doc.extract_string("string(//p)").unwrap(), "café");
+
+    // 没有声明时按 UTF-8 处理
+    assert_eq!(push_html(["<p>x</p>"]).encoding(), "UTF-8");
+}
+
+#[test]
+fn test_parser_is_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<PushParser>();
+
+    let mut parser = PushParser::html(ParseOptions::default()).unwrap();
+    parser.feed(b"<p>moved").unwrap();
+    let doc = std::thread::spawn(move || {
+        parser.feed(b" across threads</p>").unwrap();
+        parser.finish().unwrap()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        doc.extract_string("string(//p)").unwrap(),
+        "moved across threads"
+    );
+}
@@ -0,0 +1,312 @@
+//! 流式读取器测试
+
+use rlibxml::{Error, ReaderEvent, XmlParseOptions, XmlReader};
+use std::io::{self, Read};
+
+/// 按需生成 `count` 条商品记录的数据源，不在内存中保留完整文档
+struct Catalog {
+    count: usize,
+    next: usize,
+    pending: Vec<u8>,
+    finished: bool,
+}
+
+impl Catalog {
+    fn new(count: usize) -> Self {
+        Self {
+            count,
+            next: 0,
+            pending: b"<catalog>\n".to_vec(),
+            finished: false,
+        }
+    }
+}
+
+impl Read for Catalog {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            if self.next < self.count {
+                self.next += 1;
+                self.pending = format!(
+                    "<product id=\"p{0}\"><name>Item {0}</name><price>{1}.25</price></product>\n",
+                    self.next,
+                    self.next % 10
+                )
+                .into_bytes();
+            } else if !self.finished {
+                self.finished = true;
+                self.pending = b"</catalog>".to_vec();
+            }
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// 每次只返回一个字节的数据源
+struct OneByte<'a>(&'a [u8]);
+
+impl Read for OneByte<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.take(1).read(buf).inspect(|&n| self.0 = &self.0[n..])
+    }
+}
+
+fn collect(reader: &mut XmlReader<impl Read>) -> Vec<ReaderEvent> {
+    let mut events = Vec::new();
+    while let Some(event) = reader.next_event().unwrap() {
+        events.push(event);
+    }
+    events
+}
+
+#[test]
+fn test_events() {
+    let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE feed>
+<feed xmlns:g="http://example.com/g" version="2"><!-- note --><?build fast?><item g:id="a" lang="en">Pen <![CDATA[& ink]]></item><empty/></feed>"#;
+    let mut reader = XmlReader::new(xml.as_bytes()).unwrap();
+    let events = collect(&mut reader);
+
+    assert_eq!(
+        events,
+        vec![
+            ReaderEvent::StartElement {
+                name: "feed".to_string(),
+                attributes: vec![("version".to_string(), "2".to_string())],
+                depth: 0,
+                is_empty: false,
+            },
+            ReaderEvent::Comment {
+                text: " note ".to_string(),
+                depth: 1,
+            },
+            ReaderEvent::ProcessingInstruction {
+                target: "build".to_string(),
+                data: "fast".to_string(),
+                depth: 1,
+            },
+            ReaderEvent::StartElement {
+                name: "item".to_string(),
+                attributes: vec![
+                    ("g:id".to_string(), "a".to_string()),
+                    ("lang".to_string(), "en".to_string()),
+                ],
+                depth: 1,
+                is_empty: false,
+            },
+            ReaderEvent::Text {
+                text: "Pen ".to_string(),
+                depth: 2,
+            },
+            ReaderEvent::Text {
+                text: "& ink".to_string(),
+                depth: 2,
+            },
+            ReaderEvent::EndElement {
+                name: "item".to_string(),
+                depth: 1,
+            },
+            ReaderEvent::StartElement {
+                name: "empty".to_string(),
+                attributes: vec![],
+                depth: 1,
+                is_empty: true,
+            },
+            ReaderEvent::EndElement {
+                name: "feed".to_string(),
+                depth: 0,
+            },
+        ]
+    );
+    assert!(reader.diagnostics().is_empty());
+    // 读到末尾后继续读取仍然返回 None
+    assert!(reader.next_event().unwrap().is_none());
+}
+
+#[test]
+fn test_expand_records() {
+    let mut reader = XmlReader::new(Catalog::new(20_000)).unwrap();
+    let mut count = 0;
+    let mut total = 0.0;
+    let mut last_name = String::new();
+    while let Some(event) = reader.next_event().unwrap() {
+        match event {
+            ReaderEvent::StartElement { name, depth, .. } if name == "product" => {
+                assert_eq!(depth, 1);
+                let product = reader.expand().unwrap();
+                total += product
+                    .select("price")
+                    .unwrap()
+                    .iter()
+                    .map(|price| price.text().parse::<f64>().unwrap())
+                    .sum::<f64>();
+                last_name = product.select("name").unwrap()[0].text();
+                count += 1;
+            }
+            // 展开过的记录被整体跳过，不会出现其内部的事件
+            ReaderEvent::StartElement { name, .. } => assert_eq!(name, "catalog"),
+            ReaderEvent::EndElement { name, .. } => assert_eq!(name, "catalog"),
+            _ => {}
+        }
+    }
+    assert_eq!(count, 20_000);
+    assert_eq!(total, 2_000.0 * 45.0 + 20_000.0 * 0.25);
+    assert_eq!(last_name, "Item 20000");
+}
+
+#[test]
+fn test_skip_and_expand_errors() {
+    let xml = r#"<feed><skip><deep>x</deep></skip><keep a="1">y</keep></feed>"#;
+    let mut reader = XmlReader::new(xml.as_bytes()).unwrap();
+
+    reader.next_event().unwrap();
+    reader.next_event().unwrap();
+    reader.skip();
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(ReaderEvent::StartElement {
+            name: "keep".to_string(),
+            attributes: vec![("a".to_string(), "1".to_string())],
+            depth: 1,
+            is_empty: false,
+        })
+    );
+
+    reader.next_event().unwrap();
+    assert!(matches!(reader.expand(), Err(Error::Custom(_))));
+}
+
+#[test]
+fn test_malformed_input() {
+    // 读取器按块预读，错误放在足够靠后的位置
+    let mut xml = "<feed>\n".to_string();
+    xml.push_str(&"<item>a</item>\n".repeat(500));
+    xml.push_str("<item>b</feed>");
+    let mut reader = XmlReader::new(xml.as_bytes()).unwrap();
+
+    let mut seen = 0;
+    let err = loop {
+        match reader.next_event() {
+            Ok(Some(_)) => seen += 1,
+            Ok(None) => panic!("malformed input should fail"),
+            Err(e) => break e,
+        }
+    };
+    // 出错前的事件照常产生
+    assert!(seen > 100);
+    match err {
+        Error::ParseFailed { diagnostics, .. } => {
+            assert!(diagnostics.iter().any(|d| d.is_error() && d.line == 502));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // 截断的输入同样报错
+    let mut reader = XmlReader::new("<feed><item>".as_bytes()).unwrap();
+    let err = std::iter::from_fn(|| reader.next_event().transpose())
+        .find_map(|event| event.err())
+        .unwrap();
+    assert!(matches!(err, Error::ParseFailed { .. }));
+}
+
+#[test]
+fn test_io_error() {
+    let source = "<feed><item>a</item>".as_bytes().chain(FailingRead);
+    let mut reader = XmlReader::new(source).unwrap();
+    let err = std::iter::from_fn(|| reader.next_event().transpose())
+        .find_map(|event| event.err())
+        .unwrap();
+    match err {
+        Error::Io { kind, message } => {
+            assert_eq!(kind, io::ErrorKind::ConnectionReset);
+            assert!(message.contains("feed dropped"));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+struct FailingRead;
+
+impl Read for FailingRead {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "feed dropped",
+        ))
+    }
+}
+
+#[test]
+fn test_small_reads_and_encodings() {
+    // 多字节字符被拆分到不同的 read 调用中
+    let xml = "<p title=\"日本\">café 中文</p>";
+    let mut reader = XmlReader::new(OneByte(xml.as_bytes())).unwrap();
+    let events = collect(&mut reader);
+    assert_eq!(
+        events[0],
+        ReaderEvent::StartElement {
+            name: "p".to_string(),
+            attributes: vec![("title".to_string(), "日本".to_string())],
+            depth: 0,
+            is_empty: false,
+        }
+    );
+    assert_eq!(
+        events[1],
+        ReaderEvent::Text {
+            text: "café 中文".to_string(),
+            depth: 1,
+        }
+    );
+
+    // 按 XML 声明转换 Latin-1
+    let latin1 = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><p>caf\xE9</p>";
+    let mut reader = XmlReader::new(latin1.as_slice()).unwrap();
+    let events = collect(&mut reader);
+    assert_eq!(
+        events[1],
+        ReaderEvent::Text {
+            text: "café".to_string(),
+            depth: 1,
+        }
+    );
+}
+
+#[test]
+fn test_options() {
+    let xml = "<feed>\n  <item/>\n</feed>";
+
+    let mut reader = XmlReader::new(xml.as_bytes()).unwrap();
+    let texts = collect(&mut reader)
+        .into_iter()
+        .filter(|e| matches!(e, ReaderEvent::Text { .. }))
+        .count();
+    assert_eq!(texts, 2);
+
+    let options = XmlParseOptions {
+        no_blanks: true,
+        ..Default::default()
+    };
+    let mut reader = XmlReader::with_options(xml.as_bytes(), options).unwrap();
+    assert!(
+        !collect(&mut reader)
+            .iter()
+            .any(|e| matches!(e, ReaderEvent::Text { .. }))
+    );
+}
+
+#[test]
+#[should_panic(expected = "source exploded")]
+fn test_panic_in_source_propagates() {
+    struct Exploding;
+    impl Read for Exploding {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            panic!("source exploded");
+        }
+    }
+    let mut reader = XmlReader::new(Exploding).unwrap();
+    let _ = reader.next_event();
+}
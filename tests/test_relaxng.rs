@@ -0,0 +1,83 @@
+//! RELAX NG 验证测试
+#![cfg(feature = "schemas")]
+
+use rlibxml::{Document, Error, RelaxNg};
+use std::sync::Arc;
+
+const RNG: &str = r#"<?xml version="1.0"?>
+<element name="feed" xmlns="http://relaxng.org/ns/structure/1.0"
+         datatypeLibrary="http://www.w3.org/2001/XMLSchema-datatypes">
+    <oneOrMore>
+        <element name="item">
+            <attribute name="id"><data type="ID"/></attribute>
+            <element name="sku"><text/></element>
+            <element name="price"><data type="decimal"/></element>
+        </element>
+    </oneOrMore>
+</element>"#;
+
+const VALID: &str = r#"<feed>
+<item id="a"><sku>AB-1</sku><price>12.50</price></item>
+<item id="b"><sku>CD-2</sku><price>3</price></item>
+</feed>"#;
+
+const INVALID: &str = r#"<feed>
+<item id="a"><sku>AB-1</sku><price>12.50</price></item>
+<item id="b"><sku>CD-2</sku><price>free</price></item>
+</feed>"#;
+
+#[test]
+fn test_valid_document() {
+    let rng = RelaxNg::from_str(RNG).unwrap();
+    let mut doc = Document::parse_xml(VALID).unwrap();
+    assert!(rng.validate(&mut doc).unwrap().is_empty());
+}
+
+#[test]
+fn test_validation_errors() {
+    let rng: RelaxNg = RNG.parse().unwrap();
+    let mut doc = Document::parse_xml(INVALID).unwrap();
+
+    let errors = rng.validate(&mut doc).unwrap();
+    assert!(!errors.is_empty());
+    assert_eq!(errors[0].line, 3);
+    assert_eq!(errors[0].path.as_deref(), Some("/feed/item[2]/price"));
+    assert!(errors[0].message.contains("decimal"));
+    assert!(errors.iter().all(|e| e.assertion.is_none()));
+}
+
+#[test]
+fn test_invalid_grammar() {
+    let bad = r#"<element name="feed" xmlns="http://relaxng.org/ns/structure/1.0">
+        <ref name="undefined"/>
+    </element>"#;
+    match RelaxNg::from_str(bad) {
+        Err(Error::InvalidSchema { errors }) => {
+            assert!(errors.iter().any(|e| e.message.contains("undefined")));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(matches!(
+        RelaxNg::from_file("/nonexistent/feed.rng"),
+        Err(Error::InvalidSchema { .. })
+    ));
+}
+
+#[test]
+fn test_shared_across_threads() {
+    let rng = Arc::new(RelaxNg::from_str(RNG).unwrap());
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let rng = Arc::clone(&rng);
+            std::thread::spawn(move || {
+                let input = if i % 2 == 0 { VALID } else { INVALID };
+                let mut doc = Document::parse_xml(input).unwrap();
+                rng.validate(&mut doc).unwrap().is_empty()
+            })
+        })
+        .collect();
+
+    let valid: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(valid, [true, false, true, false]);
+}
@@ -0,0 +1,306 @@
+//! SAX 事件处理器测试
+
+use rlibxml::{
+    Attributes, Diagnostic, Error, ParseOptions, QName, SaxHandler, XmlParseOptions,
+    sax_parse_html, sax_parse_xml,
+};
+use std::ops::ControlFlow;
+
+/// 把事件记录为字符串，便于断言
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    errors: Vec<Diagnostic>,
+    stop_at: Option<&'static str>,
+    stop_on_error: bool,
+}
+
+impl SaxHandler for Recorder {
+    fn start_element(&mut self, name: QName<'_>, attributes: Attributes<'_>) -> ControlFlow<()> {
+        let attrs: Vec<String> = attributes
+            .iter()
+            .map(|attr| format!(" {}={}", attr.name, attr.value))
+            .collect();
+        self.events.push(format!("<{}{}>", name, attrs.concat()));
+        if self.stop_at == Some(name.local_name) {
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn end_element(&mut self, name: QName<'_>) -> ControlFlow<()> {
+        self.events.push(format!("</{}>", name));
+        ControlFlow::Continue(())
+    }
+
+    fn characters(&mut self, text: &str) -> ControlFlow<()> {
+        // 相邻的文本回调合并为一个事件
+        match self.events.last_mut() {
+            Some(last) if last.starts_with('"') => {
+                last.pop();
+                last.push_str(text);
+                last.push('"');
+            }
+            _ => self.events.push(format!("\"{}\"", text)),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn comment(&mut self, text: &str) -> ControlFlow<()> {
+        self.events.push(format!("<!--{}-->", text));
+        ControlFlow::Continue(())
+    }
+
+    fn processing_instruction(&mut self, target: &str, data: &str) -> ControlFlow<()> {
+        self.events.push(format!("<?{} {}?>", target, data));
+        ControlFlow::Continue(())
+    }
+
+    fn error(&mut self, diagnostic: &Diagnostic) -> ControlFlow<()> {
+        self.errors.push(diagnostic.clone());
+        if self.stop_on_error {
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn test_xml_events() {
+    let xml = r#"<!DOCTYPE feed [
+  <!ENTITY brand "Acme">
+  <!ATTLIST item currency CDATA "EUR">
+]>
+<feed xmlns="urn:feed" xmlns:g="urn:g"><?build fast?><!-- generated --><item g:id="a&amp;b" name="&brand;">Pen &amp; <![CDATA[<ink>]]> by &brand;</item><g:empty/></feed>"#;
+    let mut recorder = Recorder::default();
+    let flow = sax_parse_xml(xml, XmlParseOptions::default(), &mut recorder).unwrap();
+
+    assert!(flow.is_continue());
+    assert!(recorder.errors.is_empty());
+    assert_eq!(
+        recorder.events,
+        vec![
+            "<feed>",
+            "<?build fast?>",
+            "<!-- generated -->",
+            "<item g:id=a&b name=Acme currency=EUR>",
+            "\"Pen & <ink> by Acme\"",
+            "</item>",
+            "<g:empty>",
+            "</g:empty>",
+            "</feed>",
+        ]
+    );
+}
+
+#[test]
+fn test_namespaces() {
+    struct Names(Vec<(String, Option<String>)>);
+
+    impl SaxHandler for Names {
+        fn start_element(
+            &mut self,
+            name: QName<'_>,
+            attributes: Attributes<'_>,
+        ) -> ControlFlow<()> {
+            self.0.push((
+                name.local_name.to_string(),
+                name.namespace.map(str::to_string),
+            ));
+            for attr in attributes.iter() {
+                self.0.push((
+                    format!("@{}", attr.name.local_name),
+                    attr.name.namespace.map(str::to_string),
+                ));
+            }
+            assert_eq!(attributes.get("g:id").is_some(), name.local_name == "item");
+            ControlFlow::Continue(())
+        }
+    }
+
+    let xml = r#"<feed xmlns="urn:feed" xmlns:g="urn:g"><item g:id="1" plain="x"/></feed>"#;
+    let mut names = Names(Vec::new());
+    assert!(
+        sax_parse_xml(xml, XmlParseOptions::default(), &mut names)
+            .unwrap()
+            .is_continue()
+    );
+    assert_eq!(
+        names.0,
+        vec![
+            ("feed".to_string(), Some("urn:feed".to_string())),
+            ("item".to_string(), Some("urn:feed".to_string())),
+            ("@id".to_string(), Some("urn:g".to_string())),
+            ("@plain".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn test_html_events() {
+    let html = r#"<p class="lead" hidden>Fish &amp; <b>chips</p><script>if (a < b) go()</script>"#;
+    let mut recorder = Recorder::default();
+    let flow = sax_parse_html(html, ParseOptions::default(), &mut recorder).unwrap();
+
+    assert!(flow.is_continue());
+    assert_eq!(
+        recorder.events,
+        vec![
+            "<html>",
+            "<body>",
+            "<p class=lead hidden=>",
+            "\"Fish & \"",
+            "<b>",
+            "\"chips\"",
+            "</b>",
+            "</p>",
+            "<script>",
+            "\"if (a < b) go()\"",
+            "</script>",
+            "</body>",
+            "</html>",
+        ]
+    );
+    // 标签不匹配被报告给处理器，但解析照常完成
+    assert!(recorder.errors.iter().any(|d| d.message.contains("b")));
+}
+
+#[test]
+fn test_stop_early() {
+    let xml = "<feed><item>1</item><stop/><item>2</item></feed>";
+    let mut recorder = Recorder {
+        stop_at: Some("stop"),
+        ..Default::default()
+    };
+    let flow = sax_parse_xml(xml, XmlParseOptions::default(), &mut recorder).unwrap();
+    assert!(flow.is_break());
+    assert_eq!(recorder.events.last().unwrap(), "<stop>");
+    assert_eq!(recorder.events.len(), 5);
+    assert!(recorder.errors.is_empty());
+
+    let mut recorder = Recorder {
+        stop_at: Some("li"),
+        ..Default::default()
+    };
+    let flow = sax_parse_html(
+        "<ul><li>a<li>b</ul>",
+        ParseOptions::default(),
+        &mut recorder,
+    )
+    .unwrap();
+    assert!(flow.is_break());
+    assert_eq!(recorder.events.iter().filter(|e| *e == "<li>").count(), 1);
+}
+
+#[test]
+fn test_malformed_xml() {
+    let xml = "<feed><item>a</item><item>b</feed>";
+
+    let mut recorder = Recorder::default();
+    let err = sax_parse_xml(xml, XmlParseOptions::default(), &mut recorder).unwrap_err();
+    let Error::ParseFailed { diagnostics, .. } = err else {
+        panic!("unexpected error: {err:?}");
+    };
+    assert!(!diagnostics.is_empty());
+    assert_eq!(recorder.errors, diagnostics);
+    // 出错之前的事件已经报告
+    assert!(recorder.events.contains(&"</item>".to_string()));
+
+    // 处理器可以在第一条错误处中止
+    let mut recorder = Recorder {
+        stop_on_error: true,
+        ..Default::default()
+    };
+    let flow = sax_parse_xml(xml, XmlParseOptions::default(), &mut recorder).unwrap();
+    assert!(flow.is_break());
+    assert_eq!(recorder.errors.len(), 1);
+}
+
+#[test]
+fn test_external_entities_not_loaded() {
+    let dir = std::env::temp_dir().join(format!("rlibxml-sax-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let secret = dir.join("secret.txt");
+    std::fs::write(&secret, "top secret").unwrap();
+
+    let xml = format!(
+        r#"<!DOCTYPE r [<!ENTITY ext SYSTEM "{}">]><r>&ext;</r>"#,
+        secret.display()
+    );
+    let mut recorder = Recorder::default();
+    assert!(
+        sax_parse_xml(&xml, XmlParseOptions::default(), &mut recorder)
+            .unwrap()
+            .is_continue()
+    );
+    assert!(!recorder.events.iter().any(|e| e.contains("top secret")));
+
+    let options = XmlParseOptions {
+        no_ent: false,
+        ..Default::default()
+    };
+    let mut recorder = Recorder::default();
+    assert!(
+        sax_parse_xml(&xml, options, &mut recorder)
+            .unwrap()
+            .is_continue()
+    );
+    assert!(recorder.events.iter().any(|e| e.contains("top secret")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_no_blanks() {
+    let xml = "<feed>\n  <item>a</item>\n</feed>";
+
+    let mut recorder = Recorder::default();
+    assert!(
+        sax_parse_xml(xml, XmlParseOptions::default(), &mut recorder)
+            .unwrap()
+            .is_continue()
+    );
+    assert_eq!(recorder.events.len(), 7);
+
+    let options = XmlParseOptions {
+        no_blanks: true,
+        ..Default::default()
+    };
+    let mut recorder = Recorder::default();
+    assert!(
+        sax_parse_xml(xml, options, &mut recorder)
+            .unwrap()
+            .is_continue()
+    );
+    assert_eq!(
+        recorder.events,
+        vec!["<feed>", "<item>", "\"a\"", "</item>", "</feed>"]
+    );
+}
+
+#[test]
+fn test_declared_encoding_ignored() {
+    for label in ["ISO-8859-1", "GBK"] {
+        let xml = format!(r#"<?xml version="1.0" encoding="{label}"?><p>café 中文</p>"#);
+        let mut recorder = Recorder::default();
+        assert!(
+            sax_parse_xml(&xml, XmlParseOptions::default(), &mut recorder)
+                .unwrap()
+                .is_continue()
+        );
+        assert_eq!(recorder.events, vec!["<p>", "\"café 中文\"", "</p>"]);
+        assert!(recorder.errors.is_empty());
+    }
+}
+
+#[test]
+#[should_panic(expected = "handler exploded")]
+fn test_panic_in_handler_propagates() {
+    struct Exploding;
+    impl SaxHandler for Exploding {
+        fn characters(&mut self, _text: &str) -> ControlFlow<()> {
+            panic!("handler exploded");
+        }
+    }
+    let _ = sax_parse_xml("<a>text</a>", XmlParseOptions::default(), &mut Exploding);
+}
@@ -0,0 +1,149 @@
+//! XML Schema 验证测试
+#![cfg(feature = "schemas")]
+
+use rlibxml::{DiagnosticLevel, Document, Error, Schema};
+use std::sync::Arc;
+
+const XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:element name="feed">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="item" maxOccurs="unbounded">
+                    <xs:complexType>
+                        <xs:sequence>
+                            <xs:element name="sku" type="xs:string"/>
+                            <xs:element name="price" type="xs:decimal"/>
+                        </xs:sequence>
+                        <xs:attribute name="id" type="xs:ID" use="required"/>
+                    </xs:complexType>
+                </xs:element>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+
+const VALID: &str = r#"<feed>
+<item id="a"><sku>AB-1</sku><price>12.50</price></item>
+<item id="b"><sku>CD-2</sku><price>3</price></item>
+</feed>"#;
+
+const INVALID: &str = r#"<feed>
+<item id="a"><sku>AB-1</sku><price>12.50</price></item>
+<item><sku>CD-2</sku><price>free</price></item>
+<item id="c"><price>3</price></item>
+</feed>"#;
+
+#[test]
+fn test_valid_document() {
+    let schema = Schema::from_str(XSD).unwrap();
+    let mut doc = Document::parse_xml(VALID).unwrap();
+    assert!(doc.validate(&schema).unwrap().is_empty());
+}
+
+#[test]
+fn test_validation_errors() {
+    let schema: Schema = XSD.parse().unwrap();
+    let mut doc = Document::parse_xml(INVALID).unwrap();
+
+    let errors = doc.validate(&schema).unwrap();
+    let summary: Vec<_> = errors
+        .iter()
+        .map(|e| (e.line, e.path.as_deref().unwrap()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (3, "/feed/item[2]"),
+            (3, "/feed/item[2]/price"),
+            (4, "/feed/item[3]/price"),
+        ]
+    );
+    assert!(errors.iter().all(|e| e.level == DiagnosticLevel::Error));
+    assert!(errors[0].message.contains("'id' is required"));
+    assert!(errors[1].message.contains("xs:decimal"));
+    assert!(errors[2].message.contains("Expected is ( sku )"));
+    assert!(
+        errors[1]
+            .to_string()
+            .starts_with("3: /feed/item[2]/price: error: ")
+    );
+
+    // 同一文档可以重复验证
+    assert_eq!(doc.validate(&schema).unwrap().len(), 3);
+}
+
+#[test]
+fn test_invalid_schema() {
+    let bad = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+        <xs:element name="feed" type="undefined"/>
+    </xs:schema>"#;
+    match Schema::from_str(bad) {
+        Err(Error::InvalidSchema { errors }) => {
+            assert_eq!(errors[0].line, 2);
+            assert_eq!(errors[0].path.as_deref(), Some("/xs:schema/xs:element"));
+            assert!(errors[0].message.contains("undefined"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    assert!(matches!(
+        Schema::from_str("<not-closed>"),
+        Err(Error::InvalidSchema { .. })
+    ));
+    assert!(matches!(
+        Schema::from_file("/nonexistent/feed.xsd"),
+        Err(Error::InvalidSchema { .. })
+    ));
+}
+
+#[test]
+fn test_from_file_with_include() {
+    let dir = std::env::temp_dir().join(format!("rlibxml-schema-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("types.xsd"),
+        r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+            <xs:simpleType name="sku">
+                <xs:restriction base="xs:string"><xs:pattern value="[A-Z]{2}-\d+"/></xs:restriction>
+            </xs:simpleType>
+        </xs:schema>"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("main.xsd"),
+        r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+            <xs:include schemaLocation="types.xsd"/>
+            <xs:element name="sku" type="sku"/>
+        </xs:schema>"#,
+    )
+    .unwrap();
+
+    let schema = Schema::from_file(dir.join("main.xsd"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let schema = schema.unwrap();
+
+    let mut doc = Document::parse_xml("<sku>AB-12</sku>").unwrap();
+    assert!(doc.validate(&schema).unwrap().is_empty());
+    let mut doc = Document::parse_xml("<sku>ab12</sku>").unwrap();
+    assert_eq!(doc.validate(&schema).unwrap().len(), 1);
+}
+
+#[test]
+fn test_schema_shared_across_threads() {
+    let schema = Arc::new(Schema::from_str(XSD).unwrap());
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let schema = Arc::clone(&schema);
+            std::thread::spawn(move || {
+                let input = if i % 2 == 0 { VALID } else { INVALID };
+                let mut doc = Document::parse_xml(input).unwrap();
+                doc.validate(&schema).unwrap().len()
+            })
+        })
+        .collect();
+
+    let counts: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(counts, [0, 3, 0, 3]);
+}
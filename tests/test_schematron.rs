@@ -0,0 +1,99 @@
+//! Schematron 验证测试
+#![cfg(feature = "schemas")]
+
+use rlibxml::{DiagnosticLevel, Document, Error, Schematron};
+use std::sync::Arc;
+
+const RULES: &str = r#"<?xml version="1.0"?>
+<schema xmlns="http://purl.oclc.org/dsdl/schematron">
+    <pattern id="items">
+        <rule context="item">
+            <assert test="@sku">Item must have a SKU</assert>
+            <assert test="number(price) &gt; 0">Price must be positive</assert>
+            <report test="@discontinued">Item is discontinued</report>
+        </rule>
+    </pattern>
+</schema>"#;
+
+const FEED: &str = r#"<feed>
+<item sku="a"><price>12.50</price></item>
+<item><price>0</price></item>
+<item sku="c" discontinued="yes"><price>3</price></item>
+</feed>"#;
+
+#[test]
+fn test_assertions() {
+    let rules = Schematron::from_str(RULES).unwrap();
+    let doc = Document::parse_xml(FEED).unwrap();
+
+    let errors = rules.validate(&doc).unwrap();
+    let summary: Vec<_> = errors
+        .iter()
+        .map(|e| {
+            (
+                e.line,
+                e.path.as_deref().unwrap(),
+                e.assertion.as_deref().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (3, "/feed/item[2]", "Item must have a SKU"),
+            (3, "/feed/item[2]", "Price must be positive"),
+            (4, "/feed/item[3]", "Item is discontinued"),
+        ]
+    );
+    assert!(errors.iter().all(|e| e.level == DiagnosticLevel::Error));
+    assert_eq!(
+        errors[0].to_string(),
+        "3: /feed/item[2]: error: Item must have a SKU"
+    );
+
+    let doc = Document::parse_xml(r#"<feed><item sku="a"><price>1</price></item></feed>"#).unwrap();
+    assert!(rules.validate(&doc).unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_rules() {
+    for bad in [
+        r#"<schema xmlns="http://purl.oclc.org/dsdl/schematron"/>"#,
+        r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"/>"#,
+        "<schema",
+    ] {
+        match Schematron::from_str(bad) {
+            Err(Error::InvalidSchema { errors }) => assert!(!errors.is_empty(), "{bad}"),
+            other => panic!("unexpected result for {bad}: {other:?}"),
+        }
+    }
+    assert!(matches!(
+        Schematron::from_file("/nonexistent/rules.sch"),
+        Err(Error::InvalidSchema { .. })
+    ));
+
+    // libxml2 能编译没有 id 的 pattern，但验证时会失败
+    let anonymous = Schematron::from_str(&RULES.replace(r#" id="items""#, "")).unwrap();
+    let doc = Document::parse_xml(FEED).unwrap();
+    match anonymous.validate(&doc) {
+        Err(Error::ValidationFailed { validator, .. }) => assert_eq!(validator, "Schematron"),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_shared_across_threads() {
+    let rules = Arc::new(Schematron::from_str(RULES).unwrap());
+    let doc = Arc::new(Document::parse_xml(FEED).unwrap());
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let rules = Arc::clone(&rules);
+            let doc = Arc::clone(&doc);
+            std::thread::spawn(move || rules.validate(&doc).unwrap().len())
+        })
+        .collect();
+
+    let counts: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(counts, [3, 3, 3, 3]);
+}
@@ -0,0 +1,96 @@
+//! 序列化测试
+
+use rlibxml::Document;
+
+#[test]
+fn test_outer_html_preserves_attribute_order() {
+    let doc = Document::parse(r#"<div z="1" a="2" m="3">x</div>"#).unwrap();
+    let div = &doc.select("//div").unwrap()[0];
+
+    assert_eq!(div.outer_html(), r#"<div z="1" a="2" m="3">x</div>"#);
+}
+
+#[test]
+fn test_outer_html_escapes_text_and_attributes() {
+    let doc =
+        Document::parse(r#"<p title="a &quot;b&quot; &amp; c">1 &lt; 2 &amp;&amp; 3 &gt; 2</p>"#)
+            .unwrap();
+    let p = &doc.select("//p").unwrap()[0];
+
+    assert_eq!(p.text(), "1 < 2 && 3 > 2");
+    // 属性值包含双引号时改用单引号包裹
+    assert_eq!(
+        p.outer_html(),
+        r#"<p title='a "b" &amp; c'>1 &lt; 2 &amp;&amp; 3 &gt; 2</p>"#
+    );
+}
+
+#[test]
+fn test_void_elements_and_comments() {
+    let doc =
+        Document::parse(r#"<div><img src="a.png"><br><!-- note --><input type="text"></div>"#)
+            .unwrap();
+    let div = &doc.select("//div").unwrap()[0];
+
+    let html = div.outer_html();
+    assert_eq!(
+        html,
+        r#"<div><img src="a.png"><br><!-- note --><input type="text"></div>"#
+    );
+    assert!(!html.contains("</br>"));
+    assert!(!html.contains("</img>"));
+}
+
+#[test]
+fn test_inner_html_of_text_node_is_escaped() {
+    let doc = Document::parse("<p>a &amp; b</p>").unwrap();
+    let text = &doc.select("//p/text()").unwrap()[0];
+
+    assert_eq!(text.outer_html(), "a &amp; b");
+}
+
+#[test]
+fn test_unicode_not_escaped() {
+    let doc = Document::parse("<p>中文 é</p>").unwrap();
+    let p = &doc.select("//p").unwrap()[0];
+
+    assert_eq!(p.outer_html(), "<p>中文 é</p>");
+    assert!(doc.to_html().contains("中文 é"));
+}
+
+#[test]
+fn test_document_to_html_round_trip() {
+    let html = r#"<!DOCTYPE html>
+<html><head><title>T</title></head><body><p class="x">a &lt; b<br></p><!-- c --></body></html>
+"#;
+    let doc = Document::parse(html).unwrap();
+    let out = doc.to_html();
+
+    assert!(out.starts_with("<!DOCTYPE html>"));
+    assert!(out.contains(r#"<p class="x">a &lt; b<br></p><!-- c -->"#));
+
+    // 再次解析得到相同的结果
+    let reparsed = Document::parse(&out).unwrap();
+    assert_eq!(reparsed.to_html(), out);
+}
+
+#[test]
+fn test_xml_serialization() {
+    let xml = r#"<?xml version="1.0"?>
+<root><!-- c --><data><![CDATA[a < b]]></data><?proc value?><empty/></root>"#;
+    let doc = Document::parse_xml(xml).unwrap();
+
+    let root = doc.root().unwrap();
+    assert_eq!(
+        root.outer_xml(),
+        "<root><!-- c --><data><![CDATA[a < b]]></data><?proc value?><empty/></root>"
+    );
+    assert_eq!(
+        root.inner_xml(),
+        "<!-- c --><data><![CDATA[a < b]]></data><?proc value?><empty/>"
+    );
+
+    let out = doc.to_xml();
+    assert!(out.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert!(out.contains("<![CDATA[a < b]]>"));
+}
@@ -1,6 +1,6 @@
 //! 节点遍历测试
 
-use rlibxml::Document;
+use rlibxml::{Document, Edge, NodeType, SelectedNode, Visit, Visitor};
 
 #[test]
 fn test_traversal() {
@@ -83,3 +83,268 @@ fn test_text_children() {
     // 文本子节点数量取决于解析器如何处理空白
     assert!(!texts.is_empty());
 }
+
+/// 节点路径列表，用于比较迭代器与 XPath 轴的结果
+fn paths<'a>(nodes: impl IntoIterator<Item = rlibxml::SelectedNode<'a>>) -> Vec<String> {
+    nodes.into_iter().map(|n| n.path()).collect()
+}
+
+/// 对每个节点（包括属性）比较惰性迭代器与同名 XPath 轴
+fn assert_axes_match(doc: &Document) {
+    for node in doc.select("//node() | //@*").unwrap() {
+        let context = node.path();
+        let axis = |name: &str| {
+            let xpath = format!("{name}::node()");
+            paths(
+                node.select(&xpath)
+                    .unwrap_or_else(|e| panic!("{context}: {e}")),
+            )
+        };
+        let reversed = |name: &str| {
+            let mut result = axis(name);
+            result.reverse();
+            result
+        };
+
+        assert_eq!(paths(node.descendants()), axis("descendant"), "{context}");
+        assert_eq!(paths(node.following()), axis("following"), "{context}");
+        assert_eq!(paths(node.preceding()), reversed("preceding"), "{context}");
+        assert_eq!(
+            paths(node.following_siblings()),
+            axis("following-sibling"),
+            "{context}"
+        );
+        assert_eq!(
+            paths(node.preceding_siblings()),
+            reversed("preceding-sibling"),
+            "{context}"
+        );
+        let mut ancestors = paths(node.select("ancestor::*").unwrap());
+        ancestors.reverse();
+        assert_eq!(paths(node.ancestors()), ancestors, "{context}");
+    }
+}
+
+#[test]
+fn test_lazy_axes_match_xpath() {
+    let html = r#"<!DOCTYPE html><!-- head comment -->
+        <div id="a">text <p class="x">A <b>bold</b> tail</p><!-- c --><p>B</p></div>
+        <ul><li>1</li><li>2<span>s</span></li></ul>
+        trailing"#;
+    assert_axes_match(&Document::parse(html).unwrap());
+
+    let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE root [<!ELEMENT root ANY>]>
+<root a="1" b="2"><x>x<y z="3"/></x><!-- c --><?pi data?><w/>tail</root>
+<!-- after -->"#;
+    assert_axes_match(&Document::parse_xml(xml).unwrap());
+
+    // 实体引用节点及其内容不出现在各个轴中
+    // （libxml2 的 following / preceding 轴在实体引用附近的结果不完整，这里只比较后代和兄弟）
+    let xml = r#"<!DOCTYPE root [<!ENTITY e "entity <i>text</i>">]><root><x/>&e;<y/></root>"#;
+    let doc = Document::parse_xml(xml).unwrap();
+    let root = &doc.select("/root").unwrap()[0];
+    assert_eq!(
+        paths(root.descendants()),
+        paths(root.select("descendant::node()").unwrap())
+    );
+    assert_eq!(paths(root.descendants()), ["/root/x", "/root/y"]);
+    let y = &doc.select("/root/y").unwrap()[0];
+    assert_eq!(paths(y.preceding_siblings()), ["/root/x"]);
+    assert_eq!(paths(y.preceding()), ["/root/x"]);
+    assert_eq!(root.children_iter().count(), 3);
+}
+
+#[test]
+fn test_lazy_iterators() {
+    let doc = Document::parse("<ul><li>1</li><li>2</li><li>3</li></ul>").unwrap();
+    let ul = &doc.select("//ul").unwrap()[0];
+
+    // 迭代器与对应的 Vec 方法一致
+    assert_eq!(paths(ul.children_iter()), paths(ul.children()));
+    assert_eq!(ul.children_iter().count(), ul.child_count());
+
+    // 提前结束
+    let second = ul.children_iter().nth(1).unwrap();
+    assert_eq!(second.text(), "2");
+    assert_eq!(second.following_siblings().next().unwrap().text(), "3");
+    assert_eq!(second.preceding_siblings().next().unwrap().text(), "1");
+    assert_eq!(
+        paths(second.ancestors()),
+        ["/html/body/ul", "/html/body", "/html"]
+    );
+
+    // 迭代器耗尽后保持为空，可以克隆
+    let mut children = ul.children_iter();
+    let copy = children.clone();
+    assert_eq!(children.by_ref().count(), 3);
+    assert!(children.next().is_none());
+    assert_eq!(copy.count(), 3);
+
+    // 叶子和根
+    let text = &doc.select("//li[1]/text()").unwrap()[0];
+    assert_eq!(text.descendants().count(), 0);
+    assert_eq!(text.children_iter().count(), 0);
+    assert_eq!(doc.root().unwrap().ancestors().count(), 0);
+}
+
+#[test]
+fn test_select_first() {
+    let doc = Document::parse(r#"<p>A</p><div><p>B</p><p>C</p></div>"#).unwrap();
+
+    assert_eq!(doc.select_first("//p").unwrap().unwrap().text(), "A");
+    assert_eq!(
+        doc.select_first("//div/p | //p[1]")
+            .unwrap()
+            .unwrap()
+            .text(),
+        "A"
+    );
+    assert!(doc.select_first("//h1").unwrap().is_none());
+    // 非节点集合结果
+    assert!(doc.select_first("count(//p)").unwrap().is_none());
+    // 无效表达式报告原表达式
+    for xpath in ["//p[", "//p[foo()]", "//p[@a = $missing]"] {
+        match doc.select_first(xpath).unwrap_err() {
+            rlibxml::Error::InvalidXPath {
+                xpath: reported, ..
+            } => assert_eq!(reported, xpath),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+    // 括号不配对的表达式不能借助外层括号变得有效
+    assert!(doc.select_first("//p) | (//div").is_err());
+    assert_eq!(
+        doc.select_first("//p[. = ')']").unwrap().map(|p| p.text()),
+        None
+    );
+
+    let div = &doc.select("//div").unwrap()[0];
+    assert_eq!(div.select_first("p").unwrap().unwrap().text(), "B");
+    assert_eq!(div.select_first("p[last()]").unwrap().unwrap().text(), "C");
+    assert!(div.select_first("h1").unwrap().is_none());
+    match div.select_first("p[foo()]").unwrap_err() {
+        rlibxml::Error::InvalidXPath { xpath, .. } => assert_eq!(xpath, "p[foo()]"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+/// 把遍历事件写成 `<tag>` / `</tag>` / 文本
+fn edges<'a>(traverse: impl IntoIterator<Item = Edge<'a>>) -> Vec<String> {
+    traverse
+        .into_iter()
+        .map(|edge| match edge {
+            Edge::Open(n) if n.node_type().is_element() => format!("<{}>", n.tag_name()),
+            Edge::Close(n) if n.node_type().is_element() => format!("</{}>", n.tag_name()),
+            Edge::Open(n) => n.text(),
+            Edge::Close(_) => "/".to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_traverse() {
+    let doc = Document::parse("<div><p>A<b>B</b></p><br><!--c--></div>").unwrap();
+    let div = &doc.select("//div").unwrap()[0];
+    assert_eq!(
+        edges(div.traverse()),
+        [
+            "<div>", "<p>", "A", "/", "<b>", "B", "/", "</b>", "</p>", "<br>", "</br>", "c", "/",
+            "</div>"
+        ]
+    );
+
+    // 每个节点都有一对事件，Open 的顺序与 descendant-or-self 轴一致
+    let opened: Vec<_> = div
+        .traverse()
+        .filter_map(|edge| match edge {
+            Edge::Open(n) => Some(n.path()),
+            Edge::Close(_) => None,
+        })
+        .collect();
+    let expected = paths(div.select("descendant-or-self::node()").unwrap());
+    assert_eq!(opened, expected);
+    assert_eq!(div.traverse().count(), expected.len() * 2);
+
+    // 从叶子节点开始只有自身的一对事件
+    let text = &doc.select("//b/text()").unwrap()[0];
+    assert_eq!(edges(text.traverse()), ["B", "/"]);
+
+    // 跳过子树
+    let mut traverse = div.traverse();
+    let mut seen = Vec::new();
+    while let Some(edge) = traverse.next() {
+        if let Edge::Open(n) = &edge
+            && n.tag_name() == "p"
+        {
+            traverse.skip_subtree();
+        }
+        seen.push(edge);
+    }
+    assert_eq!(
+        edges(seen),
+        ["<div>", "<p>", "</p>", "<br>", "</br>", "c", "/", "</div>"]
+    );
+}
+
+#[test]
+fn test_traverse_entity_reference() {
+    let xml = r#"<!DOCTYPE r [<!ENTITY e "x<i>y</i>">]><r><a/>&e;<b/></r>"#;
+    let doc = Document::parse_xml(xml).unwrap();
+    let root = &doc.select("/r").unwrap()[0];
+    // 实体引用作为叶子节点，不进入实体声明的内容
+    let kinds: Vec<_> = root.traverse().map(|e| e.node().node_type()).collect();
+    assert_eq!(kinds.len(), 8);
+    assert_eq!(kinds[3], NodeType::EntityReference);
+    assert_eq!(kinds[4], NodeType::EntityReference);
+}
+
+#[derive(Default)]
+struct Renderer {
+    out: String,
+    left: Vec<String>,
+}
+
+impl<'a> Visitor<'a> for Renderer {
+    fn visit_element(&mut self, node: &SelectedNode<'a>) -> Visit {
+        match node.tag_name().as_str() {
+            "script" => Visit::SkipChildren,
+            "footer" => Visit::Stop,
+            tag => {
+                self.out.push_str(&format!("[{tag}]"));
+                Visit::Continue
+            }
+        }
+    }
+
+    fn leave_element(&mut self, node: &SelectedNode<'a>) {
+        self.left.push(node.tag_name());
+    }
+
+    fn visit_text(&mut self, node: &SelectedNode<'a>) {
+        self.out.push_str(node.text().trim());
+    }
+
+    fn visit_comment(&mut self, node: &SelectedNode<'a>) {
+        self.out.push_str(&format!("#{}", node.text()));
+    }
+}
+
+#[test]
+fn test_visitor() {
+    let html = "<main><h1>Title</h1><script>var x = 1;</script><!--note--><p>Body</p>\
+                <footer>ignored</footer><p>after</p></main>";
+    let doc = Document::parse(html).unwrap();
+    let main = &doc.select("//main").unwrap()[0];
+
+    let mut renderer = Renderer::default();
+    main.walk(&mut renderer);
+    assert_eq!(renderer.out, "[main][h1]Title#note[p]Body");
+    // 跳过子节点的元素仍然会离开；Stop 之后不再调用任何方法
+    assert_eq!(renderer.left, ["h1", "script", "p"]);
+
+    // 默认实现什么也不做
+    struct Nothing;
+    impl Visitor<'_> for Nothing {}
+    main.walk(&mut Nothing);
+}
@@ -0,0 +1,128 @@
+//! XPath 变量测试
+
+use rlibxml::{Document, Error, XPath, XPathResult, XPathValue};
+
+const HTML: &str = r#"
+<ul>
+    <li data-name="O'Reilly &quot;Media&quot;" data-price="10">A</li>
+    <li data-name="Manning" data-price="25">B</li>
+    <li data-name="No Starch" data-price="40">C</li>
+</ul>"#;
+
+#[test]
+fn test_string_variable_with_quotes() {
+    let doc = Document::parse(HTML).unwrap();
+    let nodes = doc
+        .select_with(
+            "//li[@data-name = $name]",
+            &[("name", r#"O'Reilly "Media""#.into())],
+        )
+        .unwrap();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].text(), "A");
+}
+
+#[test]
+fn test_number_and_boolean_variables() {
+    let doc = Document::parse(HTML).unwrap();
+
+    let nodes = doc
+        .select_with("//li[number(@data-price) > $min]", &[("min", 20.into())])
+        .unwrap();
+    assert_eq!(nodes.len(), 2);
+
+    let result = doc
+        .evaluate_with(
+            "$flag and count(//li) = $n",
+            &[("flag", true.into()), ("n", 3.5.into())],
+        )
+        .unwrap();
+    assert!(matches!(result, XPathResult::Boolean(false)));
+}
+
+#[test]
+fn test_nodeset_variable() {
+    let doc = Document::parse(HTML).unwrap();
+    let items = doc.select("//li").unwrap();
+
+    // 节点集合变量可以参与路径运算
+    let result = doc
+        .evaluate_with(
+            "string($items[2]/@data-name)",
+            &[("items", items.clone().into())],
+        )
+        .unwrap();
+    assert_eq!(result.as_string(), "Manning");
+
+    let siblings = doc
+        .select_with(
+            "$item/following-sibling::li",
+            &[("item", (&items[0]).into())],
+        )
+        .unwrap();
+    assert_eq!(siblings.len(), 2);
+
+    // 顺序被整理为文档顺序
+    let reversed: Vec<_> = items.iter().rev().cloned().collect();
+    let result = doc
+        .evaluate_with("string($set[1])", &[("set", reversed.into())])
+        .unwrap();
+    assert_eq!(result.as_string(), "A");
+}
+
+#[test]
+fn test_node_select_with() {
+    let doc = Document::parse(HTML).unwrap();
+    let ul = &doc.select("//ul").unwrap()[0];
+    let nodes = ul
+        .select_with("li[@data-name = $name]", &[("name", "Manning".into())])
+        .unwrap();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].text(), "B");
+}
+
+#[test]
+fn test_context_variables_persist() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    let by_price = XPath::compile("//li[@data-price = $price]").unwrap();
+
+    ctx.set_variable("price", "25").unwrap();
+    assert_eq!(ctx.select_compiled(&by_price).unwrap()[0].text(), "B");
+
+    // 重新设置会替换原值
+    ctx.set_variable("price", 40).unwrap();
+    assert_eq!(ctx.select_compiled(&by_price).unwrap()[0].text(), "C");
+    assert_eq!(ctx.evaluate("$price * 2").unwrap().as_number(), 80.0);
+}
+
+#[test]
+fn test_unbound_variable_is_error() {
+    let doc = Document::parse(HTML).unwrap();
+    assert!(matches!(
+        doc.select_with("//li[@data-name = $missing]", &[]),
+        Err(Error::InvalidXPath { .. })
+    ));
+}
+
+#[test]
+fn test_foreign_nodes_rejected() {
+    let doc = Document::parse(HTML).unwrap();
+    let other = Document::parse("<p>other</p>").unwrap();
+    let foreign = other.select("//p").unwrap();
+
+    let result = doc.evaluate_with(
+        "count($nodes)",
+        &[("nodes", XPathValue::NodeSet(foreign.clone()))],
+    );
+    assert!(matches!(result, Err(Error::InvalidVariable { .. })));
+}
+
+#[test]
+fn test_null_byte_in_value() {
+    let doc = Document::parse(HTML).unwrap();
+    assert!(matches!(
+        doc.evaluate_with("$v", &[("v", "a\0b".into())]),
+        Err(Error::NullByte)
+    ));
+}
@@ -0,0 +1,175 @@
+//! 自定义 XPath 函数测试
+
+use rlibxml::{Document, Error, XPath, XPathContext, XPathResult, XPathValue};
+
+const NS: &str = "urn:rlibxml-test";
+
+const HTML: &str = r#"
+<ul>
+    <li class="item"><span class="price">$1,299.00</span></li>
+    <li class="item"><span class="price">USD 15</span></li>
+    <li class="item sold-out"><span class="price">n/a</span></li>
+</ul>"#;
+
+fn context(doc: &Document) -> XPathContext<'_> {
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_namespace("my", NS).unwrap();
+    ctx.register_function(NS, "normalize-price", |args| {
+        let raw = args.first().map(|a| a.as_string()).unwrap_or_default();
+        let digits: String = raw
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        Ok(XPathValue::Number(digits.parse().unwrap_or(f64::NAN)))
+    })
+    .unwrap();
+    ctx.register_function(NS, "has-class", |args| match args {
+        [node, XPathResult::String(class)] => Ok(XPathValue::Boolean(
+            node.as_string().split_whitespace().any(|c| c == class),
+        )),
+        _ => Err("has-class expects (node, string)".into()),
+    })
+    .unwrap();
+    ctx
+}
+
+#[test]
+fn test_function_returns_number() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = context(&doc);
+
+    let total = ctx
+        .evaluate("my:normalize-price(//li[1]/span) + my:normalize-price(//li[2]/span)")
+        .unwrap();
+    assert_eq!(total.as_number(), 1314.0);
+
+    let cheap = ctx.select("//li[my:normalize-price(span) < 100]").unwrap();
+    assert_eq!(cheap.len(), 1);
+}
+
+#[test]
+fn test_function_with_multiple_arguments() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = context(&doc);
+
+    let sold_out = ctx
+        .select("//li[my:has-class(string(@class), 'sold-out')]")
+        .unwrap();
+    assert_eq!(sold_out.len(), 1);
+}
+
+#[test]
+fn test_function_receives_and_returns_node_sets() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_namespace("my", NS).unwrap();
+    ctx.register_function(NS, "last-two", |args| match args {
+        [XPathResult::NodeSet(nodes)] => {
+            let skip = nodes.len().saturating_sub(2);
+            Ok(XPathValue::NodeSet(nodes[skip..].to_vec()))
+        }
+        _ => Err("last-two expects a node-set".into()),
+    })
+    .unwrap();
+
+    let nodes = ctx.select("my:last-two(//span)/..").unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(
+        ctx.evaluate("count(my:last-two(//li))")
+            .unwrap()
+            .as_number(),
+        2.0
+    );
+}
+
+#[test]
+fn test_function_without_namespace() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_function("", "answer", |_| Ok(XPathValue::Number(42.0)))
+        .unwrap();
+    assert_eq!(ctx.evaluate("answer()").unwrap().as_number(), 42.0);
+}
+
+#[test]
+fn test_function_error_is_reported() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = context(&doc);
+
+    match ctx.evaluate("my:has-class(1)") {
+        Err(Error::InvalidXPath { reason, .. }) => {
+            assert_eq!(reason.as_deref(), Some("has-class expects (node, string)"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // 出错后上下文仍可继续使用
+    assert_eq!(ctx.select("//li").unwrap().len(), 3);
+}
+
+#[test]
+fn test_function_panic_is_caught() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_namespace("my", NS).unwrap();
+    ctx.register_function(NS, "boom", |_| panic!("kaboom"))
+        .unwrap();
+
+    match ctx.evaluate("my:boom()") {
+        Err(Error::InvalidXPath { reason, .. }) => {
+            assert!(reason.unwrap().contains("kaboom"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_function_rejects_foreign_nodes() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = doc.xpath_context().unwrap();
+    ctx.register_function("", "foreign", |_| {
+        // 返回的节点不属于被查询的文档
+        let other = Box::leak(Box::new(Document::parse("<p/>").unwrap()));
+        Ok(XPathValue::NodeSet(other.select("//p").unwrap()))
+    })
+    .unwrap();
+
+    assert!(matches!(
+        ctx.evaluate("foreign()"),
+        Err(Error::InvalidXPath { .. })
+    ));
+}
+
+#[test]
+fn test_function_with_compiled_xpath() {
+    let price = XPath::compile(
+        "sum(//span[my:normalize-price(.) = my:normalize-price(.)]/my:normalize-price(.))",
+    );
+    // XPath 1.0 不允许在路径步骤中调用函数，编译即失败
+    assert!(matches!(price, Err(Error::XPathSyntax { .. })));
+
+    let cheap = XPath::compile("count(//li[my:normalize-price(span) < 100])").unwrap();
+    let doc = Document::parse(HTML).unwrap();
+    // 同一个编译结果在多个上下文中各求值多次
+    for _ in 0..2 {
+        let mut ctx = context(&doc);
+        assert_eq!(ctx.evaluate_compiled(&cheap).unwrap().as_number(), 1.0);
+        assert_eq!(ctx.evaluate_compiled(&cheap).unwrap().as_number(), 1.0);
+    }
+}
+
+#[test]
+fn test_duplicate_function() {
+    let doc = Document::parse(HTML).unwrap();
+    let mut ctx = context(&doc);
+    let err = ctx
+        .register_function(NS, "has-class", |_| Ok(XPathValue::Boolean(true)))
+        .unwrap_err();
+    match err {
+        Error::InvalidFunction { name, uri } => {
+            assert_eq!(name, "has-class");
+            assert_eq!(uri, NS);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
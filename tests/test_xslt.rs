@@ -0,0 +1,154 @@
+//! XSLT 转换测试
+#![cfg(feature = "xslt")]
+
+use rlibxml::{Document, Error, Stylesheet};
+use std::sync::Arc;
+
+const NORMALIZE: &str = r#"<?xml version="1.0"?>
+<xsl:stylesheet version="1.0" xmlns:xsl="http://www.w3.org/1999/XSL/Transform">
+    <xsl:output method="xml" encoding="ISO-8859-1" indent="yes"/>
+    <xsl:strip-space elements="*"/>
+    <xsl:param name="vendor" select="'unknown'"/>
+    <xsl:template match="/catalog">
+        <products vendor="{$vendor}">
+            <xsl:apply-templates select="entry[@active = 'yes']"/>
+        </products>
+    </xsl:template>
+    <xsl:template match="entry">
+        <product sku="{@code}">
+            <name><xsl:value-of select="title"/></name>
+            <price><xsl:value-of select="format-number(cost, '0.00')"/></price>
+        </product>
+    </xsl:template>
+</xsl:stylesheet>"#;
+
+const CATALOG: &str = r#"<catalog>
+    <entry code="A1" active="yes"><title>Café crème</title><cost>3.5</cost></entry>
+    <entry code="B2" active="no"><title>Tea</title><cost>2</cost></entry>
+    <entry code="C3" active="yes"><title>Juice</title><cost>4</cost></entry>
+</catalog>"#;
+
+fn compile(xsl: &str) -> rlibxml::Result<Stylesheet> {
+    Stylesheet::parse(&Document::parse_xml(xsl)?)
+}
+
+#[test]
+fn test_transform() {
+    let stylesheet = compile(NORMALIZE).unwrap();
+    let catalog = Document::parse_xml(CATALOG).unwrap();
+
+    let result = stylesheet
+        .transform(&catalog, &[("vendor", "O'Brien & \"Sons\"")])
+        .unwrap();
+    assert_eq!(
+        result.extract_texts("//product/@sku").unwrap(),
+        ["A1", "C3"]
+    );
+    assert_eq!(result.extract_texts("//price").unwrap(), ["3.50", "4.00"]);
+    assert_eq!(
+        result.root().unwrap().attr("vendor").as_deref(),
+        Some("O'Brien & \"Sons\"")
+    );
+    // 结果文档可以像普通文档一样查询
+    assert_eq!(result.select("//name").unwrap()[0].text(), "Café crème");
+
+    // 源文档不受 xsl:strip-space 影响
+    assert!(catalog.root().unwrap().child_count() > 3);
+
+    // 未传参数时使用默认值
+    let result = stylesheet.transform(&catalog, &[]).unwrap();
+    assert_eq!(
+        result.root().unwrap().attr("vendor").as_deref(),
+        Some("unknown")
+    );
+}
+
+#[test]
+fn test_serialize_with_output_settings() {
+    let stylesheet = compile(NORMALIZE).unwrap();
+    let catalog = Document::parse_xml(CATALOG).unwrap();
+    let result = stylesheet.transform(&catalog, &[]).unwrap();
+
+    let bytes = stylesheet.serialize(&result).unwrap();
+    let head = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<products vendor=\"unknown\">\n  <product sku=\"A1\">";
+    assert!(bytes.starts_with(head));
+    // "é" 按 ISO-8859-1 编码为单字节
+    assert!(bytes.windows(5).any(|w| w == b"Caf\xe9 "));
+
+    let text = compile(
+        r#"<xsl:stylesheet version="1.0" xmlns:xsl="http://www.w3.org/1999/XSL/Transform">
+            <xsl:output method="text"/>
+            <xsl:template match="/"><xsl:value-of select="count(//entry)"/> entries</xsl:template>
+        </xsl:stylesheet>"#,
+    )
+    .unwrap();
+    let result = text.transform(&catalog, &[]).unwrap();
+    assert_eq!(text.serialize(&result).unwrap(), b"3 entries");
+}
+
+#[test]
+fn test_errors() {
+    assert!(matches!(
+        compile("<not-a-stylesheet/>"),
+        Err(Error::InvalidStylesheet { .. })
+    ));
+
+    let terminate = compile(
+        r#"<xsl:stylesheet version="1.0" xmlns:xsl="http://www.w3.org/1999/XSL/Transform">
+            <xsl:template match="/">
+                <xsl:message terminate="yes">Missing <xsl:value-of select="name(*)"/> header</xsl:message>
+            </xsl:template>
+        </xsl:stylesheet>"#,
+    )
+    .unwrap();
+    let doc = Document::parse_xml(CATALOG).unwrap();
+    match terminate.transform(&doc, &[]) {
+        Err(Error::TransformFailed { messages }) => {
+            assert!(messages.iter().any(|m| m == "Missing catalog header"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // 禁止写文件
+    let dir = std::env::temp_dir().join(format!("rlibxml-xslt-{}", std::process::id()));
+    let target = dir.join("out.xml");
+    let writer = compile(&format!(
+        r#"<xsl:stylesheet version="1.0" xmlns:xsl="http://www.w3.org/1999/XSL/Transform"
+                xmlns:exsl="http://exslt.org/common" extension-element-prefixes="exsl">
+            <xsl:template match="/">
+                <exsl:document href="{}"><x/></exsl:document>
+            </xsl:template>
+        </xsl:stylesheet>"#,
+        target.display()
+    ))
+    .unwrap();
+    assert!(writer.transform(&doc, &[]).is_err());
+    assert!(!target.exists());
+
+    assert!(matches!(
+        terminate.transform(&doc, &[("a\0b", "c")]),
+        Err(Error::NullByte)
+    ));
+}
+
+#[test]
+fn test_shared_across_threads() {
+    let stylesheet = Arc::new(compile(NORMALIZE).unwrap());
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let stylesheet = Arc::clone(&stylesheet);
+            std::thread::spawn(move || {
+                let catalog = Document::parse_xml(CATALOG).unwrap();
+                let vendor = format!("v{i}");
+                let result = stylesheet
+                    .transform(&catalog, &[("vendor", &vendor)])
+                    .unwrap();
+                result.root().unwrap().attr("vendor").unwrap()
+            })
+        })
+        .collect();
+
+    let vendors: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(vendors, ["v0", "v1", "v2", "v3"]);
+}